@@ -1,45 +1,141 @@
 use std::borrow::Borrow;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
+use instant::Instant;
 use wgpu::BufferUsages;
 use winit::event::{
-    ElementState, Event, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent,
+    ElementState, Event, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta,
+    VirtualKeyCode, WindowEvent,
 };
 use winit::event_loop::ControlFlow;
-use winit::window::{Window, WindowBuilder};
+use winit::window::{Window, WindowBuilder, WindowId};
 
-use engine::Engine;
-use game_state::GameState;
+use clipboard::Clipboard;
+use command::Command;
+use config::{Config, ConfigWatcher, BRIGHTNESS_RANGE, CONTRAST_RANGE, GAMMA_RANGE};
+use demo::{DemoPlayer, DemoRecorder, DEMO_DELTA_TIME};
+use engine::screenshot::read_text;
+use engine::video::VideoEncoder;
+use engine::{AdapterSelection, Engine};
+use file_watcher::FileWatcher;
+use frame_pacer::{FramePacer, Pace};
+use fractal::{with_structs, Fractal};
+use game_state::{GameState, StateStack};
+use growth_state::GrowthState;
+use keyframes::{KeyframePlayer, KeyframeScript};
 use mamndelbrot_state::MandelbrotState;
-use mandelbrot::MandelbrotEngine;
+use mandelbrot::{Coloring, MandelbrotEngine};
+use palette::Palette;
+use pause_menu_state::PauseMenuState;
+use screensaver_state::ScreensaverState;
+use script_state::ScriptState;
+use explore_state::ExploreState;
+use pipe::Pipe;
+use recovery::Recovery;
+use remote_state::RemoteState;
+use session::Session;
+use tour_state::TourState;
 use window_state::WindowState;
 
+use crate::options::Options;
+
+pub use big_real::{BigReal, Real};
+pub use location::Location;
+pub use renderer::{MandelbrotRenderer, RenderParams};
+
+mod big_real;
+mod blue_noise;
+mod bookmarks;
+mod camera_animator;
+mod clipboard;
+mod command;
+mod config;
+mod cpu_renderer;
+mod demo;
 mod engine;
+mod explore_state;
+mod exr;
+mod file_watcher;
+mod formula;
+mod iteration;
+mod fractal;
+mod frame_pacer;
+mod gallery_state;
 mod game_state;
+mod growth;
+mod growth_state;
+mod keyframes;
+mod kfr;
+mod location;
+mod location_code;
 mod mamndelbrot_state;
 mod mandelbrot;
+mod newton;
+mod orbit_worker;
+mod packed_orbit;
+mod palette;
+mod pause_menu_state;
+mod period_worker;
+mod pipe;
+mod recovery;
+mod remote_state;
+mod renderer;
+mod screensaver_state;
+mod script_state;
+mod session;
+mod sonification;
 mod to_buffer_representation;
+mod tour_state;
 mod window_state;
 
-// create an enum with the name of the different buffer
-enum GameBuffer {
-    Mandelbrot = 0,
-    PreviousMandelbrot = 1,
-    MandelbrotIterationTexture = 2,
-    PreviousMandelbrotIterationTexture = 3,
-    MandelbrotData = 4,
-    PreviousMandelbrotData = 5,
-    MandelbrotOrbitPointSuite = 6,
-}
+// the name of the explorer, in the title of the window and in the screenshots
+pub const TITLE: &str = "Realtime Mandelbrot Explorer";
 
 pub struct Game {
-    window: Rc<Window>,
-    window_state: WindowState,
+    // the window and its state, none when rendering headless
+    window: Option<Rc<Window>>,
+    window_state: Option<WindowState>,
     mandelbrot_state: MandelbrotState,
+    // the tour, the exploration and the script driving the explorer, and the states pushed over them,
+    // they get the events before it
+    states: StateStack,
     engine: Engine,
-    last_screen_update: Instant,
+    // the time the frames are drawn at
+    frame_pacer: FramePacer,
     pub last_frame_time: Duration,
+    pipe: Option<Pipe>,
+    config_watcher: ConfigWatcher,
+    config: Config,
+    fractal: Fractal,
+    // the formula of the custom fractal, the one of the command line wins over the one of the config
+    formula: String,
+    formula_from_options: bool,
+    // the source of the shader when it is available, for example when running from the repository
+    shader_watcher: Option<FileWatcher>,
+    // the zoom speed to restore when leaving the mini viewer
+    zoom_speed_before_mini: f32,
+    // the inputs of the user written to a demo file, or read from one instead
+    demo_recorder: Option<DemoRecorder>,
+    demo_player: Option<DemoPlayer>,
+    exit_requested: bool,
+    // true while the frames of the window are stopped by an error of the gpu, until Enter is pressed
+    stopped_by_gpu_error: bool,
+    // the file the navigation is saved to when the explorer exits, none when it is not saved
+    session: Option<PathBuf>,
+    // the view written to a recovery file if the explorer panics, and the one written
+    // by the last launch, until it is restored with Y
+    recovery: Option<Recovery>,
+    recovered: Option<Session>,
+    // where the location codes are copied to and pasted from
+    clipboard: Clipboard,
+    // the modifier keys held, for the shortcuts of the game
+    modifiers: ModifiersState,
+    // the last time the user did something or the camera moved
+    last_activity: Instant,
+    // the last time the user pressed a key or moved the mouse, to start the screensaver
+    last_input: Instant,
 }
 
 impl Game {
@@ -48,19 +144,739 @@ impl Game {
     }
 
     // Creating some of the wgpu types requires async code
-    pub async fn new(window: Rc<Window>) -> Self {
+    // none when there is no adapter to draw in the window
+    pub async fn new(window: Rc<Window>, options: &Options) -> Option<Self> {
         let size = window.inner_size();
-        let mut engine = Engine::new(window.borrow()).await;
-        let mandelbrot_state = MandelbrotState::new(size, &mut engine);
+        let config_watcher = ConfigWatcher::new(options.config.clone());
+        let config = config_watcher.initial();
+        let engine = Engine::new(
+            window.borrow(),
+            &Self::adapter_selection(options),
+            config.display.hdr,
+        )
+        .await?;
+        Some(Self::create(Some(window), engine, size, options, config_watcher, config))
+    }
+
+    // create a game without a window, rendering into an offscreen texture of the given size,
+    // none when there is no adapter at all
+    pub async fn new_headless(
+        size: winit::dpi::PhysicalSize<u32>,
+        options: &Options,
+    ) -> Option<Self> {
+        let engine = Engine::new_headless(size, &Self::adapter_selection(options)).await?;
+        let config_watcher = ConfigWatcher::new(options.config.clone());
+        let config = config_watcher.initial();
+        Some(Self::create(None, engine, size, options, config_watcher, config))
+    }
+
+    // render the location of the options, or the default one, to the output file on the cpu,
+    // when there is no adapter to draw the frames computed by the cpu renderer
+    pub fn render_without_adapter(
+        size: winit::dpi::PhysicalSize<u32>,
+        options: &Options,
+    ) -> Result<(), String> {
+        let config = ConfigWatcher::new(options.config.clone()).initial();
+        let (location, palette_seed) = match &options.location {
+            Some(path) => kfr::load_with_palette_seed(path)?,
+            None => (MandelbrotEngine::default().location(), None),
+        };
+        let maximum_iterations = mamndelbrot_state::zoom_iterations(
+            location.zoom as f64,
+            config.navigation.iteration_speed,
+        );
+        let palette = palette_seed.map(Palette::generate).unwrap_or_default();
+        let pixels =
+            cpu_renderer::render_image(&location, &palette, size, maximum_iterations as u32);
+        image::save_buffer(
+            &options.output,
+            &pixels,
+            size.width,
+            size.height,
+            image::ColorType::Rgba8,
+        )
+        .map_err(|error| error.to_string())?;
+        eprintln!("Saved {}", options.output.display());
+        Ok(())
+    }
+
+    fn adapter_selection(options: &Options) -> AdapterSelection {
+        AdapterSelection {
+            gpu: options.gpu.clone(),
+            force_fallback: options.force_fallback,
+            cpu: options.cpu,
+        }
+    }
+
+    fn create(
+        window: Option<Rc<Window>>,
+        mut engine: Engine,
+        size: winit::dpi::PhysicalSize<u32>,
+        options: &Options,
+        config_watcher: ConfigWatcher,
+        config: Config,
+    ) -> Self {
+        let keyframe_player = options.script.as_ref().and_then(|path| {
+            match KeyframeScript::load(path) {
+                Ok(mut script) => {
+                    if let Some(fps) = options.fps {
+                        script.fps = fps;
+                    }
+                    if let Some(directory) = &options.export {
+                        if let Err(error) = std::fs::create_dir_all(directory) {
+                            eprintln!("Impossible to create {}: {}", directory.display(), error);
+                        }
+                    }
+                    Some(KeyframePlayer::new(script, options.export.clone()))
+                }
+                Err(error) => {
+                    eprintln!("Impossible to read the script {}: {}", path.display(), error);
+                    None
+                }
+            }
+        });
+        match (&options.video, &keyframe_player) {
+            (Some(path), Some(player)) => {
+                let video = VideoEncoder::start(
+                    path,
+                    size.width,
+                    size.height,
+                    player.fps(),
+                    &options.bitrate,
+                );
+                match video {
+                    Ok(video) => engine.start_video(video),
+                    Err(error) => eprintln!("Impossible to export {}: {}", path.display(), error),
+                }
+            }
+            (Some(_), None) => eprintln!("A video is only exported from a script"),
+            _ => {}
+        }
+        let demo_recorder = match &options.record {
+            Some(path) => match DemoRecorder::create(path) {
+                Ok(recorder) => Some(recorder),
+                Err(error) => {
+                    eprintln!("Impossible to record {}: {}", path.display(), error);
+                    None
+                }
+            },
+            None => None,
+        };
+        let demo_player = match &options.replay {
+            Some(path) => match DemoPlayer::load(path) {
+                Ok(player) => Some(player),
+                Err(error) => {
+                    eprintln!("Impossible to read the demo {}: {}", path.display(), error);
+                    None
+                }
+            },
+            None => None,
+        };
+        let mut mandelbrot_state =
+            MandelbrotState::new(size, &mut engine, config.navigation.clone());
+        let is_exporting = options.export.is_some() || options.video.is_some();
+        // a demo draws the same frames whatever the speed of the computer
+        let is_demo = demo_recorder.is_some() || demo_player.is_some();
+        mandelbrot_state.set_wait_for_orbits(window.is_none() || is_exporting || is_demo);
+        mandelbrot_state.set_minimum_resolution(&mut engine, config.display.minimum_resolution);
+        mandelbrot_state.set_target_frame_time(config.display.target_frame_time);
+        mandelbrot_state.set_iteration_slice(config.display.iteration_slice);
+        mandelbrot_state.set_color_cycling_speed(config.display.color_cycling_speed);
+        mandelbrot_state.set_auto_palette_scale(config.display.auto_palette_scale);
+        // the commands report the share of the view inside the set in their status
+        mandelbrot_state.set_measures_area(
+            config.display.area_in_title || options.pipe || options.listen.is_some(),
+        );
+        mandelbrot_state.set_comparison(config.comparison.clone());
+        mandelbrot_state.set_reference_config(config.reference.clone());
+        if window.is_some() {
+            Self::apply_present_mode(&mut engine, &config);
+            // the frames rendered without a window are never shared as a gif
+            engine.set_gif_config(&config.gif);
+        }
+        Self::apply_post_effects(&mut engine, &config);
+        engine.set_color_levels(config.display.color_levels());
+        mandelbrot_state.set_dither(&engine, config.display.dither);
+        if let Some(path) = &options.profile {
+            if let Err(error) = engine.start_profiling(path) {
+                eprintln!("Impossible to measure the frames: {}", error);
+            }
+        }
+        if let Some(path) = &options.stats_out {
+            if let Err(error) = engine.start_stats(path) {
+                eprintln!("Impossible to write the statistics: {}", error);
+            }
+        }
         engine.create_pipeline();
-        Self {
-            window: window.clone(),
+        // only the navigation of the user is saved, not the one driven by a script, a pipe or a remote
+        let session = (window.is_some()
+            && keyframe_player.is_none()
+            && demo_player.is_none()
+            && !options.pipe
+            && options.listen.is_none())
+        .then(|| options.session.clone());
+        let recovery_path = session
+            .as_ref()
+            .map(|path| path.with_extension("recovery.json"));
+        let recovered = recovery_path.as_deref().and_then(Recovery::take);
+        let mut game = Self {
+            window_state: window.clone().map(WindowState::new),
+            window,
             engine,
             mandelbrot_state,
-            last_screen_update: Instant::now(),
-            window_state: WindowState::new(window.clone()),
+            states: StateStack::default(),
+            frame_pacer: FramePacer::new(),
             last_frame_time: Duration::from_secs_f32(1.0 / 120.0),
+            pipe: if options.pipe {
+                Some(Pipe::new())
+            } else {
+                None
+            },
+            config_watcher,
+            shader_watcher: if options.shader.exists() {
+                Some(FileWatcher::new(options.shader.clone()))
+            } else {
+                None
+            },
+            zoom_speed_before_mini: config.navigation.zoom_speed,
+            fractal: if options.formula.is_some() {
+                Fractal::Custom
+            } else {
+                config.fractal.kind
+            },
+            formula: options
+                .formula
+                .clone()
+                .unwrap_or_else(|| config.fractal.formula.clone()),
+            formula_from_options: options.formula.is_some(),
+            config,
+            demo_recorder,
+            demo_player,
+            exit_requested: false,
+            stopped_by_gpu_error: false,
+            session,
+            recovery: recovery_path.map(Recovery::install),
+            recovered,
+            clipboard: Clipboard::new(),
+            modifiers: ModifiersState::empty(),
+            last_activity: Instant::now(),
+            last_input: Instant::now(),
+        };
+        game.push_state(Box::new(TourState::new(
+            options.tour.clone(),
+            game.config.tour.clone(),
+        )));
+        game.push_state(Box::new(ExploreState::new(game.config.explore.interval)));
+        // the view drawn with more and more iterations, started and stopped with F6
+        game.push_state(Box::new(GrowthState::new(game.config.growth.clone())));
+        if let Some(player) = keyframe_player {
+            game.push_state(Box::new(ScriptState::new(player)));
+        }
+        if let Some(port) = options.listen {
+            match RemoteState::listen(options.listen_address, port) {
+                Ok(remote) => game.push_state(Box::new(remote)),
+                Err(error) => eprintln!("Impossible to listen on port {}: {}", port, error),
+            }
+        }
+        game.mandelbrot_state
+            .set_newton_polynomial(&game.config.fractal.newton_polynomial);
+        // the shader on disk may be newer than the one built in the program
+        game.set_fractal(game.fractal);
+        // a demo starts from the same view when it is recorded and when it is replayed
+        if !options.fresh && options.location.is_none() && !is_demo {
+            game.restore_session();
+        }
+        if let Some(path) = &options.location {
+            game.open_kfr(path);
+        }
+        // the view shared in the url of the page
+        #[cfg(target_arch = "wasm32")]
+        if let Ok(code) = game.clipboard.paste() {
+            if !code.is_empty() {
+                game.open_location_code(&code);
+            }
+        }
+        if options.mini {
+            game.toggle_mini_viewer();
+        }
+        if game.recovered.is_some() {
+            eprintln!("The last launch crashed, press Y to go back to where it stopped");
+            if let Some(window_state) = game.window_state.as_mut() {
+                window_state.set_recovery_offer(true);
+            }
+        }
+        game
+    }
+
+    // go back to the navigation saved when the explorer last exited
+    fn restore_session(&mut self) {
+        let path = match &self.session {
+            Some(path) => path,
+            None => return,
+        };
+        match Session::load(path) {
+            Ok(Some(session)) => self.mandelbrot_state.restore(&session),
+            Ok(None) => {}
+            Err(error) => eprintln!(
+                "Impossible to restore the session {}: {}",
+                path.display(),
+                error
+            ),
+        }
+    }
+
+    // go back to the view written when the last launch panicked
+    fn restore_recovered(&mut self) {
+        if let Some(session) = self.recovered.take() {
+            self.mandelbrot_state.restore(&session);
+            eprintln!("Back to the view of the last launch");
+            if let Some(window_state) = self.window_state.as_mut() {
+                window_state.set_recovery_offer(false);
+            }
+        }
+    }
+
+    fn current_session(&self) -> Session {
+        let mut session = self.mandelbrot_state.session();
+        // the mini viewer dives at its own speed
+        if self.is_mini() {
+            session.zoom_speed = self.zoom_speed_before_mini;
+        }
+        session
+    }
+
+    fn save_session(&self) {
+        let path = match &self.session {
+            Some(path) => path,
+            None => return,
+        };
+        if let Err(error) = self.current_session().save(path) {
+            eprintln!(
+                "Impossible to save the session {}: {}",
+                path.display(),
+                error
+            );
+        }
+    }
+
+    // lower or raise the gamma, the brightness or the contrast of the colors,
+    // and write it in the config file so it is kept for the next launches
+    fn adjust_color_level(&mut self, keycode: VirtualKeyCode) {
+        let display = &mut self.config.display;
+        let (name, value, step, range) = match keycode {
+            VirtualKeyCode::Key1 | VirtualKeyCode::Key2 => {
+                ("gamma", &mut display.gamma, 0.1, GAMMA_RANGE)
+            }
+            VirtualKeyCode::Key3 | VirtualKeyCode::Key4 => (
+                "brightness",
+                &mut display.brightness,
+                0.05,
+                BRIGHTNESS_RANGE,
+            ),
+            _ => ("contrast", &mut display.contrast, 0.1, CONTRAST_RANGE),
+        };
+        let step = match keycode {
+            VirtualKeyCode::Key1 | VirtualKeyCode::Key3 | VirtualKeyCode::Key5 => -step,
+            _ => step,
+        };
+        // rounded so the steps do not drift away from the values written in the file
+        *value = (((*value + step) * 100.0).round() / 100.0).clamp(*range.start(), *range.end());
+        let value = *value;
+        eprintln!("{}: {}", name, value);
+        let levels = self.config.display.color_levels();
+        self.engine.set_color_levels(levels);
+        let path = self.config_watcher.path();
+        if let Err(error) = config::save_display_value(path, name, value) {
+            eprintln!("Impossible to save the {}: {}", name, error);
+        }
+    }
+
+    // copy the next frame to the clipboard, it is given once read back from the gpu
+    fn copy_frame(&mut self) {
+        self.engine.request_frame_copy();
+    }
+
+    fn poll_copied_frame(&mut self) {
+        let copied = match self.engine.take_copied_frame() {
+            Some(copied) => copied,
+            None => return,
+        };
+        let copied = copied
+            .and_then(|(width, height, pixels)| self.clipboard.copy_image(width, height, pixels));
+        match copied {
+            Ok(()) => eprintln!("Frame copied to the clipboard"),
+            Err(error) => eprintln!("Impossible to copy the frame: {}", error),
+        }
+    }
+
+    // copy the code of the current view, to share it
+    fn copy_location_code(&mut self) {
+        let code = location_code::encode(&self.mandelbrot_state.view());
+        eprintln!("Location code: {}", code);
+        if let Err(error) = self.clipboard.copy(&code) {
+            eprintln!("Impossible to copy the location code: {}", error);
+        }
+    }
+
+    // save the last seconds kept as a gif, encoded in the background
+    fn save_gif(&mut self) {
+        let clip = match self.engine.gif_clip() {
+            Some(clip) => clip,
+            None => {
+                eprintln!("The frames are not kept for a gif, gif.seconds is 0");
+                return;
+            }
+        };
+        if clip.is_empty() {
+            eprintln!("No frame kept for the gif yet");
+            return;
+        }
+        let seconds = instant::SystemTime::now()
+            .duration_since(instant::SystemTime::UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+        let path = PathBuf::from(format!("mandelbrot_{}.gif", seconds));
+        let duration = clip.duration();
+        eprintln!("Saving the last {:.1} s to {}", duration, path.display());
+        let task = move || match clip.save(&path) {
+            Ok(()) => eprintln!("Gif saved to {}", path.display()),
+            Err(error) => eprintln!("Impossible to save {}: {}", path.display(), error),
+        };
+        // there is no thread in the browser
+        #[cfg(target_arch = "wasm32")]
+        task();
+        #[cfg(not(target_arch = "wasm32"))]
+        rayon::spawn(task);
+    }
+
+    fn paste_location_code(&mut self) {
+        match self.clipboard.paste() {
+            Ok(code) => self.open_location_code(&code),
+            Err(error) => eprintln!("Impossible to paste the location code: {}", error),
+        }
+    }
+
+    fn open_location_code(&mut self, code: &str) {
+        match location_code::decode(code) {
+            Ok(view) => self.mandelbrot_state.set_view(&view),
+            Err(error) => eprintln!("Invalid location code: {}", error),
+        }
+    }
+
+    // save the next frame with the description of the view, so it can be opened again
+    fn request_screenshot(&mut self, path: PathBuf) {
+        let view = self.mandelbrot_state.view();
+        let text = [
+            ("Software", TITLE.to_string()),
+            ("Location code", location_code::encode(&view)),
+            ("X", view.location.x.to_text()),
+            ("Y", view.location.y.to_text()),
+            ("Zoom", view.location.zoom.to_string()),
+            ("Angle", view.location.angle.to_string()),
+            (
+                "Iterations",
+                self.mandelbrot_state.maximum_iterations().to_string(),
+            ),
+            ("Fractal", format!("{:?}", self.fractal).to_lowercase()),
+            ("Palette scale", view.color_palette_scale.to_string()),
+            ("Coloring", format!("{:?}", Coloring::from(view.coloring))),
+        ];
+        let palette_seed = self
+            .mandelbrot_state
+            .palette_seed()
+            .map(|seed| ("Palette seed", seed.to_string()));
+        self.engine.request_screenshot(
+            path,
+            text.into_iter()
+                .chain(palette_seed)
+                .map(|(keyword, value)| (keyword.to_string(), value))
+                .collect(),
+        );
+    }
+
+    // go to the view of a screenshot, with its fractal
+    fn open_screenshot(&mut self, path: &Path) {
+        let text = match read_text(path) {
+            Ok(text) => text,
+            Err(error) => {
+                eprintln!("Impossible to open {}: {}", path.display(), error);
+                return;
+            }
+        };
+        let value = |name: &str| {
+            text.iter()
+                .find(|(keyword, _)| keyword == name)
+                .map(|(_, value)| value.clone())
+        };
+        let code = match value("Location code") {
+            Some(code) => code,
+            None => {
+                eprintln!("{} has no location", path.display());
+                return;
+            }
+        };
+        if let Some(fractal) = value("Fractal").and_then(|name| {
+            serde_json::from_value::<Fractal>(serde_json::Value::String(name)).ok()
+        }) {
+            if fractal != self.fractal {
+                self.set_fractal(fractal);
+            }
+        }
+        self.open_location_code(&code);
+        if let Some(seed) = value("Palette seed").and_then(|seed| seed.parse().ok()) {
+            self.mandelbrot_state.set_palette_seed(Some(seed));
+        }
+    }
+
+    // go to the location of a Kalles Fraktaler file, with the palette of the bookmarks that have one
+    fn open_kfr(&mut self, path: &Path) {
+        match kfr::load_with_palette_seed(path) {
+            Ok((location, palette_seed)) => {
+                self.mandelbrot_state.set_location(location);
+                if palette_seed.is_some() {
+                    self.mandelbrot_state.set_palette_seed(palette_seed);
+                }
+            }
+            Err(error) => eprintln!("Impossible to open {}: {}", path.display(), error),
+        }
+    }
+
+    // go to the location of a file dropped on the window: a Kalles Fraktaler file,
+    // a session or a location saved as json, or a screenshot of the explorer
+    fn open_dropped_file(&mut self, path: &Path) {
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("png") => self.open_screenshot(path),
+            Some("kfr") => self.open_kfr(path),
+            Some("json") => match std::fs::read_to_string(path)
+                .map_err(|error| error.to_string())
+                .and_then(|text| Location::from_json(&text))
+            {
+                Ok(location) => self.mandelbrot_state.set_location(location),
+                Err(error) => eprintln!("Impossible to open {}: {}", path.display(), error),
+            },
+            _ => eprintln!(
+                "Impossible to open {}: only the kfr, json and png files can be dropped",
+                path.display()
+            ),
+        }
+    }
+
+    // switch between the full explorer and the mini viewer
+    // the mini viewer is a small window on top of the others, slowly diving at a low frame rate
+    pub fn toggle_mini_viewer(&mut self) {
+        let window_state = match self.window_state.as_mut() {
+            Some(window_state) => window_state,
+            None => return,
+        };
+        let mini = !window_state.is_mini();
+        window_state.set_mini(mini, &self.config.mini_viewer);
+        if mini {
+            self.zoom_speed_before_mini = self.mandelbrot_state.zoom_speed();
+            self.mandelbrot_state
+                .set_zoom_speed(self.config.mini_viewer.zoom_speed);
+        } else {
+            self.mandelbrot_state
+                .set_zoom_speed(self.zoom_speed_before_mini);
+        }
+    }
+
+    fn is_mini(&self) -> bool {
+        self.window_state
+            .as_ref()
+            .is_some_and(|window_state| window_state.is_mini())
+    }
+
+    // true when there is no window or when it has the focus
+    fn is_focused(&self) -> bool {
+        self.window_state
+            .as_ref()
+            .is_none_or(|window_state| window_state.is_focused())
+    }
+
+    // true when nothing is drawn, after an error of the gpu or while the window does not have
+    // the focus, the mini viewer, the pipe, the remote modes and the scripts go on without it
+    fn is_paused(&self) -> bool {
+        if self.stopped_by_gpu_error {
+            return true;
+        }
+        self.config.display.pause_unfocused
+            && !self.is_focused()
+            && !self.is_mini()
+            && self.pipe.is_none()
+            && !self.states.serves_clients()
+            && self.states.time_step().is_none()
+            && self.demo_player.is_none()
+    }
+
+    // true if the id is the one of the window of the game
+    fn is_window(&self, window_id: WindowId) -> bool {
+        self.window_id() == window_id
+    }
+
+    // the id of the window, a dummy one without a window so a demo is replayed headless
+    fn window_id(&self) -> WindowId {
+        match &self.window {
+            Some(window) => window.id(),
+            // the dummy id is only compared, never given to winit
+            None => unsafe { WindowId::dummy() },
+        }
+    }
+
+    // true when the image does not need to be updated often,
+    // the mini viewer, the pipe and the remote modes are meant to run without the focus
+    // so they are never idle
+    fn is_idle(&self) -> bool {
+        !self.is_mini()
+            && self.pipe.is_none()
+            && !self.states.serves_clients()
+            && (!self.is_focused() || self.last_activity.elapsed() > Duration::from_secs(1))
+    }
+
+    // the time between two frames, none to draw them as fast as possible
+    fn frame_duration(&self) -> Option<Duration> {
+        let fps = if self.is_mini() {
+            self.config.mini_viewer.fps
+        } else if self.is_idle() {
+            self.config.display.idle_fps
+        } else if self.config.display.uncapped {
+            return None;
+        } else {
+            self.config.display.fps
+        };
+        Some(Duration::from_secs_f32(1.0 / fps as f32))
+    }
+
+    fn apply_present_mode(engine: &mut Engine, config: &Config) {
+        if let Some(present_mode) = config.display.present_mode {
+            if let Err(error) = engine.set_present_mode(present_mode.into()) {
+                eprintln!("Impossible to use the present mode {:?}: {}", present_mode, error);
+            }
+        }
+    }
+
+    fn apply_post_effects(engine: &mut Engine, config: &Config) {
+        let passes = config
+            .display
+            .post_effects
+            .iter()
+            .map(|effect| (*effect).into())
+            .collect::<Vec<_>>();
+        engine.set_post_passes(&passes);
+    }
+
+    // compile the shader file again, the current one is kept if it is invalid
+    fn reload_shader(&mut self) -> bool {
+        let path = match &self.shader_watcher {
+            Some(watcher) => watcher.path(),
+            None => return false,
+        };
+        let result = std::fs::read_to_string(path)
+            .map_err(|error| error.to_string())
+            .and_then(|source| self.engine.reload_shader(with_structs(&source)));
+        if let Err(error) = &result {
+            eprintln!("Invalid shader {}: {}", path.display(), error);
+            self.show_gpu_error(format!("invalid shader: {}", error));
+        }
+        result.is_ok()
+    }
+
+    // draw another fractal, the shader file only replaces the one of the mandelbrot set
+    fn set_fractal(&mut self, fractal: Fractal) {
+        // the cpu renderer only knows the mandelbrot set
+        if self.engine.is_cpu() {
+            if fractal != Fractal::Mandelbrot {
+                eprintln!("The {:?} fractal can not be computed by the cpu", fractal);
+            }
+            return;
+        }
+        self.fractal = fractal;
+        if fractal != Fractal::Mandelbrot || !self.reload_shader() {
+            let result = fractal
+                .shader(&self.formula)
+                .and_then(|source| self.engine.reload_shader(source));
+            if let Err(error) = result {
+                eprintln!("Invalid shader of the {:?} fractal: {}", fractal, error);
+                self.show_gpu_error(format!("invalid shader: {}", error));
+            }
         }
+        self.mandelbrot_state.invalidate();
+    }
+
+    // apply the settings of a reloaded config file
+    fn apply_config(&mut self, config: Config) {
+        if config.display.present_mode != self.config.display.present_mode {
+            Self::apply_present_mode(&mut self.engine, &config);
+        }
+        if config.display.post_effects != self.config.display.post_effects {
+            Self::apply_post_effects(&mut self.engine, &config);
+        }
+        if config.display.color_levels() != self.config.display.color_levels() {
+            self.engine.set_color_levels(config.display.color_levels());
+        }
+        if config.display.minimum_resolution != self.config.display.minimum_resolution {
+            self.mandelbrot_state
+                .set_minimum_resolution(&mut self.engine, config.display.minimum_resolution);
+        }
+        if config.display.target_frame_time != self.config.display.target_frame_time {
+            self.mandelbrot_state
+                .set_target_frame_time(config.display.target_frame_time);
+        }
+        if config.display.iteration_slice != self.config.display.iteration_slice {
+            self.mandelbrot_state
+                .set_iteration_slice(config.display.iteration_slice);
+        }
+        if config.display.color_cycling_speed != self.config.display.color_cycling_speed {
+            self.mandelbrot_state
+                .set_color_cycling_speed(config.display.color_cycling_speed);
+        }
+        if config.display.auto_palette_scale != self.config.display.auto_palette_scale {
+            self.mandelbrot_state
+                .set_auto_palette_scale(config.display.auto_palette_scale);
+        }
+        if config.display.area_in_title != self.config.display.area_in_title {
+            let measures_area =
+                config.display.area_in_title || self.pipe.is_some() || self.states.serves_clients();
+            self.mandelbrot_state.set_measures_area(measures_area);
+        }
+        if config.reference != self.config.reference {
+            self.mandelbrot_state
+                .set_reference_config(config.reference.clone());
+        }
+        if config.comparison != self.config.comparison {
+            self.mandelbrot_state
+                .set_comparison(config.comparison.clone());
+        }
+        if config.gif != self.config.gif && self.window.is_some() {
+            self.engine.set_gif_config(&config.gif);
+        }
+        if !config.display.location_in_title && self.config.display.location_in_title {
+            if let Some(window_state) = self.window_state.as_mut() {
+                window_state.reset_title();
+            }
+        }
+        if config.display.dither != self.config.display.dither {
+            self.mandelbrot_state
+                .set_dither(&self.engine, config.display.dither);
+        }
+        self.mandelbrot_state
+            .set_navigation(config.navigation.clone());
+        self.states.set_config(&config);
+        if config.fractal.kind != self.config.fractal.kind {
+            self.set_fractal(config.fractal.kind);
+        }
+        if config.fractal.formula != self.config.fractal.formula && !self.formula_from_options {
+            self.formula = config.fractal.formula.clone();
+            if self.fractal == Fractal::Custom {
+                self.set_fractal(Fractal::Custom);
+            }
+        }
+        if config.fractal.newton_polynomial != self.config.fractal.newton_polynomial {
+            self.mandelbrot_state
+                .set_newton_polynomial(&config.fractal.newton_polynomial);
+        }
+        self.config = config;
     }
 
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
@@ -69,60 +885,257 @@ impl Game {
         }
     }
 
+    // put a state over the explorer and the states already pushed, it gets the events first
+    fn push_state(&mut self, state: Box<dyn GameState>) {
+        self.states.push(state);
+    }
+
+    // fly through the set once nobody has used the explorer for the minutes of the config,
+    // unless something else drives it
+    fn start_screensaver_if_idle(&mut self) {
+        let idle_minutes = self.config.screensaver.idle_minutes;
+        if idle_minutes <= 0.0
+            || self.window.is_none()
+            || self.states.captures_input()
+            || self.states.is_driving()
+            || self.is_mini()
+            || self.pipe.is_some()
+            || self.states.serves_clients()
+            || self.demo_player.is_some()
+            || self.last_input.elapsed() < Duration::from_secs_f32(idle_minutes * 60.0)
+        {
+            return;
+        }
+        self.push_state(Box::new(ScreensaverState::new(
+            self.mandelbrot_state.location(),
+            self.config.screensaver.flight_duration,
+        )));
+    }
+
+    // push and pop the states as they ask, and run the commands they give,
+    // the status of a command is sent back to the state which asked for it
+    fn apply_transitions(&mut self) {
+        let mut control_flow = ControlFlow::Poll;
+        for (command, reply) in self.states.transitions() {
+            let mut status = self.execute(&command, &mut control_flow);
+            if let Some(reply) = reply {
+                status["status"] = "ok".into();
+                status["command"] = command.name().into();
+                // the client may be disconnected already
+                let _ = reply.send(status);
+            }
+        }
+        if control_flow == ControlFlow::Exit {
+            self.exit_requested = true;
+        }
+    }
+
     pub fn input(&mut self, event: Event<()>, control_flow: &mut ControlFlow) {
-        self.window_state.input(&event, &mut self.engine);
-        self.mandelbrot_state.input(&event, &mut self.engine);
+        // enter only draws the frames again after an error, instead of resetting the view
+        let is_enter = matches!(
+            event,
+            Event::WindowEvent {
+                event: WindowEvent::KeyboardInput {
+                    input: KeyboardInput {
+                        virtual_keycode: Some(VirtualKeyCode::Return),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                    ..
+                },
+                ..
+            }
+        );
+        if self.stopped_by_gpu_error && is_enter {
+            self.draw_again();
+            return;
+        }
+        let is_user_input = matches!(
+            event,
+            Event::WindowEvent {
+                event: WindowEvent::KeyboardInput { .. }
+                    | WindowEvent::MouseInput { .. }
+                    | WindowEvent::MouseWheel { .. }
+                    | WindowEvent::CursorMoved { .. },
+                ..
+            }
+        );
+        if let (Some(recorder), Event::WindowEvent { event, .. }) =
+            (self.demo_recorder.as_mut(), &event)
+        {
+            if let Err(error) = recorder.record(event) {
+                eprintln!("Impossible to record the demo: {}", error);
+                self.demo_recorder = None;
+            }
+        }
+        // the frames are shown at full speed as soon as the user does something
+        if is_user_input {
+            self.last_activity = Instant::now();
+            self.last_input = Instant::now();
+        }
+        // the keys and the mouse captured by a state, like a menu, do not reach the explorer
+        let captured = self.states.input(&event, &mut self.engine) && is_user_input;
+        self.apply_transitions();
+        // nor the click moving the mini viewer
+        let moves_window = self
+            .window_state
+            .as_ref()
+            .is_some_and(|window_state| window_state.moves_window(&event));
+        if let Some(window_state) = self.window_state.as_mut() {
+            window_state.input(&event, &mut self.engine);
+        }
+        if !captured && !moves_window {
+            self.mandelbrot_state.input(&event, &mut self.engine);
+        }
         match event {
-            Event::RedrawRequested(window_id) if window_id == self.window.id() => {
-                self.update();
+            Event::RedrawRequested(window_id) if self.is_window(window_id) => {
+                // the frame asked by the system while paused is drawn again as it was
+                if !self.is_paused() {
+                    self.last_frame_time = self.frame_pacer.start_frame();
+                    self.update();
+                }
                 match self.render() {
                     Ok(_) => {}
                     // Reconfigure the surface if lost
-                    Err(wgpu::SurfaceError::Lost) => self.resize(self.window.inner_size()),
+                    Err(wgpu::SurfaceError::Lost) => {
+                        if let Some(window) = self.window.clone() {
+                            self.resize(window.inner_size());
+                        }
+                    }
                     // The system is out of memory, we should probably quit
                     Err(wgpu::SurfaceError::OutOfMemory) => *control_flow = ControlFlow::Exit,
                     // All other errors (Outdated, Timeout) should be resolved by the next frame
                     Err(e) => eprintln!("{:?}", e),
                 }
             }
+            // every way of quitting the explorer ends the event loop
+            Event::LoopDestroyed => self.save_session(),
+            // wait for the focus to come back without drawing anything
+            Event::MainEventsCleared if self.is_paused() => {
+                *control_flow = ControlFlow::Wait;
+            }
             Event::MainEventsCleared => {
-                // this is the time between screen updates
-                let time_between_screen_updates = Duration::from_millis(1000 / 120);
-                // this is the time between the last screen update and now
-                let time_since_last_screen_update = Instant::now() - self.last_screen_update;
-                self.last_frame_time = time_since_last_screen_update;
-                self.last_screen_update = Instant::now();
-                // this is the time until the next screen update
-                // if the time since the last screen update is greater than the time between screen updates
-                if time_since_last_screen_update < time_between_screen_updates {
-                    // if the time since the last screen update is less than the time between screen updates
-                    // then we need to wait until the next screen update
-                    // so we set the time until the next screen update
-                    let time_until_next_screen_update =
-                        time_between_screen_updates - time_since_last_screen_update;
-                    // update the last screen update time
-                    if time_until_next_screen_update > Duration::from_millis(0) {
-                        // and we set the control flow to wait until the next screen update
-                        *control_flow =
-                            ControlFlow::WaitUntil(Instant::now() + time_until_next_screen_update);
+                *control_flow = match self.frame_pacer.pace(self.frame_duration()) {
+                    Pace::Redraw => {
+                        if let Some(window) = &self.window {
+                            window.request_redraw();
+                        }
+                        ControlFlow::Poll
                     }
+                    Pace::WaitUntil(time) => ControlFlow::WaitUntil(time),
+                };
+                // read the commands last, so a quit is not overridden by the frame pacing
+                self.read_pipe(control_flow);
+                if self.exit_requested {
+                    *control_flow = ControlFlow::Exit;
                 }
-                // request a redraw
-                self.window.request_redraw();
             }
             Event::WindowEvent {
                 ref event,
                 window_id,
-            } if window_id == self.window.id() => match event {
+            } if self.is_window(window_id) => match event {
+                WindowEvent::Focused(_) => {
+                    self.last_activity = Instant::now();
+                    // the time spent paused is not a frame, the camera goes on from where it stopped
+                    self.frame_pacer.reset();
+                }
                 WindowEvent::Resized(physical_size) => {
                     self.resize(*physical_size);
                 }
+                WindowEvent::DroppedFile(path) => self.open_dropped_file(path),
+                WindowEvent::ModifiersChanged(modifiers) => self.modifiers = *modifiers,
                 WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
                     self.resize(**new_inner_size);
                 }
-                // when the escape key is pressed exit the program
-                WindowEvent::CloseRequested
-                | WindowEvent::KeyboardInput {
+                WindowEvent::KeyboardInput { .. } if captured => {}
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::F10),
+                            ..
+                        },
+                    ..
+                } => self.toggle_mini_viewer(),
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::F9),
+                            ..
+                        },
+                    ..
+                } => self.save_gif(),
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::Y),
+                            ..
+                        },
+                    ..
+                } => self.restore_recovered(),
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::K),
+                            ..
+                        },
+                    ..
+                } => self.copy_location_code(),
+                // control, shift and c to copy the frame, c alone changes the coloring
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::C),
+                            ..
+                        },
+                    ..
+                } if self.modifiers.ctrl() && self.modifiers.shift() => self.copy_frame(),
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::L),
+                            ..
+                        },
+                    ..
+                } => self.paste_location_code(),
+                // 1 and 2 for the gamma, 3 and 4 for the brightness, 5 and 6 for the contrast
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode:
+                                Some(
+                                    keycode @ (VirtualKeyCode::Key1
+                                    | VirtualKeyCode::Key2
+                                    | VirtualKeyCode::Key3
+                                    | VirtualKeyCode::Key4
+                                    | VirtualKeyCode::Key5
+                                    | VirtualKeyCode::Key6),
+                                ),
+                            ..
+                        },
+                    ..
+                } => self.adjust_color_level(*keycode),
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::F),
+                            ..
+                        },
+                    ..
+                } => {
+                    let fractal = self.fractal.next();
+                    eprintln!("Fractal: {:?}", fractal);
+                    self.set_fractal(fractal);
+                }
+                // escape opens the pause menu, which gets the keys until it is closed
+                WindowEvent::KeyboardInput {
                     input:
                         KeyboardInput {
                             state: ElementState::Pressed,
@@ -130,7 +1143,11 @@ impl Game {
                             ..
                         },
                     ..
-                } => *control_flow = ControlFlow::Exit,
+                } => self.push_state(Box::new(PauseMenuState::new(
+                    self.window.clone(),
+                    self.config_watcher.path().to_path_buf(),
+                ))),
+                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
                 _ => {}
             },
 
@@ -138,14 +1155,359 @@ impl Game {
         }
     }
 
+    // render frames without a window until the script, the pipe or the requested frames are done
+    // the last frame is saved to the output file when the frames are neither scripted nor piped
+    pub fn run_headless(&mut self, frames: u32, output: PathBuf) {
+        let delta_time = Duration::from_secs_f32(1.0 / 60.0);
+        let mut control_flow = ControlFlow::Poll;
+        let mut frame = 0;
+        loop {
+            let is_replaying = self.demo_player.is_some();
+            let is_scripted = self.states.time_step().is_some() || is_replaying;
+            let is_piped = self.pipe.is_some() || self.states.serves_clients();
+            if !is_scripted && !is_piped && frame + 1 == frames {
+                self.request_screenshot(output.clone());
+            }
+            self.last_frame_time = delta_time;
+            self.update();
+            // the view reached at the end of a demo is saved, to be compared with the one of another run
+            if is_replaying && self.demo_player.is_none() {
+                self.request_screenshot(output.clone());
+            }
+            if let Err(error) = self.render() {
+                eprintln!("{:?}", error);
+                return;
+            }
+            frame += 1;
+            self.read_pipe(&mut control_flow);
+            if control_flow == ControlFlow::Exit || self.exit_requested {
+                return;
+            }
+            // the players are dropped once all the frames of the script or the demo are rendered
+            if is_scripted && self.states.time_step().is_none() && self.demo_player.is_none() {
+                return;
+            }
+            if !is_scripted && !is_piped && frame >= frames {
+                return;
+            }
+            if is_piped {
+                // wait for the next commands without using all the gpu
+                std::thread::sleep(delta_time);
+            }
+        }
+    }
+
+    // render every waypoint of the tour to a png file of the directory, at the given size
+    // or at the size and with the iterations of the waypoint when the tour file has them
+    pub fn run_gallery(
+        &mut self,
+        tour: &Path,
+        directory: &Path,
+        size: winit::dpi::PhysicalSize<u32>,
+    ) {
+        if let Err(error) = std::fs::create_dir_all(directory) {
+            eprintln!("Impossible to create {}: {}", directory.display(), error);
+            return;
+        }
+        let waypoints = tour_state::load_waypoints(tour);
+        if waypoints.is_empty() {
+            eprintln!("No waypoint to render in the tour");
+            return;
+        }
+        self.last_frame_time = Duration::from_secs_f32(1.0 / 60.0);
+        for (index, waypoint) in waypoints.iter().enumerate() {
+            let size = winit::dpi::PhysicalSize::new(
+                waypoint.width.unwrap_or(size.width),
+                waypoint.height.unwrap_or(size.height),
+            );
+            if size.width == 0
+                || size.height == 0
+                || !MandelbrotState::supports_size(&self.engine, size)
+            {
+                eprintln!(
+                    "Waypoint {} skipped, the gpu can not render {}x{} frames",
+                    index + 1,
+                    size.width,
+                    size.height
+                );
+                continue;
+            }
+            self.resize(size);
+            self.mandelbrot_state.resize(size);
+            self.mandelbrot_state.set_location(waypoint.location.clone());
+            self.mandelbrot_state.set_fixed_iterations(waypoint.iterations);
+            let path = directory.join(format!("waypoint_{:03}.png", index + 1));
+            self.request_screenshot(path.clone());
+            self.update();
+            if let Err(error) = self.render() {
+                eprintln!("{:?}", error);
+                return;
+            }
+            eprintln!("{} rendered", path.display());
+        }
+    }
+
+    // zoom into a known location for the given number of frames, with a fixed time step,
+    // then print the frame times and the number of iterations of the rendered pixels
+    pub fn run_benchmark(&mut self, frames: u32) {
+        self.push_state(Box::new(ScriptState::new(KeyframePlayer::new(
+            KeyframeScript::benchmark(frames),
+            None,
+        ))));
+        self.last_frame_time = Duration::from_secs_f32(1.0 / 60.0);
+        let mut frame_times = Vec::with_capacity(frames as usize);
+        let mut iterations = 0.0;
+        for _ in 0..frames {
+            let start = Instant::now();
+            self.update();
+            if let Err(error) = self.render() {
+                eprintln!("{:?}", error);
+                return;
+            }
+            // the frame is only done once the gpu has drawn it
+            self.engine.wait();
+            frame_times.push(start.elapsed());
+            match self.mandelbrot_state.rendered_iterations(&self.engine) {
+                Ok(count) => iterations += count,
+                Err(error) => eprintln!("Impossible to read the iterations: {}", error),
+            }
+        }
+        frame_times.sort();
+        let total: Duration = frame_times.iter().sum();
+        let milliseconds = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        let p99 = frame_times[(frame_times.len() * 99).div_ceil(100).max(1) - 1];
+        let size = self.engine.size();
+        println!("Benchmark: {} frames of {}x{}", frames, size.width, size.height);
+        println!("  min: {:.3} ms", milliseconds(frame_times[0]));
+        println!("  avg: {:.3} ms", milliseconds(total) / frames as f64);
+        println!("  p99: {:.3} ms", milliseconds(p99));
+        println!("  total: {:.3} s", total.as_secs_f64());
+        println!("  iterations: {:.0}", iterations);
+    }
+
+    // execute the commands received on stdin and write their status to stdout
+    fn read_pipe(&mut self, control_flow: &mut ControlFlow) {
+        let pipe = match self.pipe.take() {
+            Some(pipe) => pipe,
+            None => return,
+        };
+        while let Some(command) = pipe.try_recv() {
+            match command {
+                Ok(command) => {
+                    let mut status = self.execute(&command, control_flow);
+                    status["status"] = "ok".into();
+                    status["command"] = command.name().into();
+                    pipe.respond(status);
+                    // the screenshot is taken on the next render, wait for it before going on
+                    if let Command::Screenshot { .. } = command {
+                        break;
+                    }
+                }
+                Err(error) => pipe.respond(serde_json::json!({
+                    "status": "error",
+                    "error": error,
+                })),
+            }
+        }
+        self.pipe = Some(pipe);
+    }
+
+    // execute a command and return the resulting location
+    pub fn execute(&mut self, command: &Command, control_flow: &mut ControlFlow) -> serde_json::Value {
+        match command {
+            Command::Goto {
+                x,
+                y,
+                zoom,
+                duration,
+            } => {
+                // the coordinates have been checked when the command was parsed
+                let x = command::parse_coordinate(x).unwrap();
+                let y = command::parse_coordinate(y).unwrap();
+                match duration {
+                    Some(duration) if *duration > 0.0 => {
+                        let current = self.mandelbrot_state.location();
+                        self.mandelbrot_state.animate_to(
+                            Location {
+                                x,
+                                y,
+                                zoom: zoom.unwrap_or(current.zoom),
+                                angle: current.angle,
+                            },
+                            *duration,
+                        );
+                    }
+                    _ => self.mandelbrot_state.goto(x, y, *zoom),
+                }
+            }
+            Command::Zoom { zoom } => self.mandelbrot_state.set_zoom(*zoom),
+            Command::Reset => self.mandelbrot_state.reset_view(),
+            Command::Explore { .. } => self.states.execute(command),
+            Command::Palette {
+                color_palette_scale,
+            } => self
+                .mandelbrot_state
+                .set_color_palette_scale(*color_palette_scale),
+            Command::Screenshot { path } => self.request_screenshot(PathBuf::from(path)),
+            Command::Exr { path } => {
+                if let Err(error) = self.mandelbrot_state.export_exr(&self.engine, Path::new(path)) {
+                    eprintln!("Impossible to export {}: {}", path, error);
+                }
+            }
+            Command::Open { path } => self.open_kfr(Path::new(path)),
+            Command::Save { path } => {
+                let location = self.mandelbrot_state.location();
+                let iterations = self.mandelbrot_state.maximum_iterations();
+                let seed = self.mandelbrot_state.palette_seed();
+                match kfr::save(Path::new(path), &location, iterations, seed) {
+                    // the thumbnail shows the bookmark in the gallery
+                    Ok(()) if bookmarks::is_bookmark(Path::new(path)) => {
+                        let thumbnail = bookmarks::thumbnail_path(Path::new(path));
+                        self.engine
+                            .request_thumbnail(thumbnail, bookmarks::THUMBNAIL_SIDE);
+                    }
+                    Ok(()) => {}
+                    Err(error) => eprintln!("Impossible to save {}: {}", path, error),
+                }
+            }
+            Command::Status => {}
+            Command::Quit => *control_flow = ControlFlow::Exit,
+        }
+        self.mandelbrot_state.status()
+    }
+
     pub fn update(&mut self) {
-        let delta_time = self.last_frame_time.as_secs_f32();
-        self.window_state.update(&mut self.engine, delta_time);
-        self.mandelbrot_state.update(&mut self.engine, delta_time);
+        profiling::scope!("Game::update");
+        if let Some(config) = self.config_watcher.poll() {
+            self.apply_config(config);
+        }
+        if let Some(watcher) = self.shader_watcher.as_mut() {
+            if watcher.changed() && self.fractal == Fractal::Mandelbrot && self.reload_shader() {
+                eprintln!("Shader reloaded");
+                // the error of the previous version is fixed
+                if self.stopped_by_gpu_error {
+                    self.draw_again();
+                }
+            }
+        }
+        self.replay_demo();
+        self.poll_copied_frame();
+        let mut delta_time = self.last_frame_time.as_secs_f32();
+        if self.demo_recorder.is_some() || self.demo_player.is_some() {
+            delta_time = DEMO_DELTA_TIME;
+        }
+        // a script is played with a fixed time step, so it renders the same way on every machine
+        if let Some(time_step) = self.states.time_step() {
+            delta_time = time_step;
+        }
+        if let Some(window_state) = self.window_state.as_mut() {
+            let location = (self.config.display.location_in_title && !self.states.shows_title())
+                .then(|| self.mandelbrot_state.location());
+            // the period of the minibrot at the center is searched only while it is written
+            let minibrot = (location.is_some() && self.fractal == Fractal::Mandelbrot)
+                .then(|| self.mandelbrot_state.minibrot())
+                .flatten();
+            let area_statistics = (location.is_some() && self.config.display.area_in_title)
+                .then(|| self.mandelbrot_state.area_statistics())
+                .flatten();
+            window_state.set_location(location);
+            window_state.set_minibrot(minibrot);
+            window_state.set_area_statistics(area_statistics);
+            window_state.set_depth_warning(
+                (self.fractal == Fractal::Mandelbrot)
+                    .then(|| self.mandelbrot_state.depth_warning())
+                    .flatten(),
+            );
+            window_state.set_orbit_progress(
+                (self.fractal == Fractal::Mandelbrot)
+                    .then(|| self.mandelbrot_state.orbit_progress())
+                    .flatten(),
+            );
+            window_state.update(&mut self.engine, delta_time);
+        }
+        self.start_screensaver_if_idle();
+        self.states.update(&mut self.engine, delta_time);
+        self.states
+            .drive(&mut self.mandelbrot_state, &mut self.engine, delta_time);
+        // the commands of the states, like the screenshots of a script, apply to this frame
+        self.apply_transitions();
+        // the view stays as it is under the pause menu, the camera and the colors do not move
+        if !self.states.pauses_simulation() {
+            let update_start = Instant::now();
+            self.mandelbrot_state.update(&mut self.engine, delta_time);
+            self.engine.set_cpu_time(update_start.elapsed());
+        }
+        self.engine.set_orbit_stats(
+            self.mandelbrot_state.maximum_iterations(),
+            self.mandelbrot_state.orbit_length(),
+        );
+        if !self.mandelbrot_state.is_still()
+            || self.mandelbrot_state.is_refining()
+            || self.states.is_driving()
+        {
+            self.last_activity = Instant::now();
+        }
+        if let Some(recorder) = self.demo_recorder.as_mut() {
+            recorder.next_frame();
+        }
+        if let Some(recovery) = &self.recovery {
+            recovery.update(self.current_session());
+        }
         self.engine.update();
     }
 
+    // give the inputs of the demo recorded before this frame, as if the user had just made them
+    fn replay_demo(&mut self) {
+        let inputs = match self.demo_player.as_mut().map(|player| player.next_frame()) {
+            Some(Some(inputs)) => inputs,
+            Some(None) => {
+                eprintln!("Demo finished");
+                self.demo_player = None;
+                return;
+            }
+            None => return,
+        };
+        let window_id = self.window_id();
+        let mut control_flow = ControlFlow::Poll;
+        for input in inputs {
+            self.input(input.to_event(window_id), &mut control_flow);
+        }
+        if control_flow == ControlFlow::Exit {
+            self.exit_requested = true;
+        }
+    }
+
     pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-        self.engine.render()
+        if self.engine.is_cpu() {
+            self.mandelbrot_state.render_cpu(&mut self.engine);
+        }
+        self.engine.render()?;
+        self.mandelbrot_state.end_frame();
+        for error in self.engine.take_gpu_errors() {
+            eprintln!("Gpu error {}", error);
+            self.show_gpu_error(error);
+        }
+        Ok(())
+    }
+
+    // write an error of the gpu in the title and stop drawing the frames, so it is not repeated
+    // at each frame, until Enter is pressed, without a window the frames go on
+    fn show_gpu_error(&mut self, error: String) {
+        if let Some(window_state) = self.window_state.as_mut() {
+            window_state.set_gpu_error(Some(error));
+            self.stopped_by_gpu_error = true;
+        }
+    }
+
+    // draw the frames again after an error of the gpu,
+    // the errors of the frames drawn again as they were meanwhile are dropped
+    fn draw_again(&mut self) {
+        self.stopped_by_gpu_error = false;
+        self.engine.take_gpu_errors();
+        // the time spent stopped is not a frame, the camera goes on from where it stopped
+        self.frame_pacer.reset();
+        if let Some(window_state) = self.window_state.as_mut() {
+            window_state.set_gpu_error(None);
+        }
     }
 }
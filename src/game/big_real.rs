@@ -0,0 +1,193 @@
+use num_bigfloat::BigFloat;
+
+// the arbitrary precision numbers the reference orbit is computed with
+// num_bigfloat is the default, it is pure rust but limited to 40 digits and slow,
+// the `rug` feature uses GMP through MPFR and the `dashu` feature a pure rust crate,
+// both with PRECISION bits
+// the coordinates of the locations and of the commands are numbers of this type too,
+// written as decimal texts with all their digits, so the locations saved keep the precision
+// of the backend
+// only the numbers of num_bigfloat are Copy, the others are cloned with Real::clone,
+// and the methods of BigReal have names the numbers do not have, so they are not hidden by theirs
+#[cfg(feature = "rug")]
+pub type Real = rug::Float;
+#[cfg(all(feature = "dashu", not(feature = "rug")))]
+pub type Real = dashu_float::FBig;
+#[cfg(not(any(feature = "rug", feature = "dashu")))]
+pub type Real = BigFloat;
+
+// the number of bits of the mantissa of the rug and dashu numbers
+#[cfg(any(feature = "rug", feature = "dashu"))]
+const PRECISION: u32 = 256;
+
+// the number of decimal digits written for the dashu numbers, enough to read the same bits back
+#[cfg(feature = "dashu")]
+const DECIMAL_DIGITS: usize = 79;
+
+// the smallest difference between two numbers of the precision of Real, relatively to them
+#[cfg(any(feature = "rug", feature = "dashu"))]
+// 2^-PRECISION
+pub const RELATIVE_PRECISION: f64 = 8.6e-78;
+#[cfg(not(any(feature = "rug", feature = "dashu")))]
+pub const RELATIVE_PRECISION: f64 = 1e-39;
+
+// the significant decimal digits kept by Real, the next ones of a text are dropped
+#[cfg(any(feature = "rug", feature = "dashu"))]
+pub const SIGNIFICANT_DIGITS: usize = 77;
+#[cfg(not(any(feature = "rug", feature = "dashu")))]
+pub const SIGNIFICANT_DIGITS: usize = 40;
+
+pub trait BigReal: Clone + PartialEq + Send + 'static {
+    fn from_double(value: f64) -> Self;
+    fn to_double(&self) -> f64;
+    fn plus(&self, other: &Self) -> Self;
+    fn minus(&self, other: &Self) -> Self;
+    fn times(&self, other: &Self) -> Self;
+    // a decimal number like -1.25e-3, none when it is not one
+    fn from_text(text: &str) -> Option<Self>;
+    // the decimal text of the number, with enough digits to parse the same number back
+    fn to_text(&self) -> String;
+
+    fn to_single(&self) -> f32 {
+        self.to_double() as f32
+    }
+}
+
+impl BigReal for BigFloat {
+    fn from_double(value: f64) -> Self {
+        BigFloat::from_f64(value)
+    }
+
+    fn to_double(&self) -> f64 {
+        BigFloat::to_f64(self)
+    }
+
+    fn plus(&self, other: &Self) -> Self {
+        *self + *other
+    }
+
+    fn minus(&self, other: &Self) -> Self {
+        *self - *other
+    }
+
+    fn times(&self, other: &Self) -> Self {
+        *self * *other
+    }
+
+    fn from_text(text: &str) -> Option<Self> {
+        BigFloat::parse(text).filter(|value| !value.is_nan() && !value.is_inf())
+    }
+
+    fn to_text(&self) -> String {
+        self.to_string()
+    }
+}
+
+// the double precision, enough for the shallow zooms
+impl BigReal for f64 {
+    fn from_double(value: f64) -> Self {
+        value
+    }
+
+    fn to_double(&self) -> f64 {
+        *self
+    }
+
+    fn plus(&self, other: &Self) -> Self {
+        self + other
+    }
+
+    fn minus(&self, other: &Self) -> Self {
+        self - other
+    }
+
+    fn times(&self, other: &Self) -> Self {
+        self * other
+    }
+
+    fn from_text(text: &str) -> Option<Self> {
+        text.parse().ok().filter(|value: &f64| value.is_finite())
+    }
+
+    // the shortest text read back as the same f64
+    fn to_text(&self) -> String {
+        format!("{:e}", self)
+    }
+}
+
+#[cfg(feature = "rug")]
+impl BigReal for rug::Float {
+    fn from_double(value: f64) -> Self {
+        rug::Float::with_val(PRECISION, value)
+    }
+
+    fn to_double(&self) -> f64 {
+        rug::Float::to_f64(self)
+    }
+
+    fn plus(&self, other: &Self) -> Self {
+        rug::Float::with_val(PRECISION, self + other)
+    }
+
+    fn minus(&self, other: &Self) -> Self {
+        rug::Float::with_val(PRECISION, self - other)
+    }
+
+    fn times(&self, other: &Self) -> Self {
+        rug::Float::with_val(PRECISION, self * other)
+    }
+
+    fn from_text(text: &str) -> Option<Self> {
+        let value = rug::Float::with_val(PRECISION, rug::Float::parse(text).ok()?);
+        value.is_finite().then_some(value)
+    }
+
+    // without a number of digits, MPFR writes enough of them to read the same bits back
+    fn to_text(&self) -> String {
+        self.to_string_radix(10, None)
+    }
+}
+
+// the result of an operation has the precision of its operands, so only the conversion sets it
+#[cfg(feature = "dashu")]
+impl BigReal for dashu_float::FBig {
+    fn from_double(value: f64) -> Self {
+        dashu_float::FBig::try_from(value)
+            .unwrap_or(dashu_float::FBig::ZERO)
+            .with_precision(PRECISION as usize)
+            .value()
+    }
+
+    fn to_double(&self) -> f64 {
+        dashu_float::FBig::to_f64(self).value()
+    }
+
+    fn plus(&self, other: &Self) -> Self {
+        self + other
+    }
+
+    fn minus(&self, other: &Self) -> Self {
+        self - other
+    }
+
+    fn times(&self, other: &Self) -> Self {
+        self * other
+    }
+
+    // read in base 10 then rounded to the bits of the precision
+    fn from_text(text: &str) -> Option<Self> {
+        let decimal: dashu_float::FBig<dashu_float::round::mode::Zero, 10> = text.parse().ok()?;
+        Some(
+            decimal
+                .with_base_and_precision::<2>(PRECISION as usize)
+                .value(),
+        )
+    }
+
+    fn to_text(&self) -> String {
+        self.clone()
+            .with_base_and_precision::<10>(DECIMAL_DIGITS)
+            .value()
+            .to_string()
+    }
+}
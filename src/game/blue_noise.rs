@@ -0,0 +1,85 @@
+// a tileable square of blue noise, where close pixels have values far apart,
+// used to dither the colors without visible patterns
+// it is built with the void and cluster method: each pixel is set in turn where the set pixels
+// are the farthest, and its value is the order in which it has been set
+use std::sync::OnceLock;
+
+// the side of the tile used to dither the colors
+pub const BLUE_NOISE_SIDE: u32 = 64;
+
+// the tile, generated the first time it is asked for, as it takes a while
+pub fn blue_noise() -> &'static [u8] {
+    static NOISE: OnceLock<Vec<u8>> = OnceLock::new();
+    NOISE.get_or_init(|| generate(BLUE_NOISE_SIDE as usize))
+}
+
+fn generate(side: usize) -> Vec<u8> {
+    let count = side * side;
+    // the weight of a set pixel on the others, depending on their distance, wrapped around the edges
+    let sigma = 1.5f32;
+    let mut weights = vec![0.0f32; count];
+    for y in 0..side {
+        for x in 0..side {
+            let dx = x.min(side - x) as f32;
+            let dy = y.min(side - y) as f32;
+            weights[y * side + x] = (-(dx * dx + dy * dy) / (2.0 * sigma * sigma)).exp();
+        }
+    }
+    // the closeness of each pixel to the set ones, starting with a small deterministic jitter
+    // so the equal energies of the first steps do not give a regular grid
+    let mut seed = 0x2545_f491u32;
+    let mut energy = (0..count)
+        .map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed as f32 / u32::MAX as f32 * 1e-3
+        })
+        .collect::<Vec<_>>();
+    let mut noise = vec![0u8; count];
+    let mut set = vec![false; count];
+    for rank in 0..count {
+        // the largest void, the unset pixel the farthest from the set ones
+        let index = (0..count)
+            .filter(|index| !set[*index])
+            .min_by(|a, b| energy[*a].total_cmp(&energy[*b]))
+            .unwrap();
+        set[index] = true;
+        noise[index] = (rank * 256 / count) as u8;
+        let (x, y) = (index % side, index / side);
+        for other_y in 0..side {
+            for other_x in 0..side {
+                let dx = (other_x + side - x) % side;
+                let dy = (other_y + side - y) % side;
+                energy[other_y * side + other_x] += weights[dy * side + dx];
+            }
+        }
+    }
+    noise
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_small_tile_is_a_permutation_of_the_ranks() {
+        // 256 pixels, one for each value
+        let mut noise = generate(16);
+        noise.sort();
+        assert_eq!(noise, (0..=255).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn the_tile_is_generated_once() {
+        let side = BLUE_NOISE_SIDE as usize;
+        let noise = blue_noise();
+        assert_eq!(noise.len(), side * side);
+        assert!(std::ptr::eq(noise, blue_noise()));
+        let mut counts = [0; 256];
+        for value in noise {
+            counts[*value as usize] += 1;
+        }
+        assert!(counts.iter().all(|count| *count == side * side / 256));
+    }
+}
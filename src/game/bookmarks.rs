@@ -0,0 +1,53 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::game::kfr;
+use crate::game::location::Location;
+
+// the bookmarks are Kalles Fraktaler files of the current directory,
+// bookmark_001.kfr, bookmark_002.kfr and so on,
+// each with a thumbnail of the view saved next to it, bookmark_001.png
+
+// the side in pixels of the square thumbnails
+pub const THUMBNAIL_SIDE: u32 = 256;
+
+// the first bookmark file that does not exist yet
+pub fn next_path() -> PathBuf {
+    (1..)
+        .map(|index| PathBuf::from(format!("bookmark_{:03}.kfr", index)))
+        .find(|path| !path.exists())
+        .unwrap()
+}
+
+pub fn is_bookmark(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    name.starts_with("bookmark_") && name.ends_with(".kfr")
+}
+
+// the thumbnail of a bookmark, which may not exist for the bookmarks saved before the thumbnails
+pub fn thumbnail_path(path: &Path) -> PathBuf {
+    path.with_extension("png")
+}
+
+// the bookmark files, in the order they were saved
+pub fn paths() -> Vec<PathBuf> {
+    let entries = match fs::read_dir(".") {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+    let mut paths = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_bookmark(path))
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
+
+// the locations of the bookmarks, the files that can not be read are left out
+pub fn load_all() -> Vec<Location> {
+    paths()
+        .iter()
+        .filter_map(|path| kfr::load(path).ok())
+        .collect()
+}
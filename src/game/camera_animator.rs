@@ -0,0 +1,115 @@
+use std::f32::consts::PI;
+
+use crate::game::big_real::{BigReal, Real};
+use crate::game::location::Location;
+
+// how the speed of the camera evolves during an animation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    // constant speed
+    Linear,
+    // start slowly
+    In,
+    // stop slowly
+    Out,
+    // start and stop slowly
+    InOut,
+}
+
+impl Easing {
+    pub fn apply(&self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::In => t * t,
+            Easing::Out => t * (2.0 - t),
+            Easing::InOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+// move the camera smoothly from a location to another in a given duration
+// the zoom is interpolated exponentially, so the zoom speed feels constant,
+// and the position follows the zoom, so the target stays at the same place on the screen
+pub struct CameraAnimator {
+    start: Location,
+    target: Location,
+    // the difference between the target and the start coordinates
+    offset: (Real, Real),
+    // the difference between the target and the start angles, by the shortest way
+    angle_offset: f32,
+    duration: f32,
+    elapsed: f32,
+    easing: Easing,
+    // go on past the target at the same rates instead of stopping on it
+    endless: bool,
+}
+
+impl CameraAnimator {
+    pub fn new(start: Location, target: Location, duration: f32) -> Self {
+        let mut angle_offset = (target.angle - start.angle) % (2.0 * PI);
+        if angle_offset > PI {
+            angle_offset -= 2.0 * PI;
+        } else if angle_offset < -PI {
+            angle_offset += 2.0 * PI;
+        }
+        Self {
+            offset: (target.x.minus(&start.x), target.y.minus(&start.y)),
+            start,
+            target,
+            angle_offset,
+            duration: duration.max(0.0),
+            elapsed: 0.0,
+            easing: Easing::InOut,
+            endless: false,
+        }
+    }
+
+    // turn and zoom at constant rates without end, around the center of the start location,
+    // the zoom is divided by e^zoom_speed and the angle grows by rotation_speed each second
+    pub fn endless(start: Location, zoom_speed: f32, rotation_speed: f32) -> Self {
+        let target = Location {
+            zoom: start.zoom * (-zoom_speed).exp(),
+            angle: start.angle + rotation_speed,
+            ..start.clone()
+        };
+        Self {
+            // the angle is not taken by the shortest way, the camera may turn faster than half a turn
+            angle_offset: rotation_speed,
+            endless: true,
+            ..Self::new(start, target, 1.0).with_easing(Easing::Linear)
+        }
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !self.endless && self.elapsed >= self.duration
+    }
+
+    // advance the animation by delta_time and return the location of the camera
+    pub fn advance(&mut self, delta_time: f32) -> Location {
+        self.elapsed += delta_time;
+        if self.is_finished() {
+            return self.target.clone();
+        }
+        let t = self.easing.apply(self.elapsed / self.duration);
+        let zoom = self.start.zoom * (self.target.zoom / self.start.zoom).powf(t);
+        // the share of the way done, relatively to the zoom
+        let progress = if (self.target.zoom - self.start.zoom).abs() > f32::EPSILON * self.start.zoom
+        {
+            (self.start.zoom - zoom) / (self.start.zoom - self.target.zoom)
+        } else {
+            t
+        };
+        let progress = Real::from_double(progress as f64);
+        Location {
+            x: self.start.x.plus(&self.offset.0.times(&progress)),
+            y: self.start.y.plus(&self.offset.1.times(&progress)),
+            zoom,
+            angle: self.start.angle + self.angle_offset * t,
+        }
+    }
+}
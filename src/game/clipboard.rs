@@ -0,0 +1,81 @@
+// the clipboard of the system, or the hash of the url of the page in the browser,
+// which can not read the clipboard without asking the user
+pub struct Clipboard {
+    // created the first time it is used, the copied text is lost when it is dropped on linux
+    #[cfg(not(target_arch = "wasm32"))]
+    clipboard: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            clipboard: None,
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn clipboard(&mut self) -> Result<&mut arboard::Clipboard, String> {
+        if self.clipboard.is_none() {
+            self.clipboard = Some(arboard::Clipboard::new().map_err(|error| error.to_string())?);
+        }
+        Ok(self.clipboard.as_mut().unwrap())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn copy(&mut self, text: &str) -> Result<(), String> {
+        self.clipboard()?
+            .set_text(text)
+            .map_err(|error| error.to_string())
+    }
+
+    // copy an image of rgba pixels, 4 bytes each from the top line
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn copy_image(&mut self, width: u32, height: u32, pixels: Vec<u8>) -> Result<(), String> {
+        let image = arboard::ImageData {
+            width: width as usize,
+            height: height as usize,
+            bytes: pixels.into(),
+        };
+        self.clipboard()?
+            .set_image(image)
+            .map_err(|error| error.to_string())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn paste(&mut self) -> Result<String, String> {
+        self.clipboard()?
+            .get_text()
+            .map_err(|error| error.to_string())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn copy(&mut self, text: &str) -> Result<(), String> {
+        web_sys::window()
+            .ok_or("there is no page")?
+            .location()
+            .set_hash(text)
+            .map_err(|error| format!("{:?}", error))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn copy_image(
+        &mut self,
+        _width: u32,
+        _height: u32,
+        _pixels: Vec<u8>,
+    ) -> Result<(), String> {
+        Err("the browser does not give the clipboard to copy an image".to_string())
+    }
+
+    // the hash of the url without the #, empty when there is none
+    #[cfg(target_arch = "wasm32")]
+    pub fn paste(&mut self) -> Result<String, String> {
+        web_sys::window()
+            .ok_or("there is no page")?
+            .location()
+            .hash()
+            .map(|hash| hash.trim_start_matches('#').to_string())
+            .map_err(|error| format!("{:?}", error))
+    }
+}
@@ -0,0 +1,272 @@
+use serde::Deserialize;
+
+use crate::game::big_real::{BigReal, Real};
+
+// a command that drive the explorer from outside of the window
+// it can be written as a line of words, like "goto -0.75 0.1 0.001 5",
+// or as a json object, like {"command": "goto", "x": "-0.75", "y": "0.1", "zoom": 0.001}
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    // move the center of the screen to the x and y coordinate, optionally at a given zoom
+    // and flying there in duration seconds
+    Goto {
+        x: String,
+        y: String,
+        zoom: Option<f32>,
+        duration: Option<f32>,
+    },
+    // set the zoom
+    Zoom { zoom: f32 },
+    // reset the zoom and the rotation, as the Enter key
+    Reset,
+    // start or stop exploring the detailed regions, as the X key
+    Explore { enabled: bool },
+    // set the scale of the color palette
+    Palette { color_palette_scale: f32 },
+    // save the next rendered frame to a png file
+    Screenshot { path: String },
+    // save the iterations and the derivatives of the last frame to an OpenEXR file
+    Exr { path: String },
+    // move to the location of a Kalles Fraktaler file
+    Open { path: String },
+    // save the current location to a Kalles Fraktaler file
+    Save { path: String },
+    // only report the current location
+    Status,
+    // exit the program
+    Quit,
+}
+
+impl Command {
+    // parse a command from a line of text
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        let command = if line.starts_with('{') {
+            serde_json::from_str(line).map_err(|error| error.to_string())?
+        } else {
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("goto") => Command::Goto {
+                    x: words.next().ok_or("missing x coordinate")?.to_string(),
+                    y: words.next().ok_or("missing y coordinate")?.to_string(),
+                    zoom: words
+                        .next()
+                        .map(|zoom| parse_number(zoom, "zoom"))
+                        .transpose()?,
+                    duration: words
+                        .next()
+                        .map(|duration| parse_number(duration, "duration"))
+                        .transpose()?,
+                },
+                Some("zoom") => Command::Zoom {
+                    zoom: parse_number(words.next().ok_or("missing zoom")?, "zoom")?,
+                },
+                Some("reset") => Command::Reset,
+                Some("explore") => Command::Explore {
+                    enabled: match words.next() {
+                        Some("on") => true,
+                        Some("off") => false,
+                        _ => return Err("explore must be followed by on or off".to_string()),
+                    },
+                },
+                Some("palette") => Command::Palette {
+                    color_palette_scale: parse_number(
+                        words.next().ok_or("missing color palette scale")?,
+                        "color palette scale",
+                    )?,
+                },
+                Some("screenshot") => Command::Screenshot {
+                    path: words.next().ok_or("missing path")?.to_string(),
+                },
+                Some("exr") => Command::Exr {
+                    path: words.next().ok_or("missing path")?.to_string(),
+                },
+                Some("open") => Command::Open {
+                    path: words.next().ok_or("missing path")?.to_string(),
+                },
+                Some("save") => Command::Save {
+                    path: words.next().ok_or("missing path")?.to_string(),
+                },
+                Some("status") => Command::Status,
+                Some("quit") => Command::Quit,
+                Some(word) => return Err(format!("unknown command: {}", word)),
+                None => return Err("empty command".to_string()),
+            }
+        };
+        command.validate()?;
+        Ok(command)
+    }
+
+    // check the values of the command before accepting it, whatever the way it is written,
+    // so the explorer is never given a zoom or a palette scale it can not draw
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Command::Goto {
+                x,
+                y,
+                zoom,
+                duration,
+            } => {
+                parse_coordinate(x)?;
+                parse_coordinate(y)?;
+                if let Some(zoom) = zoom {
+                    check_zoom(*zoom)?;
+                }
+                if let Some(duration) = duration {
+                    check_duration(*duration)?;
+                }
+            }
+            Command::Zoom { zoom } => check_zoom(*zoom)?,
+            Command::Palette {
+                color_palette_scale,
+            } => check_color_palette_scale(*color_palette_scale)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // the name of the command, as written in the status
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Goto { .. } => "goto",
+            Command::Zoom { .. } => "zoom",
+            Command::Reset => "reset",
+            Command::Explore { .. } => "explore",
+            Command::Palette { .. } => "palette",
+            Command::Screenshot { .. } => "screenshot",
+            Command::Exr { .. } => "exr",
+            Command::Open { .. } => "open",
+            Command::Save { .. } => "save",
+            Command::Status => "status",
+            Command::Quit => "quit",
+        }
+    }
+}
+
+// parse an arbitrary precision coordinate
+pub fn parse_coordinate(value: &str) -> Result<Real, String> {
+    Real::from_text(value).ok_or(format!("invalid coordinate: {}", value))
+}
+
+// parse a number of the command written as words, its value is checked with the command
+fn parse_number(value: &str, name: &str) -> Result<f32, String> {
+    value
+        .parse::<f32>()
+        .map_err(|_| format!("invalid {}: {}", name, value))
+}
+
+fn check_duration(duration: f32) -> Result<(), String> {
+    if duration >= 0.0 && duration.is_finite() {
+        Ok(())
+    } else {
+        Err(format!("invalid duration: {}", duration))
+    }
+}
+
+// the palette scale divides the iterations in the shader
+fn check_color_palette_scale(scale: f32) -> Result<(), String> {
+    if scale > 0.0 && scale.is_finite() {
+        Ok(())
+    } else {
+        Err(format!("invalid color palette scale: {}", scale))
+    }
+}
+
+fn check_zoom(zoom: f32) -> Result<(), String> {
+    if zoom > 0.0 && zoom.is_finite() {
+        Ok(())
+    } else {
+        Err(format!("invalid zoom: {}", zoom))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_words_and_the_json_give_the_same_commands() {
+        let goto = Command::Goto {
+            x: "-0.75".to_string(),
+            y: "0.1".to_string(),
+            zoom: Some(0.001),
+            duration: Some(5.0),
+        };
+        assert_eq!(Command::parse("goto -0.75 0.1 0.001 5"), Ok(goto.clone()));
+        assert_eq!(
+            Command::parse(
+                r#"{"command": "goto", "x": "-0.75", "y": "0.1", "zoom": 0.001, "duration": 5}"#
+            ),
+            Ok(goto)
+        );
+        let palette = Command::Palette {
+            color_palette_scale: 2.0,
+        };
+        assert_eq!(Command::parse("  palette 2 "), Ok(palette.clone()));
+        assert_eq!(
+            Command::parse(r#"{"command": "palette", "color_palette_scale": 2}"#),
+            Ok(palette)
+        );
+        assert_eq!(
+            Command::parse("explore on"),
+            Command::parse(r#"{"command": "explore", "enabled": true}"#)
+        );
+        assert_eq!(Command::parse("quit"), Ok(Command::Quit));
+        assert_eq!(Command::parse(r#"{"command": "quit"}"#), Ok(Command::Quit));
+    }
+
+    #[test]
+    fn the_optional_values_of_goto_can_be_left_out() {
+        let goto = Command::Goto {
+            x: "1".to_string(),
+            y: "-2e-30".to_string(),
+            zoom: None,
+            duration: None,
+        };
+        assert_eq!(Command::parse("goto 1 -2e-30"), Ok(goto.clone()));
+        assert_eq!(
+            Command::parse(r#"{"command": "goto", "x": "1", "y": "-2e-30"}"#),
+            Ok(goto)
+        );
+    }
+
+    #[test]
+    fn the_invalid_values_are_refused_in_both_syntaxes() {
+        for line in [
+            "zoom 0",
+            "zoom -1",
+            "zoom inf",
+            "zoom NaN",
+            "zoom a",
+            "palette 0",
+            "palette -2",
+            "goto 0 0 0",
+            "goto 0 0 1 -1",
+            "goto a 0",
+            "goto 0",
+            r#"{"command": "zoom", "zoom": 0}"#,
+            r#"{"command": "zoom", "zoom": -1}"#,
+            r#"{"command": "palette", "color_palette_scale": 0}"#,
+            r#"{"command": "palette", "color_palette_scale": -2}"#,
+            r#"{"command": "goto", "x": "0", "y": "0", "zoom": 0}"#,
+            r#"{"command": "goto", "x": "0", "y": "0", "duration": -1}"#,
+            r#"{"command": "goto", "x": "a", "y": "0"}"#,
+            r#"{"command": "goto", "x": "0"}"#,
+        ] {
+            assert!(Command::parse(line).is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn the_unknown_and_empty_commands_are_refused() {
+        assert_eq!(
+            Command::parse("fly 0 0"),
+            Err("unknown command: fly".to_string())
+        );
+        assert_eq!(Command::parse("   "), Err("empty command".to_string()));
+        assert!(Command::parse("explore maybe").is_err());
+        assert!(Command::parse(r#"{"command": "fly"}"#).is_err());
+        assert!(Command::parse("{not json").is_err());
+    }
+}
@@ -0,0 +1,944 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::game::engine::color_adjustment::ColorLevels;
+use crate::game::engine::post_process::PostPass;
+use crate::game::file_watcher::FileWatcher;
+use crate::game::formula;
+use crate::game::fractal::Fractal;
+use crate::game::mandelbrot::Coloring;
+use crate::game::newton;
+
+// the settings read from the config file
+// every value is optional, a missing value keeps its default
+#[derive(Debug, Clone, PartialEq, Deserialize, Default)]
+#[serde(default)]
+pub struct Config {
+    pub navigation: NavigationConfig,
+    pub mini_viewer: MiniViewerConfig,
+    pub tour: TourConfig,
+    pub explore: ExploreConfig,
+    pub screensaver: ScreensaverConfig,
+    pub fractal: FractalConfig,
+    pub display: DisplayConfig,
+    pub comparison: ComparisonConfig,
+    pub reference: ReferenceConfig,
+    pub gif: GifConfig,
+    pub growth: GrowthConfig,
+}
+
+// how the camera slows down once it is left alone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+    // the damping and the epsilons of the navigation config
+    Custom,
+    // stops almost at once, to position precisely
+    Snappy,
+    // slows down in about a second
+    Smooth,
+    // long decays, for the videos
+    Cinematic,
+}
+
+impl Easing {
+    pub fn next(self) -> Self {
+        match self {
+            Easing::Custom => Easing::Snappy,
+            Easing::Snappy => Easing::Smooth,
+            Easing::Smooth => Easing::Cinematic,
+            Easing::Cinematic => Easing::Custom,
+        }
+    }
+}
+
+// the share of the speeds kept after one second, and the speeds under which they stop
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EasingCurve {
+    pub damping: f32,
+    pub zoom_epsilon: f32,
+    pub rotate_epsilon: f32,
+    pub move_epsilon: f32,
+}
+
+// the constants that give the feel of the navigation
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct NavigationConfig {
+    // the zoom speed at startup, negative to zoom out
+    pub zoom_speed: f32,
+    // the number of iterations added each time the zoom is divided by 2.1
+    pub iteration_speed: u32,
+    // estimate the maximum iterations from the pixels that escaped or not in the last frames
+    // instead of the zoom, the iteration speed is then the smallest maximum
+    pub auto_iterations: bool,
+    // the curve of the decay of the camera at startup, custom to use the damping and the epsilons
+    // of this config, it can be changed while exploring
+    pub easing: Easing,
+    // the share of the speed kept after one second, the lower the faster the movement stops
+    pub damping: f32,
+    // the zoom acceleration under which the zoom stops
+    pub zoom_epsilon: f32,
+    // the rotation speed under which the rotation stops
+    pub rotate_epsilon: f32,
+    // the movement speed under which the movement stops
+    pub move_epsilon: f32,
+    // the zoom acceleration given by each mouse wheel tick
+    pub wheel_acceleration: f32,
+    // the multiplier of the wheel acceleration while shift is held, to browse quickly
+    pub wheel_fast_multiplier: f32,
+    // the multiplier of the wheel acceleration while control is held, to position precisely
+    pub wheel_slow_multiplier: f32,
+}
+
+impl Default for NavigationConfig {
+    fn default() -> Self {
+        Self {
+            zoom_speed: 0.5,
+            iteration_speed: 100,
+            auto_iterations: false,
+            easing: Easing::Custom,
+            damping: 0.05,
+            zoom_epsilon: 0.1,
+            rotate_epsilon: 0.001,
+            move_epsilon: 0.001,
+            wheel_acceleration: 2.0,
+            wheel_fast_multiplier: 10.0,
+            wheel_slow_multiplier: 0.1,
+        }
+    }
+}
+
+impl NavigationConfig {
+    // the damping and the epsilons of an easing, the ones of the config for the custom one
+    pub fn easing_curve(&self, easing: Easing) -> EasingCurve {
+        match easing {
+            Easing::Custom => EasingCurve {
+                damping: self.damping,
+                zoom_epsilon: self.zoom_epsilon,
+                rotate_epsilon: self.rotate_epsilon,
+                move_epsilon: self.move_epsilon,
+            },
+            Easing::Snappy => EasingCurve {
+                damping: 0.002,
+                zoom_epsilon: 0.3,
+                rotate_epsilon: 0.01,
+                move_epsilon: 0.01,
+            },
+            Easing::Smooth => EasingCurve {
+                damping: 0.05,
+                zoom_epsilon: 0.1,
+                rotate_epsilon: 0.001,
+                move_epsilon: 0.001,
+            },
+            Easing::Cinematic => EasingCurve {
+                damping: 0.5,
+                zoom_epsilon: 0.005,
+                rotate_epsilon: 0.00005,
+                move_epsilon: 0.00005,
+            },
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.zoom_speed.is_finite() {
+            return Err("navigation.zoom_speed must be a number".to_string());
+        }
+        if !(10..=10000).contains(&self.iteration_speed) {
+            return Err("navigation.iteration_speed must be between 10 and 10000".to_string());
+        }
+        if !(self.damping > 0.0 && self.damping < 1.0) {
+            return Err("navigation.damping must be between 0 and 1 excluded".to_string());
+        }
+        for (name, value) in [
+            ("zoom_epsilon", self.zoom_epsilon),
+            ("rotate_epsilon", self.rotate_epsilon),
+            ("move_epsilon", self.move_epsilon),
+        ] {
+            if !(value >= 0.0 && value.is_finite()) {
+                return Err(format!("navigation.{} must be positive", name));
+            }
+        }
+        for (name, value) in [
+            ("wheel_acceleration", self.wheel_acceleration),
+            ("wheel_fast_multiplier", self.wheel_fast_multiplier),
+            ("wheel_slow_multiplier", self.wheel_slow_multiplier),
+        ] {
+            if !(value > 0.0 && value.is_finite()) {
+                return Err(format!("navigation.{} must be strictly positive", name));
+            }
+        }
+        Ok(())
+    }
+}
+
+// the compact always on top window showing a slow dive
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct MiniViewerConfig {
+    // the size of the window in logical pixels
+    pub width: u32,
+    pub height: u32,
+    // the zoom speed of the dive
+    pub zoom_speed: f32,
+    // the number of frames rendered per second
+    pub fps: u32,
+    // let the mouse clicks go through the window to the one below, refused for now since
+    // the window could not get the focus back, nor be left
+    pub click_through: bool,
+}
+
+impl Default for MiniViewerConfig {
+    fn default() -> Self {
+        Self {
+            width: 320,
+            height: 320,
+            zoom_speed: 0.05,
+            fps: 30,
+            click_through: false,
+        }
+    }
+}
+
+impl MiniViewerConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width < 64 || self.height < 64 {
+            return Err("mini_viewer.width and mini_viewer.height must be at least 64".to_string());
+        }
+        if !self.zoom_speed.is_finite() {
+            return Err("mini_viewer.zoom_speed must be a number".to_string());
+        }
+        if !(1..=120).contains(&self.fps) {
+            return Err("mini_viewer.fps must be between 1 and 120".to_string());
+        }
+        if self.click_through {
+            return Err(
+                "mini_viewer.click_through is not supported, the mini viewer could not be left"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+// the waypoint tours
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TourConfig {
+    // the time in seconds to fly to a newly recorded waypoint
+    pub leg_duration: f32,
+    // the speed of the zoom of the cinematic mode, the zoom is divided by e^speed each second,
+    // negative to zoom out
+    pub cinematic_zoom_speed: f32,
+    // the speed of the rotation of the cinematic mode, in radians per second
+    pub cinematic_rotation_speed: f32,
+}
+
+impl Default for TourConfig {
+    fn default() -> Self {
+        Self {
+            leg_duration: 5.0,
+            cinematic_zoom_speed: 0.1,
+            cinematic_rotation_speed: 0.05,
+        }
+    }
+}
+
+impl TourConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.leg_duration > 0.0 && self.leg_duration.is_finite()) {
+            return Err("tour.leg_duration must be strictly positive".to_string());
+        }
+        for (name, value) in [
+            ("cinematic_zoom_speed", self.cinematic_zoom_speed),
+            ("cinematic_rotation_speed", self.cinematic_rotation_speed),
+        ] {
+            if !value.is_finite() {
+                return Err(format!("tour.{} must be a number", name));
+            }
+        }
+        Ok(())
+    }
+}
+
+// the exploration of the detailed regions without the user
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ExploreConfig {
+    // the time in seconds to fly to each new target
+    pub interval: f32,
+}
+
+impl Default for ExploreConfig {
+    fn default() -> Self {
+        Self { interval: 4.0 }
+    }
+}
+
+impl ExploreConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.interval > 0.0 && self.interval.is_finite()) {
+            return Err("explore.interval must be strictly positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+// the flight through the set started when nobody uses the explorer
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ScreensaverConfig {
+    // the minutes without a key or a move of the mouse before it starts, 0 to never start it
+    pub idle_minutes: f32,
+    // the time in seconds to zoom into the bookmark chosen
+    pub flight_duration: f32,
+}
+
+impl Default for ScreensaverConfig {
+    fn default() -> Self {
+        Self {
+            idle_minutes: 0.0,
+            flight_duration: 60.0,
+        }
+    }
+}
+
+impl ScreensaverConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.idle_minutes >= 0.0 && self.idle_minutes.is_finite()) {
+            return Err("screensaver.idle_minutes must be positive".to_string());
+        }
+        if !(self.flight_duration > 0.0 && self.flight_duration.is_finite()) {
+            return Err("screensaver.flight_duration must be strictly positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+// the fractal explored
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct FractalConfig {
+    // the fractal at startup
+    pub kind: Fractal,
+    // the real coefficients of the polynomial of the newton fractal, from the highest degree
+    pub newton_polynomial: Vec<f32>,
+    // the formula of the custom fractal, like "z^2 + c"
+    pub formula: String,
+}
+
+impl Default for FractalConfig {
+    fn default() -> Self {
+        Self {
+            kind: Fractal::Mandelbrot,
+            newton_polynomial: vec![1.0, 0.0, 0.0, -1.0],
+            formula: "z^2 + c".to_string(),
+        }
+    }
+}
+
+impl FractalConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        newton::validate(&self.newton_polynomial)
+            .map_err(|error| format!("fractal.newton_polynomial: {}", error))?;
+        formula::compile(&self.formula).map_err(|error| format!("fractal.formula: {}", error))?;
+        Ok(())
+    }
+}
+
+// how the frames are shown
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct DisplayConfig {
+    // the present mode at startup, mailbox when supported and fifo otherwise if not set
+    pub present_mode: Option<PresentMode>,
+    // the maximum number of frames rendered per second
+    pub fps: u32,
+    // draw the frames as fast as possible instead of fps per second, the idle frames still wait
+    pub uncapped: bool,
+    // the number of frames rendered per second when the window does not have the focus
+    // and is not paused, or when the camera has not moved for a second
+    pub idle_fps: u32,
+    // stop drawing while the window does not have the focus, the frames go on where they stopped
+    // when it gets it back
+    pub pause_unfocused: bool,
+    // the effects applied to the frames, in this order
+    pub post_effects: Vec<PostEffect>,
+    // add a little noise to the colors, so the smooth gradients do not show bands
+    pub dither: bool,
+    // the iterations the palette moves by each second, negative to move it the other way,
+    // 0 for a still palette
+    pub color_cycling_speed: f32,
+    // fit the palette scale to the iterations outside the set of the last frames,
+    // H toggles it while exploring and page up and page down turn it off
+    pub auto_palette_scale: bool,
+    // write the zoom and the coordinates of the view in the title of the window, once a second,
+    // with the period and the nucleus of the minibrot at the center of the mandelbrot set
+    pub location_in_title: bool,
+    // write after the location the share of the view inside the set, its estimated area
+    // in the complex plane and the mean iterations of the pixels outside it
+    pub area_in_title: bool,
+    // the correction of the colors for the screen, applied after the effects,
+    // above 1 to lighten the dark colors
+    pub gamma: f32,
+    // added to the colors, negative to darken them
+    pub brightness: f32,
+    // the colors are spread around the middle gray by this factor
+    pub contrast: f32,
+    // use a 16 bits float or a 10 bits surface when the display supports it, read at startup
+    pub hdr: bool,
+    // the smallest share of the width and the height of the window the fractal is drawn at,
+    // while the camera moves and the frames are too slow, 1 to always draw every pixel
+    pub minimum_resolution: f32,
+    // the time in milliseconds the gpu should take to draw the fractal while the camera moves,
+    // the iterations are lowered when it takes longer, 0 to always compute every iteration
+    pub target_frame_time: f32,
+    // the most iterations a pixel is advanced by in a frame, the deepest pixels are finished
+    // in the next frames instead of stalling this one, 0 to compute them all at once
+    pub iteration_slice: u32,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            present_mode: None,
+            fps: 120,
+            uncapped: false,
+            idle_fps: 5,
+            pause_unfocused: true,
+            post_effects: vec![],
+            dither: true,
+            color_cycling_speed: 5.0,
+            auto_palette_scale: false,
+            location_in_title: true,
+            area_in_title: false,
+            gamma: 1.0,
+            brightness: 0.0,
+            contrast: 1.0,
+            hdr: false,
+            minimum_resolution: 0.5,
+            target_frame_time: 8.0,
+            iteration_slice: 1000,
+        }
+    }
+}
+
+// the values the correction of the colors can take
+pub const GAMMA_RANGE: RangeInclusive<f32> = 0.2..=5.0;
+pub const BRIGHTNESS_RANGE: RangeInclusive<f32> = -0.5..=0.5;
+pub const CONTRAST_RANGE: RangeInclusive<f32> = 0.2..=5.0;
+
+impl DisplayConfig {
+    pub fn color_levels(&self) -> ColorLevels {
+        ColorLevels::new(self.gamma, self.brightness, self.contrast)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=1000).contains(&self.fps) {
+            return Err("display.fps must be between 1 and 1000".to_string());
+        }
+        if !(1..=self.fps).contains(&self.idle_fps) {
+            return Err("display.idle_fps must be between 1 and display.fps".to_string());
+        }
+        if !(0.1..=1.0).contains(&self.minimum_resolution) {
+            return Err("display.minimum_resolution must be between 0.1 and 1".to_string());
+        }
+        if !self.color_cycling_speed.is_finite() {
+            return Err("display.color_cycling_speed must be a number".to_string());
+        }
+        for (name, value, range) in [
+            ("gamma", self.gamma, GAMMA_RANGE),
+            ("brightness", self.brightness, BRIGHTNESS_RANGE),
+            ("contrast", self.contrast, CONTRAST_RANGE),
+        ] {
+            if !range.contains(&value) {
+                return Err(format!(
+                    "display.{} must be between {} and {}",
+                    name,
+                    range.start(),
+                    range.end()
+                ));
+            }
+        }
+        if !(0.0..=1000.0).contains(&self.target_frame_time) {
+            return Err("display.target_frame_time must be between 0 and 1000".to_string());
+        }
+        Ok(())
+    }
+}
+
+// the settings of the right part of the screen when it is split to compare them with the left one
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ComparisonConfig {
+    // the share of the width of the screen on the left of the split
+    pub split: f32,
+    // the coloring of the right part, the other one than the left part when not set
+    pub coloring: Option<Coloring>,
+    // the color palette scale of the right part, the one of the left part when not set
+    pub color_palette_scale: Option<f32>,
+    // the share of the iterations of the left part computed in the right part,
+    // the orbit is only computed for the left part so it can not be more
+    pub iterations: f32,
+}
+
+impl Default for ComparisonConfig {
+    fn default() -> Self {
+        Self {
+            split: 0.5,
+            coloring: None,
+            color_palette_scale: None,
+            iterations: 1.0,
+        }
+    }
+}
+
+impl ComparisonConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.split) {
+            return Err("comparison.split must be between 0 and 1".to_string());
+        }
+        if let Some(color_palette_scale) = self.color_palette_scale {
+            if !(color_palette_scale > 0.0 && color_palette_scale.is_finite()) {
+                return Err("comparison.color_palette_scale must be strictly positive".to_string());
+            }
+        }
+        if !(self.iterations > 0.0 && self.iterations <= 1.0) {
+            return Err("comparison.iterations must be between 0 excluded and 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+// when the reference point of the perturbation moves to the center of the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferencePolicy {
+    // as soon as the center is further than the threshold from the reference point
+    Automatic,
+    // only when asked with U, or when the center is so far the pixels would lose their precision
+    Manual,
+}
+
+// how the reference point follows the center of the screen, the orbit of the new one is computed
+// in the background and used once complete, which can show as a hitch while recording
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ReferenceConfig {
+    pub policy: ReferencePolicy,
+    // the distance from the center of the screen to the reference point, in zooms,
+    // from which the orbit of the center is computed
+    pub threshold: f32,
+    // the distance added to the threshold within which the orbit being computed is kept
+    // instead of starting again from the new center, so a moving camera changes less often
+    pub hysteresis: f32,
+}
+
+impl Default for ReferenceConfig {
+    fn default() -> Self {
+        Self {
+            policy: ReferencePolicy::Automatic,
+            threshold: 2.0,
+            hysteresis: 0.0,
+        }
+    }
+}
+
+impl ReferenceConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.threshold > 0.0 && self.threshold < 1000.0) {
+            return Err("reference.threshold must be between 0 and 1000 excluded".to_string());
+        }
+        if !(self.hysteresis >= 0.0 && self.threshold + self.hysteresis < 1000.0) {
+            return Err(
+                "reference.hysteresis must be positive, with the threshold below 1000".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+// the last seconds of the exploration kept in small frames, saved as a gif with F9
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct GifConfig {
+    // the seconds kept, 0 to keep none
+    pub seconds: f32,
+    // the frames kept per second
+    pub fps: u32,
+    // the width of the frames, the height follows the window
+    pub width: u32,
+}
+
+impl Default for GifConfig {
+    fn default() -> Self {
+        Self {
+            seconds: 10.0,
+            fps: 10,
+            width: 320,
+        }
+    }
+}
+
+impl GifConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.seconds >= 0.0 && self.seconds <= 60.0) {
+            return Err("gif.seconds must be between 0 and 60".to_string());
+        }
+        // the delays of the frames of a gif are in hundredths of a second
+        if !(1..=50).contains(&self.fps) {
+            return Err("gif.fps must be between 1 and 50".to_string());
+        }
+        if !(16..=1920).contains(&self.width) {
+            return Err("gif.width must be between 16 and 1920".to_string());
+        }
+        Ok(())
+    }
+}
+
+// the animation of the view drawn with more and more iterations, played with F6
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct GrowthConfig {
+    // the duration of the animation in seconds
+    pub seconds: f32,
+    // the iterations of the first frame, the last one has the iterations of the view
+    pub start_iterations: u32,
+    // save every frame to a png file of a new directory, growth_<time>
+    pub export: bool,
+    // the frames per second of the exported animation
+    pub fps: u32,
+}
+
+impl Default for GrowthConfig {
+    fn default() -> Self {
+        Self {
+            seconds: 5.0,
+            start_iterations: 1,
+            export: false,
+            fps: 30,
+        }
+    }
+}
+
+impl GrowthConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.seconds > 0.0 && self.seconds <= 600.0) {
+            return Err("growth.seconds must be between 0 excluded and 600".to_string());
+        }
+        if self.start_iterations == 0 {
+            return Err("growth.start_iterations must be at least 1".to_string());
+        }
+        if !(1..=120).contains(&self.fps) {
+            return Err("growth.fps must be between 1 and 120".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresentMode {
+    // wait for the vertical sync
+    Fifo,
+    // do not wait, but only show complete frames
+    Mailbox,
+    // do not wait, even if the frames tear
+    Immediate,
+}
+
+impl From<PresentMode> for wgpu::PresentMode {
+    fn from(mode: PresentMode) -> Self {
+        match mode {
+            PresentMode::Fifo => wgpu::PresentMode::Fifo,
+            PresentMode::Mailbox => wgpu::PresentMode::Mailbox,
+            PresentMode::Immediate => wgpu::PresentMode::Immediate,
+        }
+    }
+}
+
+// the full screen effects applied to the frames before they are shown
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PostEffect {
+    // compress the colors with a filmic curve
+    ToneMapping,
+    // darken the corners
+    Vignette,
+    // sharpen the edges
+    Sharpen,
+    // a subtle halo around the bright filaments
+    Bloom,
+}
+
+impl From<PostEffect> for PostPass<'static> {
+    fn from(effect: PostEffect) -> Self {
+        match effect {
+            PostEffect::ToneMapping => {
+                PostPass::Shader(include_str!("../shaders/post/tone_mapping.wgsl"))
+            }
+            PostEffect::Vignette => PostPass::Shader(include_str!("../shaders/post/vignette.wgsl")),
+            PostEffect::Sharpen => PostPass::Shader(include_str!("../shaders/post/sharpen.wgsl")),
+            PostEffect::Bloom => PostPass::Bloom,
+        }
+    }
+}
+
+impl Config {
+    // read and validate a config file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+        let config: Config = toml::from_str(&text).map_err(|error| error.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.navigation.validate()?;
+        self.mini_viewer.validate()?;
+        self.tour.validate()?;
+        self.explore.validate()?;
+        self.screensaver.validate()?;
+        self.fractal.validate()?;
+        self.display.validate()?;
+        self.comparison.validate()?;
+        self.reference.validate()?;
+        self.gif.validate()?;
+        self.growth.validate()
+    }
+}
+
+// write a value of the [display] section in the config file, so a setting changed while exploring
+// is kept, the other lines and the comments of the file are left as they are
+// the file is created when it does not exist
+pub fn save_display_value(path: &Path, key: &str, value: f32) -> Result<(), String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) if error.kind() == ErrorKind::NotFound => String::new(),
+        Err(error) => return Err(error.to_string()),
+    };
+    let setting = format!("{} = {:?}", key, value);
+    let mut lines = text.lines().map(str::to_string).collect::<Vec<_>>();
+    let mut section = String::new();
+    let mut display_header = None;
+    let mut replaced = false;
+    for (index, line) in lines.iter_mut().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            section = trimmed.trim_matches(['[', ']']).trim().to_string();
+            if section == "display" {
+                display_header = Some(index);
+            }
+        } else if section == "display"
+            && trimmed
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.trim_start().starts_with('='))
+        {
+            *line = setting.clone();
+            replaced = true;
+            break;
+        }
+    }
+    if !replaced {
+        match display_header {
+            Some(index) => lines.insert(index + 1, setting),
+            None => {
+                if !lines.is_empty() {
+                    lines.push(String::new());
+                }
+                lines.push("[display]".to_string());
+                lines.push(setting);
+            }
+        }
+    }
+    let mut text = lines.join("\n");
+    text.push('\n');
+    fs::write(path, text).map_err(|error| error.to_string())
+}
+
+// open the config file with the program the system uses for its type, so it can be edited,
+// the file is created empty when it does not exist, the settings left out keep their default
+#[cfg(not(target_arch = "wasm32"))]
+pub fn open_in_editor(path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        fs::write(path, "").map_err(|error| error.to_string())?;
+    }
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    };
+    #[cfg(target_os = "macos")]
+    let mut command = std::process::Command::new("open");
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = std::process::Command::new("xdg-open");
+    let mut child = command
+        .arg(path)
+        .spawn()
+        .map_err(|error| error.to_string())?;
+    // the opener is waited for beside the explorer, so it does not stay as a zombie process
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+// look for changes of the config file, at most once per second
+pub struct ConfigWatcher {
+    watcher: FileWatcher,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            watcher: FileWatcher::new(path),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        self.watcher.path()
+    }
+
+    // the config to use at startup, the default one if the file does not exist or is invalid
+    pub fn initial(&self) -> Config {
+        let path = self.watcher.path();
+        if !path.exists() {
+            return Config::default();
+        }
+        Config::load(path).unwrap_or_else(|error| {
+            eprintln!("Invalid config file {}: {}", path.display(), error);
+            Config::default()
+        })
+    }
+
+    // the new config if the file has been modified since the last call
+    // an invalid file is reported and ignored, so the current config is kept
+    pub fn poll(&mut self) -> Option<Config> {
+        if !self.watcher.changed() {
+            return None;
+        }
+        let path = self.watcher.path();
+        match Config::load(path) {
+            Ok(config) => {
+                eprintln!("Config reloaded from {}", path.display());
+                Some(config)
+            }
+            Err(error) => {
+                eprintln!("Invalid config file {}: {}", path.display(), error);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temporary_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("mandelbrot_{}_{}.toml", name, std::process::id()))
+    }
+
+    // the text of the file after saving a value in it
+    fn saved(name: &str, text: &str, key: &str, value: f32) -> String {
+        let path = temporary_file(name);
+        fs::write(&path, text).unwrap();
+        save_display_value(&path, key, value).unwrap();
+        let saved = fs::read_to_string(&path);
+        fs::remove_file(&path).unwrap();
+        saved.unwrap()
+    }
+
+    #[test]
+    fn an_existing_display_value_is_replaced() {
+        let text = "# my settings\n[navigation]\ngamma = 3.0\n\n[display]\n# the brightness\ngamma_bias = 1.0\ngamma = 1.0\nfps = 60\n";
+        assert_eq!(
+            saved("replaced", text, "gamma", 1.5),
+            "# my settings\n[navigation]\ngamma = 3.0\n\n[display]\n# the brightness\ngamma_bias = 1.0\ngamma = 1.5\nfps = 60\n"
+        );
+    }
+
+    #[test]
+    fn a_missing_display_value_is_added_to_its_section() {
+        let text = "[display]\nfps = 60\n\n[gif]\nfps = 20\n";
+        assert_eq!(
+            saved("added", text, "contrast", 1.25),
+            "[display]\ncontrast = 1.25\nfps = 60\n\n[gif]\nfps = 20\n"
+        );
+    }
+
+    #[test]
+    fn a_missing_display_section_is_added_at_the_end() {
+        let text = "# my settings\n[navigation]\nzoom_speed = 0.5";
+        assert_eq!(
+            saved("section", text, "brightness", 0.5),
+            "# my settings\n[navigation]\nzoom_speed = 0.5\n\n[display]\nbrightness = 0.5\n"
+        );
+        let path = temporary_file("created");
+        save_display_value(&path, "gamma", 2.0).unwrap();
+        let created = fs::read_to_string(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(created.unwrap(), "[display]\ngamma = 2.0\n");
+    }
+
+    #[test]
+    fn the_saved_file_is_still_a_valid_config() {
+        let text = saved("valid", "[mini_viewer]\nfps = 20\n", "gamma", 1.5);
+        let config: Config = toml::from_str(&text).unwrap();
+        assert_eq!(config.mini_viewer.fps, 20);
+        assert_eq!(config.display.gamma, 1.5);
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn the_default_config_is_valid() {
+        assert_eq!(Config::default().validate(), Ok(()));
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        for text in [
+            "[navigation]\nzoom_speed = nan",
+            "[navigation]\niteration_speed = 5",
+            "[navigation]\ndamping = 1.0",
+            "[navigation]\ndamping = 0.0",
+            "[navigation]\nzoom_epsilon = -0.1",
+            "[navigation]\nwheel_acceleration = 0.0",
+            "[navigation]\nwheel_slow_multiplier = inf",
+            "[mini_viewer]\nfps = 0",
+            "[mini_viewer]\nclick_through = true",
+            "[display]\nminimum_resolution = 2.0",
+        ] {
+            let config: Config = toml::from_str(text).unwrap();
+            assert!(config.validate().is_err(), "{:?}", text);
+        }
+        let config: Config = toml::from_str(
+            "[navigation]\nzoom_speed = -1.0\ndamping = 0.5\nzoom_epsilon = 0.0\nwheel_acceleration = 4.0",
+        )
+        .unwrap();
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn an_invalid_file_is_not_loaded() {
+        let path = temporary_file("invalid");
+        fs::write(&path, "[navigation]\niteration_speed = 1").unwrap();
+        let invalid = Config::load(&path);
+        fs::write(&path, "[navigation]\niteration_speed = \"fast\"").unwrap();
+        let mistyped = Config::load(&path);
+        fs::write(&path, "[navigation]\niteration_speed = 500").unwrap();
+        let valid = Config::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            invalid,
+            Err("navigation.iteration_speed must be between 10 and 10000".to_string())
+        );
+        assert!(mistyped.is_err());
+        assert_eq!(valid.unwrap().navigation.iteration_speed, 500);
+    }
+}
@@ -0,0 +1,416 @@
+use bytemuck::Zeroable;
+use rayon::prelude::*;
+use winit::dpi::PhysicalSize;
+
+use crate::game::big_real::Real;
+use crate::game::location::Location;
+use crate::game::mamndelbrot_state::MandelbrotDot;
+use crate::game::mandelbrot::{Coloring, MandelbrotData, MandelbrotEngine, Precision};
+use crate::game::orbit_worker::OrbitCycle;
+use crate::game::palette::Palette;
+
+// the mandelbrot shader ported to the cpu, for the adapters that can not run it
+// the lines of the frame are computed in parallel, with the same perturbation of the orbit
+// and the same colors as the shader, but without the reuse of the previous frame
+
+// render a location without any adapter, the pixels are then saved instead of being drawn,
+// 4 bytes each from the top line, encoded in srgb like the screenshots
+pub fn render_image(
+    location: &Location,
+    palette: &Palette,
+    size: PhysicalSize<u32>,
+    maximum_iterations: u32,
+) -> Vec<u8> {
+    let mut mandelbrot = MandelbrotEngine::new(maximum_iterations, size.width, size.height);
+    mandelbrot.resize(size.width, size.height);
+    // the precision of the zoom gives the length of the orbit, computed at once with the center
+    mandelbrot.set_zoom(location.zoom as f64);
+    mandelbrot.data.get_mut().angle = location.angle;
+    mandelbrot.set_maximum_iterations(maximum_iterations);
+    mandelbrot.set_center(Real::clone(&location.x), Real::clone(&location.y));
+    let data = *mandelbrot.data.get();
+    let pixel_count = (size.width * size.height) as usize;
+    let mut iterations = vec![-2.0; pixel_count];
+    let mut dots = vec![MandelbrotDot::zeroed(); pixel_count];
+    compute(
+        &data,
+        &mandelbrot.orbit_point_suite.points(),
+        mandelbrot.orbit_cycle,
+        &mut iterations,
+        &mut dots,
+    );
+    let mut pixels = vec![];
+    colorize(&data, palette, &iterations, &dots, &mut pixels);
+    for pixel in pixels.chunks_exact_mut(4) {
+        for channel in &mut pixel[..3] {
+            *channel = encode_srgb(*channel);
+        }
+    }
+    pixels
+}
+
+fn encode_srgb(linear: u8) -> u8 {
+    let linear = linear as f32 / 255.0;
+    let encoded = if linear <= 0.0031308 {
+        linear * 12.92
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    };
+    (encoded * 255.0).round() as u8
+}
+
+// compute the smooth iteration count and the dot of every pixel, from the bottom line
+// like the buffers of the shader, -1 inside the set
+pub fn compute(
+    data: &MandelbrotData,
+    orbit: &[[f32; 2]],
+    cycle: Option<OrbitCycle>,
+    iterations: &mut [f32],
+    dots: &mut [MandelbrotDot],
+) {
+    let (width, height) = (data.width as usize, data.height as usize);
+    if width == 0 || iterations.len() < width * height || dots.len() < width * height {
+        return;
+    }
+    let precision = Precision::from(data.precision_mode);
+    // the orbit is the reference of every pixel, it can not be iterated further than its length
+    // unless it is cyclic, the direct mode does not use it
+    let maximum_iterations = match (cycle, precision) {
+        (Some(_), _) | (None, Precision::Direct) => data.maximum_iterations,
+        (None, _) => data.maximum_iterations.min(orbit.len() as u32 - 1),
+    };
+    let (sin, cos) = data.angle.sin_cos();
+    let ratio = width as f32 / height as f32;
+    // the exponent of the power of two of the zoom, the deltas are divided by it
+    // in the extended exponent mode
+    let scale = match precision {
+        Precision::ExtendedExponent => data.zoom.log2().floor() as i32 + data.zoom_exponent,
+        Precision::Direct | Precision::Perturbation => 0,
+    };
+    iterations[..width * height]
+        .par_chunks_mut(width)
+        .zip(dots[..width * height].par_chunks_mut(width))
+        .enumerate()
+        .for_each(|(line, (iterations, dots))| {
+            let y = ((line as f32 + 0.5) / height as f32 * 2.0 - 1.0) * data.zoom;
+            for (column, (iteration, dot)) in iterations.iter_mut().zip(dots.iter_mut()).enumerate()
+            {
+                let x = ((column as f32 + 0.5) / width as f32 * 2.0 - 1.0) * data.zoom * ratio;
+                let dc = times_power_of_two(
+                    [
+                        data.center_delta[0] + (x * cos - y * sin),
+                        data.center_delta[1] + (x * sin + y * cos),
+                    ],
+                    data.zoom_exponent - scale,
+                );
+                *iteration =
+                    compute_iteration(data, orbit, cycle, dc, scale, maximum_iterations, dot);
+            }
+        });
+}
+
+// write the color of every pixel, 4 bytes each from the top line, in linear colors
+pub fn colorize(
+    data: &MandelbrotData,
+    palette: &Palette,
+    iterations: &[f32],
+    dots: &[MandelbrotDot],
+    pixels: &mut Vec<u8>,
+) {
+    let (width, height) = (data.width as usize, data.height as usize);
+    pixels.resize(width * height * 4, 0);
+    if width == 0 || iterations.len() < width * height || dots.len() < width * height {
+        return;
+    }
+    let coloring = Coloring::from(data.coloring);
+    pixels
+        .par_chunks_mut(width * 4)
+        .enumerate()
+        .for_each(|(row, pixels)| {
+            let line = height - 1 - row;
+            let y = (line as f32 + 0.5) / height as f32 * 2.0 - 1.0;
+            for (column, pixel) in pixels.chunks_exact_mut(4).enumerate() {
+                let index = line * width + column;
+                let x = (column as f32 + 0.5) / width as f32 * 2.0 - 1.0;
+                let color = match coloring {
+                    Coloring::Iterations => {
+                        let derivative = dots[index].derivative;
+                        colorize_iterations(data, palette, [x, y], iterations[index], derivative)
+                    }
+                    Coloring::AtomDomains => colorize_atom_domain(iterations[index], &dots[index]),
+                };
+                for (channel, value) in pixel.iter_mut().zip(color) {
+                    *channel = (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+                }
+                pixel[3] = 255;
+            }
+        });
+}
+
+// the iteration of a pixel at dc from the reference point, perturbing the orbit like the shader
+// dc and the delta are divided by 2 to the power of scale while the delta is tiny,
+// scale is 0 otherwise
+fn compute_iteration(
+    data: &MandelbrotData,
+    orbit: &[[f32; 2]],
+    cycle: Option<OrbitCycle>,
+    mut dc: [f32; 2],
+    mut scale: i32,
+    maximum_iterations: u32,
+    dot: &mut MandelbrotDot,
+) -> f32 {
+    let maximum_iterations = maximum_iterations as f32;
+    // in the direct mode the reference is 0, so the delta is the value of z itself,
+    // and dc is moved from the reference point, the second point of the orbit, to 0
+    let precision = Precision::from(data.precision_mode);
+    if precision == Precision::Direct {
+        dc = add(dc, orbit[1]);
+    }
+    let reference_point = |i: usize| match precision {
+        Precision::Direct => [0.0, 0.0],
+        Precision::Perturbation | Precision::ExtendedExponent => {
+            orbit[cycle.map_or(i, |cycle| cycle.fold(i))]
+        }
+    };
+    let mut dz = [0.0, 0.0];
+    let mut der = [1.0, 0.0];
+    let mut i = 0.0;
+    let mut ref_i = 0;
+    let mut atom_period = 0.0;
+    let mut atom_distance = data.mu;
+    while i < maximum_iterations {
+        let z = reference_point(ref_i);
+        let square = times_power_of_two(cmul(dz, dz), scale);
+        dz = add(add(times(cmul(dz, z), 2.0), square), dc);
+        ref_i += 1;
+        let delta = times_power_of_two(dz, scale);
+        let z = add(reference_point(ref_i), delta);
+        dot.derivative = cdiv(der, z);
+        let dot_z = norm_squared(z);
+        if dot_z < atom_distance {
+            atom_distance = dot_z;
+            atom_period = i + 1.0;
+        }
+        // out of the mandelbrot set
+        if dot_z >= data.mu {
+            break;
+        }
+        if norm_squared(der) < data.epsilon {
+            i = -3.0;
+            break;
+        }
+        der = cmul(times(der, 2.0), z);
+        let dot_dz = norm_squared(delta);
+        // rebase on the start of the orbit when the pixel is closer to 0 than its delta
+        if dot_z < dot_dz || ref_i as f32 == maximum_iterations {
+            dz = z;
+            ref_i = 0;
+            dc = times_power_of_two(dc, scale);
+            scale = 0;
+        } else if scale != 0 && dot_dz > 1e-30 {
+            // the delta is big enough to be stored as it is
+            dz = delta;
+            dc = times_power_of_two(dc, scale);
+            scale = 0;
+        }
+        i += 1.0;
+    }
+    dot.atom_period = atom_period;
+    dot.atom_distance = atom_distance.sqrt();
+    if i >= maximum_iterations {
+        i = -1.0;
+    } else if i > 0.0 {
+        // add the rest to i to get a smooth color gradient
+        let log_zn = norm_squared(times_power_of_two(dz, scale)).ln() / 2.0;
+        let nu = (log_zn / 2f32.ln()).ln() / 2f32.ln();
+        i += 1.0 - nu;
+    }
+    i
+}
+
+// the palette of the shader, shaded by the derivative with a light turning around the z axis
+// the rounded values of pi are the ones of the shader, so the colors are the same
+#[allow(clippy::approx_constant)]
+fn colorize_iterations(
+    data: &MandelbrotData,
+    palette: &Palette,
+    coordinate: [f32; 2],
+    iterations: f32,
+    derivative: [f32; 2],
+) -> [f32; 3] {
+    if iterations < 0.0 {
+        return [0.0, 0.0, 0.0];
+    }
+    let scale = data.color_palette_scale;
+    let t = (1.0 - ((iterations + data.palette_phase) % scale) * 2.0 / scale).abs();
+    let dx = coordinate[0] / 5.0;
+    let dy = coordinate[1] / 5.0;
+    let color = palette.color(t).unwrap_or([
+        0.5 + 0.5 * (t * 6.28 + 1.4 + dx.sin() - 0.5).cos(),
+        0.5 + 0.5 * (t * 5.88 - 3.14 + (dy - dx).sin()).sin(),
+        0.5 + 0.5 * (t * 3.14 - 3.14 + (dx * 3.14).cos() - 0.5).cos(),
+    ]);
+    let light = normalize([
+        (data.time_elapsed * 0.5).cos(),
+        (data.time_elapsed * 0.5).sin(),
+        1.5,
+    ]);
+    let normal = normalize([derivative[0], derivative[1], 1.0]);
+    let lighting = normal[0] * light[0] + normal[1] * light[1] + normal[2] * light[2];
+    let diffuse = (lighting.max(0.2) * 2.5).min(1.0);
+    color.map(|channel| channel * diffuse)
+}
+
+// the hue of the period of the atom domain, brighter close to its nucleus
+#[allow(clippy::approx_constant)]
+fn colorize_atom_domain(iterations: f32, dot: &MandelbrotDot) -> [f32; 3] {
+    if iterations < -1.5 {
+        return [0.0, 0.0, 0.0];
+    }
+    // the golden ratio spreads the hues of the successive periods
+    let hue = (dot.atom_period * 0.618034).fract();
+    let mut shade = 1.0 / (1.0 + 4.0 * dot.atom_distance);
+    if iterations < 0.0 {
+        shade *= 0.5;
+    }
+    [0.0, 0.33, 0.67].map(|offset| (0.5 + 0.5 * (6.28318 * (hue + offset)).cos()) * shade)
+}
+
+fn add(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] + b[0], a[1] + b[1]]
+}
+
+fn times(a: [f32; 2], factor: f32) -> [f32; 2] {
+    [a[0] * factor, a[1] * factor]
+}
+
+// multiply by 2 to the power of the exponent, which may not fit in a f32
+fn times_power_of_two(a: [f32; 2], exponent: i32) -> [f32; 2] {
+    let factor = 2f64.powi(exponent);
+    a.map(|value| (value as f64 * factor) as f32)
+}
+
+fn cmul(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] * b[0] - a[1] * b[1], a[0] * b[1] + a[1] * b[0]]
+}
+
+fn cdiv(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    let denominator = norm_squared(b);
+    [
+        (a[0] * b[0] + a[1] * b[1]) / denominator,
+        (a[1] * b[0] - a[0] * b[1]) / denominator,
+    ]
+}
+
+fn norm_squared(a: [f32; 2]) -> f32 {
+    a[0] * a[0] + a[1] * a[1]
+}
+
+fn normalize(a: [f32; 3]) -> [f32; 3] {
+    let length = (a[0] * a[0] + a[1] * a[1] + a[2] * a[2]).sqrt();
+    a.map(|value| value / length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::big_real::{BigReal, Real};
+    use crate::game::iteration::{step, Iterations};
+
+    const MAXIMUM_ITERATIONS: u32 = 1000;
+
+    fn data(precision: Precision) -> MandelbrotData {
+        let mut data = *MandelbrotEngine::default().data.get();
+        data.precision_mode = precision as u32;
+        data.maximum_iterations = MAXIMUM_ITERATIONS;
+        data
+    }
+
+    // the smooth iteration count of the point at dc from the reference of the orbit
+    fn iteration(data: &MandelbrotData, orbit: &[[f32; 2]], dc: [f32; 2]) -> f32 {
+        let mut dot = MandelbrotDot::zeroed();
+        compute_iteration(data, orbit, None, dc, 0, MAXIMUM_ITERATIONS, &mut dot)
+    }
+
+    const POINTS: [[f32; 2]; 8] = [
+        [1.0, 0.0],
+        [0.5, 0.0],
+        [-0.75, 0.1],
+        [0.3, 0.6],
+        [-1.8, 0.05],
+        [0.0, 0.0],
+        [-1.0, 0.0],
+        [0.3, 0.5],
+    ];
+
+    #[test]
+    fn the_direct_mode_escapes_with_the_reference_iterations() {
+        let data = data(Precision::Direct);
+        for c in POINTS {
+            let smooth = iteration(&data, &[[0.0, 0.0]; 2], c);
+            let reference = (c[0] as f64, c[1] as f64);
+            let mut iterations = Iterations::new(&reference, data.mu as f64);
+            let maximum_iterations = MAXIMUM_ITERATIONS as usize;
+            iterations.by_ref().take(maximum_iterations).for_each(drop);
+            match iterations.escape_info().escape_iteration {
+                // the smoothing removes the log2 of the log2 of |z|, between 2.7 and 3.8
+                // as the square of |z| is over mu but its square root was not
+                Some(escape) => {
+                    let escape = escape as f32;
+                    assert!(
+                        smooth > escape - 3.8 && smooth < escape - 2.7,
+                        "c = {:?}, {} for {}",
+                        c,
+                        smooth,
+                        escape
+                    );
+                }
+                None => assert!(smooth < 0.0, "c = {:?}, {}", c, smooth),
+            }
+        }
+    }
+
+    #[test]
+    fn the_perturbation_gives_the_iterations_of_the_direct_mode() {
+        // the orbit of a point of the main cardioid, which never escapes
+        let reference = (-0.5, 0.0);
+        let mut z = (0.0, 0.0);
+        let mut orbit = vec![[0.0, 0.0]];
+        for _ in 0..MAXIMUM_ITERATIONS {
+            z = step(&z, &reference);
+            orbit.push([z.0 as f32, z.1 as f32]);
+        }
+        let direct = data(Precision::Direct);
+        let perturbation = data(Precision::Perturbation);
+        for c in POINTS {
+            let dc = [c[0] - reference.0 as f32, c[1] - reference.1 as f32];
+            let expected = iteration(&direct, &[[0.0, 0.0]; 2], c);
+            let smooth = iteration(&perturbation, &orbit, dc);
+            assert!(
+                (smooth - expected).abs() < 0.01 || (expected < 0.0 && smooth < 0.0),
+                "c = {:?}, {} instead of {}",
+                c,
+                smooth,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn the_image_rendered_without_adapter_is_centered_on_the_location() {
+        // the center of the period 2 bulb, the corners of the view are outside the set
+        // while the ones of a view centered on 0 would be inside
+        let location = Location {
+            x: Real::from_double(-1.0),
+            y: Real::from_double(0.0),
+            zoom: 0.3,
+            angle: 0.0,
+        };
+        let size = PhysicalSize::new(32, 24);
+        let pixels = render_image(&location, &Palette::default(), size, 200);
+        assert_eq!(pixels.len(), 32 * 24 * 4);
+        let pixel = |x: usize, y: usize| &pixels[(y * 32 + x) * 4..(y * 32 + x) * 4 + 4];
+        assert_eq!(pixel(16, 12), [0, 0, 0, 255]);
+        assert_ne!(pixel(0, 0), [0, 0, 0, 255]);
+    }
+}
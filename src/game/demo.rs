@@ -0,0 +1,215 @@
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use winit::dpi::PhysicalPosition;
+use winit::event::{
+    DeviceId, ElementState, Event, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta,
+    TouchPhase, VirtualKeyCode, WindowEvent,
+};
+use winit::window::WindowId;
+
+// the fixed time between two frames while a demo is recorded or replayed,
+// so the camera moves the same way whatever the speed of the computer
+pub const DEMO_DELTA_TIME: f32 = 1.0 / 60.0;
+
+// an input of the user, without what depends on the machine like the device it comes from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DemoInput {
+    Key {
+        key: VirtualKeyCode,
+        state: ElementState,
+    },
+    Mouse {
+        button: MouseButton,
+        state: ElementState,
+    },
+    Wheel {
+        delta: MouseScrollDelta,
+    },
+    Cursor {
+        x: f64,
+        y: f64,
+    },
+    Modifiers {
+        modifiers: ModifiersState,
+    },
+}
+
+// a line of a demo file, an input and the number of frames drawn before it,
+// the last line has no input and gives the number of frames of the demo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DemoLine {
+    frame: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    input: Option<DemoInput>,
+}
+
+impl DemoInput {
+    // the input of an event of the window, none for the events that are not inputs
+    fn from_event(event: &WindowEvent) -> Option<Self> {
+        match event {
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        virtual_keycode: Some(key),
+                        state,
+                        ..
+                    },
+                ..
+            } => Some(DemoInput::Key {
+                key: *key,
+                state: *state,
+            }),
+            WindowEvent::MouseInput { button, state, .. } => Some(DemoInput::Mouse {
+                button: *button,
+                state: *state,
+            }),
+            WindowEvent::MouseWheel { delta, .. } => Some(DemoInput::Wheel { delta: *delta }),
+            WindowEvent::CursorMoved { position, .. } => Some(DemoInput::Cursor {
+                x: position.x,
+                y: position.y,
+            }),
+            WindowEvent::ModifiersChanged(modifiers) => Some(DemoInput::Modifiers {
+                modifiers: *modifiers,
+            }),
+            _ => None,
+        }
+    }
+
+    // the event of the given window the input came from
+    #[allow(deprecated)]
+    pub fn to_event(self, window_id: WindowId) -> Event<'static, ()> {
+        // the device is never read, any one will do
+        let device_id = unsafe { DeviceId::dummy() };
+        let modifiers = ModifiersState::empty();
+        let event = match self {
+            DemoInput::Key { key, state } => WindowEvent::KeyboardInput {
+                device_id,
+                input: KeyboardInput {
+                    scancode: 0,
+                    state,
+                    virtual_keycode: Some(key),
+                    modifiers,
+                },
+                is_synthetic: false,
+            },
+            DemoInput::Mouse { button, state } => WindowEvent::MouseInput {
+                device_id,
+                state,
+                button,
+                modifiers,
+            },
+            DemoInput::Wheel { delta } => WindowEvent::MouseWheel {
+                device_id,
+                delta,
+                phase: TouchPhase::Moved,
+                modifiers,
+            },
+            DemoInput::Cursor { x, y } => WindowEvent::CursorMoved {
+                device_id,
+                position: PhysicalPosition::new(x, y),
+                modifiers,
+            },
+            DemoInput::Modifiers { modifiers } => WindowEvent::ModifiersChanged(modifiers),
+        };
+        Event::WindowEvent { window_id, event }
+    }
+}
+
+// write the inputs of the user to a demo file as they come, one json line each,
+// the file is flushed at each input so a demo is kept even when the explorer crashes
+pub struct DemoRecorder {
+    writer: BufWriter<File>,
+    // the number of frames drawn since the start of the demo
+    frame: u64,
+}
+
+impl DemoRecorder {
+    pub fn create(path: &Path) -> Result<Self, String> {
+        let file = File::create(path).map_err(|error| error.to_string())?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            frame: 0,
+        })
+    }
+
+    // write the input of an event of the window, the other events are ignored
+    pub fn record(&mut self, event: &WindowEvent) -> Result<(), String> {
+        match DemoInput::from_event(event) {
+            Some(input) => self.write(Some(input)),
+            None => Ok(()),
+        }
+    }
+
+    // count a frame drawn, the next inputs are replayed after it
+    pub fn next_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    fn write(&mut self, input: Option<DemoInput>) -> Result<(), String> {
+        let line = DemoLine {
+            frame: self.frame,
+            input,
+        };
+        let text = serde_json::to_string(&line).map_err(|error| error.to_string())?;
+        writeln!(self.writer, "{}", text)
+            .and_then(|_| self.writer.flush())
+            .map_err(|error| error.to_string())
+    }
+}
+
+impl Drop for DemoRecorder {
+    // the frames drawn after the last input are replayed too
+    fn drop(&mut self) {
+        if let Err(error) = self.write(None) {
+            eprintln!("Impossible to finish the demo: {}", error);
+        }
+    }
+}
+
+// give back the inputs of a demo file at the frames they were recorded at
+pub struct DemoPlayer {
+    lines: VecDeque<DemoLine>,
+    // the number of frames of the demo, the last input when it has not been finished
+    length: u64,
+    frame: u64,
+}
+
+impl DemoPlayer {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+        let mut lines = VecDeque::new();
+        for (index, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let line: DemoLine = serde_json::from_str(line)
+                .map_err(|error| format!("line {}: {}", index + 1, error))?;
+            lines.push_back(line);
+        }
+        let length = lines.back().map_or(0, |line| line.frame);
+        Ok(Self {
+            lines,
+            length,
+            frame: 0,
+        })
+    }
+
+    // the inputs to give before the next frame is drawn, none once the demo is over
+    pub fn next_frame(&mut self) -> Option<Vec<DemoInput>> {
+        if self.frame > self.length {
+            return None;
+        }
+        let mut inputs = vec![];
+        while let Some(line) = self.lines.front().filter(|line| line.frame <= self.frame) {
+            inputs.extend(line.input);
+            self.lines.pop_front();
+        }
+        self.frame += 1;
+        Some(inputs)
+    }
+}
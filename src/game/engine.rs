@@ -1,26 +1,220 @@
 use std::cell::RefCell;
-use std::ops::Deref;
+use std::path::PathBuf;
 use std::rc::Rc;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::time::Duration;
 
 use wgpu::util::DeviceExt;
 use wgpu::{BufferAddress, BufferBindingType, BufferUsages, ShaderModule, ShaderStages};
 use winit::window::{Fullscreen, Window};
 
+use crate::game::config::GifConfig;
 use crate::game::engine::bind_group_buffer_entry::BindGroupBufferEntry;
+use crate::game::engine::bind_group_texture_entry::BindGroupTextureEntry;
+use crate::game::engine::color_adjustment::ColorLevels;
+use crate::game::engine::cpu_frame::CpuFrame;
+use crate::game::engine::gallery::Gallery;
+use crate::game::engine::gif_recorder::{GifClip, GifRecorder};
+use crate::game::engine::julia_preview::JuliaPreview;
+use crate::game::engine::orbit_path::OrbitPath;
+use crate::game::engine::overlay::Overlay;
+use crate::game::engine::overview::Overview;
+use crate::game::engine::pixel_texture::{PixelAccess, PixelTexture};
+use crate::game::engine::post_process::{PostPass, PostProcessor};
+use crate::game::engine::profiler::{Profiler, FRAME_START, MANDELBROT_END, OVERVIEW_END};
+use crate::game::engine::readback::Readback;
+use crate::game::engine::resolution_scaler::ResolutionScaler;
+use crate::game::engine::screenshot::{PendingScreenshot, Screenshot};
+use crate::game::engine::stats::{FrameStats, StatsWriter};
+use crate::game::engine::typed_buffer::{StorageBuffer, TypedBuffer, UniformBuffer};
 use crate::game::engine::vertex::{Vertex, VERTICES};
+use crate::game::engine::video::VideoEncoder;
+use crate::game::fractal::with_structs;
+use crate::game::location::Location;
+use crate::game::mandelbrot::MandelbrotData;
 use crate::game::to_buffer_representation::ToBufferRepresentation;
 
 pub mod bind_group_buffer_entry;
+pub mod bind_group_texture_entry;
+pub mod bloom;
+pub mod color_adjustment;
+pub mod cpu_frame;
+pub mod gallery;
+pub mod gif_recorder;
+pub mod julia_preview;
+pub mod orbit_path;
+pub mod overlay;
+pub mod overview;
+pub mod pixel_texture;
+pub mod post_process;
+pub mod profiler;
+pub mod readback;
+pub mod resolution_scaler;
+pub mod screenshot;
+pub mod stats;
+pub mod typed_buffer;
 pub mod vertex;
+pub mod video;
+
+// the size of the chunks of the staging belt, a bigger write gets a chunk of its own
+const STAGING_CHUNK_SIZE: BufferAddress = 1 << 20;
+
+// the width, the height and the rgba pixels of a frame read back from the gpu
+pub type FramePixels = (u32, u32, Vec<u8>);
+
+// a buffer added to the engine, its binding in the shader is the first one free in its group
+// when it is added, so a buffer added after one removed takes its binding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferHandle(usize);
+
+// a texture added to the engine, bound with its sampler after the bindings added before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureHandle(usize);
+
+// a texture with a value per pixel added to the engine, resized with the frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelTextureHandle(usize);
+
+// the bind groups of the shader, by how often their resources change,
+// so a buffer reallocated only creates its own group again
+// the bindings of a group follow the order in which its resources are added
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResourceGroup {
+    // @group(0), the textures and their samplers, written once
+    Static = 0,
+    // @group(1), the uniform buffers, written each frame
+    Frame = 1,
+    // @group(2), the storage buffers and the pixel textures, reallocated with the frames
+    Storage = 2,
+}
+
+impl ResourceGroup {
+    const ALL: [ResourceGroup; 3] = [
+        ResourceGroup::Static,
+        ResourceGroup::Frame,
+        ResourceGroup::Storage,
+    ];
+
+    // the uniform buffers change each frame, the others hold the data of the pixels
+    fn of_buffer_type(buffer_binding_type: BufferBindingType) -> Self {
+        match buffer_binding_type {
+            BufferBindingType::Uniform => ResourceGroup::Frame,
+            _ => ResourceGroup::Storage,
+        }
+    }
+
+    fn of_buffer(buffer: &BindGroupBufferEntry) -> Self {
+        match buffer.bind_group_layout_entry.ty {
+            wgpu::BindingType::Buffer { ty, .. } => Self::of_buffer_type(ty),
+            _ => ResourceGroup::Storage,
+        }
+    }
+}
+
+// how to choose the gpu, from the command line
+#[derive(Debug, Clone, Default)]
+pub struct AdapterSelection {
+    // the index or a part of the name of the adapter
+    pub gpu: Option<String>,
+    // use the software adapter
+    pub force_fallback: bool,
+    // compute the frames on the cpu, even when the adapter can run the shader
+    pub cpu: bool,
+}
 
 pub struct Engine {
-    surface: wgpu::Surface,
+    // the surface of the window, none when rendering headless
+    surface: Option<wgpu::Surface>,
+    // the texture rendered into when there is no surface
+    offscreen: Option<(wgpu::Texture, wgpu::TextureView)>,
     config: wgpu::SurfaceConfiguration,
-    pub queue: wgpu::Queue,
-    pub device: wgpu::Device,
+    // shared with the application when the explorer is embedded
+    pub queue: Arc<wgpu::Queue>,
+    pub device: Arc<wgpu::Device>,
     render_pipeline: Option<wgpu::RenderPipeline>,
-    pub buffers: Vec<BindGroupBufferEntry>,
+    // none once removed, so the handles of the others stay valid
+    buffers: Vec<Option<BindGroupBufferEntry>>,
+    textures: Vec<BindGroupTextureEntry>,
+    pixel_textures: Vec<PixelTexture>,
+    // the layouts of the groups of buffers and textures, created again only when one is added
+    bind_group_layouts: [wgpu::BindGroupLayout; 3],
+    // the bind groups, by group, none when it must be created again because one of its
+    // resources has been added or reallocated
+    bind_groups: [Option<wgpu::BindGroup>; 3],
+    // the buffers copied to others once a frame has been drawn, as source and destination
+    buffer_copies: Vec<(BufferHandle, BufferHandle)>,
+    // the pixel textures copied to others once a frame has been drawn, as source and destination
+    pixel_texture_copies: Vec<(PixelTextureHandle, PixelTextureHandle)>,
     vertex_buffer: wgpu::Buffer,
+    // the file the next frame is saved to, and the text embedded in it
+    screenshot_request: Option<(PathBuf, Vec<(String, String)>)>,
+    // the file the next frame is saved to as a square thumbnail, and the side of the thumbnail
+    thumbnail_request: Option<(PathBuf, u32)>,
+    // true when the next frame is copied to the clipboard, and the frame being read back for it
+    frame_copy_requested: bool,
+    copied_frame: Option<PendingScreenshot>,
+    // the video the frames of a script are added to, and true when the next frame is added
+    video: Option<VideoEncoder>,
+    video_frame_requested: bool,
+    // the last seconds kept to be saved as a gif, none when they are not kept
+    gif_recorder: Option<GifRecorder>,
+    // the whole set drawn in a corner of the screen, none when hidden
+    overview: Option<Overview>,
+    // the julia set of the point under the cursor drawn on the right half of the screen,
+    // none when hidden
+    julia_preview: Option<JuliaPreview>,
+    // the orbit of the reference point drawn on top of the fractal, none when hidden
+    orbit_path: Option<OrbitPath>,
+    // the thumbnails of the bookmarks drawn on top of the fractal, none when hidden
+    gallery: Option<Gallery>,
+    // the lines drawn by the states on top of the fractal, like the rectangle selected to zoom on,
+    // created the first time one is drawn
+    overlay: Option<Overlay>,
+    // the passes run on the fractal before it is presented, none when there is no pass
+    post_processor: Option<PostProcessor>,
+    // the passes asked for, the color adjustment and the srgb encoding are added after them
+    post_passes: Vec<PostPass<'static>>,
+    // the correction of the colors in the last passes, no pass when it is neutral
+    color_levels: ColorLevels,
+    // convert the linear colors to srgb in a last pass, for the formats that store them as they are
+    encode_srgb: bool,
+    // the source of the shader, replaced when it is reloaded
+    shader_source: String,
+    // the memory the changes of the buffers are written to before being copied to them,
+    // kept across the frames
+    staging_belt: wgpu::util::StagingBelt,
+    // the present modes supported by the surface
+    present_modes: Vec<wgpu::PresentMode>,
+    // the timings of the frames, none when the gpu can not measure them
+    profiler: Option<Profiler>,
+    // the validation errors of the gpu, reported by the device instead of panicking
+    gpu_error_sender: Sender<String>,
+    gpu_error_receiver: Receiver<String>,
+    // the number of frames rendered, and what is known of the current one
+    frame: u64,
+    frame_stats: FrameStats,
+    // the statistics of the frames written to a csv file, none when they are not asked for
+    stats_writer: Option<StatsWriter>,
+    // the frame computed on the cpu and drawn instead of the shader, none when the shader is used
+    // the buffers are then never bound, as the adapter may not support them
+    cpu_frame: Option<CpuFrame>,
+    // the share of the width and the height of the frame the fractal is drawn at
+    render_scale: f32,
+    // the texture the fractal is drawn into when it is not drawn at the full size,
+    // created the first time it is needed
+    resolution_scaler: Option<ResolutionScaler>,
+}
+
+// a share of the width and the height of a frame, at least one pixel
+pub fn scaled_size(
+    size: winit::dpi::PhysicalSize<u32>,
+    scale: f32,
+) -> winit::dpi::PhysicalSize<u32> {
+    winit::dpi::PhysicalSize::new(
+        ((size.width as f32 * scale).round() as u32).max(1),
+        ((size.height as f32 * scale).round() as u32).max(1),
+    )
 }
 
 // implement engine for Engine struct whith a new function
@@ -28,199 +222,1318 @@ impl Engine {
     // the new function takes a window as a parameter
     // and initializes the engine with the window like it is done in Game new function
     // the idea is to refactor the Game new function to use the Engine new function
-    pub async fn new(window: &Window) -> Self {
+    // none when there is neither an adapter nor a software one to draw in the window
+    pub async fn new(window: &Window, selection: &AdapterSelection, hdr: bool) -> Option<Self> {
         // create surface
         let size = window.inner_size();
         let instance = wgpu::Instance::new(wgpu::Backends::all());
         let surface = unsafe { instance.create_surface(window) };
-        // create adapter
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-            .expect("Impossible to find a GPU!");
-        // create device and queue
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    features: wgpu::Features::empty(),
-                    // WebGL doesn't support all of wgpu's features, so if
-                    // we're building for the web we'll have to disable some.
-                    limits: if cfg!(target_arch = "wasm32") {
-                        wgpu::Limits::downlevel_webgl2_defaults()
-                    } else {
-                        wgpu::Limits::default()
-                    },
-                    label: None,
-                },
-                None, // Trace path
-            )
-            .await
-            .expect("Impossible to create device and queue!");
+        let (adapter, device, queue, cpu) =
+            Self::request_device(&instance, Some(&surface), selection).await?;
         let modes = surface.get_supported_modes(&adapter);
         // if modes countain Mailbox, use it, otherwise use FIFO
         let mode = modes
             .iter()
             .find(|m| **m == wgpu::PresentMode::Mailbox)
             .unwrap_or(&wgpu::PresentMode::Fifo);
-        let formats = surface.get_supported_formats(&adapter);
+        let format = Self::choose_format(&surface.get_supported_formats(&adapter), hdr);
+        log::info!("Surface format: {:?}", format);
         let config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: formats[0],
+            format,
             width: size.width,
             height: size.height,
             present_mode: *mode,
         };
         surface.configure(&device, &config);
+        let mut engine = Self::from_parts(Some(surface), config, Arc::new(device), Arc::new(queue));
+        engine.present_modes = modes;
+        if cpu {
+            engine.use_cpu_frame();
+        }
+        Some(engine)
+    }
+
+    // the format of the surface, a 16 bits float or a 10 bits one when hdr is requested and supported
+    // the 16 bits float format is the extended linear srgb, so the colors are the same as in 8 bits
+    // otherwise a srgb format, so the colors are the same whatever the order of the formats given
+    // by the driver, and the first one when there is none, the colors are then encoded by a last pass
+    fn choose_format(formats: &[wgpu::TextureFormat], hdr: bool) -> wgpu::TextureFormat {
+        if hdr {
+            let format = [
+                wgpu::TextureFormat::Rgba16Float,
+                wgpu::TextureFormat::Rgb10a2Unorm,
+            ]
+            .iter()
+            .copied()
+            .find(|format| formats.contains(format));
+            match format {
+                Some(format) => return format,
+                None => eprintln!("The display does not support a hdr format"),
+            }
+        }
+        formats
+            .iter()
+            .copied()
+            .find(|format| format.describe().srgb)
+            .unwrap_or(formats[0])
+    }
+
+    // true if the colors written in the format are converted to be displayed,
+    // so the shader can write linear colors
+    fn is_linear_format(format: wgpu::TextureFormat) -> bool {
+        format.describe().srgb || format == wgpu::TextureFormat::Rgba16Float
+    }
+
+    // initializes the engine without a window, rendering into an offscreen texture of the given size
+    // none when there is no adapter at all
+    pub async fn new_headless(
+        size: winit::dpi::PhysicalSize<u32>,
+        selection: &AdapterSelection,
+    ) -> Option<Self> {
+        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        let (_, device, queue, cpu) = Self::request_device(&instance, None, selection).await?;
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::Fifo,
+        };
+        let mut engine = Self::from_parts(None, config, Arc::new(device), Arc::new(queue));
+        engine.offscreen = Some(engine.create_offscreen_texture());
+        if cpu {
+            engine.use_cpu_frame();
+        }
+        Some(engine)
+    }
+
+    // initializes the engine with the device of an application,
+    // the frames are rendered into the texture views it provides
+    pub fn from_device(
+        device: Arc<wgpu::Device>,
+        queue: Arc<wgpu::Queue>,
+        format: wgpu::TextureFormat,
+        size: winit::dpi::PhysicalSize<u32>,
+    ) -> Self {
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format,
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::Fifo,
+        };
+        Self::from_parts(None, config, device, queue)
+    }
+
+    // the device of the adapter, and true when the frames must be computed on the cpu
+    // because it is asked or because the adapter can not run the shader,
+    // none when there is no adapter or its device can not be created
+    async fn request_device(
+        instance: &wgpu::Instance,
+        surface: Option<&wgpu::Surface>,
+        selection: &AdapterSelection,
+    ) -> Option<(wgpu::Adapter, wgpu::Device, wgpu::Queue, bool)> {
+        let adapter = Self::request_adapter(instance, surface, selection).await?;
+        let info = adapter.get_info();
+        log::info!("Using {} ({:?}, {:?})", info.name, info.backend, info.device_type);
+        let mut cpu = selection.cpu;
+        if !cpu && !Self::can_run_shader(&adapter) {
+            eprintln!("{} can not run the shader, the frames are computed by the cpu", info.name);
+            cpu = true;
+        }
+        // create device and queue
+        let mut result = Self::open_device(&adapter, cpu).await;
+        if !cpu {
+            if let Err(error) = &result {
+                eprintln!(
+                    "Impossible to create the device ({}), the frames are computed by the cpu",
+                    error
+                );
+                cpu = true;
+                result = Self::open_device(&adapter, cpu).await;
+            }
+        }
+        let (device, queue) = result
+            .map_err(|error| eprintln!("Impossible to create the device: {}", error))
+            .ok()?;
+        Some((adapter, device, queue, cpu))
+    }
+
+    // the shader writes storage buffers from the fragment stage,
+    // which the old gpus, the drivers of the virtual machines and WebGL2 can't do
+    fn can_run_shader(adapter: &wgpu::Adapter) -> bool {
+        adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::FRAGMENT_WRITABLE_STORAGE)
+            && (cfg!(target_arch = "wasm32")
+                || wgpu::Limits::default().check_limits(&adapter.limits()))
+    }
+
+    async fn open_device(
+        adapter: &wgpu::Adapter,
+        cpu: bool,
+    ) -> Result<(wgpu::Device, wgpu::Queue), wgpu::RequestDeviceError> {
+        adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    // the timestamps are used to measure the frames when they are supported
+                    features: adapter.features() & wgpu::Features::TIMESTAMP_QUERY,
+                    // The browsers limits are lower than the native ones, so if
+                    // we're building for the web we ask for what the adapter can do.
+                    // The frames computed on the cpu only need a texture,
+                    // so the lowest limits are enough for them.
+                    limits: if cpu {
+                        wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits())
+                    } else if cfg!(target_arch = "wasm32") {
+                        adapter.limits()
+                    } else {
+                        wgpu::Limits::default()
+                    },
+                    label: None,
+                },
+                None, // Trace path
+            )
+            .await
+    }
+
+    // the adapter asked on the command line, or the best one available
+    // the software adapter is used when there is no gpu, none when there is not even one
+    async fn request_adapter(
+        instance: &wgpu::Instance,
+        surface: Option<&wgpu::Surface>,
+        selection: &AdapterSelection,
+    ) -> Option<wgpu::Adapter> {
+        // the adapters can not be listed in the browser
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(gpu) = &selection.gpu {
+            let adapters = instance
+                .enumerate_adapters(wgpu::Backends::all())
+                .collect::<Vec<_>>();
+            eprintln!("Adapters:");
+            for (index, adapter) in adapters.iter().enumerate() {
+                let info = adapter.get_info();
+                eprintln!(
+                    "  {}: {} ({:?}, {:?})",
+                    index, info.name, info.backend, info.device_type
+                );
+            }
+            let adapter = match gpu.parse::<usize>() {
+                Ok(index) => adapters.into_iter().nth(index),
+                Err(_) => adapters.into_iter().find(|adapter| {
+                    adapter
+                        .get_info()
+                        .name
+                        .to_lowercase()
+                        .contains(&gpu.to_lowercase())
+                }),
+            };
+            match adapter {
+                Some(adapter)
+                    if surface.is_none_or(|surface| adapter.is_surface_supported(surface)) =>
+                {
+                    return Some(adapter);
+                }
+                Some(_) => eprintln!("The adapter {} can not draw in the window", gpu),
+                None => eprintln!("No adapter matches {}", gpu),
+            }
+        }
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                compatible_surface: surface,
+                force_fallback_adapter: selection.force_fallback,
+            })
+            .await;
+        if adapter.is_some() {
+            return adapter;
+        }
+        eprintln!("Impossible to find a GPU, falling back to the software adapter");
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                compatible_surface: surface,
+                force_fallback_adapter: true,
+            })
+            .await;
+        if adapter.is_none() {
+            eprintln!("Impossible to find a GPU or a software adapter");
+        }
+        adapter
+    }
+
+    fn from_parts(
+        surface: Option<wgpu::Surface>,
+        config: wgpu::SurfaceConfiguration,
+        device: Arc<wgpu::Device>,
+        queue: Arc<wgpu::Queue>,
+    ) -> Self {
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Vertex Buffer"),
             contents: bytemuck::cast_slice(VERTICES),
             usage: wgpu::BufferUsages::VERTEX,
         });
+        let bind_group_layouts = ResourceGroup::ALL
+            .map(|group| Self::create_bind_group_layout(&device, group, &[], &[], &[]));
+        // the frames are always timed when the gpu can, to adapt the work to its speed
+        let profiler = Profiler::new(&device, &queue).ok();
+        let (gpu_error_sender, gpu_error_receiver) = channel();
+        // the errors outside of a scope would panic, they are shown and the frames go on instead
+        let sender = gpu_error_sender.clone();
+        device.on_uncaptured_error(move |error| {
+            sender.send(error.to_string()).ok();
+        });
         let mut engine = Self {
             surface,
+            offscreen: None,
             config,
             queue,
             device,
             render_pipeline: None,
             buffers: vec![],
+            textures: vec![],
+            pixel_textures: vec![],
+            bind_group_layouts,
+            bind_groups: [None, None, None],
+            buffer_copies: vec![],
+            pixel_texture_copies: vec![],
             vertex_buffer,
+            screenshot_request: None,
+            thumbnail_request: None,
+            frame_copy_requested: false,
+            copied_frame: None,
+            video: None,
+            video_frame_requested: false,
+            gif_recorder: None,
+            overview: None,
+            julia_preview: None,
+            orbit_path: None,
+            gallery: None,
+            overlay: None,
+            post_processor: None,
+            post_passes: vec![],
+            color_levels: ColorLevels::NEUTRAL,
+            encode_srgb: false,
+            shader_source: with_structs(include_str!("../shaders/mandelbrot.wgsl")),
+            staging_belt: wgpu::util::StagingBelt::new(STAGING_CHUNK_SIZE),
+            present_modes: vec![],
+            profiler,
+            gpu_error_sender,
+            gpu_error_receiver,
+            frame: 0,
+            frame_stats: FrameStats::default(),
+            stats_writer: None,
+            cpu_frame: None,
+            render_scale: 1.0,
+            resolution_scaler: None,
         };
+        // the shader writes linear colors, a format that stores the encoded colors needs them encoded
+        engine.encode_srgb = !Self::is_linear_format(engine.config.format);
+        engine.set_post_passes(&[]);
         engine
     }
 
+    // draw the frames written by the cpu renderer instead of running the shader
+    fn use_cpu_frame(&mut self) {
+        self.cpu_frame = Some(CpuFrame::new(&self.device, self.config.format));
+    }
+
+    // true when the frames must be computed by the cpu renderer and written with write_cpu_frame
+    pub fn is_cpu(&self) -> bool {
+        self.cpu_frame.is_some()
+    }
+
+    // upload the pixels computed by the cpu renderer, drawn by the next frames,
+    // 4 bytes each from the top line, in linear colors like the output of the shader
+    pub fn write_cpu_frame(&mut self, pixels: &[u8]) {
+        let size = self.render_size();
+        if let Some(cpu_frame) = self.cpu_frame.as_mut() {
+            cpu_frame.write(&self.device, &self.queue, size, pixels);
+            self.frame_stats.upload_bytes += pixels.len();
+        }
+    }
+
+    fn create_offscreen_texture(&self) -> (wgpu::Texture, wgpu::TextureView) {
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen Texture"),
+            size: wgpu::Extent3d {
+                width: self.config.width,
+                height: self.config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        (texture, view)
+    }
+
     pub fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
         self.config.width = size.width;
         self.config.height = size.height;
-        self.surface.configure(&self.device, &self.config);
+        self.resize_pixel_textures(size);
+        match &self.surface {
+            Some(surface) => surface.configure(&self.device, &self.config),
+            None if self.offscreen.is_some() => {
+                self.offscreen = Some(self.create_offscreen_texture())
+            }
+            // the texture is provided by the application
+            None => {}
+        }
+    }
+
+    // reconfigure the surface with another present mode, if it is supported
+    pub fn set_present_mode(&mut self, present_mode: wgpu::PresentMode) -> Result<(), String> {
+        let surface = match &self.surface {
+            Some(surface) => surface,
+            None => return Err("there is no window".to_string()),
+        };
+        if !self.present_modes.contains(&present_mode) {
+            return Err(format!("{:?} is not supported", present_mode));
+        }
+        self.config.present_mode = present_mode;
+        surface.configure(&self.device, &self.config);
+        Ok(())
+    }
+
+    // switch to the next supported present mode, among fifo, mailbox and immediate
+    pub fn next_present_mode(&mut self) -> wgpu::PresentMode {
+        let modes = [
+            wgpu::PresentMode::Fifo,
+            wgpu::PresentMode::Mailbox,
+            wgpu::PresentMode::Immediate,
+        ];
+        let current = modes
+            .iter()
+            .position(|mode| *mode == self.config.present_mode)
+            .unwrap_or(0);
+        for offset in 1..modes.len() {
+            let mode = modes[(current + offset) % modes.len()];
+            if self.set_present_mode(mode).is_ok() {
+                break;
+            }
+        }
+        self.config.present_mode
+    }
+
+    // the limits of the device, which bound the size of the frames
+    pub fn limits(&self) -> wgpu::Limits {
+        self.device.limits()
+    }
+
+    pub fn size(&self) -> winit::dpi::PhysicalSize<u32> {
+        winit::dpi::PhysicalSize::new(self.config.width, self.config.height)
+    }
+
+    // draw the fractal at a share of the size of the frame and stretch it, 1 for the full size
+    pub fn set_render_scale(&mut self, render_scale: f32) {
+        self.render_scale = render_scale.clamp(0.0, 1.0);
+    }
+
+    // the size the fractal is drawn at, the buffers of the pixels are indexed with its width
+    pub fn render_size(&self) -> winit::dpi::PhysicalSize<u32> {
+        scaled_size(self.size(), self.render_scale)
+    }
+
+    // the frame computed on the cpu is stretched by its own pass
+    fn is_scaled(&self) -> bool {
+        self.render_size() != self.size() && self.cpu_frame.is_none()
     }
 
     pub fn update(&mut self) {}
 
     pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-        let output = self.surface.get_current_texture()?;
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+        profiling::scope!("Engine::render");
+        match &self.surface {
+            Some(surface) => {
+                let output = surface.get_current_texture()?;
+                let view = output
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                self.render_to_view(&view, self.size());
+                output.present();
+            }
+            None => {
+                // the texture is taken out of the engine while it is rendered into
+                if let Some(offscreen) = self.offscreen.take() {
+                    self.render_to_view(&offscreen.1, self.size());
+                    self.offscreen = Some(offscreen);
+                }
+            }
+        }
+        // the end of a frame in the profiler, once it has been presented
+        profiling::finish_frame!();
+        Ok(())
+    }
+
+    // draw the next frame into any texture view of the format of the engine,
+    // without touching the surface, for overlays, thumbnails or tiles
+    // the buffers that store a value per pixel must have been resized to the size of the view
+    pub fn render_to_view(
+        &mut self,
+        view: &wgpu::TextureView,
+        size: winit::dpi::PhysicalSize<u32>,
+    ) {
+        // the errors of the frame are told apart from the ones of the resources created meanwhile
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        // the frame is drawn at the size of the view, the surface keeps its own one
+        let surface_size = self.size();
+        self.config.width = size.width;
+        self.config.height = size.height;
+        self.render_frame(view, size);
+        self.config.width = surface_size.width;
+        self.config.height = surface_size.height;
+        if let Some(error) = pollster::block_on(self.device.pop_error_scope()) {
+            let message = format!("while drawing the frame: {}", error);
+            self.gpu_error_sender.send(message).ok();
+        }
+    }
+
+    fn render_frame(&mut self, view: &wgpu::TextureView, size: winit::dpi::PhysicalSize<u32>) {
+        self.resize_pixel_textures(size);
+        if let Some(post_processor) = self.post_processor.as_mut() {
+            post_processor.resize(&self.device, size);
+        }
+        if self.is_scaled() {
+            let render_size = self.render_size();
+            self.resolution_scaler
+                .get_or_insert_with(|| ResolutionScaler::new(&self.device, self.config.format))
+                .resize(&self.device, render_size);
+        }
         let mut encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("Render Encoder"),
             });
-        {
-            // create a bind group layout from the buffers bind group layouts entries
-            let bind_group_layout =
-                self.device
-                    .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                        label: Some("Bind Group Layout"),
-                        entries: &self
-                            .buffers
-                            .iter()
-                            .map(|b| b.bind_group_layout_entry)
-                            .collect::<Vec<_>>(),
-                    });
-            // do the same for the bind group
-            let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Bind Group"),
-                layout: &bind_group_layout,
-                entries: &self
-                    .buffers
-                    .iter()
-                    .map(|b| b.bind_group_entry())
-                    .collect::<Vec<_>>(),
+        self.flush_buffers(&mut encoder);
+        if self.cpu_frame.is_none() {
+            // the resources have changed since the pipeline was built
+            if self.render_pipeline.is_none() {
+                self.create_pipeline();
+            }
+            for group in ResourceGroup::ALL {
+                if self.bind_groups[group as usize].is_none() {
+                    self.bind_groups[group as usize] = Some(self.create_bind_group(group));
+                }
+            }
+        }
+        let bind_groups = self.bind_groups.iter().map(Option::as_ref).collect::<Option<Vec<_>>>();
+        let bind_groups = bind_groups.as_deref();
+        // the screenshot is drawn with the same data as the screen, before the buffers are copied,
+        // so both render the exact same image
+        let screenshot = self.screenshot_request.take().map(|(path, text)| {
+            Screenshot::new(
+                &self.device,
+                path,
+                self.config.width,
+                self.config.height,
+                self.config.format,
+            )
+            .with_text(text)
+        });
+        if let Some(screenshot) = &screenshot {
+            self.draw_frame(&mut encoder, screenshot.view(), bind_groups);
+            screenshot.copy_to_buffer(&mut encoder);
+        }
+        let thumbnail = self.thumbnail_request.take().map(|(path, side)| {
+            let thumbnail = Screenshot::new(
+                &self.device,
+                path,
+                self.config.width,
+                self.config.height,
+                self.config.format,
+            );
+            (thumbnail, side)
+        });
+        if let Some((thumbnail, _)) = &thumbnail {
+            self.draw_frame(&mut encoder, thumbnail.view(), bind_groups);
+            thumbnail.copy_to_buffer(&mut encoder);
+        }
+        let frame_copy = std::mem::take(&mut self.frame_copy_requested).then(|| {
+            Screenshot::new(
+                &self.device,
+                PathBuf::new(),
+                self.config.width,
+                self.config.height,
+                self.config.format,
+            )
+        });
+        if let Some(frame_copy) = &frame_copy {
+            self.draw_frame(&mut encoder, frame_copy.view(), bind_groups);
+            frame_copy.copy_to_buffer(&mut encoder);
+        }
+        let video_frame = (self.video.is_some() && std::mem::take(&mut self.video_frame_requested))
+            .then(|| {
+                Screenshot::new(
+                    &self.device,
+                    PathBuf::new(),
+                    self.config.width,
+                    self.config.height,
+                    self.config.format,
+                )
             });
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Load,
-                        store: true,
-                    },
-                })],
-                depth_stencil_attachment: None,
+        if let Some(video_frame) = &video_frame {
+            self.draw_frame(&mut encoder, video_frame.view(), bind_groups);
+            video_frame.copy_to_buffer(&mut encoder);
+        }
+        let gif_frame = self
+            .gif_recorder
+            .as_ref()
+            .filter(|gif_recorder| gif_recorder.wants_frame())
+            .map(|_| {
+                Screenshot::new(
+                    &self.device,
+                    PathBuf::new(),
+                    self.config.width,
+                    self.config.height,
+                    self.config.format,
+                )
             });
-            render_pass.set_pipeline(&self.render_pipeline.as_ref().unwrap());
-
-            // set bind groups from bind buffers with incrementing index
-            render_pass.set_bind_group(0, &bind_group, &[]);
-            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            render_pass.draw(0..VERTICES.len() as u32, 0..1);
-        }
-        encoder.copy_buffer_to_buffer(
-            &self.buffers[2].buffer,
-            0,
-            &self.buffers[3].buffer,
-            0,
-            self.buffers[3].length() as BufferAddress,
-        );
-        encoder.copy_buffer_to_buffer(
-            &self.buffers[4].buffer,
-            0,
-            &self.buffers[5].buffer,
-            0,
-            self.buffers[5].length() as BufferAddress,
-        );
+        if let Some(gif_frame) = &gif_frame {
+            self.draw_frame(&mut encoder, gif_frame.view(), bind_groups);
+            gif_frame.copy_to_buffer(&mut encoder);
+        }
+        if let Some(profiler) = &self.profiler {
+            profiler.write_timestamp(&mut encoder, FRAME_START);
+        }
+        self.draw_frame(&mut encoder, view, bind_groups);
+        if let Some(profiler) = &self.profiler {
+            profiler.write_timestamp(&mut encoder, MANDELBROT_END);
+        }
+        // the overlays are not part of the screenshots
+        if let Some(julia_preview) = &self.julia_preview {
+            julia_preview.draw(&mut encoder, view, size, &self.vertex_buffer);
+        }
+        if let Some(orbit_path) = &self.orbit_path {
+            orbit_path.draw(&mut encoder, view);
+        }
+        if let Some(overview) = &self.overview {
+            overview.draw(&mut encoder, view, size, &self.vertex_buffer);
+        }
+        if let Some(gallery) = &self.gallery {
+            gallery.draw(&mut encoder, view, size, &self.vertex_buffer);
+        }
+        if let Some(overlay) = self.overlay.as_mut() {
+            overlay.flush(&self.device, &self.queue, size);
+            overlay.draw(&mut encoder, view);
+        }
+        if let Some(profiler) = &self.profiler {
+            profiler.write_timestamp(&mut encoder, OVERVIEW_END);
+            profiler.resolve(&mut encoder);
+        }
+        for (source, destination) in &self.buffer_copies {
+            encoder.copy_buffer_to_buffer(
+                &self.buffer(*source).buffer,
+                0,
+                &self.buffer(*destination).buffer,
+                0,
+                self.buffer(*destination).length() as BufferAddress,
+            );
+        }
+        for (source, destination) in &self.pixel_texture_copies {
+            self.pixel_textures[source.0].copy_to(&mut encoder, &self.pixel_textures[destination.0]);
+        }
+        self.staging_belt.finish();
         // submit will accept anything that implements IntoIter
         self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+        // the chunks of the belt are reused once the gpu has copied them
+        self.staging_belt.recall();
+        let timings = self
+            .profiler
+            .as_mut()
+            .and_then(|profiler| profiler.end_frame(&self.device, self.frame));
+        if let Some(stats_writer) = self.stats_writer.as_mut() {
+            let measured_frame = self.profiler.as_ref().and_then(Profiler::measured_frame);
+            let written =
+                stats_writer.end_frame(self.frame, self.frame_stats, timings, measured_frame);
+            if let Err(error) = written {
+                eprintln!("Impossible to write the statistics: {}", error);
+                self.stats_writer = None;
+            }
+        }
+        self.frame += 1;
+        self.frame_stats.upload_bytes = 0;
+        if let Some(screenshot) = screenshot {
+            if let Err(error) = screenshot.save(&self.device) {
+                eprintln!("Impossible to save the screenshot: {}", error);
+            }
+        }
+        if let Some((thumbnail, side)) = thumbnail {
+            if let Err(error) = thumbnail.save_thumbnail(&self.device, side) {
+                eprintln!("Impossible to save the thumbnail: {}", error);
+            }
+        }
+        // read in the background, so the frames go on while the pixels are copied back
+        if let Some(frame_copy) = frame_copy {
+            self.copied_frame = Some(frame_copy.read_later(self.device.clone()));
+        }
+        if let Some(gif_recorder) = self.gif_recorder.as_mut() {
+            if let Some(gif_frame) = gif_frame {
+                gif_recorder.capture(gif_frame.read_later(self.device.clone()));
+            }
+            if let Err(error) = gif_recorder.poll() {
+                eprintln!("Impossible to keep the frame for the gif: {}", error);
+            }
+        }
+        if let (Some(video_frame), Some(video)) = (video_frame, self.video.as_mut()) {
+            let written = video_frame
+                .read_pixels(&self.device)
+                .and_then(|pixels| video.write_frame(&pixels));
+            if let Err(error) = written {
+                eprintln!("Impossible to add the frame to the video: {}", error);
+            }
+        }
+    }
+
+    // the validation errors of the gpu since the last call, from the oldest one
+    pub fn take_gpu_errors(&mut self) -> Vec<String> {
+        self.gpu_error_receiver.try_iter().collect()
+    }
+
+    // write the timings of the frames to a csv file
+    pub fn start_profiling(&mut self, path: &std::path::Path) -> Result<(), String> {
+        match self.profiler.as_mut() {
+            Some(profiler) => profiler.write_to(path),
+            None => Err("the gpu does not support timestamp queries".to_string()),
+        }
+    }
+
+    // write the statistics of the frames to a csv file, without the gpu timings
+    // when the gpu does not support timestamp queries
+    pub fn start_stats(&mut self, path: &std::path::Path) -> Result<(), String> {
+        self.stats_writer = Some(StatsWriter::create(path)?);
         Ok(())
     }
 
-    pub fn update_buffer(&mut self, index: usize) {
-        self.buffers[index].update(&self.device, &self.queue);
+    // the time taken by the gpu to draw the fractal in the last frame measured since the last call,
+    // none when nothing has been measured or when the gpu does not support timestamp queries
+    pub fn take_gpu_time(&mut self) -> Option<Duration> {
+        self.profiler.as_mut()?.take_mandelbrot_time()
+    }
+
+    // the time taken by the cpu to update the current frame, written with its gpu timings
+    pub fn set_cpu_time(&mut self, cpu_time: Duration) {
+        self.frame_stats.cpu_time = cpu_time;
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.set_cpu_time(cpu_time);
+        }
+    }
+
+    // the iterations and the length of the orbit of the current frame, written to the statistics
+    pub fn set_orbit_stats(&mut self, maximum_iterations: u32, orbit_length: usize) {
+        self.frame_stats.maximum_iterations = maximum_iterations;
+        self.frame_stats.orbit_length = orbit_length;
+    }
+
+    pub fn toggle_overview(&mut self) {
+        self.overview = match self.overview {
+            Some(_) => None,
+            None => Some(Overview::new(&self.device, self.config.format)),
+        };
+    }
+
+    // show the given location of the explorer on the overview
+    pub fn update_overview(&mut self, location: &Location, mandelbrot: &MandelbrotData) {
+        let size = self.size();
+        if let Some(overview) = self.overview.as_mut() {
+            overview.update(size, location, mandelbrot);
+        }
+    }
+
+    pub fn toggle_julia_preview(&mut self) {
+        self.julia_preview = match self.julia_preview {
+            Some(_) => None,
+            None => Some(JuliaPreview::new(&self.device, self.config.format)),
+        };
+    }
+
+    pub fn shows_julia_preview(&self) -> bool {
+        self.julia_preview.is_some()
+    }
+
+    // draw the julia set of the given point on the preview
+    pub fn update_julia_preview(&mut self, c: [f32; 2], mandelbrot: &MandelbrotData) {
+        let size = self.size();
+        if let Some(julia_preview) = self.julia_preview.as_mut() {
+            julia_preview.update(size, c, mandelbrot);
+        }
+    }
+
+    pub fn toggle_orbit_path(&mut self) {
+        self.orbit_path = match self.orbit_path {
+            Some(_) => None,
+            None => Some(OrbitPath::new(&self.device, self.config.format)),
+        };
+    }
+
+    pub fn shows_orbit_path(&self) -> bool {
+        self.orbit_path.is_some()
+    }
+
+    // draw the points of the orbit, relative to the center of the screen, on the next frames
+    pub fn update_orbit_path(&mut self, points: &[[f32; 2]], mandelbrot: &MandelbrotData) {
+        if let Some(orbit_path) = self.orbit_path.as_mut() {
+            orbit_path.update(&self.device, &self.queue, points, mandelbrot);
+        }
+    }
+
+    // draw an image of the thumbnails of the bookmarks on top of the next frames, or hide it
+    pub fn show_gallery(&mut self, image: Option<&image::RgbaImage>) {
+        let linear = Self::is_linear_format(self.config.format);
+        self.gallery = image.map(|image| {
+            Gallery::new(&self.device, &self.queue, self.config.format, linear, image)
+        });
+    }
+
+    // the corners of the rectangle the gallery is drawn in, in pixels of the window
+    pub fn update_gallery(&mut self, rectangle: [[f32; 2]; 2]) {
+        if let Some(gallery) = self.gallery.as_mut() {
+            gallery.set_rectangle(rectangle);
+        }
+    }
+
+    // the lines to draw on top of the next frame, in pixels of the window
+    pub fn overlay(&mut self) -> &mut Overlay {
+        let (device, format) = (&self.device, self.config.format);
+        self.overlay
+            .get_or_insert_with(|| Overlay::new(device, format))
+    }
+
+    // wait until the gpu has done all the work submitted
+    pub fn wait(&self) {
+        self.device.poll(wgpu::Maintain::Wait);
+    }
+
+    // copy the content of a buffer from the gpu, once the frames submitted so far are drawn
+    // the future is ready after a next submit or poll of the device, it does not block the rendering
+    pub fn read_buffer(&self, handle: BufferHandle) -> Readback {
+        let size = self.buffer(handle).length() as BufferAddress;
+        let read_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Read Buffer"),
+            size,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Read Encoder"),
+            });
+        encoder.copy_buffer_to_buffer(&self.buffer(handle).buffer, 0, &read_buffer, 0, size);
+        self.queue.submit(std::iter::once(encoder.finish()));
+        Readback::new(self.device.clone(), read_buffer)
+    }
+
+    // copy the content of a buffer from the gpu, waiting for it
+    pub fn read_buffer_blocking(&self, handle: BufferHandle) -> Result<Vec<u8>, String> {
+        let readback = self.read_buffer(handle);
+        self.wait();
+        pollster::block_on(readback)
+    }
+
+    // save the next rendered frame to a png file, with the text given as keyword and value
+    pub fn request_screenshot(&mut self, path: PathBuf, text: Vec<(String, String)>) {
+        self.screenshot_request = Some((path, text));
+    }
+
+    // save the middle square of the next rendered frame to a png file of the given side
+    pub fn request_thumbnail(&mut self, path: PathBuf, side: u32) {
+        self.thumbnail_request = Some((path, side));
+    }
+
+    // copy the next rendered frame to the clipboard, the pixels are given by take_copied_frame
+    pub fn request_frame_copy(&mut self) {
+        self.frame_copy_requested = true;
+    }
+
+    // the frame copied, once read back from the gpu
+    pub fn take_copied_frame(&mut self) -> Option<Result<FramePixels, String>> {
+        let pixels = self.copied_frame.as_mut()?.try_read_pixels()?;
+        let (width, height) = self.copied_frame.take()?.size();
+        Some(pixels.map(|pixels| (width, height, pixels)))
+    }
+
+    // keep the last seconds of the exploration to save them as a gif, none to keep nothing
+    pub fn set_gif_config(&mut self, config: &GifConfig) {
+        self.gif_recorder = (config.seconds > 0.0).then(|| GifRecorder::new(config.clone()));
+    }
+
+    // the last seconds kept, none when they are not kept
+    pub fn gif_clip(&self) -> Option<GifClip> {
+        self.gif_recorder.as_ref().map(GifRecorder::clip)
+    }
+
+    // add the frames to a video, from the next one requested
+    pub fn start_video(&mut self, video: VideoEncoder) {
+        self.video = Some(video);
+    }
+
+    // add the next rendered frame to the video, if there is one
+    pub fn request_video_frame(&mut self) {
+        self.video_frame_requested = true;
+    }
+
+    // wait for the video to be written, none when there is no video
+    pub fn finish_video(&mut self) -> Option<Result<PathBuf, String>> {
+        let mut video = self.video.take()?;
+        Some(video.finish().map(|_| video.path().to_path_buf()))
+    }
+
+    // create the bind group layout of a group from the layout entries of its buffers and textures
+    fn create_bind_group_layout(
+        device: &wgpu::Device,
+        group: ResourceGroup,
+        buffers: &[Option<BindGroupBufferEntry>],
+        textures: &[BindGroupTextureEntry],
+        pixel_textures: &[PixelTexture],
+    ) -> wgpu::BindGroupLayout {
+        let buffers = buffers
+            .iter()
+            .flatten()
+            .filter(|b| ResourceGroup::of_buffer(b) == group)
+            .map(|b| b.bind_group_layout_entry);
+        let entries = match group {
+            ResourceGroup::Static => textures
+                .iter()
+                .flat_map(|t| t.bind_group_layout_entries())
+                .collect::<Vec<_>>(),
+            ResourceGroup::Frame => buffers.collect(),
+            ResourceGroup::Storage => buffers
+                .chain(pixel_textures.iter().map(|t| t.bind_group_layout_entry()))
+                .collect(),
+        };
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Bind Group Layout"),
+            entries: &entries,
+        })
+    }
+
+    fn create_bind_group(&self, group: ResourceGroup) -> wgpu::BindGroup {
+        let buffers = self
+            .buffers
+            .iter()
+            .flatten()
+            .filter(|b| ResourceGroup::of_buffer(b) == group)
+            .map(|b| b.bind_group_entry());
+        let entries = match group {
+            ResourceGroup::Static => self
+                .textures
+                .iter()
+                .flat_map(|t| t.bind_group_entries())
+                .collect::<Vec<_>>(),
+            ResourceGroup::Frame => buffers.collect(),
+            ResourceGroup::Storage => buffers
+                .chain(self.pixel_textures.iter().map(|t| t.bind_group_entry()))
+                .collect(),
+        };
+        self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Bind Group"),
+            layout: &self.bind_group_layouts[group as usize],
+            entries: &entries,
+        })
+    }
+
+    // draw the fractal, stretched when it is drawn at a lower resolution,
+    // and run the post processing passes on it
+    fn draw_frame(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        bind_groups: Option<&[&wgpu::BindGroup]>,
+    ) {
+        let target = match &self.post_processor {
+            Some(post_processor) => post_processor.input_view(),
+            None => view,
+        };
+        match self.resolution_scaler.as_ref().filter(|_| self.is_scaled()) {
+            Some(resolution_scaler) => {
+                self.draw(encoder, resolution_scaler.input_view(), bind_groups);
+                resolution_scaler.draw(encoder, target, &self.vertex_buffer);
+            }
+            None => self.draw(encoder, target, bind_groups),
+        }
+        if let Some(post_processor) = &self.post_processor {
+            post_processor.draw(encoder, view, &self.vertex_buffer);
+        }
+    }
+
+    // run full screen passes on the fractal before it is presented, in the given order
+    pub fn set_post_passes(&mut self, passes: &[PostPass<'static>]) {
+        self.post_passes = passes.to_vec();
+        let mut passes = self.post_passes.clone();
+        if !self.color_levels.is_neutral() {
+            passes.push(PostPass::ColorAdjustment(self.color_levels));
+        }
+        if self.encode_srgb {
+            passes.push(PostPass::Shader(include_str!("../shaders/post/encode_srgb.wgsl")));
+        }
+        self.post_processor = if passes.is_empty() {
+            None
+        } else {
+            Some(PostProcessor::new(&self.device, self.config.format, &passes))
+        };
+    }
+
+    // correct the gamma, the brightness and the contrast of the frames, on top of the other passes
+    // the pass is only run while the levels change the colors
+    pub fn set_color_levels(&mut self, levels: ColorLevels) {
+        let was_neutral = self.color_levels.is_neutral();
+        self.color_levels = levels;
+        if levels.is_neutral() != was_neutral {
+            let passes = self.post_passes.clone();
+            self.set_post_passes(&passes);
+        } else if let Some(post_processor) = &self.post_processor {
+            post_processor.set_color_levels(&self.queue, levels);
+        }
+    }
+
+    // draw the fractal in a texture view, the bind groups are none when the frame comes from the cpu
+    fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        bind_groups: Option<&[&wgpu::BindGroup]>,
+    ) {
+        let bind_groups = match (&self.cpu_frame, bind_groups) {
+            (Some(cpu_frame), _) => {
+                cpu_frame.draw(encoder, view, &self.vertex_buffer);
+                return;
+            }
+            (None, Some(bind_groups)) => bind_groups,
+            (None, None) => return,
+        };
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(self.render_pipeline.as_ref().unwrap());
+
+        // set the bind groups at the index of their group
+        for (index, bind_group) in bind_groups.iter().enumerate() {
+            render_pass.set_bind_group(index as u32, bind_group, &[]);
+        }
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.draw(0..VERTICES.len() as u32, 0..1);
+    }
+
+    // bind a typed uniform buffer to the shader, it is written to the gpu each time it changes
+    pub fn add_uniform_buffer<T: ToBufferRepresentation + 'static>(
+        &mut self,
+        buffer: &UniformBuffer<T>,
+        visibility: ShaderStages,
+    ) -> BufferHandle {
+        let (data, dirty) = buffer.shared();
+        let handle = self.add_buffer(
+            BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            BufferBindingType::Uniform,
+            visibility,
+            data,
+        );
+        self.buffer_mut(handle).share_dirty_range(dirty);
+        handle
+    }
+
+    // bind a typed storage buffer to the shader, it is written to the gpu each time it changes
+    pub fn add_storage_buffer<T: bytemuck::Pod>(
+        &mut self,
+        buffer: &StorageBuffer<T>,
+        visibility: ShaderStages,
+    ) -> BufferHandle {
+        let (data, dirty) = buffer.shared();
+        let handle = self.add_buffer(
+            BufferUsages::STORAGE | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
+            BufferBindingType::Storage { read_only: false },
+            visibility,
+            data,
+        );
+        self.buffer_mut(handle).share_dirty_range(dirty);
+        handle
+    }
+
+    // copy a buffer to another each time a frame has been drawn
+    pub fn add_buffer_copy(&mut self, source: BufferHandle, destination: BufferHandle) {
+        self.buffer_copies.push((source, destination));
+    }
+
+    // bind the data of another typed buffer in place of the one of a buffer,
+    // at the same binding, for the states that swap their data with the ones of another state
+    pub fn replace_buffer<B: TypedBuffer>(&mut self, handle: BufferHandle, buffer: &B) {
+        let (data, dirty) = buffer.shared();
+        let mut replacement = self.buffer(handle).with_data(&self.device, data);
+        replacement.share_dirty_range(dirty);
+        self.bind_groups[ResourceGroup::of_buffer(&replacement) as usize] = None;
+        self.buffers[handle.0] = Some(replacement);
+    }
+
+    // unbind a buffer and stop its copies, its handle must not be used anymore
+    // its binding is given to the next buffer added to its group, the pipeline is built again
+    // for the next frame, so the shader must not use the binding until then
+    // only the tests remove a buffer for now, the explorer keeps all of its own for the shaders
+    // of every fractal, it is for the states bringing buffers of their own, like a julia mode
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn remove_buffer(&mut self, handle: BufferHandle) {
+        let buffer = match self.buffers[handle.0].take() {
+            Some(buffer) => buffer,
+            None => return,
+        };
+        self.buffer_copies
+            .retain(|(source, destination)| *source != handle && *destination != handle);
+        self.invalidate_bind_group_layout(ResourceGroup::of_buffer(&buffer));
+    }
+
+    fn buffer(&self, handle: BufferHandle) -> &BindGroupBufferEntry {
+        self.buffers[handle.0]
+            .as_ref()
+            .expect("the buffer has been removed")
     }
 
-    pub fn add_buffer(
+    fn buffer_mut(&mut self, handle: BufferHandle) -> &mut BindGroupBufferEntry {
+        self.buffers[handle.0]
+            .as_mut()
+            .expect("the buffer has been removed")
+    }
+
+    // bind a texture with a value per pixel of the frame to the shader,
+    // written by it or read as the values of the last frame, resized with the frames
+    pub fn add_pixel_texture(
+        &mut self,
+        access: PixelAccess,
+        initial_value: f32,
+        visibility: ShaderStages,
+    ) -> PixelTextureHandle {
+        let handle = PixelTextureHandle(self.pixel_textures.len());
+        let binding = self.next_binding(ResourceGroup::Storage);
+        self.pixel_textures.push(PixelTexture::new(
+            &self.device,
+            &self.queue,
+            binding,
+            visibility,
+            access,
+            self.size(),
+            initial_value,
+        ));
+        self.invalidate_bind_group_layout(ResourceGroup::Storage);
+        handle
+    }
+
+    // copy a pixel texture to another each time a frame has been drawn
+    pub fn add_pixel_texture_copy(
+        &mut self,
+        source: PixelTextureHandle,
+        destination: PixelTextureHandle,
+    ) {
+        self.pixel_texture_copies.push((source, destination));
+    }
+
+    // replace the values of a pixel texture, line by line from the bottom one
+    pub fn write_pixel_texture(&self, handle: PixelTextureHandle, values: &[f32], width: u32) {
+        self.pixel_textures[handle.0].write(&self.queue, values, width);
+    }
+
+    // the values of the pixels of the area of the given size at the bottom left of a pixel texture,
+    // line by line from the bottom one, waiting for the gpu
+    pub fn read_pixel_texture_blocking(
+        &self,
+        handle: PixelTextureHandle,
+        size: winit::dpi::PhysicalSize<u32>,
+    ) -> Result<Vec<f32>, String> {
+        let pixel_texture = &self.pixel_textures[handle.0];
+        let size = winit::dpi::PhysicalSize::new(
+            size.width.min(pixel_texture.size().width).max(1),
+            size.height.min(pixel_texture.size().height).max(1),
+        );
+        let read_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Read Buffer"),
+            size: (PixelTexture::padded_bytes_per_row(size.width) * size.height) as BufferAddress,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Read Encoder"),
+            });
+        pixel_texture.copy_to_buffer(&mut encoder, &read_buffer, size);
+        self.queue.submit(std::iter::once(encoder.finish()));
+        let readback = Readback::new(self.device.clone(), read_buffer);
+        self.wait();
+        let bytes = pollster::block_on(readback)?;
+        Ok(PixelTexture::red_channel(&bytes, size))
+    }
+
+    // create the pixel textures again at the size of the frames
+    fn resize_pixel_textures(&mut self, size: winit::dpi::PhysicalSize<u32>) {
+        for pixel_texture in self.pixel_textures.iter_mut() {
+            if pixel_texture.resize(&self.device, &self.queue, size) {
+                self.bind_groups[ResourceGroup::Storage as usize] = None;
+            }
+        }
+    }
+
+    // write the changes of the buffers by the commands of the encoder
+    fn flush_buffers(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        for buffer in self.buffers.iter_mut().flatten() {
+            // a reallocated buffer is not the one bound anymore
+            if buffer.flush(&self.device, encoder, &mut self.staging_belt) {
+                self.bind_groups[ResourceGroup::of_buffer(buffer) as usize] = None;
+            }
+            self.frame_stats.upload_bytes += buffer.take_written_bytes();
+        }
+        if let Some(overview) = self.overview.as_mut() {
+            overview.flush(&self.device, encoder, &mut self.staging_belt);
+        }
+        if let Some(julia_preview) = self.julia_preview.as_mut() {
+            julia_preview.flush(&self.device, encoder, &mut self.staging_belt);
+        }
+    }
+
+    fn add_buffer(
         &mut self,
         usage: BufferUsages,
         buffer_binding_type: BufferBindingType,
         visibility: ShaderStages,
         data: Rc<RefCell<dyn ToBufferRepresentation>>,
-    ) {
-        self.buffers.push(BindGroupBufferEntry::new(
+    ) -> BufferHandle {
+        let handle = BufferHandle(self.buffers.len());
+        let group = ResourceGroup::of_buffer_type(buffer_binding_type);
+        let binding = self.next_binding(group);
+        self.buffers.push(Some(BindGroupBufferEntry::new(
             &self.device,
-            self.buffers.len() as u32,
+            binding,
             visibility,
             usage,
             buffer_binding_type,
             data,
+        )));
+        self.invalidate_bind_group_layout(group);
+        handle
+    }
+
+    // bind a rgba texture and its sampler to the shader, for palettes, look up tables
+    // or the result of another pass
+    // the pixels are 4 bytes each, row by row
+    pub fn add_texture(
+        &mut self,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        visibility: ShaderStages,
+    ) -> TextureHandle {
+        let handle = TextureHandle(self.textures.len());
+        let binding = self.next_binding(ResourceGroup::Static);
+        self.textures.push(BindGroupTextureEntry::new(
+            &self.device,
+            &self.queue,
+            binding,
+            visibility,
+            width,
+            height,
+            pixels,
         ));
+        self.invalidate_bind_group_layout(ResourceGroup::Static);
+        handle
+    }
+
+    // replace the pixels of a texture, with the size it has been added with
+    pub fn write_texture(&self, handle: TextureHandle, pixels: &[u8]) {
+        self.textures[handle.0].write(&self.queue, pixels);
+    }
+
+    // the bindings of a group follow the order in which its buffers and textures are added,
+    // a texture takes two of them, one for its sampler, the ones of the removed buffers are reused
+    fn next_binding(&self, group: ResourceGroup) -> u32 {
+        let buffers = self
+            .buffers
+            .iter()
+            .flatten()
+            .filter(|b| ResourceGroup::of_buffer(b) == group)
+            .map(|b| b.bind_group_layout_entry.binding);
+        let used = match group {
+            ResourceGroup::Static => self
+                .textures
+                .iter()
+                .flat_map(|t| t.bind_group_layout_entries().map(|entry| entry.binding))
+                .collect::<Vec<_>>(),
+            ResourceGroup::Frame => buffers.collect(),
+            ResourceGroup::Storage => buffers
+                .chain(
+                    self.pixel_textures
+                        .iter()
+                        .map(|t| t.bind_group_layout_entry().binding),
+                )
+                .collect(),
+        };
+        (0..).find(|binding| !used.contains(binding)).unwrap()
+    }
+
+    fn invalidate_bind_group_layout(&mut self, group: ResourceGroup) {
+        // the adapters that need the cpu renderer may not support the buffers of the shader
+        if self.cpu_frame.is_some() {
+            return;
+        }
+        self.bind_group_layouts[group as usize] = Self::create_bind_group_layout(
+            &self.device,
+            group,
+            &self.buffers,
+            &self.textures,
+            &self.pixel_textures,
+        );
+        self.bind_groups[group as usize] = None;
+        // the pipeline is built with the layouts, it is built again by the next frame
+        self.render_pipeline = None;
     }
 
     pub fn create_pipeline(&mut self) {
+        if self.cpu_frame.is_some() {
+            return;
+        }
+        self.render_pipeline = Some(self.build_pipeline(&self.shader_source));
+    }
+
+    // compile a new version of the shader and use it if it is valid
+    // otherwise the current pipeline is kept and the error is returned
+    pub fn reload_shader(&mut self, source: String) -> Result<(), String> {
+        if self.cpu_frame.is_some() {
+            return Err("the frames are computed by the cpu, without shader".to_string());
+        }
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let render_pipeline = self.build_pipeline(&source);
+        if let Some(error) = pollster::block_on(self.device.pop_error_scope()) {
+            return Err(error.to_string());
+        }
+        self.render_pipeline = Some(render_pipeline);
+        self.shader_source = source;
+        Ok(())
+    }
+
+    fn build_pipeline(&self, source: &str) -> wgpu::RenderPipeline {
         let shader = self
             .device
             .create_shader_module(wgpu::ShaderModuleDescriptor {
                 label: Some("Shader"),
-                source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/mandelbrot.wgsl").into()),
+                source: wgpu::ShaderSource::Wgsl(source.into()),
             });
-        // create a bind group layout from the buffers bind group layouts entries
-        let bind_group_layout =
-            self.device
-                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                    label: Some("Bind Group Layout"),
-                    entries: &self
-                        .buffers
-                        .iter()
-                        .map(|b| b.bind_group_layout_entry)
-                        .collect::<Vec<_>>(),
-                });
-
         // create a render pipeline layout
         let render_pipeline_layout =
             self.device
                 .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                     label: Some("Render Pipeline Layout"),
-                    bind_group_layouts: &[&bind_group_layout],
+                    bind_group_layouts: &self.bind_group_layouts.iter().collect::<Vec<_>>(),
                     push_constant_ranges: &[],
                 });
-        let render_pipeline = self
-            .device
+        self.device
             .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
                 label: Some("Render Pipeline"),
                 layout: Some(&render_pipeline_layout),
@@ -257,7 +1570,65 @@ impl Engine {
                     alpha_to_coverage_enabled: false,
                 },
                 multiview: None,
-            });
-        self.render_pipeline = Some(render_pipeline);
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // an engine without a window, none when there is no adapter, the tests are then skipped
+    fn engine() -> Option<Engine> {
+        let size = winit::dpi::PhysicalSize::new(4, 4);
+        let engine = pollster::block_on(Engine::new_headless(size, &AdapterSelection::default()));
+        if engine.is_none() {
+            eprintln!("There is no adapter, the test is skipped");
+        }
+        engine
+    }
+
+    fn binding(engine: &Engine, handle: BufferHandle) -> u32 {
+        engine.buffer(handle).bind_group_layout_entry.binding
+    }
+
+    #[test]
+    fn a_replaced_buffer_keeps_its_binding_and_binds_the_new_data() {
+        let mut engine = match engine() {
+            Some(engine) => engine,
+            None => return,
+        };
+        let first = StorageBuffer::new(vec![1u32; 4]);
+        let handle = engine.add_storage_buffer(&first, ShaderStages::FRAGMENT);
+        let other = StorageBuffer::new(vec![0u32; 4]);
+        let other_handle = engine.add_storage_buffer(&other, ShaderStages::FRAGMENT);
+        let first_binding = binding(&engine, handle);
+        let replacement = StorageBuffer::new(vec![2u32; 8]);
+        engine.replace_buffer(handle, &replacement);
+        assert_eq!(binding(&engine, handle), first_binding);
+        assert_ne!(binding(&engine, other_handle), first_binding);
+        let (data, _) = replacement.shared();
+        assert!(Rc::ptr_eq(&engine.buffer(handle).data, &data));
+    }
+
+    #[test]
+    fn the_binding_of_a_removed_buffer_is_given_to_the_next_one() {
+        let mut engine = match engine() {
+            Some(engine) => engine,
+            None => return,
+        };
+        let first = StorageBuffer::new(vec![1u32; 4]);
+        let handle = engine.add_storage_buffer(&first, ShaderStages::FRAGMENT);
+        let second = StorageBuffer::new(vec![2u32; 4]);
+        let second_handle = engine.add_storage_buffer(&second, ShaderStages::FRAGMENT);
+        engine.add_buffer_copy(handle, second_handle);
+        let removed_binding = binding(&engine, handle);
+        engine.remove_buffer(handle);
+        assert!(engine.buffers[handle.0].is_none());
+        assert!(engine.buffer_copies.is_empty());
+        let third = StorageBuffer::new(vec![3u32; 4]);
+        let third_handle = engine.add_storage_buffer(&third, ShaderStages::FRAGMENT);
+        assert_eq!(binding(&engine, third_handle), removed_binding);
+        assert_ne!(binding(&engine, second_handle), removed_binding);
     }
 }
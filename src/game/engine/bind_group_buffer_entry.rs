@@ -1,16 +1,46 @@
 use std::borrow::Borrow;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use wgpu::{
     BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
     BindGroupLayoutEntry, BindingResource, BindingType, Buffer, BufferBinding, BufferBindingType,
-    BufferUsages, Device, Queue, ShaderStages,
+    BufferSize, BufferUsages, CommandEncoder, Device, ShaderStages,
 };
-use wgpu::util::{BufferInitDescriptor, DeviceExt};
+use wgpu::util::{BufferInitDescriptor, DeviceExt, StagingBelt};
 
 use crate::game::to_buffer_representation::ToBufferRepresentation;
 
+// the bytes of the data changed since they were last written to the buffer
+#[derive(Clone, Copy)]
+pub struct DirtyRange {
+    start: usize,
+    end: usize,
+}
+
+impl DirtyRange {
+    pub const CLEAN: Self = Self {
+        start: usize::MAX,
+        end: 0,
+    };
+    pub const ALL: Self = Self {
+        start: 0,
+        end: usize::MAX,
+    };
+
+    // the smallest range containing this one and the bytes from start to end
+    pub fn with(self, start: usize, end: usize) -> Self {
+        Self {
+            start: self.start.min(start),
+            end: self.end.max(end),
+        }
+    }
+
+    fn is_clean(&self) -> bool {
+        self.start >= self.end
+    }
+}
+
 // create a struct to hold a bind group layout entry, a bind group entry, and a buffer
 
 pub struct BindGroupBufferEntry {
@@ -19,13 +49,17 @@ pub struct BindGroupBufferEntry {
     length: usize,
     usage: BufferUsages,
     pub data: Rc<RefCell<dyn ToBufferRepresentation>>,
+    // the part of the data changed since it was last written to the buffer
+    // shared with the typed buffers, which extend it when their values change
+    dirty: Rc<Cell<DirtyRange>>,
+    // the bytes written to the buffer since they were last counted
+    written_bytes: usize,
 }
 
 // implement new for BindGroupBufferEntry
 impl BindGroupBufferEntry {
-
     // create that return a bing group entry
-    pub fn bind_group_entry(&self) -> BindGroupEntry {
+    pub fn bind_group_entry(&self) -> BindGroupEntry<'_> {
         let buffer = &self.buffer;
         let binding = self.bind_group_layout_entry.binding;
         BindGroupEntry {
@@ -39,22 +73,94 @@ impl BindGroupBufferEntry {
         self.length
     }
 
-    pub fn update(&mut self, device: &Device, queue: &Queue) {
+    // a new buffer at the same binding, with the same usage, for other data
+    pub fn with_data(
+        &self,
+        device: &Device,
+        data: Rc<RefCell<dyn ToBufferRepresentation>>,
+    ) -> Self {
+        let buffer_binding_type = match self.bind_group_layout_entry.ty {
+            BindingType::Buffer { ty, .. } => ty,
+            _ => unreachable!("the entry of a buffer binds a buffer"),
+        };
+        Self::new(
+            device,
+            self.bind_group_layout_entry.binding,
+            self.bind_group_layout_entry.visibility,
+            self.usage,
+            buffer_binding_type,
+            data,
+        )
+    }
+
+    // use the range of a typed buffer, so its changes are written automatically
+    pub fn share_dirty_range(&mut self, dirty: Rc<Cell<DirtyRange>>) {
+        self.dirty = dirty;
+    }
+
+    // mark some bytes of the data as changed, only the changed bytes are written by the next flush
+    pub fn mark_dirty(&self, offset: usize, length: usize) {
+        self.dirty
+            .set(self.dirty.get().with(offset, offset + length));
+    }
+
+    // write the changed part of the data to the buffer, through the staging belt,
+    // by the commands of the encoder
+    // return true if the buffer has been reallocated, so it must be bound again
+    pub fn flush(
+        &mut self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        staging_belt: &mut StagingBelt,
+    ) -> bool {
+        profiling::scope!("BindGroupBufferEntry::flush");
+        let dirty = self.dirty.replace(DirtyRange::CLEAN);
+        if dirty.is_clean() {
+            return false;
+        }
+        self.write(device, encoder, staging_belt, dirty)
+    }
+
+    // the bytes written to the buffer since the last call, for the statistics of the frames
+    pub fn take_written_bytes(&mut self) -> usize {
+        std::mem::take(&mut self.written_bytes)
+    }
+
+    // write a range of the data to the buffer, a new buffer is created when the length of the data
+    // changed, return true if the buffer has been reallocated
+    fn write(
+        &mut self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        staging_belt: &mut StagingBelt,
+        range: DirtyRange,
+    ) -> bool {
         let data: &RefCell<dyn ToBufferRepresentation> = self.data.borrow();
         let data = data.borrow();
         let contents = data.to_bits();
-        if self.length != contents.len() {
-            self.length = contents.len();
+        if self.length != data.len_bytes() {
+            self.length = data.len_bytes();
             self.buffer = device.create_buffer_init(&BufferInitDescriptor {
                 label: Some("Buffer"),
                 contents,
                 usage: self.usage,
             });
+            self.written_bytes += contents.len();
+            return true;
+        }
+        // the offset and the size of a write are multiples of 4 bytes
+        let end = range.end.min(contents.len()).div_ceil(4) * 4;
+        let end = end.min(contents.len());
+        let start = range.start.min(end) / 4 * 4;
+        if let Some(size) = BufferSize::new((end - start) as u64) {
+            staging_belt
+                .write_buffer(encoder, &self.buffer, start as u64, size, device)
+                .copy_from_slice(&contents[start..end]);
+            self.written_bytes += end - start;
         }
-        queue.write_buffer(&self.buffer, 0, contents);
+        false
     }
 
-
     // create a new BindGroupBufferEntry
     pub fn new(
         device: &Device,
@@ -63,10 +169,10 @@ impl BindGroupBufferEntry {
         usage: BufferUsages,
         buffer_binding_type: BufferBindingType,
         data: Rc<RefCell<dyn ToBufferRepresentation>>,
-    ) -> Self { ;
+    ) -> Self {
         // create a buffer from the data
         let _data: &RefCell<dyn ToBufferRepresentation> = data.borrow();
-        let length = _data.borrow().to_bits().len();
+        let length = _data.borrow().len_bytes();
         let buffer = device.create_buffer_init(&BufferInitDescriptor {
             label: Some("Buffer"),
             contents: _data.borrow().to_bits(),
@@ -89,6 +195,8 @@ impl BindGroupBufferEntry {
             usage,
             buffer,
             data,
+            dirty: Rc::new(Cell::new(DirtyRange::CLEAN)),
+            written_bytes: 0,
         }
     }
 }
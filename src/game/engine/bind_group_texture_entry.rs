@@ -0,0 +1,128 @@
+use std::num::NonZeroU32;
+
+use wgpu::{
+    AddressMode, BindGroupEntry, BindGroupLayoutEntry, BindingResource, BindingType, Device,
+    Extent3d, FilterMode, ImageCopyTexture, ImageDataLayout, Origin3d, Queue, Sampler,
+    SamplerBindingType, SamplerDescriptor, ShaderStages, Texture, TextureAspect,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureSampleType, TextureUsages,
+    TextureView, TextureViewDimension,
+};
+
+// a rgba texture and its sampler, bound to the shader next to the buffers
+// the texture uses the given binding and the sampler the next one
+pub struct BindGroupTextureEntry {
+    pub texture: Texture,
+    pub view: TextureView,
+    pub sampler: Sampler,
+    texture_layout_entry: BindGroupLayoutEntry,
+    sampler_layout_entry: BindGroupLayoutEntry,
+    width: u32,
+    height: u32,
+}
+
+impl BindGroupTextureEntry {
+    // create the texture from rgba pixels, 4 bytes per pixel, row by row
+    // it can also be rendered into, for the passes that draw in a texture
+    pub fn new(
+        device: &Device,
+        queue: &Queue,
+        binding: u32,
+        visibility: ShaderStages,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+    ) -> Self {
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some("Texture"),
+            size: Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8Unorm,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        // the palettes and the look up tables are read between their values
+        let sampler = device.create_sampler(&SamplerDescriptor {
+            label: Some("Sampler"),
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            address_mode_w: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            mipmap_filter: FilterMode::Nearest,
+            ..Default::default()
+        });
+        let entry = Self {
+            texture,
+            view,
+            sampler,
+            texture_layout_entry: BindGroupLayoutEntry {
+                binding,
+                visibility,
+                ty: BindingType::Texture {
+                    sample_type: TextureSampleType::Float { filterable: true },
+                    view_dimension: TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            sampler_layout_entry: BindGroupLayoutEntry {
+                binding: binding + 1,
+                visibility,
+                ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                count: None,
+            },
+            width,
+            height,
+        };
+        entry.write(queue, pixels);
+        entry
+    }
+
+    // replace the pixels of the texture, its size does not change
+    pub fn write(&self, queue: &Queue, pixels: &[u8]) {
+        queue.write_texture(
+            ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            pixels,
+            ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(self.width * 4),
+                rows_per_image: NonZeroU32::new(self.height),
+            },
+            Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    pub fn bind_group_layout_entries(&self) -> [BindGroupLayoutEntry; 2] {
+        [self.texture_layout_entry, self.sampler_layout_entry]
+    }
+
+    pub fn bind_group_entries(&self) -> [BindGroupEntry<'_>; 2] {
+        [
+            BindGroupEntry {
+                binding: self.texture_layout_entry.binding,
+                resource: BindingResource::TextureView(&self.view),
+            },
+            BindGroupEntry {
+                binding: self.sampler_layout_entry.binding,
+                resource: BindingResource::Sampler(&self.sampler),
+            },
+        ]
+    }
+}
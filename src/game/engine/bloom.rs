@@ -0,0 +1,177 @@
+use winit::dpi::PhysicalSize;
+
+use crate::game::engine::post_process::{create_pipeline, draw_pass};
+
+// a halo around the bright parts of the image, like the filaments at a high palette scale
+// the bright parts are extracted in a texture of half the size of the frame, blurred horizontally
+// then vertically between two textures, and added to the image
+pub struct Bloom {
+    format: wgpu::TextureFormat,
+    bind_group_layout: wgpu::BindGroupLayout,
+    // the layout of the last pass, reading the image and the blurred bright parts
+    composite_bind_group_layout: wgpu::BindGroupLayout,
+    threshold: wgpu::RenderPipeline,
+    blur_horizontal: wgpu::RenderPipeline,
+    blur_vertical: wgpu::RenderPipeline,
+    composite: wgpu::RenderPipeline,
+    // the half size textures, with the bind groups reading them
+    blur_targets: Vec<(wgpu::TextureView, wgpu::BindGroup)>,
+    // for each texture the bloom can read the image from, the bind group reading it
+    // and the one reading it with the blurred bright parts
+    inputs: Vec<(wgpu::BindGroup, wgpu::BindGroup)>,
+}
+
+impl Bloom {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let sampler_entry = wgpu::BindGroupLayoutEntry {
+            binding: 1,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Bloom Bind Group Layout"),
+            entries: &[texture_entry(0), sampler_entry],
+        });
+        let composite_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Bloom Composite Bind Group Layout"),
+                entries: &[texture_entry(0), sampler_entry, texture_entry(2)],
+            });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Bloom Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/post/bloom.wgsl").into()),
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Bloom Render Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let composite_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Bloom Composite Render Pipeline Layout"),
+            bind_group_layouts: &[&composite_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        Self {
+            format,
+            threshold: create_pipeline(device, &layout, &shader, "fs_threshold", format),
+            blur_horizontal: create_pipeline(device, &layout, &shader, "fs_blur_horizontal", format),
+            blur_vertical: create_pipeline(device, &layout, &shader, "fs_blur_vertical", format),
+            composite: create_pipeline(device, &composite_layout, &shader, "fs_composite", format),
+            bind_group_layout,
+            composite_bind_group_layout,
+            blur_targets: vec![],
+            inputs: vec![],
+        }
+    }
+
+    // create the textures for a new size of the frames,
+    // the inputs are the textures the image can be read from
+    pub fn resize(
+        &mut self,
+        device: &wgpu::Device,
+        size: PhysicalSize<u32>,
+        sampler: &wgpu::Sampler,
+        inputs: &[&wgpu::TextureView],
+    ) {
+        self.blur_targets = (0..2)
+            .map(|_| {
+                let texture = device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some("Bloom Texture"),
+                    size: wgpu::Extent3d {
+                        width: (size.width / 2).max(1),
+                        height: (size.height / 2).max(1),
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: self.format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                        | wgpu::TextureUsages::TEXTURE_BINDING,
+                });
+                let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+                let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Bloom Bind Group"),
+                    layout: &self.bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(&view),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Sampler(sampler),
+                        },
+                    ],
+                });
+                (view, bind_group)
+            })
+            .collect();
+        self.inputs = inputs
+            .iter()
+            .map(|input| {
+                let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Bloom Input Bind Group"),
+                    layout: &self.bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(input),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Sampler(sampler),
+                        },
+                    ],
+                });
+                let composite_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Bloom Composite Bind Group"),
+                    layout: &self.composite_bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(input),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Sampler(sampler),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: wgpu::BindingResource::TextureView(&self.blur_targets[0].0),
+                        },
+                    ],
+                });
+                (bind_group, composite_bind_group)
+            })
+            .collect();
+    }
+
+    // add the halo to the image read from the given input, into the view
+    pub fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        input: usize,
+        vertex_buffer: &wgpu::Buffer,
+    ) {
+        let (input_bind_group, composite_bind_group) = &self.inputs[input];
+        let (first, first_bind_group) = &self.blur_targets[0];
+        let (second, second_bind_group) = &self.blur_targets[1];
+        draw_pass(encoder, first, &self.threshold, input_bind_group, vertex_buffer);
+        draw_pass(encoder, second, &self.blur_horizontal, first_bind_group, vertex_buffer);
+        draw_pass(encoder, first, &self.blur_vertical, second_bind_group, vertex_buffer);
+        draw_pass(encoder, view, &self.composite, composite_bind_group, vertex_buffer);
+    }
+}
@@ -0,0 +1,156 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+
+use crate::game::engine::post_process::{create_pipeline, draw_pass};
+
+// the correction of the colors for the screen they are shown on
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub struct ColorLevels {
+    // above 1 to lighten the dark colors, below 1 to darken them
+    pub gamma: f32,
+    // added to the colors, negative to darken them
+    pub brightness: f32,
+    // the colors are spread around the middle gray by this factor
+    pub contrast: f32,
+    pub _padding: u32,
+}
+
+impl ColorLevels {
+    // the levels that leave the colors as they are
+    pub const NEUTRAL: ColorLevels = ColorLevels {
+        gamma: 1.0,
+        brightness: 0.0,
+        contrast: 1.0,
+        _padding: 0,
+    };
+
+    pub fn new(gamma: f32, brightness: f32, contrast: f32) -> Self {
+        Self {
+            gamma,
+            brightness,
+            contrast,
+            _padding: 0,
+        }
+    }
+
+    pub fn is_neutral(&self) -> bool {
+        *self == Self::NEUTRAL
+    }
+}
+
+// the pass correcting the gamma, the brightness and the contrast of the image,
+// the levels are a uniform so they are changed without creating the pipeline again
+pub struct ColorAdjustment {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+    levels: wgpu::Buffer,
+    // for each texture the image can be read from, the bind group reading it with the levels
+    inputs: Vec<wgpu::BindGroup>,
+}
+
+impl ColorAdjustment {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, levels: ColorLevels) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Color Adjustment Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Color Adjustment Shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                include_str!("../../shaders/post/color_adjustment.wgsl").into(),
+            ),
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Color Adjustment Render Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let levels = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Color Adjustment Buffer"),
+            contents: bytemuck::bytes_of(&levels),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        Self {
+            pipeline: create_pipeline(device, &layout, &shader, "fs_main", format),
+            bind_group_layout,
+            levels,
+            inputs: vec![],
+        }
+    }
+
+    // the inputs are the textures the image can be read from
+    pub fn resize(
+        &mut self,
+        device: &wgpu::Device,
+        sampler: &wgpu::Sampler,
+        inputs: &[&wgpu::TextureView],
+    ) {
+        self.inputs = inputs
+            .iter()
+            .map(|input| {
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Color Adjustment Bind Group"),
+                    layout: &self.bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(input),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Sampler(sampler),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: self.levels.as_entire_binding(),
+                        },
+                    ],
+                })
+            })
+            .collect();
+    }
+
+    pub fn set_levels(&self, queue: &wgpu::Queue, levels: ColorLevels) {
+        queue.write_buffer(&self.levels, 0, bytemuck::bytes_of(&levels));
+    }
+
+    // correct the image read from the given input, into the view
+    pub fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        input: usize,
+        vertex_buffer: &wgpu::Buffer,
+    ) {
+        let bind_group = &self.inputs[input];
+        draw_pass(encoder, view, &self.pipeline, bind_group, vertex_buffer);
+    }
+}
@@ -0,0 +1,115 @@
+use wgpu::ShaderStages;
+use winit::dpi::PhysicalSize;
+
+use crate::game::engine::bind_group_texture_entry::BindGroupTextureEntry;
+use crate::game::engine::post_process::{create_pipeline, draw_pass};
+
+// the frame computed by the cpu renderer, uploaded to a texture and drawn in place of the shader
+// of the fractal, for the adapters that can not run it
+// it only needs a texture and a sampler, which every adapter supports
+pub struct CpuFrame {
+    render_pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    // the texture of the last frame and the bind group reading it, none before the first frame
+    texture: Option<(BindGroupTextureEntry, wgpu::BindGroup)>,
+    // the size of the texture
+    size: PhysicalSize<u32>,
+}
+
+impl CpuFrame {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Cpu Frame Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Cpu Frame Render Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Cpu Frame Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/stretch.wgsl").into()),
+        });
+        Self {
+            render_pipeline: create_pipeline(
+                device,
+                &render_pipeline_layout,
+                &shader,
+                "fs_main",
+                format,
+            ),
+            bind_group_layout,
+            texture: None,
+            size: PhysicalSize::new(0, 0),
+        }
+    }
+
+    // upload the pixels of a frame, 4 bytes each from the top line,
+    // the texture is created again when the size of the frames changes
+    pub fn write(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        size: PhysicalSize<u32>,
+        pixels: &[u8],
+    ) {
+        match &self.texture {
+            Some((texture, _)) if size == self.size => texture.write(queue, pixels),
+            _ => {
+                let texture = BindGroupTextureEntry::new(
+                    device,
+                    queue,
+                    0,
+                    ShaderStages::FRAGMENT,
+                    size.width,
+                    size.height,
+                    pixels,
+                );
+                let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Cpu Frame Bind Group"),
+                    layout: &self.bind_group_layout,
+                    entries: &texture.bind_group_entries(),
+                });
+                self.texture = Some((texture, bind_group));
+                self.size = size;
+            }
+        }
+    }
+
+    // draw the last frame into a texture view, nothing is drawn before the first frame
+    pub fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        vertex_buffer: &wgpu::Buffer,
+    ) {
+        if let Some((_, bind_group)) = &self.texture {
+            draw_pass(
+                encoder,
+                view,
+                &self.render_pipeline,
+                bind_group,
+                vertex_buffer,
+            );
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use winit::dpi::PhysicalSize;
+
+use crate::game::engine::post_process::create_pipeline;
+use crate::game::engine::vertex::VERTICES;
+
+// the thumbnails of the bookmarks put together in one image by the gallery,
+// drawn over the fractal in a rectangle of the window
+pub struct Gallery {
+    render_pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+    // the corners of the rectangle the image is drawn in, in pixels of the window from its top left
+    rectangle: [[f32; 2]; 2],
+}
+
+impl Gallery {
+    // the pixels are rgba, 4 bytes each from the top line, and encoded in srgb like a png file,
+    // they are decoded when the surface expects linear colors
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        linear: bool,
+        image: &image::RgbaImage,
+    ) -> Self {
+        let size = wgpu::Extent3d {
+            width: image.width().max(1),
+            height: image.height().max(1),
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Gallery Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: if linear {
+                wgpu::TextureFormat::Rgba8UnormSrgb
+            } else {
+                wgpu::TextureFormat::Rgba8Unorm
+            },
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        });
+        if !image.is_empty() {
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                image.as_raw(),
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: std::num::NonZeroU32::new(4 * image.width()),
+                    rows_per_image: std::num::NonZeroU32::new(image.height()),
+                },
+                size,
+            );
+        }
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Gallery Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Gallery Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Gallery Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Gallery Render Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        // the image is stretched to the rectangle like the fractal drawn at a lower resolution
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Gallery Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/stretch.wgsl").into()),
+        });
+        Self {
+            render_pipeline: create_pipeline(
+                device,
+                &render_pipeline_layout,
+                &shader,
+                "fs_main",
+                format,
+            ),
+            bind_group,
+            rectangle: [[0.0, 0.0], [0.0, 0.0]],
+        }
+    }
+
+    pub fn set_rectangle(&mut self, rectangle: [[f32; 2]; 2]) {
+        self.rectangle = rectangle;
+    }
+
+    pub fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        size: PhysicalSize<u32>,
+        vertex_buffer: &wgpu::Buffer,
+    ) {
+        // the viewport must stay inside the frame
+        let [start, end] = self.rectangle;
+        let (width, height) = (size.width as f32, size.height as f32);
+        let x = start[0].clamp(0.0, width);
+        let y = start[1].clamp(0.0, height);
+        let (w, h) = (end[0].clamp(x, width) - x, end[1].clamp(y, height) - y);
+        if w < 1.0 || h < 1.0 {
+            return;
+        }
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Gallery Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_viewport(x, y, w, h, 0.0, 1.0);
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.draw(0..VERTICES.len() as u32, 0..1);
+    }
+}
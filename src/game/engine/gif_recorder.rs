@@ -0,0 +1,121 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::imageops::FilterType;
+use image::{Delay, Frame, RgbaImage};
+use instant::Instant;
+
+use crate::game::config::GifConfig;
+use crate::game::engine::screenshot::PendingScreenshot;
+
+// the last seconds of the exploration kept as small frames, so they can be saved as a gif
+// a few frames per second are read back from the gpu without waiting for them,
+// and the oldest ones are dropped once there are enough
+pub struct GifRecorder {
+    config: GifConfig,
+    frames: VecDeque<RgbaImage>,
+    // the frame being copied from the gpu
+    pending: Option<PendingScreenshot>,
+    last_capture: Option<Instant>,
+}
+
+impl GifRecorder {
+    pub fn new(config: GifConfig) -> Self {
+        Self {
+            config,
+            frames: VecDeque::new(),
+            pending: None,
+            last_capture: None,
+        }
+    }
+
+    // true when the next frame should be kept
+    pub fn wants_frame(&self) -> bool {
+        let interval = 1.0 / self.config.fps as f32;
+        self.pending.is_none()
+            && self
+                .last_capture
+                .is_none_or(|time| time.elapsed().as_secs_f32() >= interval)
+    }
+
+    pub fn capture(&mut self, pending: PendingScreenshot) {
+        self.pending = Some(pending);
+        self.last_capture = Some(Instant::now());
+    }
+
+    // keep the frame being copied once the copy is done
+    pub fn poll(&mut self) -> Result<(), String> {
+        let pixels = match self
+            .pending
+            .as_mut()
+            .and_then(|pending| pending.try_read_pixels())
+        {
+            Some(pixels) => pixels,
+            None => return Ok(()),
+        };
+        let (width, height) = self.pending.take().map_or((0, 0), |pending| pending.size());
+        let image = RgbaImage::from_raw(width, height, pixels?).ok_or("invalid frame size")?;
+        let frame_width = self.config.width;
+        let frame_height = (height as u64 * frame_width as u64 / width.max(1) as u64).max(1) as u32;
+        // the frames of a gif have the same size, the ones of another size of the window are dropped
+        if self
+            .frames
+            .back()
+            .is_some_and(|frame| frame.dimensions() != (frame_width, frame_height))
+        {
+            self.frames.clear();
+        }
+        let frame =
+            image::imageops::resize(&image, frame_width, frame_height, FilterType::Triangle);
+        self.frames.push_back(frame);
+        let capacity = (self.config.seconds * self.config.fps as f32).round() as usize;
+        while self.frames.len() > capacity {
+            self.frames.pop_front();
+        }
+        Ok(())
+    }
+
+    // the frames kept, from the oldest one
+    pub fn clip(&self) -> GifClip {
+        GifClip {
+            frames: self.frames.iter().cloned().collect(),
+            fps: self.config.fps,
+        }
+    }
+}
+
+// frames taken from the recorder, saved apart so the exploration goes on meanwhile
+pub struct GifClip {
+    frames: Vec<RgbaImage>,
+    fps: u32,
+}
+
+impl GifClip {
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.frames.len() as f32 / self.fps as f32
+    }
+
+    // encode the frames, each with its own palette, which takes a few seconds
+    pub fn save(self, path: &Path) -> Result<(), String> {
+        let file = BufWriter::new(File::create(path).map_err(|error| error.to_string())?);
+        let mut encoder = GifEncoder::new_with_speed(file, 10);
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .map_err(|error| error.to_string())?;
+        let delay = Delay::from_numer_denom_ms(1000, self.fps);
+        encoder
+            .encode_frames(
+                self.frames
+                    .into_iter()
+                    .map(|frame| Frame::from_parts(frame, 0, 0, delay)),
+            )
+            .map_err(|error| error.to_string())
+    }
+}
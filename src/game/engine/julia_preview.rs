@@ -0,0 +1,203 @@
+use std::cell::RefCell;
+use std::mem::size_of;
+use std::ops::Deref;
+use std::rc::Rc;
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::StagingBelt;
+use wgpu::{BufferBindingType, BufferUsages, ShaderStages};
+use winit::dpi::PhysicalSize;
+
+use to_buffer_representation_derive::ToBufferRepresentation;
+
+use crate::game::engine::bind_group_buffer_entry::BindGroupBufferEntry;
+use crate::game::engine::post_process::create_pipeline;
+use crate::game::engine::vertex::VERTICES;
+use crate::game::mandelbrot::{Coloring, DebugView, MandelbrotData, Precision};
+use crate::game::to_buffer_representation::ToBufferRepresentation;
+
+// the half height of the plane shown by the preview, so the whole julia set fits in it
+const JULIA_ZOOM: f32 = 1.6;
+// the iterations of the preview stay within these, so it is drawn at once at any depth
+const MINIMUM_ITERATIONS: u32 = 100;
+const MAXIMUM_ITERATIONS: u32 = 1000;
+
+// the point of the mandelbrot set the julia set is drawn for
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable, ToBufferRepresentation)]
+pub struct JuliaParameter {
+    pub c: [f32; 2],
+    // the half height of the plane shown
+    pub zoom: f32,
+    #[padding]
+    _padding: u32,
+}
+
+// the julia set of the point under the cursor, drawn on the right half of the screen
+// while the mandelbrot set is explored on the left half, so the parameter plane and
+// the dynamical plane of the same point are seen side by side
+// it is computed directly in simple precision, a julia set is always seen as a whole
+pub struct JuliaPreview {
+    render_pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+    buffers: Vec<BindGroupBufferEntry>,
+    data: Rc<RefCell<MandelbrotData>>,
+    parameter: Rc<RefCell<JuliaParameter>>,
+}
+
+impl JuliaPreview {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let data = Rc::new(RefCell::new(MandelbrotData {
+            generation: 0,
+            time_elapsed: 0.0,
+            zoom: JULIA_ZOOM,
+            angle: 0.0,
+            center_delta: [0.0, 0.0],
+            epsilon: 0.0,
+            maximum_iterations: MINIMUM_ITERATIONS,
+            width: 1,
+            height: 1,
+            mu: 10000.0,
+            color_palette_scale: 100.0,
+            palette_phase: 0.0,
+            coloring: Coloring::Iterations as u32,
+            precision_mode: Precision::Direct as u32,
+            debug_view: DebugView::Off as u32,
+            split: 1.0,
+            zoom_exponent: 0,
+        }));
+        let parameter = Rc::new(RefCell::new(JuliaParameter {
+            c: [0.0, 0.0],
+            zoom: JULIA_ZOOM,
+            _padding: 0,
+        }));
+        let buffers = vec![
+            BindGroupBufferEntry::new(
+                device,
+                0,
+                ShaderStages::FRAGMENT,
+                BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                BufferBindingType::Uniform,
+                data.clone(),
+            ),
+            BindGroupBufferEntry::new(
+                device,
+                1,
+                ShaderStages::FRAGMENT,
+                BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                BufferBindingType::Uniform,
+                parameter.clone(),
+            ),
+        ];
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Julia Preview Bind Group Layout"),
+            entries: &buffers
+                .iter()
+                .map(|b| b.bind_group_layout_entry)
+                .collect::<Vec<_>>(),
+        });
+        // the buffers are small uniforms, they are never recreated so the bind group is kept
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Julia Preview Bind Group"),
+            layout: &bind_group_layout,
+            entries: &buffers
+                .iter()
+                .map(|b| b.bind_group_entry())
+                .collect::<Vec<_>>(),
+        });
+        let structs = [
+            MandelbrotData::wgsl_struct("Mandelbrot"),
+            JuliaParameter::wgsl_struct("JuliaParameter"),
+        ];
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Julia Preview Shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                include_str!("../../shaders/julia_preview.wgsl")
+                    .replace("// STRUCTS\n", &structs.join("\n"))
+                    .into(),
+            ),
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Julia Preview Render Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        Self {
+            render_pipeline: create_pipeline(device, &layout, &shader, "fs_main", format),
+            bind_group,
+            buffers,
+            data,
+            parameter,
+        }
+    }
+
+    // the position and the size of the right half of a screen of the given size
+    fn rectangle(size: PhysicalSize<u32>) -> (u32, u32, u32, u32) {
+        let left = size.width / 2;
+        (left, 0, (size.width - left).max(1), size.height.max(1))
+    }
+
+    // true when the given column of the window is on the preview, so not on the mandelbrot set
+    pub fn covers(size: PhysicalSize<u32>, x: f32) -> bool {
+        x >= Self::rectangle(size).0 as f32
+    }
+
+    // draw the julia set of the given point, with the colors of the explorer
+    pub fn update(&mut self, size: PhysicalSize<u32>, c: [f32; 2], mandelbrot: &MandelbrotData) {
+        let (_, _, width, height) = Self::rectangle(size);
+        {
+            let mut data = self.data.deref().borrow_mut();
+            data.width = width;
+            data.height = height;
+            data.time_elapsed = mandelbrot.time_elapsed;
+            data.maximum_iterations = mandelbrot
+                .maximum_iterations
+                .clamp(MINIMUM_ITERATIONS, MAXIMUM_ITERATIONS);
+            data.color_palette_scale = mandelbrot.color_palette_scale;
+            data.palette_phase = mandelbrot.palette_phase;
+        }
+        self.parameter.deref().borrow_mut().c = c;
+        let (data, parameter) = (&self.buffers[0], &self.buffers[1]);
+        data.mark_dirty(0, size_of::<MandelbrotData>());
+        parameter.mark_dirty(0, size_of::<JuliaParameter>());
+    }
+
+    // write the changes of the uniforms, before the preview is drawn
+    pub fn flush(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        staging_belt: &mut StagingBelt,
+    ) {
+        for buffer in self.buffers.iter_mut() {
+            buffer.flush(device, encoder, staging_belt);
+        }
+    }
+
+    pub fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        size: PhysicalSize<u32>,
+        vertex_buffer: &wgpu::Buffer,
+    ) {
+        let (x, y, width, height) = Self::rectangle(size);
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Julia Preview Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_viewport(x as f32, y as f32, width as f32, height as f32, 0.0, 1.0);
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.draw(0..VERTICES.len() as u32, 0..1);
+    }
+}
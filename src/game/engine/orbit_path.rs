@@ -0,0 +1,165 @@
+use bytemuck::{Pod, Zeroable};
+
+use crate::game::mandelbrot::MandelbrotData;
+
+// the points further from the center of the screen are moved to this distance, in screen sizes,
+// the deep zooms would give coordinates too large for the gpu
+const MAXIMUM_DISTANCE: f32 = 10000.0;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct PathVertex {
+    position: [f32; 2],
+    // the share of the orbit before the point, from 0 to 1
+    progress: f32,
+}
+
+impl PathVertex {
+    fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<PathVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32,
+                },
+            ],
+        }
+    }
+}
+
+// the orbit of the reference point drawn as a line on top of the fractal,
+// to see where the iterations go, the points are given again every frame
+pub struct OrbitPath {
+    render_pipeline: wgpu::RenderPipeline,
+    // grown when the orbit has more points than it can hold
+    vertex_buffer: wgpu::Buffer,
+    capacity: usize,
+    vertex_count: u32,
+}
+
+impl OrbitPath {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Orbit Path Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/orbit_path.wgsl").into()),
+        });
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Orbit Path Render Pipeline Layout"),
+                bind_group_layouts: &[],
+                push_constant_ranges: &[],
+            });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Orbit Path Render Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[PathVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineStrip,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+        Self {
+            render_pipeline,
+            vertex_buffer: Self::create_vertex_buffer(device, 1),
+            capacity: 1,
+            vertex_count: 0,
+        }
+    }
+
+    fn create_vertex_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Orbit Path Vertex Buffer"),
+            size: (capacity * std::mem::size_of::<PathVertex>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    // place the points of the orbit, relative to the center of the screen, on the screen
+    // drawn with the zoom and the angle of the data
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        points: &[[f32; 2]],
+        mandelbrot: &MandelbrotData,
+    ) {
+        let ratio = mandelbrot.width as f32 / mandelbrot.height.max(1) as f32;
+        let (sin, cos) = mandelbrot.angle.sin_cos();
+        let last = points.len().saturating_sub(1).max(1) as f32;
+        // the opposite of the rotation and the scale of the coordinates of the pixels by the shader
+        let vertices = points
+            .iter()
+            .enumerate()
+            .map(|(index, point)| PathVertex {
+                position: [
+                    ((point[0] * cos + point[1] * sin) / (mandelbrot.zoom * ratio))
+                        .clamp(-MAXIMUM_DISTANCE, MAXIMUM_DISTANCE),
+                    ((point[1] * cos - point[0] * sin) / mandelbrot.zoom)
+                        .clamp(-MAXIMUM_DISTANCE, MAXIMUM_DISTANCE),
+                ],
+                progress: index as f32 / last,
+            })
+            .collect::<Vec<_>>();
+        if vertices.len() > self.capacity {
+            self.capacity = vertices.len().next_power_of_two();
+            self.vertex_buffer = Self::create_vertex_buffer(device, self.capacity);
+        }
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+        self.vertex_count = vertices.len() as u32;
+    }
+
+    pub fn draw(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        if self.vertex_count < 2 {
+            return;
+        }
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Orbit Path Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.draw(0..self.vertex_count, 0..1);
+    }
+}
@@ -0,0 +1,252 @@
+use bytemuck::{Pod, Zeroable};
+use winit::dpi::PhysicalSize;
+
+pub const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+pub const ORANGE: [f32; 4] = [1.0, 0.5, 0.0, 1.0];
+
+// the segments of the digits written like on a seven-segment display, in a cell of 1 by 2,
+// from its top left: top, top right, bottom right, bottom, bottom left, top left and middle
+const SEGMENTS: [[[f32; 2]; 2]; 7] = [
+    [[0.0, 0.0], [1.0, 0.0]],
+    [[1.0, 0.0], [1.0, 1.0]],
+    [[1.0, 1.0], [1.0, 2.0]],
+    [[0.0, 2.0], [1.0, 2.0]],
+    [[0.0, 1.0], [0.0, 2.0]],
+    [[0.0, 0.0], [0.0, 1.0]],
+    [[0.0, 1.0], [1.0, 1.0]],
+];
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct OverlayVertex {
+    // in pixels of the window from its top left until the lines are drawn, then in clip space
+    position: [f32; 2],
+    color: [f32; 4],
+}
+
+impl OverlayVertex {
+    fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<OverlayVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+            ],
+        }
+    }
+}
+
+// the lines drawn on top of the fractal by the states, like the selection box, the crosshairs
+// or the markers, in pixels of the window from its top left
+// they are given again every frame and forgotten once drawn
+pub struct Overlay {
+    render_pipeline: wgpu::RenderPipeline,
+    // grown when the lines of a frame do not fit in it
+    vertex_buffer: wgpu::Buffer,
+    capacity: usize,
+    // the two ends of each line given for the next frame
+    vertices: Vec<OverlayVertex>,
+    // the number of vertices written to the buffer for the frame being drawn
+    vertex_count: u32,
+}
+
+impl Overlay {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Overlay Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/overlay.wgsl").into()),
+        });
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Overlay Render Pipeline Layout"),
+                bind_group_layouts: &[],
+                push_constant_ranges: &[],
+            });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Overlay Render Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[OverlayVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+        let capacity = 64;
+        Self {
+            render_pipeline,
+            vertex_buffer: Self::create_vertex_buffer(device, capacity),
+            capacity,
+            vertices: vec![],
+            vertex_count: 0,
+        }
+    }
+
+    fn create_vertex_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Overlay Vertex Buffer"),
+            size: (capacity * std::mem::size_of::<OverlayVertex>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    pub fn line(&mut self, start: [f32; 2], end: [f32; 2], color: [f32; 4]) {
+        self.vertices.push(OverlayVertex {
+            position: start,
+            color,
+        });
+        self.vertices.push(OverlayVertex {
+            position: end,
+            color,
+        });
+    }
+
+    // a rectangle between two opposite corners
+    pub fn rectangle(&mut self, corners: [[f32; 2]; 2], color: [f32; 4]) {
+        let [start, end] = corners;
+        let points = [start, [end[0], start[1]], end, [start[0], end[1]]];
+        for (index, point) in points.iter().enumerate() {
+            self.line(*point, points[(index + 1) % points.len()], color);
+        }
+    }
+
+    // a cross centered on a point, with a gap in its middle so the point itself stays visible
+    pub fn crosshair(&mut self, center: [f32; 2], radius: f32, color: [f32; 4]) {
+        let gap = radius / 3.0;
+        let [x, y] = center;
+        self.line([x - radius, y], [x - gap, y], color);
+        self.line([x + gap, y], [x + radius, y], color);
+        self.line([x, y - radius], [x, y - gap], color);
+        self.line([x, y + gap], [x, y + radius], color);
+    }
+
+    // a diamond around a point
+    pub fn marker(&mut self, center: [f32; 2], radius: f32, color: [f32; 4]) {
+        let [x, y] = center;
+        let points = [
+            [x, y - radius],
+            [x + radius, y],
+            [x, y + radius],
+            [x - radius, y],
+        ];
+        for (index, point) in points.iter().enumerate() {
+            self.line(*point, points[(index + 1) % points.len()], color);
+        }
+    }
+
+    // write a number from its top left, with characters of the given height,
+    // only the digits, the point, the minus sign and the e of the scientific notation are drawn
+    pub fn text(&mut self, position: [f32; 2], height: f32, text: &str, color: [f32; 4]) {
+        let scale = height / 2.0;
+        let mut x = position[0];
+        for character in text.chars() {
+            if character == '.' {
+                let bottom = position[1] + height;
+                self.line([x, bottom], [x + scale * 0.2, bottom], color);
+                x += scale * 0.7;
+                continue;
+            }
+            // the segments lit, from the top one
+            let segments: u8 = match character {
+                '0' => 0b0111111,
+                '1' => 0b0000110,
+                '2' => 0b1011011,
+                '3' => 0b1001111,
+                '4' => 0b1100110,
+                '5' => 0b1101101,
+                '6' => 0b1111101,
+                '7' => 0b0000111,
+                '8' => 0b1111111,
+                '9' => 0b1101111,
+                '-' => 0b1000000,
+                'e' | 'E' => 0b1111001,
+                _ => 0,
+            };
+            for (index, [start, end]) in SEGMENTS.iter().enumerate() {
+                if segments & (1 << index) != 0 {
+                    self.line(
+                        [x + start[0] * scale, position[1] + start[1] * scale],
+                        [x + end[0] * scale, position[1] + end[1] * scale],
+                        color,
+                    );
+                }
+            }
+            x += scale * 1.5;
+        }
+    }
+
+    // write the lines given since the last frame to the buffer, placed on a screen of the given size
+    pub fn flush(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, size: PhysicalSize<u32>) {
+        let (width, height) = (size.width.max(1) as f32, size.height.max(1) as f32);
+        // the middle of the pixels, so the lines of one pixel wide fall on them
+        for vertex in self.vertices.iter_mut() {
+            let [x, y] = vertex.position;
+            vertex.position = [
+                (x + 0.5) / width * 2.0 - 1.0,
+                1.0 - (y + 0.5) / height * 2.0,
+            ];
+        }
+        if self.vertices.len() > self.capacity {
+            self.capacity = self.vertices.len().next_power_of_two();
+            self.vertex_buffer = Self::create_vertex_buffer(device, self.capacity);
+        }
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&self.vertices));
+        self.vertex_count = self.vertices.len() as u32;
+        self.vertices.clear();
+    }
+
+    pub fn draw(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        if self.vertex_count == 0 {
+            return;
+        }
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Overlay Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.draw(0..self.vertex_count, 0..1);
+    }
+}
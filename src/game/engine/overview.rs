@@ -0,0 +1,250 @@
+use std::cell::RefCell;
+use std::mem::{offset_of, size_of};
+use std::ops::Deref;
+use std::rc::Rc;
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::StagingBelt;
+use wgpu::{BufferBindingType, BufferUsages, ShaderStages};
+use winit::dpi::PhysicalSize;
+
+use to_buffer_representation_derive::ToBufferRepresentation;
+
+use crate::game::big_real::BigReal;
+use crate::game::engine::bind_group_buffer_entry::BindGroupBufferEntry;
+use crate::game::engine::vertex::{Vertex, VERTICES};
+use crate::game::location::Location;
+use crate::game::mandelbrot::{Coloring, DebugView, MandelbrotData, Precision};
+use crate::game::to_buffer_representation::ToBufferRepresentation;
+
+// the part of the set currently explored, drawn as a rectangle on the overview
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable, ToBufferRepresentation)]
+pub struct OverviewViewport {
+    pub center: [f32; 2],
+    pub zoom: f32,
+    pub angle: f32,
+    pub ratio: f32,
+    #[padding]
+    _padding: u32,
+}
+
+// a small view of the whole mandelbrot set drawn in the bottom right corner of the screen,
+// so the explored place is not lost when zooming deep in the set
+// it is computed directly in simple precision, which is enough at this zoom
+pub struct Overview {
+    render_pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+    buffers: Vec<BindGroupBufferEntry>,
+    data: Rc<RefCell<MandelbrotData>>,
+    viewport: Rc<RefCell<OverviewViewport>>,
+}
+
+impl Overview {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let data = Rc::new(RefCell::new(MandelbrotData {
+            generation: 0,
+            time_elapsed: 0.0,
+            zoom: 3.0,
+            angle: 0.0,
+            // the center of the overview, not a delta
+            center_delta: [-0.75, 0.0],
+            epsilon: 0.0,
+            maximum_iterations: 200,
+            width: 1,
+            height: 1,
+            mu: 10000.0,
+            color_palette_scale: 100.0,
+            palette_phase: 0.0,
+            coloring: Coloring::Iterations as u32,
+            precision_mode: Precision::Direct as u32,
+            debug_view: DebugView::Off as u32,
+            split: 1.0,
+            zoom_exponent: 0,
+        }));
+        let viewport = Rc::new(RefCell::new(OverviewViewport {
+            center: [0.0, 0.0],
+            zoom: 3.0,
+            angle: 0.0,
+            ratio: 1.0,
+            _padding: 0,
+        }));
+        let buffers = vec![
+            BindGroupBufferEntry::new(
+                device,
+                0,
+                ShaderStages::FRAGMENT,
+                BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                BufferBindingType::Uniform,
+                data.clone(),
+            ),
+            BindGroupBufferEntry::new(
+                device,
+                1,
+                ShaderStages::FRAGMENT,
+                BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                BufferBindingType::Uniform,
+                viewport.clone(),
+            ),
+        ];
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Overview Bind Group Layout"),
+            entries: &buffers
+                .iter()
+                .map(|b| b.bind_group_layout_entry)
+                .collect::<Vec<_>>(),
+        });
+        // the buffers are small uniforms, they are never recreated so the bind group is kept
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Overview Bind Group"),
+            layout: &bind_group_layout,
+            entries: &buffers
+                .iter()
+                .map(|b| b.bind_group_entry())
+                .collect::<Vec<_>>(),
+        });
+        let structs = [
+            MandelbrotData::wgsl_struct("Mandelbrot"),
+            OverviewViewport::wgsl_struct("Viewport"),
+        ];
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Overview Shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                include_str!("../../shaders/overview.wgsl")
+                    .replace("// STRUCTS\n", &structs.join("\n"))
+                    .into(),
+            ),
+        });
+        let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Overview Render Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Overview Render Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+        Self {
+            render_pipeline,
+            bind_group,
+            buffers,
+            data,
+            viewport,
+        }
+    }
+
+    // the position and the side of the square drawn in the corner of a screen of the given size
+    fn rectangle(size: PhysicalSize<u32>) -> (u32, u32, u32) {
+        let side = (size.width.min(size.height) / 4).max(1);
+        let margin = side / 16;
+        (
+            size.width.saturating_sub(side + margin),
+            size.height.saturating_sub(side + margin),
+            side,
+        )
+    }
+
+    // follow the location and the colors of the explorer
+    pub fn update(
+        &mut self,
+        size: PhysicalSize<u32>,
+        location: &Location,
+        mandelbrot: &MandelbrotData,
+    ) {
+        let (_, _, side) = Self::rectangle(size);
+        {
+            let mut data = self.data.deref().borrow_mut();
+            data.width = side;
+            data.height = side;
+            data.time_elapsed = mandelbrot.time_elapsed;
+            data.color_palette_scale = mandelbrot.color_palette_scale;
+            data.palette_phase = mandelbrot.palette_phase;
+        }
+        {
+            let mut viewport = self.viewport.deref().borrow_mut();
+            viewport.center = [location.x.to_single(), location.y.to_single()];
+            viewport.zoom = location.zoom;
+            viewport.angle = location.angle;
+            viewport.ratio = size.width as f32 / size.height.max(1) as f32;
+        }
+        // only the fields of the overview that follow the view are written again
+        let (data, viewport) = (&self.buffers[0], &self.buffers[1]);
+        data.mark_dirty(offset_of!(MandelbrotData, time_elapsed), size_of::<f32>());
+        data.mark_dirty(offset_of!(MandelbrotData, width), 2 * size_of::<u32>());
+        data.mark_dirty(
+            offset_of!(MandelbrotData, color_palette_scale),
+            2 * size_of::<f32>(),
+        );
+        viewport.mark_dirty(0, size_of::<OverviewViewport>());
+    }
+
+    // write the changes of the uniforms, before the overview is drawn
+    pub fn flush(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        staging_belt: &mut StagingBelt,
+    ) {
+        // the uniforms never change of size, so the bind group stays valid
+        for buffer in self.buffers.iter_mut() {
+            buffer.flush(device, encoder, staging_belt);
+        }
+    }
+
+    pub fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        size: PhysicalSize<u32>,
+        vertex_buffer: &wgpu::Buffer,
+    ) {
+        let (x, y, side) = Self::rectangle(size);
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Overview Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_viewport(x as f32, y as f32, side as f32, side as f32, 0.0, 1.0);
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.draw(0..VERTICES.len() as u32, 0..1);
+    }
+}
@@ -0,0 +1,228 @@
+use std::num::NonZeroU32;
+
+use wgpu::{
+    BindGroupEntry, BindGroupLayoutEntry, BindingResource, BindingType, Buffer, CommandEncoder,
+    Device, Extent3d, ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, Origin3d, Queue,
+    ShaderStages, StorageTextureAccess, Texture, TextureAspect, TextureDescriptor,
+    TextureDimension, TextureFormat, TextureSampleType, TextureUsages, TextureView,
+    TextureViewDimension,
+};
+use winit::dpi::PhysicalSize;
+
+// the bytes of a texel, four f32
+const TEXEL_SIZE: u32 = 16;
+
+// how the shader uses a pixel texture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelAccess {
+    // written as a storage texture, the values of the frame being drawn
+    Write,
+    // read with textureLoad, the values of the last frame
+    Read,
+}
+
+// a rgba32float texture of the size of the frame, with the state of each pixel
+// the neighbours of a pixel are close in memory, unlike in a buffer of lines
+// only the red channel is used so far, the others are free for more values of the pixels
+// the rgba32float textures can not be filtered by the gpus without a feature missing from wgpu,
+// so the texels are read one by one
+pub struct PixelTexture {
+    texture: Texture,
+    view: TextureView,
+    layout_entry: BindGroupLayoutEntry,
+    size: PhysicalSize<u32>,
+    // the value of the pixels of a new texture
+    initial_value: f32,
+}
+
+impl PixelTexture {
+    pub fn new(
+        device: &Device,
+        queue: &Queue,
+        binding: u32,
+        visibility: ShaderStages,
+        access: PixelAccess,
+        size: PhysicalSize<u32>,
+        initial_value: f32,
+    ) -> Self {
+        let (texture, view) = Self::create_texture(device, size);
+        let ty = match access {
+            PixelAccess::Write => BindingType::StorageTexture {
+                access: StorageTextureAccess::WriteOnly,
+                format: TextureFormat::Rgba32Float,
+                view_dimension: TextureViewDimension::D2,
+            },
+            PixelAccess::Read => BindingType::Texture {
+                sample_type: TextureSampleType::Float { filterable: false },
+                view_dimension: TextureViewDimension::D2,
+                multisampled: false,
+            },
+        };
+        let pixel_texture = Self {
+            texture,
+            view,
+            layout_entry: BindGroupLayoutEntry {
+                binding,
+                visibility,
+                ty,
+                count: None,
+            },
+            size,
+            initial_value,
+        };
+        pixel_texture.clear(queue);
+        pixel_texture
+    }
+
+    fn create_texture(device: &Device, size: PhysicalSize<u32>) -> (Texture, TextureView) {
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some("Pixel Texture"),
+            size: Extent3d {
+                width: size.width.max(1),
+                height: size.height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba32Float,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::STORAGE_BINDING
+                | TextureUsages::COPY_SRC
+                | TextureUsages::COPY_DST,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        (texture, view)
+    }
+
+    // create the texture again when the size of the frame changes, its pixels are lost
+    // return true when it has been created again, so it must be bound again
+    pub fn resize(&mut self, device: &Device, queue: &Queue, size: PhysicalSize<u32>) -> bool {
+        if size == self.size {
+            return false;
+        }
+        (self.texture, self.view) = Self::create_texture(device, size);
+        self.size = size;
+        self.clear(queue);
+        true
+    }
+
+    fn clear(&self, queue: &Queue) {
+        let size = self.extent();
+        let values = vec![self.initial_value; (size.width * size.height) as usize];
+        self.write(queue, &values, size.width);
+    }
+
+    fn extent(&self) -> PhysicalSize<u32> {
+        PhysicalSize::new(self.size.width.max(1), self.size.height.max(1))
+    }
+
+    // replace the values of the pixels from the bottom line, width by width,
+    // in the red channel of the texels
+    pub fn write(&self, queue: &Queue, values: &[f32], width: u32) {
+        let width = width.min(self.extent().width);
+        if width == 0 {
+            return;
+        }
+        let height = ((values.len() as u32) / width).min(self.extent().height);
+        let texels = values[..(width * height) as usize]
+            .iter()
+            .flat_map(|value| [*value, 0.0, 0.0, 0.0])
+            .collect::<Vec<f32>>();
+        queue.write_texture(
+            ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            bytemuck::cast_slice(&texels),
+            ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(width * TEXEL_SIZE),
+                rows_per_image: NonZeroU32::new(height),
+            },
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    // copy the pixels to another texture of the same size, they are the previous ones of the
+    // next frame
+    pub fn copy_to(&self, encoder: &mut CommandEncoder, destination: &PixelTexture) {
+        let size = self.extent();
+        encoder.copy_texture_to_texture(
+            self.texture.as_image_copy(),
+            destination.texture.as_image_copy(),
+            Extent3d {
+                width: size.width.min(destination.extent().width),
+                height: size.height.min(destination.extent().height),
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    // the bytes of a line of texels in a buffer, aligned as the copies need it
+    pub fn padded_bytes_per_row(width: u32) -> u32 {
+        let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        (width * TEXEL_SIZE).div_ceil(alignment) * alignment
+    }
+
+    // copy the pixels of the area of the given size at the bottom left to a buffer,
+    // of padded_bytes_per_row bytes per line
+    pub fn copy_to_buffer(
+        &self,
+        encoder: &mut CommandEncoder,
+        buffer: &Buffer,
+        size: PhysicalSize<u32>,
+    ) {
+        encoder.copy_texture_to_buffer(
+            self.texture.as_image_copy(),
+            ImageCopyBuffer {
+                buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(Self::padded_bytes_per_row(size.width)),
+                    rows_per_image: NonZeroU32::new(size.height),
+                },
+            },
+            Extent3d {
+                width: size.width,
+                height: size.height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    // the red channel of the texels copied by copy_to_buffer, line by line
+    pub fn red_channel(bytes: &[u8], size: PhysicalSize<u32>) -> Vec<f32> {
+        let padded_bytes_per_row = Self::padded_bytes_per_row(size.width) as usize;
+        bytes
+            .chunks(padded_bytes_per_row)
+            .take(size.height as usize)
+            .flat_map(|line| {
+                line[..(size.width * TEXEL_SIZE) as usize]
+                    .chunks_exact(TEXEL_SIZE as usize)
+                    .map(|texel| f32::from_le_bytes([texel[0], texel[1], texel[2], texel[3]]))
+            })
+            .collect()
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.size
+    }
+
+    pub fn bind_group_layout_entry(&self) -> BindGroupLayoutEntry {
+        self.layout_entry
+    }
+
+    pub fn bind_group_entry(&self) -> BindGroupEntry<'_> {
+        BindGroupEntry {
+            binding: self.layout_entry.binding,
+            resource: BindingResource::TextureView(&self.view),
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use winit::dpi::PhysicalSize;
+
+use crate::game::engine::bloom::Bloom;
+use crate::game::engine::color_adjustment::{ColorAdjustment, ColorLevels};
+use crate::game::engine::vertex::{Vertex, VERTICES};
+
+// a pass run on the frame
+#[derive(Clone, Copy)]
+pub enum PostPass<'a> {
+    // a wgsl shader with a vs_main and a fs_main entry points,
+    // reading the previous image from the texture at binding 0 and the sampler at binding 1
+    Shader(&'a str),
+    // a halo around the bright parts of the image
+    Bloom,
+    // the correction of the gamma, the brightness and the contrast, starting at these levels
+    ColorAdjustment(ColorLevels),
+}
+
+// a pass ready to be drawn
+enum Stage {
+    Shader(wgpu::RenderPipeline),
+    Bloom(Bloom),
+    ColorAdjustment(ColorAdjustment),
+}
+
+// the full screen passes run on the rendered fractal before it is presented,
+// like tone mapping, vignette or sharpening
+// the fractal is drawn into a texture, then each pass reads the texture written by the previous one
+// and the last pass writes into the view of the frame
+// the shader of a pass reads its input from the texture at binding 0 and the sampler at binding 1
+pub struct PostProcessor {
+    format: wgpu::TextureFormat,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    passes: Vec<Stage>,
+    // the textures drawn into before the last pass, with the bind groups reading them,
+    // two of them at most, used in turn
+    targets: Vec<(wgpu::TextureView, wgpu::BindGroup)>,
+    // the size of the targets
+    size: PhysicalSize<u32>,
+}
+
+impl PostProcessor {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, passes: &[PostPass]) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Post Process Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Post Process Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Post Process Render Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let passes = passes
+            .iter()
+            .map(|pass| match pass {
+                PostPass::Shader(source) => {
+                    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                        label: Some("Post Process Shader"),
+                        source: wgpu::ShaderSource::Wgsl((*source).into()),
+                    });
+                    Stage::Shader(create_pipeline(
+                        device,
+                        &render_pipeline_layout,
+                        &shader,
+                        "fs_main",
+                        format,
+                    ))
+                }
+                PostPass::Bloom => Stage::Bloom(Bloom::new(device, format)),
+                PostPass::ColorAdjustment(levels) => {
+                    Stage::ColorAdjustment(ColorAdjustment::new(device, format, *levels))
+                }
+            })
+            .collect();
+        Self {
+            format,
+            bind_group_layout,
+            sampler,
+            passes,
+            targets: vec![],
+            size: PhysicalSize::new(0, 0),
+        }
+    }
+
+    // create the textures again when the size of the frames changes
+    pub fn resize(&mut self, device: &wgpu::Device, size: PhysicalSize<u32>) {
+        if size == self.size && !self.targets.is_empty() {
+            return;
+        }
+        self.size = size;
+        self.targets = (0..self.passes.len().min(2))
+            .map(|_| {
+                let texture = device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some("Post Process Texture"),
+                    size: wgpu::Extent3d {
+                        width: size.width,
+                        height: size.height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: self.format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                        | wgpu::TextureUsages::TEXTURE_BINDING,
+                });
+                let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+                let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Post Process Bind Group"),
+                    layout: &self.bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(&view),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Sampler(&self.sampler),
+                        },
+                    ],
+                });
+                (view, bind_group)
+            })
+            .collect();
+        let inputs = self.targets.iter().map(|(view, _)| view).collect::<Vec<_>>();
+        for pass in self.passes.iter_mut() {
+            match pass {
+                Stage::Shader(_) => {}
+                Stage::Bloom(bloom) => bloom.resize(device, size, &self.sampler, &inputs),
+                Stage::ColorAdjustment(adjustment) => {
+                    adjustment.resize(device, &self.sampler, &inputs)
+                }
+            }
+        }
+    }
+
+    // change the levels of the color adjustment passes, without creating them again
+    pub fn set_color_levels(&self, queue: &wgpu::Queue, levels: ColorLevels) {
+        for pass in self.passes.iter() {
+            if let Stage::ColorAdjustment(adjustment) = pass {
+                adjustment.set_levels(queue, levels);
+            }
+        }
+    }
+
+    // the view the fractal must be drawn into, resize must have been called before
+    pub fn input_view(&self) -> &wgpu::TextureView {
+        &self.targets[0].0
+    }
+
+    // run the passes, the last one writes into the given view
+    pub fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        vertex_buffer: &wgpu::Buffer,
+    ) {
+        for (index, pass) in self.passes.iter().enumerate() {
+            let target = if index + 1 == self.passes.len() {
+                view
+            } else {
+                &self.targets[(index + 1) % 2].0
+            };
+            let input = index % 2;
+            match pass {
+                Stage::Shader(pipeline) => draw_pass(
+                    encoder,
+                    target,
+                    pipeline,
+                    &self.targets[input].1,
+                    vertex_buffer,
+                ),
+                Stage::Bloom(bloom) => bloom.draw(encoder, target, input, vertex_buffer),
+                Stage::ColorAdjustment(adjustment) => {
+                    adjustment.draw(encoder, target, input, vertex_buffer)
+                }
+            }
+        }
+    }
+}
+
+// draw a full screen pass into a texture view
+pub fn draw_pass(
+    encoder: &mut wgpu::CommandEncoder,
+    view: &wgpu::TextureView,
+    pipeline: &wgpu::RenderPipeline,
+    bind_group: &wgpu::BindGroup,
+    vertex_buffer: &wgpu::Buffer,
+) {
+    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Post Process Render Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                store: true,
+            },
+        })],
+        depth_stencil_attachment: None,
+    });
+    render_pass.set_pipeline(pipeline);
+    render_pass.set_bind_group(0, bind_group, &[]);
+    render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+    render_pass.draw(0..VERTICES.len() as u32, 0..1);
+}
+
+// the pipeline of a full screen pass, drawing the vertices of the engine with the given fragment entry point
+pub fn create_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    fragment_entry_point: &str,
+    format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Post Process Render Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[Vertex::desc()],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: fragment_entry_point,
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Back),
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+    })
+}
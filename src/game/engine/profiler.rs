@@ -0,0 +1,180 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use wgpu::{BufferAddress, BufferAsyncError, BufferUsages, Maintain, MapMode};
+
+// the moments of a frame measured on the gpu
+pub const FRAME_START: u32 = 0;
+pub const MANDELBROT_END: u32 = 1;
+pub const OVERVIEW_END: u32 = 2;
+const QUERY_COUNT: u32 = 3;
+const BUFFER_SIZE: BufferAddress = QUERY_COUNT as BufferAddress * 8;
+
+// the times taken by the render passes of a frame on the gpu
+#[derive(Debug, Clone, Copy)]
+pub struct GpuTimings {
+    pub frame: u64,
+    pub mandelbrot_ms: f64,
+    pub overview_ms: f64,
+}
+
+// measure the time taken by each render pass on the gpu, and the time taken by the cpu to update
+// the frame, mostly computing the reference orbit, and write them to a csv file if asked
+// the timings are read without waiting for the gpu, so some frames are not measured
+pub struct Profiler {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    read_buffer: wgpu::Buffer,
+    // the result of the mapping of the read buffer, none until it is done
+    mapped: Arc<Mutex<Option<Result<(), BufferAsyncError>>>>,
+    // the frame and the cpu time of the timings being read, none when the read buffer is free
+    pending: Option<(u64, Duration)>,
+    // the number of nanoseconds of a timestamp tick
+    period: f32,
+    cpu_time: Duration,
+    writer: Option<BufWriter<File>>,
+    // the time taken by the fractal in the last frame measured, until it is taken
+    mandelbrot_time: Option<Duration>,
+}
+
+impl Profiler {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Self, String> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return Err("the gpu does not support timestamp queries".to_string());
+        }
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Profiler Query Set"),
+            ty: wgpu::QueryType::Timestamp,
+            count: QUERY_COUNT,
+        });
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Profiler Resolve Buffer"),
+            size: BUFFER_SIZE,
+            usage: BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let read_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Profiler Read Buffer"),
+            size: BUFFER_SIZE,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        Ok(Self {
+            query_set,
+            resolve_buffer,
+            read_buffer,
+            mapped: Arc::new(Mutex::new(None)),
+            pending: None,
+            period: queue.get_timestamp_period(),
+            cpu_time: Duration::ZERO,
+            writer: None,
+            mandelbrot_time: None,
+        })
+    }
+
+    // write the timings of the next frames to a csv file
+    pub fn write_to(&mut self, path: &Path) -> Result<(), String> {
+        let mut writer = BufWriter::new(File::create(path).map_err(|error| error.to_string())?);
+        writeln!(writer, "frame,cpu_update_ms,gpu_mandelbrot_ms,gpu_overview_ms")
+            .map_err(|error| error.to_string())?;
+        self.writer = Some(writer);
+        Ok(())
+    }
+
+    // the time taken by the fractal in the last frame measured since the last call
+    pub fn take_mandelbrot_time(&mut self) -> Option<Duration> {
+        self.mandelbrot_time.take()
+    }
+
+    // the time taken by the cpu to update the current frame
+    pub fn set_cpu_time(&mut self, cpu_time: Duration) {
+        self.cpu_time = cpu_time;
+    }
+
+    // true if the current frame can be measured
+    pub fn is_free(&self) -> bool {
+        self.pending.is_none()
+    }
+
+    // the frame whose timings are being read
+    pub fn measured_frame(&self) -> Option<u64> {
+        self.pending.map(|(frame, _)| frame)
+    }
+
+    pub fn write_timestamp(&self, encoder: &mut wgpu::CommandEncoder, index: u32) {
+        if self.is_free() {
+            encoder.write_timestamp(&self.query_set, index);
+        }
+    }
+
+    // copy the timestamps of the frame to the read buffer
+    pub fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+        if self.is_free() {
+            encoder.resolve_query_set(&self.query_set, 0..QUERY_COUNT, &self.resolve_buffer, 0);
+            encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &self.read_buffer, 0, BUFFER_SIZE);
+        }
+    }
+
+    // once the given frame has been submitted, read its timestamps when they are ready
+    // and write the timings of the previous measured frame if they are, which are returned
+    pub fn end_frame(&mut self, device: &wgpu::Device, frame: u64) -> Option<GpuTimings> {
+        if self.is_free() {
+            let mapped = self.mapped.clone();
+            self.read_buffer
+                .slice(..)
+                .map_async(MapMode::Read, move |result| {
+                    *mapped.lock().unwrap() = Some(result);
+                });
+            self.pending = Some((frame, self.cpu_time));
+        }
+        device.poll(Maintain::Poll);
+        let result = self.mapped.lock().unwrap().take()?;
+        let (frame, cpu_time) = self.pending.take()?;
+        if let Err(error) = result {
+            // the frame is not measured, the buffer is freed for the next ones
+            // a failed mapping usually leaves it unmapped, which is not reported as an error
+            device.push_error_scope(wgpu::ErrorFilter::Validation);
+            self.read_buffer.unmap();
+            pollster::block_on(device.pop_error_scope());
+            eprintln!("Impossible to read the timings of frame {}: {}", frame, error);
+            return None;
+        }
+        let timestamps: Vec<u64> = {
+            let data = self.read_buffer.slice(..).get_mapped_range();
+            bytemuck::cast_slice(&data).to_vec()
+        };
+        self.read_buffer.unmap();
+        let milliseconds = |start: u64, end: u64| {
+            end.saturating_sub(start) as f64 * self.period as f64 / 1_000_000.0
+        };
+        let timings = GpuTimings {
+            frame,
+            mandelbrot_ms: milliseconds(
+                timestamps[FRAME_START as usize],
+                timestamps[MANDELBROT_END as usize],
+            ),
+            overview_ms: milliseconds(
+                timestamps[MANDELBROT_END as usize],
+                timestamps[OVERVIEW_END as usize],
+            ),
+        };
+        self.mandelbrot_time = Some(Duration::from_secs_f64(timings.mandelbrot_ms / 1000.0));
+        if let Some(writer) = self.writer.as_mut() {
+            let result = writeln!(
+                writer,
+                "{},{:.3},{:.3},{:.3}",
+                frame,
+                cpu_time.as_secs_f64() * 1000.0,
+                timings.mandelbrot_ms,
+                timings.overview_ms,
+            );
+            if let Err(error) = result {
+                eprintln!("Impossible to write the timings: {}", error);
+            }
+        }
+        Some(timings)
+    }
+}
@@ -0,0 +1,77 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use wgpu::{Buffer, BufferAsyncError, Device, Maintain};
+
+// the state shared with the callback of the mapping
+#[derive(Default)]
+struct Mapping {
+    result: Option<Result<(), BufferAsyncError>>,
+    waker: Option<Waker>,
+}
+
+// the content of a buffer copied back from the gpu, available once the copy is done
+// the mapping completes when the device is polled, which every submit of a frame does,
+// so the future can be awaited while the explorer keeps rendering
+pub struct Readback {
+    device: Arc<Device>,
+    buffer: Buffer,
+    mapping: Arc<Mutex<Mapping>>,
+}
+
+impl Readback {
+    // the buffer must have been copied from the gpu buffer in a submitted command
+    pub fn new(device: Arc<Device>, buffer: Buffer) -> Self {
+        let mapping = Arc::new(Mutex::new(Mapping::default()));
+        let callback_mapping = mapping.clone();
+        buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            let mut mapping = callback_mapping.lock().unwrap();
+            mapping.result = Some(result);
+            if let Some(waker) = mapping.waker.take() {
+                waker.wake();
+            }
+        });
+        Self {
+            device,
+            buffer,
+            mapping,
+        }
+    }
+
+    // the content once the copy is done, none while it is not, without waiting for it
+    pub fn try_read(&mut self) -> Option<Result<Vec<u8>, String>> {
+        self.device.poll(Maintain::Poll);
+        let result = self.mapping.lock().unwrap().result.take()?;
+        Some(match result {
+            Err(error) => Err(format!("{:?}", error)),
+            Ok(()) => {
+                let data = self.buffer.slice(..).get_mapped_range().to_vec();
+                self.buffer.unmap();
+                Ok(data)
+            }
+        })
+    }
+}
+
+impl Future for Readback {
+    type Output = Result<Vec<u8>, String>;
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Self::Output> {
+        self.device.poll(Maintain::Poll);
+        let mut mapping = self.mapping.lock().unwrap();
+        match mapping.result.take() {
+            None => {
+                mapping.waker = Some(context.waker().clone());
+                Poll::Pending
+            }
+            Some(Err(error)) => Poll::Ready(Err(format!("{:?}", error))),
+            Some(Ok(())) => {
+                let data = self.buffer.slice(..).get_mapped_range().to_vec();
+                self.buffer.unmap();
+                Poll::Ready(Ok(data))
+            }
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use winit::dpi::PhysicalSize;
+
+use crate::game::engine::post_process::{create_pipeline, draw_pass};
+
+// the fractal drawn into a smaller texture while the camera moves too fast for the gpu,
+// then stretched to the size of the frame
+pub struct ResolutionScaler {
+    format: wgpu::TextureFormat,
+    render_pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    // the texture the fractal is drawn into and the bind group reading it
+    target: Option<(wgpu::TextureView, wgpu::BindGroup)>,
+    // the size of the texture
+    size: PhysicalSize<u32>,
+}
+
+impl ResolutionScaler {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Resolution Scaler Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Resolution Scaler Render Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Resolution Scaler Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/stretch.wgsl").into()),
+        });
+        // the pixels are blended with their neighbours, so the stretched frame is smooth
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Resolution Scaler Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        Self {
+            format,
+            render_pipeline: create_pipeline(
+                device,
+                &render_pipeline_layout,
+                &shader,
+                "fs_main",
+                format,
+            ),
+            bind_group_layout,
+            sampler,
+            target: None,
+            size: PhysicalSize::new(0, 0),
+        }
+    }
+
+    // create the texture again when the size the fractal is drawn at changes
+    pub fn resize(&mut self, device: &wgpu::Device, size: PhysicalSize<u32>) {
+        if size == self.size && self.target.is_some() {
+            return;
+        }
+        self.size = size;
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Resolution Scaler Texture"),
+            size: wgpu::Extent3d {
+                width: size.width.max(1),
+                height: size.height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Resolution Scaler Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        });
+        self.target = Some((view, bind_group));
+    }
+
+    // the view the fractal must be drawn into, resize must have been called before
+    pub fn input_view(&self) -> &wgpu::TextureView {
+        &self.target.as_ref().unwrap().0
+    }
+
+    // stretch the fractal to the given view
+    pub fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        vertex_buffer: &wgpu::Buffer,
+    ) {
+        if let Some((_, bind_group)) = &self.target {
+            draw_pass(
+                encoder,
+                view,
+                &self.render_pipeline,
+                bind_group,
+                vertex_buffer,
+            );
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+use std::sync::Arc;
+
+use wgpu::{
+    Buffer, BufferDescriptor, BufferUsages, CommandEncoder, Device, Extent3d, ImageCopyBuffer,
+    ImageCopyTexture, ImageDataLayout, Maintain, MapMode, Origin3d, Texture, TextureAspect,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureView,
+};
+
+use crate::game::engine::readback::Readback;
+
+// an offscreen texture the frame is rendered into, and the buffer it is copied to
+// so it can be read back and saved as a png file
+pub struct Screenshot {
+    path: PathBuf,
+    // the keywords and the values written in text chunks of the png file
+    text: Vec<(String, String)>,
+    texture: Texture,
+    view: TextureView,
+    buffer: Buffer,
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: u32,
+    format: TextureFormat,
+}
+
+impl Screenshot {
+    pub fn new(device: &Device, path: PathBuf, width: u32, height: u32, format: TextureFormat) -> Self {
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some("Screenshot Texture"),
+            size: Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        // the rows copied to a buffer must be aligned on 256 bytes
+        let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let bytes_per_row = width * format.describe().block_size as u32;
+        let padded_bytes_per_row = bytes_per_row.div_ceil(alignment) * alignment;
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("Screenshot Buffer"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        Self {
+            path,
+            text: vec![],
+            texture,
+            view,
+            buffer,
+            width,
+            height,
+            padded_bytes_per_row,
+            format,
+        }
+    }
+
+    pub fn with_text(mut self, text: Vec<(String, String)>) -> Self {
+        self.text = text;
+        self
+    }
+
+    pub fn view(&self) -> &TextureView {
+        &self.view
+    }
+
+    // copy the rendered texture to the readable buffer
+    pub fn copy_to_buffer(&self, encoder: &mut CommandEncoder) {
+        encoder.copy_texture_to_buffer(
+            ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            ImageCopyBuffer {
+                buffer: &self.buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(self.padded_bytes_per_row),
+                    rows_per_image: NonZeroU32::new(self.height),
+                },
+            },
+            Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    // wait for the copy to be done and write the png file
+    // must be called after the encoder given to copy_to_buffer has been submitted
+    pub fn save(&self, device: &Device) -> Result<(), String> {
+        let pixels = self.read_pixels(device)?;
+        let is_png = self
+            .path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
+        if !is_png || self.text.is_empty() {
+            // the format is chosen from the extension
+            return image::RgbaImage::from_raw(self.width, self.height, pixels)
+                .ok_or("invalid screenshot size")?
+                .save(&self.path)
+                .map_err(|error| error.to_string());
+        }
+        self.write_png(&pixels).map_err(|error| error.to_string())
+    }
+
+    // wait for the copy to be done and write a png file of the square in the middle of the frame,
+    // shrunk to the given side
+    pub fn save_thumbnail(&self, device: &Device, side: u32) -> Result<(), String> {
+        let pixels = self.read_pixels(device)?;
+        let image = image::RgbaImage::from_raw(self.width, self.height, pixels)
+            .ok_or("invalid screenshot size")?;
+        let square = self.width.min(self.height);
+        let square = image::imageops::crop_imm(
+            &image,
+            (self.width - square) / 2,
+            (self.height - square) / 2,
+            square,
+            square,
+        )
+        .to_image();
+        image::imageops::resize(&square, side, side, image::imageops::FilterType::Triangle)
+            .save(&self.path)
+            .map_err(|error| error.to_string())
+    }
+
+    // wait for the copy to be done and read the pixels, 4 bytes each from the top line
+    // must be called after the encoder given to copy_to_buffer has been submitted
+    pub fn read_pixels(&self, device: &Device) -> Result<Vec<u8>, String> {
+        let slice = self.buffer.slice(..);
+        let (sender, receiver) = channel();
+        slice.map_async(MapMode::Read, move |result| {
+            sender.send(result).ok();
+        });
+        device.poll(Maintain::Wait);
+        receiver
+            .recv()
+            .map_err(|error| error.to_string())?
+            .map_err(|error| format!("{:?}", error))?;
+        let pixels = unpad(
+            &slice.get_mapped_range(),
+            self.width,
+            self.height,
+            self.padded_bytes_per_row,
+            self.format,
+        );
+        self.buffer.unmap();
+        pixels
+    }
+
+    // read the pixels without waiting for the copy, so the next frames are rendered meanwhile
+    // must be called after the encoder given to copy_to_buffer has been submitted
+    pub fn read_later(self, device: Arc<Device>) -> PendingScreenshot {
+        PendingScreenshot {
+            readback: Readback::new(device, self.buffer),
+            width: self.width,
+            height: self.height,
+            padded_bytes_per_row: self.padded_bytes_per_row,
+            format: self.format,
+        }
+    }
+
+    // write the pixels and the text chunks
+    fn write_png(&self, pixels: &[u8]) -> Result<(), png::EncodingError> {
+        let file = BufWriter::new(File::create(&self.path)?);
+        let mut encoder = png::Encoder::new(file, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        for (keyword, value) in &self.text {
+            encoder.add_text_chunk(keyword.clone(), value.clone())?;
+        }
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(pixels)?;
+        writer.finish()
+    }
+
+    // convert a pixel of the format of the surface to the rgba pixel of a png file
+    fn to_rgba8(format: TextureFormat, pixel: &[u8]) -> Result<[u8; 4], String> {
+        match format {
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => {
+                Ok([pixel[0], pixel[1], pixel[2], pixel[3]])
+            }
+            // the surface is often in a bgra format, png wants rgba
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => {
+                Ok([pixel[2], pixel[1], pixel[0], pixel[3]])
+            }
+            // 10 bits per color and 2 bits of alpha, from the lowest bits
+            TextureFormat::Rgb10a2Unorm => {
+                let value = u32::from_le_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+                Ok([
+                    ((value >> 2) & 0xff) as u8,
+                    ((value >> 12) & 0xff) as u8,
+                    ((value >> 22) & 0xff) as u8,
+                    (value >> 30) as u8 * 85,
+                ])
+            }
+            // linear colors in half floats, encoded in srgb like an 8 bits srgb surface would do
+            TextureFormat::Rgba16Float => {
+                let channel = |index: usize| {
+                    half_to_f32(u16::from_le_bytes([pixel[index * 2], pixel[index * 2 + 1]]))
+                };
+                let encode = |linear: f32| {
+                    let linear = linear.clamp(0.0, 1.0);
+                    let encoded = if linear <= 0.0031308 {
+                        linear * 12.92
+                    } else {
+                        1.055 * linear.powf(1.0 / 2.4) - 0.055
+                    };
+                    (encoded * 255.0).round() as u8
+                };
+                Ok([
+                    encode(channel(0)),
+                    encode(channel(1)),
+                    encode(channel(2)),
+                    (channel(3).clamp(0.0, 1.0) * 255.0).round() as u8,
+                ])
+            }
+            _ => Err(format!("the format {:?} can not be saved", format)),
+        }
+    }
+}
+
+// the pixels of a screenshot being copied from the gpu
+pub struct PendingScreenshot {
+    readback: Readback,
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: u32,
+    format: TextureFormat,
+}
+
+impl PendingScreenshot {
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    // the pixels once the copy is done, 4 bytes each from the top line, none while it is not
+    pub fn try_read_pixels(&mut self) -> Option<Result<Vec<u8>, String>> {
+        let data = self.readback.try_read()?;
+        Some(data.and_then(|data| {
+            unpad(
+                &data,
+                self.width,
+                self.height,
+                self.padded_bytes_per_row,
+                self.format,
+            )
+        }))
+    }
+}
+
+// the rgba pixels of the rows copied to a buffer, which are padded
+fn unpad(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: u32,
+    format: TextureFormat,
+) -> Result<Vec<u8>, String> {
+    let bytes_per_pixel = format.describe().block_size as usize;
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+    for row in data
+        .chunks(padded_bytes_per_row as usize)
+        .take(height as usize)
+    {
+        let row = &row[..width as usize * bytes_per_pixel];
+        for pixel in row.chunks_exact(bytes_per_pixel) {
+            pixels.extend_from_slice(&Screenshot::to_rgba8(format, pixel)?);
+        }
+    }
+    Ok(pixels)
+}
+
+// the keywords and the values of the text chunks of a png file, like the ones of the screenshots
+pub fn read_text(path: &Path) -> Result<Vec<(String, String)>, String> {
+    let decoder = png::Decoder::new(BufReader::new(
+        File::open(path).map_err(|error| error.to_string())?,
+    ));
+    let reader = decoder.read_info().map_err(|error| error.to_string())?;
+    Ok(reader
+        .info()
+        .uncompressed_latin1_text
+        .iter()
+        .map(|chunk| (chunk.keyword.clone(), chunk.text.clone()))
+        .collect())
+}
+
+// a 16 bits float, with 5 bits of exponent and 10 bits of mantissa
+fn half_to_f32(half: u16) -> f32 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((half >> 10) & 0x1f) as i32;
+    let mantissa = (half & 0x3ff) as f32;
+    match exponent {
+        0 => sign * mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0.0 => sign * f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zeros_and_one_are_converted() {
+        assert_eq!(half_to_f32(0x0000), 0.0);
+        assert!(half_to_f32(0x8000).is_sign_negative());
+        assert_eq!(half_to_f32(0x8000), 0.0);
+        assert_eq!(half_to_f32(0x3c00), 1.0);
+        assert_eq!(half_to_f32(0xbc00), -1.0);
+        // the largest finite half
+        assert_eq!(half_to_f32(0x7bff), 65504.0);
+    }
+
+    #[test]
+    fn subnormals_are_converted() {
+        // the smallest subnormal and the largest one
+        assert_eq!(half_to_f32(0x0001), 2f32.powi(-24));
+        assert_eq!(half_to_f32(0x03ff), 1023.0 * 2f32.powi(-24));
+        assert_eq!(half_to_f32(0x8001), -(2f32.powi(-24)));
+    }
+
+    #[test]
+    fn infinities_and_nan_are_converted() {
+        assert_eq!(half_to_f32(0x7c00), f32::INFINITY);
+        assert_eq!(half_to_f32(0xfc00), f32::NEG_INFINITY);
+        assert!(half_to_f32(0x7e00).is_nan());
+        assert!(half_to_f32(0xfc01).is_nan());
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::game::engine::profiler::GpuTimings;
+
+// the rows waiting for their gpu timings, written without them beyond this
+const MAXIMUM_WAITING_ROWS: usize = 16;
+
+// what is known of a frame on the cpu
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameStats {
+    // the time taken to update the frame, mostly computing the reference orbit
+    pub cpu_time: Duration,
+    pub maximum_iterations: u32,
+    // the number of points of the orbit of the reference point stored for the frame
+    pub orbit_length: usize,
+    // the bytes written to the buffers and the textures of the gpu for the frame
+    pub upload_bytes: usize,
+}
+
+// write the statistics of every frame to a csv file, to compare the performances across versions
+// the gpu timings come a few frames later, and only for the frames the profiler could measure,
+// so each row waits for them while its frame is being measured
+pub struct StatsWriter {
+    writer: BufWriter<File>,
+    rows: VecDeque<(u64, FrameStats, Option<GpuTimings>)>,
+}
+
+impl StatsWriter {
+    pub fn create(path: &Path) -> Result<Self, String> {
+        let mut writer = BufWriter::new(File::create(path).map_err(|error| error.to_string())?);
+        writeln!(
+            writer,
+            "frame,cpu_update_ms,gpu_mandelbrot_ms,gpu_overview_ms,maximum_iterations,orbit_length,upload_bytes"
+        )
+        .map_err(|error| error.to_string())?;
+        Ok(Self {
+            writer,
+            rows: VecDeque::new(),
+        })
+    }
+
+    // add the statistics of a frame and the gpu timings that came with it, and write the rows
+    // which are not measured, the frame measured by the profiler is given while it is read
+    pub fn end_frame(
+        &mut self,
+        frame: u64,
+        stats: FrameStats,
+        timings: Option<GpuTimings>,
+        measured_frame: Option<u64>,
+    ) -> Result<(), String> {
+        self.rows.push_back((frame, stats, None));
+        if let Some(timings) = timings {
+            if let Some(row) = self.rows.iter_mut().find(|row| row.0 == timings.frame) {
+                row.2 = Some(timings);
+            }
+        }
+        while let Some(&(frame, stats, timings)) = self.rows.front() {
+            let is_measured = measured_frame.is_some_and(|measured| measured <= frame);
+            if is_measured && self.rows.len() <= MAXIMUM_WAITING_ROWS {
+                break;
+            }
+            self.rows.pop_front();
+            self.write(frame, &stats, timings)?;
+        }
+        self.writer.flush().map_err(|error| error.to_string())
+    }
+
+    fn write(
+        &mut self,
+        frame: u64,
+        stats: &FrameStats,
+        timings: Option<GpuTimings>,
+    ) -> Result<(), String> {
+        // the gpu timings are left empty when the frame has not been measured
+        let (mandelbrot, overview) = match timings {
+            Some(timings) => (
+                format!("{:.3}", timings.mandelbrot_ms),
+                format!("{:.3}", timings.overview_ms),
+            ),
+            None => (String::new(), String::new()),
+        };
+        writeln!(
+            self.writer,
+            "{},{:.3},{},{},{},{},{}",
+            frame,
+            stats.cpu_time.as_secs_f64() * 1000.0,
+            mandelbrot,
+            overview,
+            stats.maximum_iterations,
+            stats.orbit_length,
+            stats.upload_bytes,
+        )
+        .map_err(|error| error.to_string())
+    }
+}
@@ -0,0 +1,106 @@
+use std::cell::{Cell, Ref, RefCell, RefMut};
+use std::mem::size_of;
+use std::ops::Range;
+use std::rc::Rc;
+
+use bytemuck::Pod;
+
+use crate::game::engine::bind_group_buffer_entry::DirtyRange;
+use crate::game::to_buffer_representation::ToBufferRepresentation;
+
+// the data of a typed buffer and the part of it changed, shared with the engine
+type Shared = (
+    Rc<RefCell<dyn ToBufferRepresentation>>,
+    Rc<Cell<DirtyRange>>,
+);
+
+// the typed buffers, which can be bound to the shader in place of another one
+pub trait TypedBuffer {
+    fn shared(&self) -> Shared;
+}
+
+// a value bound to the shader as a uniform
+// every change is written to the gpu on the next frame, once the buffer is added to the engine
+pub struct UniformBuffer<T> {
+    data: Rc<RefCell<T>>,
+    dirty: Rc<Cell<DirtyRange>>,
+}
+
+impl<T: ToBufferRepresentation + 'static> UniformBuffer<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            data: Rc::new(RefCell::new(value)),
+            dirty: Rc::new(Cell::new(DirtyRange::ALL)),
+        }
+    }
+
+    pub fn get(&self) -> Ref<'_, T> {
+        self.data.borrow()
+    }
+
+    // change the value in place
+    pub fn get_mut(&mut self) -> RefMut<'_, T> {
+        self.dirty.set(DirtyRange::ALL);
+        self.data.borrow_mut()
+    }
+
+    pub fn set(&mut self, value: T) {
+        *self.get_mut() = value;
+    }
+}
+
+impl<T: ToBufferRepresentation + 'static> TypedBuffer for UniformBuffer<T> {
+    fn shared(&self) -> Shared {
+        (self.data.clone(), self.dirty.clone())
+    }
+}
+
+// an array bound to the shader as a storage buffer
+// every change is written to the gpu on the next frame, once the buffer is added to the engine,
+// so the values written by the shader must not be changed from the cpu, except to resize the array
+pub struct StorageBuffer<T> {
+    data: Rc<RefCell<Vec<T>>>,
+    dirty: Rc<Cell<DirtyRange>>,
+}
+
+impl<T: Pod> StorageBuffer<T> {
+    pub fn new(values: Vec<T>) -> Self {
+        Self {
+            data: Rc::new(RefCell::new(values)),
+            dirty: Rc::new(Cell::new(DirtyRange::ALL)),
+        }
+    }
+
+    pub fn get(&self) -> Ref<'_, Vec<T>> {
+        self.data.borrow()
+    }
+
+    // change the values in place
+    pub fn get_mut(&mut self) -> RefMut<'_, Vec<T>> {
+        self.dirty.set(DirtyRange::ALL);
+        self.data.borrow_mut()
+    }
+
+    // change some of the values in place, only them are written on the next frame
+    pub fn get_range_mut(&mut self, range: Range<usize>) -> RefMut<'_, [T]> {
+        let size = size_of::<T>();
+        let dirty = self.dirty.get();
+        self.dirty
+            .set(dirty.with(range.start * size, range.end * size));
+        RefMut::map(self.data.borrow_mut(), |values| &mut values[range])
+    }
+
+    pub fn set(&mut self, values: Vec<T>) {
+        *self.get_mut() = values;
+    }
+
+    pub fn resize(&mut self, length: usize, value: T) {
+        self.get_mut().resize(length, value);
+    }
+}
+
+impl<T: Pod> TypedBuffer for StorageBuffer<T> {
+    fn shared(&self) -> Shared {
+        (self.data.clone(), self.dirty.clone())
+    }
+}
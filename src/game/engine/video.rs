@@ -0,0 +1,85 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+// the frames encoded into a video by ffmpeg, which must be installed:
+// the rgba pixels of each frame are written to its standard input as they are read back,
+// and the codec is chosen by ffmpeg from the extension of the file, h264 for a mp4 file
+pub struct VideoEncoder {
+    path: PathBuf,
+    child: Child,
+    // closed once the video is finished, so ffmpeg writes the end of the file
+    stdin: Option<ChildStdin>,
+    width: u32,
+    height: u32,
+}
+
+impl VideoEncoder {
+    // the bitrate is given to ffmpeg as it is, like 8M
+    pub fn start(
+        path: &Path,
+        width: u32,
+        height: u32,
+        fps: f32,
+        bitrate: &str,
+    ) -> Result<Self, String> {
+        let mut child = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error"])
+            .args(["-f", "rawvideo", "-pix_fmt", "rgba"])
+            .args(["-s", &format!("{}x{}", width, height)])
+            .args(["-r", &fps.to_string()])
+            .args(["-i", "-"])
+            // most players only read yuv 4:2:0, whose sizes must be even
+            .args(["-vf", "crop=trunc(iw/2)*2:trunc(ih/2)*2"])
+            .args(["-pix_fmt", "yuv420p", "-b:v", bitrate])
+            .arg(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .map_err(|error| format!("impossible to run ffmpeg: {}", error))?;
+        let stdin = child.stdin.take();
+        Ok(Self {
+            path: path.to_path_buf(),
+            child,
+            stdin,
+            width,
+            height,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // add a frame of the size of the video, 4 bytes a pixel from the top line
+    pub fn write_frame(&mut self, pixels: &[u8]) -> Result<(), String> {
+        if pixels.len() != (self.width * self.height * 4) as usize {
+            return Err("the frame is not of the size of the video".to_string());
+        }
+        self.stdin
+            .as_mut()
+            .ok_or("the video is finished")?
+            .write_all(pixels)
+            .map_err(|error| format!("ffmpeg stopped: {}", error))
+    }
+
+    // close the input of ffmpeg and wait for it to write the file
+    pub fn finish(&mut self) -> Result<(), String> {
+        drop(self.stdin.take());
+        let status = self.child.wait().map_err(|error| error.to_string())?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(format!("ffmpeg failed with {}", status))
+        }
+    }
+}
+
+impl Drop for VideoEncoder {
+    // the video is not left unfinished when the explorer exits before the end of the script
+    fn drop(&mut self) {
+        if self.stdin.is_some() {
+            self.finish().ok();
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use rand::Rng;
+use winit::dpi::PhysicalSize;
+use winit::event::{ElementState, Event, VirtualKeyCode, WindowEvent};
+
+use crate::game::big_real::{BigReal, Real};
+use crate::game::camera_animator::CameraAnimator;
+use crate::game::command::Command;
+use crate::game::config::Config;
+use crate::game::engine::Engine;
+use crate::game::game_state::GameState;
+use crate::game::location::Location;
+use crate::game::mamndelbrot_state::{is_navigation_key, MandelbrotState};
+
+// the frame is split in a grid of cells of this many columns and rows to look for details
+const GRID: usize = 8;
+// the zoom is divided by this factor at each new target
+const ZOOM_FACTOR: f32 = 4.0;
+// the target is picked at random among this many of the most detailed cells,
+// so two explorations do not follow the same path
+const CANDIDATES: usize = 3;
+// the variance of the iterations under which a cell has no detail
+const FLAT_VARIANCE: f32 = 1e-3;
+
+// fly toward the most detailed parts of the set without the user
+// - X to start or stop the exploration
+// every few seconds the iterations of the last frame are read back, the frame is split in cells,
+// and the camera flies to one of the cells where the iterations vary the most
+pub struct ExploreState {
+    exploring: bool,
+    // the time in seconds to fly to each target
+    interval: f32,
+}
+
+impl ExploreState {
+    pub fn new(interval: f32) -> Self {
+        Self {
+            exploring: false,
+            interval,
+        }
+    }
+
+    fn stop(&mut self) {
+        self.exploring = false;
+    }
+
+    // true when a new target must be picked, once the camera has reached the previous one
+    fn needs_target(&self, camera_is_animating: bool) -> bool {
+        self.exploring && !camera_is_animating
+    }
+
+    // the animation to the next target, picked from the iterations of the last frame,
+    // or away from the current location when there is no detail left on the screen
+    fn next_leg(
+        &self,
+        current: Location,
+        size: PhysicalSize<u32>,
+        iterations: &[f32],
+        maximum_iterations: u32,
+    ) -> CameraAnimator {
+        let mut cells = cell_scores(size, iterations, maximum_iterations);
+        cells.sort_by(|a, b| b.0.total_cmp(&a.0));
+        cells.retain(|(score, _, _)| *score > FLAT_VARIANCE);
+        cells.truncate(CANDIDATES);
+        let target = if cells.is_empty() {
+            Location {
+                zoom: (current.zoom * ZOOM_FACTOR).min(3.0),
+                ..current.clone()
+            }
+        } else {
+            let (_, column, row) = cells[rand::thread_rng().gen_range(0..cells.len())];
+            target_of_cell(&current, size, column, row)
+        };
+        CameraAnimator::new(current, target, self.interval)
+    }
+}
+
+impl GameState for ExploreState {
+    fn update(&mut self, _engine: &mut Engine, _delta_time: f32) {}
+
+    fn drive(&mut self, explorer: &mut MandelbrotState, engine: &mut Engine, _delta_time: f32) {
+        if !self.needs_target(explorer.is_animating()) {
+            return;
+        }
+        match explorer.iterations(engine) {
+            Ok(iterations) => {
+                let animator = self.next_leg(
+                    explorer.location(),
+                    explorer.size(),
+                    &iterations,
+                    explorer.maximum_iterations(),
+                );
+                explorer.animate(animator);
+            }
+            Err(error) => {
+                eprintln!("Impossible to read the iterations: {}", error);
+                self.stop();
+            }
+        }
+    }
+
+    fn is_driving(&self) -> bool {
+        self.exploring
+    }
+
+    fn set_config(&mut self, config: &Config) {
+        self.interval = config.explore.interval;
+    }
+
+    fn execute(&mut self, command: &Command) {
+        if let Command::Explore { enabled } = command {
+            self.exploring = *enabled;
+        }
+    }
+
+    fn input(&mut self, event: &Event<()>, _engine: &mut Engine) {
+        if let Event::WindowEvent { event, .. } = event {
+            match event {
+                WindowEvent::KeyboardInput { input, .. } if input.state == ElementState::Pressed => {
+                    match input.virtual_keycode {
+                        Some(VirtualKeyCode::X) => {
+                            self.exploring = !self.exploring;
+                            if self.exploring {
+                                eprintln!("Exploration started");
+                            } else {
+                                eprintln!("Exploration stopped");
+                            }
+                        }
+                        // the navigation keys take back the control of the camera
+                        Some(keycode) if is_navigation_key(keycode) => self.stop(),
+                        _ => {}
+                    }
+                }
+                WindowEvent::MouseWheel { .. } => self.stop(),
+                WindowEvent::MouseInput {
+                    state: ElementState::Pressed,
+                    ..
+                } => self.stop(),
+                _ => {}
+            }
+        }
+    }
+}
+
+// the variance of the iterations of each cell of the grid, with its column and row from the bottom
+// the iterations are compared on a logarithmic scale, so the deep pixels do not hide the others,
+// and the cells mostly inside the set are left out as they are black
+fn cell_scores(
+    size: PhysicalSize<u32>,
+    iterations: &[f32],
+    maximum_iterations: u32,
+) -> Vec<(f32, usize, usize)> {
+    let (width, height) = (size.width as usize, size.height as usize);
+    if iterations.len() < width * height || width < GRID || height < GRID {
+        return vec![];
+    }
+    let inside = (1.0 + maximum_iterations as f32).ln();
+    let mut scores = Vec::with_capacity(GRID * GRID);
+    for row in 0..GRID {
+        for column in 0..GRID {
+            let mut count = 0.0;
+            let mut inside_count = 0.0;
+            let mut sum = 0.0;
+            let mut square_sum = 0.0;
+            for y in row * height / GRID..(row + 1) * height / GRID {
+                for x in column * width / GRID..(column + 1) * width / GRID {
+                    let value = match iterations[y * width + x] {
+                        // the pixel has not been computed yet
+                        value if value < -1.5 => continue,
+                        value if value < 0.0 => {
+                            inside_count += 1.0;
+                            inside
+                        }
+                        value => (1.0 + value).ln(),
+                    };
+                    count += 1.0;
+                    sum += value;
+                    square_sum += value * value;
+                }
+            }
+            if count == 0.0 || inside_count / count > 0.5 {
+                continue;
+            }
+            let mean = sum / count;
+            scores.push((square_sum / count - mean * mean, column, row));
+        }
+    }
+    scores
+}
+
+// the location zoomed on the center of a cell
+fn target_of_cell(current: &Location, size: PhysicalSize<u32>, column: usize, row: usize) -> Location {
+    // the center of the cell between -1 and 1, like the coordinates of the shader
+    let x = (column as f32 + 0.5) / GRID as f32 * 2.0 - 1.0;
+    let y = (row as f32 + 0.5) / GRID as f32 * 2.0 - 1.0;
+    let x = x * current.zoom * size.width as f32 / size.height as f32;
+    let y = y * current.zoom;
+    let (sin, cos) = current.angle.sin_cos();
+    Location {
+        x: current
+            .x
+            .plus(&Real::from_double((x * cos - y * sin) as f64)),
+        y: current
+            .y
+            .plus(&Real::from_double((x * sin + y * cos) as f64)),
+        zoom: current.zoom / ZOOM_FACTOR,
+        angle: current.angle,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: PhysicalSize<u32> = PhysicalSize::new(16, 16);
+
+    #[test]
+    fn a_flat_frame_has_no_detail() {
+        let iterations = vec![10.0; 16 * 16];
+        let scores = cell_scores(SIZE, &iterations, 100);
+        assert_eq!(scores.len(), GRID * GRID);
+        assert!(scores.iter().all(|(score, _, _)| *score < FLAT_VARIANCE));
+    }
+
+    #[test]
+    fn the_detailed_cell_has_the_highest_score() {
+        let mut iterations = vec![10.0; 16 * 16];
+        // the cell of the column 5 and the row 2 covers the pixels 10 and 11 of the rows 4 and 5
+        iterations[4 * 16 + 10] = 50.0;
+        iterations[5 * 16 + 11] = 2.0;
+        let scores = cell_scores(SIZE, &iterations, 100);
+        let best = scores.iter().max_by(|a, b| a.0.total_cmp(&b.0)).unwrap();
+        assert_eq!((best.1, best.2), (5, 2));
+        assert!(best.0 > FLAT_VARIANCE);
+    }
+
+    #[test]
+    fn the_cells_inside_the_set_and_the_frames_too_small_are_left_out() {
+        let mut iterations = vec![10.0; 16 * 16];
+        // most of the first cell is inside the set, the pixels not computed yet are ignored
+        iterations[0] = -1.0;
+        iterations[1] = -1.0;
+        iterations[16] = -1.0;
+        iterations[17 + 16 * 2] = -2.0;
+        let scores = cell_scores(SIZE, &iterations, 100);
+        assert_eq!(scores.len(), GRID * GRID - 1);
+        assert!(scores.iter().all(|(_, column, row)| *column + *row > 0));
+        assert!(cell_scores(PhysicalSize::new(4, 4), &iterations, 100).is_empty());
+        assert!(cell_scores(SIZE, &iterations[..100], 100).is_empty());
+    }
+
+    #[test]
+    fn the_target_is_the_center_of_the_cell() {
+        let current = Location {
+            x: Real::from_double(-0.5),
+            y: Real::from_double(0.0),
+            zoom: 1.0,
+            angle: 0.0,
+        };
+        // the cell on the right of the center, in a frame twice as wide as high
+        let target = target_of_cell(&current, PhysicalSize::new(32, 16), 4, 3);
+        assert!((target.x.to_double() - (-0.5 + 0.125 * 2.0)).abs() < 1e-6);
+        assert!((target.y.to_double() + 0.125).abs() < 1e-6);
+        assert_eq!(target.zoom, current.zoom / ZOOM_FACTOR);
+        // the offset turns with the view
+        let turned = Location {
+            angle: std::f32::consts::FRAC_PI_2,
+            ..current
+        };
+        let target = target_of_cell(&turned, SIZE, 4, 4);
+        assert!((target.x.to_double() + 0.5 + 0.125).abs() < 1e-6);
+        assert!((target.y.to_double() - 0.125).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,186 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+// write an uncompressed OpenEXR file of 32 bits float channels
+// each channel has a value per pixel, row by row from the top left corner
+pub fn write_exr(
+    path: &Path,
+    width: u32,
+    height: u32,
+    channels: &[(&str, Vec<f32>)],
+) -> Result<(), String> {
+    let pixel_count = (width * height) as usize;
+    if channels.iter().any(|(_, values)| values.len() != pixel_count) {
+        return Err("the channels must have a value per pixel".to_string());
+    }
+    // the channels are stored sorted by name
+    let mut channels = channels.iter().collect::<Vec<_>>();
+    channels.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut header = Vec::new();
+    // the magic number and the version 2, for a single part file of scan lines
+    header.extend_from_slice(&[0x76, 0x2f, 0x31, 0x01]);
+    header.extend_from_slice(&2u32.to_le_bytes());
+    let mut channel_list = Vec::new();
+    for (name, _) in &channels {
+        channel_list.extend_from_slice(name.as_bytes());
+        channel_list.push(0);
+        // 32 bits float, not linear, reserved, sampled on each pixel
+        channel_list.extend_from_slice(&2i32.to_le_bytes());
+        channel_list.extend_from_slice(&[0, 0, 0, 0]);
+        channel_list.extend_from_slice(&1i32.to_le_bytes());
+        channel_list.extend_from_slice(&1i32.to_le_bytes());
+    }
+    channel_list.push(0);
+    attribute(&mut header, "channels", "chlist", &channel_list);
+    // no compression
+    attribute(&mut header, "compression", "compression", &[0]);
+    let mut window = Vec::new();
+    for value in [0, 0, width as i32 - 1, height as i32 - 1] {
+        window.extend_from_slice(&value.to_le_bytes());
+    }
+    attribute(&mut header, "dataWindow", "box2i", &window);
+    attribute(&mut header, "displayWindow", "box2i", &window);
+    // the lines are stored from the top
+    attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+    attribute(&mut header, "pixelAspectRatio", "float", &1f32.to_le_bytes());
+    attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+    attribute(&mut header, "screenWindowWidth", "float", &1f32.to_le_bytes());
+    header.push(0);
+
+    // without compression each chunk is a line: its number, its size, then the values of each channel
+    let line_size = width as usize * 4 * channels.len();
+    let chunk_size = 8 + line_size;
+    let first_chunk = header.len() + height as usize * 8;
+    let file = File::create(path).map_err(|error| error.to_string())?;
+    let mut writer = BufWriter::new(file);
+    let mut write = |bytes: &[u8]| writer.write_all(bytes).map_err(|error| error.to_string());
+    write(&header)?;
+    for line in 0..height as usize {
+        write(&((first_chunk + line * chunk_size) as u64).to_le_bytes())?;
+    }
+    for line in 0..height as usize {
+        write(&(line as i32).to_le_bytes())?;
+        write(&(line_size as i32).to_le_bytes())?;
+        for (_, values) in &channels {
+            let start = line * width as usize;
+            for value in &values[start..start + width as usize] {
+                write(&value.to_le_bytes())?;
+            }
+        }
+    }
+    writer.flush().map_err(|error| error.to_string())
+}
+
+fn attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    header.extend_from_slice(name.as_bytes());
+    header.push(0);
+    header.extend_from_slice(kind.as_bytes());
+    header.push(0);
+    header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+    header.extend_from_slice(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the attributes of the header, by name, with their type and value, and the offset after it
+    fn read_header(bytes: &[u8]) -> (Vec<(String, String, Vec<u8>)>, usize) {
+        let mut attributes = Vec::new();
+        let mut offset = 8;
+        let string = |offset: &mut usize| {
+            let end = *offset + bytes[*offset..].iter().position(|byte| *byte == 0).unwrap();
+            let string = String::from_utf8(bytes[*offset..end].to_vec()).unwrap();
+            *offset = end + 1;
+            string
+        };
+        while bytes[offset] != 0 {
+            let name = string(&mut offset);
+            let kind = string(&mut offset);
+            let size = i32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize;
+            offset += 4;
+            attributes.push((name, kind, bytes[offset..offset + size].to_vec()));
+            offset += size;
+        }
+        (attributes, offset + 1)
+    }
+
+    fn floats(bytes: &[u8]) -> Vec<f32> {
+        bytes
+            .chunks(4)
+            .map(|value| f32::from_le_bytes(value.try_into().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn a_small_image_is_written_by_lines() {
+        let path = std::env::temp_dir().join(format!("mandelbrot_{}.exr", std::process::id()));
+        let channels = [
+            ("Z", vec![1.0, 2.0, 3.0, 4.0]),
+            ("A", vec![5.0, 6.0, 7.0, 8.0]),
+        ];
+        write_exr(&path, 2, 2, &channels).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(bytes[..8], [0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0]);
+        let (attributes, header_end) = read_header(&bytes);
+        let names = attributes
+            .iter()
+            .map(|(name, kind, _)| (name.as_str(), kind.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                ("channels", "chlist"),
+                ("compression", "compression"),
+                ("dataWindow", "box2i"),
+                ("displayWindow", "box2i"),
+                ("lineOrder", "lineOrder"),
+                ("pixelAspectRatio", "float"),
+                ("screenWindowCenter", "v2f"),
+                ("screenWindowWidth", "float"),
+            ]
+        );
+        // the channels sorted by name, each a 32 bits float sampled on every pixel
+        let mut channel_list = Vec::new();
+        for name in ["A", "Z"] {
+            channel_list.extend_from_slice(name.as_bytes());
+            channel_list.extend_from_slice(&[0, 2, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0]);
+        }
+        channel_list.push(0);
+        assert_eq!(attributes[0].2, channel_list);
+        assert_eq!(attributes[1].2, [0]);
+        assert_eq!(
+            attributes[2].2,
+            [0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0]
+        );
+
+        // a chunk by line: its number, the size of its values, then the values of each channel
+        let offsets = (0..2)
+            .map(|line| {
+                let start = header_end + line * 8;
+                u64::from_le_bytes(bytes[start..start + 8].try_into().unwrap()) as usize
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, [header_end + 16, header_end + 16 + 24]);
+        for (line, offset) in offsets.into_iter().enumerate() {
+            let chunk = &bytes[offset..offset + 24];
+            assert_eq!(chunk[..4], (line as i32).to_le_bytes());
+            assert_eq!(chunk[4..8], 16i32.to_le_bytes());
+            let values = floats(&chunk[8..]);
+            let first = line as f32 * 2.0;
+            assert_eq!(values, [first + 5.0, first + 6.0, first + 1.0, first + 2.0]);
+        }
+        assert_eq!(bytes.len(), header_end + 16 + 2 * 24);
+    }
+
+    #[test]
+    fn channels_without_a_value_per_pixel_are_rejected() {
+        let path = std::env::temp_dir().join("mandelbrot_rejected.exr");
+        assert!(write_exr(&path, 2, 2, &[("Z", vec![1.0; 3])]).is_err());
+        assert!(!path.exists());
+    }
+}
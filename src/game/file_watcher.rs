@@ -0,0 +1,45 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use instant::Instant;
+
+// detect the changes of a file by checking its modification time at most once per second
+pub struct FileWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    last_check: Instant,
+}
+
+impl FileWatcher {
+    pub fn new(path: PathBuf) -> Self {
+        let modified = Self::modified(&path);
+        Self {
+            path,
+            modified,
+            last_check: Instant::now(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // true if the file has been modified since the last call
+    pub fn changed(&mut self) -> bool {
+        if self.last_check.elapsed() < Duration::from_secs(1) {
+            return false;
+        }
+        self.last_check = Instant::now();
+        let modified = Self::modified(&self.path);
+        if modified == self.modified {
+            return false;
+        }
+        self.modified = modified;
+        true
+    }
+
+    fn modified(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+    }
+}
@@ -0,0 +1,266 @@
+use std::fmt;
+
+// the most nested parentheses, signs and powers, so a long formula cannot overflow the stack
+const MAXIMUM_DEPTH: usize = 64;
+
+// a formula written by the user, like "z^2 + c" or "(|re(z)| + i*|im(z)|)^2 + c",
+// compiled to the iteration function of a formula shader
+// - z is the current value, p the previous one and c the point of the pixel
+// - i is the imaginary unit
+// - + - * / and ^ on complex numbers, |x| and abs(x) for the modulus
+// - re, im, conj, exp, log, sin and cos
+// every value is a complex number, written as a vec2<f32> in the shader
+pub fn compile(formula: &str) -> Result<String, String> {
+    let expression = parse(formula)?;
+    Ok(include_str!("../shaders/formulas/custom.wgsl").replace("EXPRESSION", &expression))
+}
+
+// the wgsl expression of the formula
+fn parse(formula: &str) -> Result<String, String> {
+    let tokens = tokenize(formula)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        position: 0,
+        depth: 0,
+    };
+    let expression = parser.expression()?;
+    if let Some(token) = parser.peek() {
+        return Err(format!("unexpected {} in the formula", token));
+    }
+    Ok(expression)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f32),
+    Name(String),
+    Symbol(char),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Number(value) => write!(formatter, "{}", value),
+            Token::Name(name) => write!(formatter, "{}", name),
+            Token::Symbol(symbol) => write!(formatter, "{}", symbol),
+        }
+    }
+}
+
+fn tokenize(formula: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = formula.chars().peekable();
+    while let Some(&character) = chars.peek() {
+        if character.is_whitespace() {
+            chars.next();
+        } else if character.is_ascii_digit() || character == '.' {
+            let mut number = String::new();
+            while let Some(&character) = chars.peek() {
+                // the sign of an exponent, like in 1e-3
+                let is_exponent_sign = (character == '-' || character == '+')
+                    && (number.ends_with('e') || number.ends_with('E'));
+                if character.is_ascii_digit()
+                    || character == '.'
+                    || character == 'e'
+                    || character == 'E'
+                    || is_exponent_sign
+                {
+                    number.push(character);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            let value = number
+                .parse::<f32>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .ok_or(format!("invalid number {} in the formula", number))?;
+            tokens.push(Token::Number(value));
+        } else if character.is_ascii_alphabetic() {
+            let mut name = String::new();
+            while let Some(&character) = chars.peek() {
+                if character.is_ascii_alphanumeric() {
+                    name.push(character);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push(Token::Name(name));
+        } else if "+-*/^()|".contains(character) {
+            tokens.push(Token::Symbol(character));
+            chars.next();
+        } else {
+            return Err(format!("unexpected {} in the formula", character));
+        }
+    }
+    Ok(tokens)
+}
+
+// a recursive descent parser writing the wgsl of each part of the formula
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    // the parts of the formula being parsed inside each other
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), String> {
+        match self.next() {
+            Some(Token::Symbol(character)) if *character == symbol => Ok(()),
+            Some(token) => Err(format!("expected {} but found {} in the formula", symbol, token)),
+            None => Err(format!("expected {} at the end of the formula", symbol)),
+        }
+    }
+
+    // the sums and the differences
+    fn expression(&mut self) -> Result<String, String> {
+        let mut left = self.term()?;
+        while let Some(Token::Symbol(symbol @ ('+' | '-'))) = self.peek() {
+            self.position += 1;
+            let right = self.term()?;
+            left = format!("({} {} {})", left, symbol, right);
+        }
+        Ok(left)
+    }
+
+    // the products and the quotients
+    fn term(&mut self) -> Result<String, String> {
+        let mut left = self.unary()?;
+        while let Some(Token::Symbol(symbol @ ('*' | '/'))) = self.peek() {
+            self.position += 1;
+            let right = self.unary()?;
+            let function = if *symbol == '*' { "cmul" } else { "cdiv" };
+            left = format!("{}({}, {})", function, left, right);
+        }
+        Ok(left)
+    }
+
+    // every nested part of the formula goes through the signs, so the depth is checked here
+    fn unary(&mut self) -> Result<String, String> {
+        if self.depth >= MAXIMUM_DEPTH {
+            return Err("the formula is nested too deeply".to_string());
+        }
+        self.depth += 1;
+        let result = if let Some(Token::Symbol('-')) = self.peek() {
+            self.position += 1;
+            self.unary().map(|operand| format!("(-{})", operand))
+        } else {
+            self.power()
+        };
+        self.depth -= 1;
+        result
+    }
+
+    // the exponent binds tighter than the sign on its left, so -z^2 is -(z^2)
+    fn power(&mut self) -> Result<String, String> {
+        let base = self.atom()?;
+        if let Some(Token::Symbol('^')) = self.peek() {
+            self.position += 1;
+            // the small integer powers are computed exactly with multiplications
+            if let Some(Token::Number(exponent)) = self.peek() {
+                if exponent.fract() == 0.0 && (1.0..=64.0).contains(exponent) {
+                    self.position += 1;
+                    return Ok(format!("cpowi({}, {})", base, *exponent as i32));
+                }
+            }
+            let exponent = self.unary()?;
+            return Ok(format!("cpow({}, {})", base, exponent));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(format!("vec2<f32>({:?}, 0.0)", value)),
+            Some(Token::Symbol('(')) => {
+                let expression = self.expression()?;
+                self.expect(')')?;
+                Ok(expression)
+            }
+            Some(Token::Symbol('|')) => {
+                let expression = self.expression()?;
+                self.expect('|')?;
+                Ok(format!("cabs({})", expression))
+            }
+            Some(Token::Name(name)) => match name.as_str() {
+                "z" => Ok("z".to_string()),
+                "p" => Ok("previous_z".to_string()),
+                "c" => Ok("c".to_string()),
+                "i" => Ok("vec2<f32>(0.0, 1.0)".to_string()),
+                "re" | "im" | "abs" | "conj" | "exp" | "log" | "sin" | "cos" => {
+                    self.expect('(')?;
+                    let argument = self.expression()?;
+                    self.expect(')')?;
+                    Ok(format!("c{}({})", name, argument))
+                }
+                _ => Err(format!("unknown name {} in the formula", name)),
+            },
+            Some(token) => Err(format!("unexpected {} in the formula", token)),
+            None => Err("the formula ends too early".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_products_and_the_powers_bind_tighter_than_the_sums() {
+        assert_eq!(parse("z^2 + c").unwrap(), "(cpowi(z, 2) + c)");
+        assert_eq!(
+            parse("z*c - p/z").unwrap(),
+            "(cmul(z, c) - cdiv(previous_z, z))"
+        );
+        assert_eq!(parse("(z + c)*z").unwrap(), "cmul((z + c), z)");
+        assert_eq!(parse("z^2.5").unwrap(), "cpow(z, vec2<f32>(2.5, 0.0))");
+    }
+
+    #[test]
+    fn the_sign_applies_after_the_power() {
+        assert_eq!(parse("-z^2").unwrap(), "(-cpowi(z, 2))");
+        assert_eq!(parse("--c").unwrap(), "(-(-c))");
+        assert_eq!(parse("z^-2").unwrap(), "cpow(z, (-vec2<f32>(2.0, 0.0)))");
+    }
+
+    #[test]
+    fn the_functions_and_the_modulus_are_compiled() {
+        assert_eq!(
+            parse("(|re(z)| + i*|im(z)|)^2 + c").unwrap(),
+            "(cpowi((cabs(cre(z)) + cmul(vec2<f32>(0.0, 1.0), cabs(cim(z)))), 2) + c)"
+        );
+        assert_eq!(parse("exp(conj(z))").unwrap(), "cexp(cconj(z))");
+    }
+
+    #[test]
+    fn the_invalid_formulas_are_rejected() {
+        for formula in [
+            "", "z +", "(z", "|z", "sin z", "w + c", "z $ c", "z c", "1e50", "1.2.3",
+        ] {
+            assert!(parse(formula).is_err(), "{}", formula);
+        }
+    }
+
+    #[test]
+    fn the_depth_is_limited() {
+        let nested = |depth| format!("{}z{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(parse(&nested(MAXIMUM_DEPTH - 1)).is_ok());
+        assert!(parse(&nested(MAXIMUM_DEPTH)).is_err());
+        assert!(parse(&nested(100_000)).is_err());
+        assert!(parse(&format!("{}z", "-".repeat(100_000))).is_err());
+        assert!(parse(&"z^".repeat(100_000)).is_err());
+    }
+}
@@ -0,0 +1,92 @@
+use serde::Deserialize;
+
+use crate::game::formula;
+use crate::game::mamndelbrot_state::{LastRenderedMandelbrot, MandelbrotDot};
+use crate::game::mandelbrot::MandelbrotData;
+use crate::game::newton::NewtonPolynomial;
+use crate::game::palette::Palette;
+
+// the fractals that can be explored, each drawn by its own shader
+// the shaders share the bindings of the mandelbrot one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fractal {
+    // the escape time of z² + c, with perturbation theory for the deep zooms
+    #[default]
+    Mandelbrot,
+    // the root reached by the newton method from each point, for a polynomial of the config,
+    // computed in simple precision so it can not be zoomed deeply
+    Newton,
+    // z² + c + p z_{n-1}, in simple precision
+    Phoenix,
+    // a newton step toward a root of z³ - 1 shifted by c, in simple precision
+    Nova,
+    // the formula of the config or of the command line, in simple precision
+    Custom,
+}
+
+impl Fractal {
+    pub const ALL: [Fractal; 5] = [
+        Fractal::Mandelbrot,
+        Fractal::Newton,
+        Fractal::Phoenix,
+        Fractal::Nova,
+        Fractal::Custom,
+    ];
+
+    // the source of the shader, the formula is only used by the custom fractal
+    pub fn shader(&self, formula: &str) -> Result<String, String> {
+        let source = match self {
+            Fractal::Mandelbrot => include_str!("../shaders/mandelbrot.wgsl").to_string(),
+            Fractal::Newton => include_str!("../shaders/newton.wgsl").to_string(),
+            Fractal::Phoenix => formula_shader(include_str!("../shaders/formulas/phoenix.wgsl")),
+            Fractal::Nova => formula_shader(include_str!("../shaders/formulas/nova.wgsl")),
+            Fractal::Custom => formula_shader(&formula::compile(formula)?),
+        };
+        Ok(with_structs(&source))
+    }
+
+    // the fractal after this one in the list
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|fractal| *fractal == self).unwrap();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+// the shader of a formula iterated in simple precision
+fn formula_shader(formula: &str) -> String {
+    include_str!("../shaders/formula.wgsl").replace("// FORMULA", formula)
+}
+
+// the shader with the structs of the buffers in place of its // STRUCTS line,
+// written from the rust structs so the shaders can not read them with another layout
+pub fn with_structs(source: &str) -> String {
+    let structs = [
+        MandelbrotData::wgsl_struct("Mandelbrot"),
+        MandelbrotDot::wgsl_struct("MandelbrotDot"),
+        LastRenderedMandelbrot::wgsl_struct("LastRenderedMandelbrot"),
+        NewtonPolynomial::wgsl_struct("Polynomial"),
+        Palette::wgsl_struct("Palette"),
+    ];
+    source.replace("// STRUCTS\n", &structs.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use naga::valid::{Capabilities, ValidationFlags, Validator};
+
+    use super::*;
+
+    #[test]
+    fn the_shader_of_each_fractal_is_valid() {
+        for fractal in Fractal::ALL {
+            let source = fractal.shader("z * z + c").unwrap();
+            let module = naga::front::wgsl::parse_str(&source)
+                .map_err(|error| error.emit_to_string(&source))
+                .unwrap_or_else(|error| panic!("{:?}: {}", fractal, error));
+            Validator::new(ValidationFlags::all(), Capabilities::all())
+                .validate(&module)
+                .unwrap_or_else(|error| panic!("{:?}: {:?}", fractal, error));
+        }
+    }
+}
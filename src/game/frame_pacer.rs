@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+use instant::Instant;
+
+// the time before a frame is due under which the pacer spins instead of letting the system sleep,
+// the system may wake up the event loop a millisecond or two later than asked
+#[cfg(not(target_arch = "wasm32"))]
+const SPIN_MARGIN: Duration = Duration::from_micros(1500);
+// the system can not be waited for in the browser, the frame is drawn as soon as it is close
+#[cfg(target_arch = "wasm32")]
+const SPIN_MARGIN: Duration = Duration::from_micros(0);
+// the share of a new measure of the latency of the redraws kept in its average
+const LATENCY_SMOOTHING: f32 = 0.1;
+
+// what the event loop must do until the next frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pace {
+    // ask the window for a redraw now
+    Redraw,
+    // sleep until then, and ask again
+    WaitUntil(Instant),
+}
+
+// give the frames a steady rate, whatever the refresh rate of the screen
+// - the frames are due at whole frame durations from each other, so the rate does not drift
+//   when the event loop wakes up a little late
+// - the event loop sleeps until just before a frame is due, then spins the rest of the time,
+//   the system is not precise enough to sleep until the exact time
+// - the redraw is asked a little early, by the time the window takes to give it back
+// - without a frame duration, the frames are drawn as fast as possible
+pub struct FramePacer {
+    // the time the next frame is due
+    next_frame: Instant,
+    // the time the last frame has started, to measure the time between two of them
+    last_frame: Instant,
+    // the time the redraw not drawn yet has been asked,
+    // the window may never give it back, while it is minimized for example
+    requested: Option<Instant>,
+    // the average time between asking a redraw and drawing it
+    redraw_latency: Duration,
+}
+
+impl Default for FramePacer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FramePacer {
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            next_frame: now,
+            last_frame: now,
+            requested: None,
+            redraw_latency: Duration::ZERO,
+        }
+    }
+
+    // start the frames again from now, after a pause, so the time spent is not a frame
+    pub fn reset(&mut self) {
+        *self = Self {
+            redraw_latency: self.redraw_latency,
+            ..Self::new()
+        };
+    }
+
+    // what to do once the events are handled, with the time between two frames,
+    // none to draw them as fast as possible
+    pub fn pace(&mut self, frame_duration: Option<Duration>) -> Pace {
+        if let Some(frame_duration) = frame_duration {
+            let now = Instant::now();
+            // a frame much later than due is not caught up with a burst of frames
+            if now > self.next_frame + frame_duration {
+                self.next_frame = now;
+            }
+            let due = self
+                .next_frame
+                .checked_sub(self.redraw_latency)
+                .unwrap_or(self.next_frame);
+            if now + SPIN_MARGIN < due {
+                return Pace::WaitUntil(due - SPIN_MARGIN);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            while Instant::now() < due {
+                std::hint::spin_loop();
+            }
+            self.next_frame += frame_duration;
+        }
+        self.requested = Some(Instant::now());
+        Pace::Redraw
+    }
+
+    // call when the frame starts to be drawn, return the time since the last one
+    pub fn start_frame(&mut self) -> Duration {
+        let now = Instant::now();
+        if let Some(requested) = self.requested.take() {
+            let latency = (now - requested).as_secs_f32();
+            self.redraw_latency = Duration::from_secs_f32(
+                self.redraw_latency.as_secs_f32() * (1.0 - LATENCY_SMOOTHING)
+                    + latency * LATENCY_SMOOTHING,
+            );
+        }
+        let frame_time = now - self.last_frame;
+        self.last_frame = now;
+        frame_time
+    }
+}
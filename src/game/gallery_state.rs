@@ -0,0 +1,285 @@
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use winit::dpi::PhysicalSize;
+use winit::event::{ElementState, Event, MouseButton, VirtualKeyCode, WindowEvent};
+use winit::window::Window;
+
+use crate::game::bookmarks::{self, THUMBNAIL_SIDE};
+use crate::game::command::Command;
+use crate::game::engine::overlay::WHITE;
+use crate::game::engine::Engine;
+use crate::game::game_state::{GameState, Transition};
+use crate::game::TITLE;
+
+// the thumbnails shown at once, the next ones are on the next pages
+const COLUMNS: u32 = 4;
+const ROWS: u32 = 3;
+// the pixels between the thumbnails in the image of a page
+const SPACING: u32 = 16;
+// the smallest distance in pixels between the gallery and the border of the window
+const MARGIN: f32 = 32.0;
+const BACKGROUND: [u8; 4] = [24, 24, 24, 255];
+const NUMBER_HEIGHT: f32 = 12.0;
+
+// the thumbnails of the bookmarks drawn in a grid over the explorer, to go to one of them
+// - the arrows or the mouse to choose a bookmark, Page up and Page down to change of page
+// - Enter or a click to go to the bookmark chosen, Escape to close the gallery
+// the bookmark chosen is written in the title of the window
+pub struct GalleryState {
+    window: Option<Rc<Window>>,
+    paths: Vec<PathBuf>,
+    selected: usize,
+    // the image of the page shown, until it is given to the engine
+    page_image: Option<image::RgbaImage>,
+    // the size of the window at the last frame, to find the thumbnail under the cursor
+    size: PhysicalSize<u32>,
+    cursor: [f32; 2],
+    transitions: Vec<Transition>,
+}
+
+impl GalleryState {
+    // none when there is no bookmark to show
+    pub fn new(window: Option<Rc<Window>>) -> Option<Self> {
+        let paths = bookmarks::paths();
+        if paths.is_empty() {
+            return None;
+        }
+        eprintln!("Bookmarks: {} saved", paths.len());
+        let mut gallery = Self {
+            window,
+            paths,
+            selected: 0,
+            page_image: None,
+            size: PhysicalSize::new(0, 0),
+            cursor: [0.0, 0.0],
+            transitions: vec![],
+        };
+        gallery.page_image = Some(gallery.draw_page());
+        gallery.show();
+        Some(gallery)
+    }
+
+    fn page(&self) -> usize {
+        self.selected / (COLUMNS * ROWS) as usize
+    }
+
+    // the thumbnails of the page of the selected bookmark put together in one image,
+    // the bookmarks without thumbnail are left empty
+    fn draw_page(&self) -> image::RgbaImage {
+        let (width, height) = Self::page_size();
+        let mut page = image::RgbaImage::from_pixel(width, height, image::Rgba(BACKGROUND));
+        let first = self.page() * (COLUMNS * ROWS) as usize;
+        for (index, path) in self
+            .paths
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take((COLUMNS * ROWS) as usize)
+        {
+            let thumbnail = match image::open(bookmarks::thumbnail_path(path)) {
+                Ok(thumbnail) => thumbnail.to_rgba8(),
+                Err(_) => continue,
+            };
+            let thumbnail = image::imageops::resize(
+                &thumbnail,
+                THUMBNAIL_SIDE,
+                THUMBNAIL_SIDE,
+                image::imageops::FilterType::Triangle,
+            );
+            let [x, y] = Self::tile_position(index - first);
+            image::imageops::replace(&mut page, &thumbnail, x as i64, y as i64);
+        }
+        page
+    }
+
+    // the size in pixels of the image of a page
+    fn page_size() -> (u32, u32) {
+        (
+            COLUMNS * THUMBNAIL_SIDE + (COLUMNS + 1) * SPACING,
+            ROWS * THUMBNAIL_SIDE + (ROWS + 1) * SPACING,
+        )
+    }
+
+    // the top left corner of a thumbnail in the image of a page, from its index in the page
+    fn tile_position(index: usize) -> [u32; 2] {
+        let (column, row) = (index as u32 % COLUMNS, index as u32 / COLUMNS);
+        [
+            SPACING + column * (THUMBNAIL_SIDE + SPACING),
+            SPACING + row * (THUMBNAIL_SIDE + SPACING),
+        ]
+    }
+
+    // the rectangle of the window the page is drawn in, centered and shrunk to fit in it
+    fn rectangle(&self) -> [[f32; 2]; 2] {
+        let (width, height) = Self::page_size();
+        let (width, height) = (width as f32, height as f32);
+        let available = (
+            self.size.width as f32 - 2.0 * MARGIN,
+            self.size.height as f32 - 2.0 * MARGIN,
+        );
+        let scale = (available.0 / width)
+            .min(available.1 / height)
+            .clamp(0.0, 1.0);
+        let start = [
+            (self.size.width as f32 - width * scale) / 2.0,
+            (self.size.height as f32 - height * scale) / 2.0,
+        ];
+        [start, [start[0] + width * scale, start[1] + height * scale]]
+    }
+
+    // the corners of a thumbnail in the window, from its index in the page
+    fn tile_rectangle(&self, index: usize) -> [[f32; 2]; 2] {
+        let [start, end] = self.rectangle();
+        let scale = (end[0] - start[0]) / Self::page_size().0 as f32;
+        let [x, y] = Self::tile_position(index);
+        let corner = [start[0] + x as f32 * scale, start[1] + y as f32 * scale];
+        let side = THUMBNAIL_SIDE as f32 * scale;
+        [corner, [corner[0] + side, corner[1] + side]]
+    }
+
+    // the bookmark of the page shown under a pixel of the window
+    fn bookmark_at(&self, position: [f32; 2]) -> Option<usize> {
+        let first = self.page() * (COLUMNS * ROWS) as usize;
+        let count = (self.paths.len() - first).min((COLUMNS * ROWS) as usize);
+        (0..count)
+            .find(|&index| {
+                let [start, end] = self.tile_rectangle(index);
+                (start[0]..end[0]).contains(&position[0])
+                    && (start[1]..end[1]).contains(&position[1])
+            })
+            .map(|index| first + index)
+    }
+
+    fn select(&mut self, selected: usize) {
+        let page = self.page();
+        self.selected = selected.min(self.paths.len() - 1);
+        if self.page() != page {
+            self.page_image = Some(self.draw_page());
+        }
+        self.show();
+    }
+
+    fn show(&self) {
+        if let Some(window) = &self.window {
+            window.set_title(&format!(
+                "Bookmarks - {}/{} {} - Enter to go, Escape to close",
+                self.selected + 1,
+                self.paths.len(),
+                self.paths[self.selected].display()
+            ));
+        }
+    }
+
+    fn open(&mut self, engine: &mut Engine) {
+        let path = self.paths[self.selected].to_string_lossy().into_owned();
+        eprintln!("Going to the bookmark {}", path);
+        self.transitions
+            .push(Transition::Command(Command::Open { path }));
+        self.close(engine);
+    }
+
+    fn close(&mut self, engine: &mut Engine) {
+        engine.show_gallery(None);
+        if let Some(window) = &self.window {
+            window.set_title(TITLE);
+        }
+        self.transitions.push(Transition::Pop);
+    }
+}
+
+impl GameState for GalleryState {
+    fn update(&mut self, engine: &mut Engine, _delta_time: f32) {
+        self.size = engine.size();
+        if let Some(page_image) = self.page_image.take() {
+            engine.show_gallery(Some(&page_image));
+        }
+        engine.update_gallery(self.rectangle());
+        // the number of each bookmark above its thumbnail, and a frame around the selected one
+        let first = self.page() * (COLUMNS * ROWS) as usize;
+        let count = (self.paths.len() - first).min((COLUMNS * ROWS) as usize);
+        for index in 0..count {
+            let [start, _] = self.tile_rectangle(index);
+            let number = (first + index + 1).to_string();
+            let position = [start[0], start[1] - NUMBER_HEIGHT - 2.0];
+            engine
+                .overlay()
+                .text(position, NUMBER_HEIGHT, &number, WHITE);
+        }
+        let [start, end] = self.tile_rectangle(self.selected - first);
+        engine.overlay().rectangle(
+            [
+                [start[0] - 2.0, start[1] - 2.0],
+                [end[0] + 2.0, end[1] + 2.0],
+            ],
+            WHITE,
+        );
+    }
+
+    fn input(&mut self, event: &Event<()>, engine: &mut Engine) {
+        let event = match event {
+            Event::WindowEvent { event, .. } => event,
+            _ => return,
+        };
+        let last = self.paths.len() - 1;
+        let page_length = (COLUMNS * ROWS) as usize;
+        match event {
+            WindowEvent::KeyboardInput { input, .. } if input.state == ElementState::Pressed => {
+                match input.virtual_keycode {
+                    Some(VirtualKeyCode::Escape) => self.close(engine),
+                    Some(VirtualKeyCode::Return) => self.open(engine),
+                    Some(VirtualKeyCode::Left) => self.select(self.selected.saturating_sub(1)),
+                    Some(VirtualKeyCode::Right) => self.select((self.selected + 1).min(last)),
+                    Some(VirtualKeyCode::Up) => {
+                        self.select(self.selected.saturating_sub(COLUMNS as usize))
+                    }
+                    Some(VirtualKeyCode::Down) => {
+                        self.select((self.selected + COLUMNS as usize).min(last))
+                    }
+                    Some(VirtualKeyCode::PageUp) => {
+                        self.select(self.selected.saturating_sub(page_length))
+                    }
+                    Some(VirtualKeyCode::PageDown) => {
+                        self.select((self.selected + page_length).min(last))
+                    }
+                    _ => {}
+                }
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor = [position.x as f32, position.y as f32];
+                if let Some(index) = self.bookmark_at(self.cursor) {
+                    if index != self.selected {
+                        self.select(index);
+                    }
+                }
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } => {
+                if let Some(index) = self.bookmark_at(self.cursor) {
+                    self.select(index);
+                    self.open(engine);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn captures_input(&self) -> bool {
+        true
+    }
+
+    fn shows_title(&self) -> bool {
+        true
+    }
+
+    fn transition(&mut self) -> Transition {
+        if self.transitions.is_empty() {
+            Transition::None
+        } else {
+            self.transitions.remove(0)
+        }
+    }
+}
@@ -1,10 +1,182 @@
+use std::sync::mpsc::Sender;
+
 use bytemuck::{Pod, Zeroable};
 use winit::event::Event;
 
+use crate::game::command::Command;
+use crate::game::config::Config;
 use crate::game::engine::Engine;
+use crate::game::mamndelbrot_state::MandelbrotState;
 use crate::game::Game;
 
 pub trait GameState {
     fn update(&mut self, engine: &mut Engine, delta_time: f32);
     fn input(&mut self, event: &Event<()>, engine: &mut Engine);
-}
\ No newline at end of file
+
+    // move the camera of the explorer or change its iterations, after the update of each frame
+    fn drive(&mut self, _explorer: &mut MandelbrotState, _engine: &mut Engine, _delta_time: f32) {}
+
+    // true while the state moves the camera or changes the image by itself, like a tour,
+    // so the frames are drawn at full speed and the screensaver does not start
+    fn is_driving(&self) -> bool {
+        false
+    }
+
+    // true for the states serving clients over the network, like the remote,
+    // the explorer goes on for them while the window does not have the focus
+    fn serves_clients(&self) -> bool {
+        false
+    }
+
+    // the fixed time step of the frames of a script, so it renders the same way on every machine
+    fn time_step(&self) -> Option<f32> {
+        None
+    }
+
+    // apply the settings of a reloaded config file
+    fn set_config(&mut self, _config: &Config) {}
+
+    // a command of the pipe, the remote or another state, for the states it concerns
+    fn execute(&mut self, _command: &Command) {}
+
+    // true when the states below must not get the keys and the mouse, like a menu over the explorer
+    fn captures_input(&self) -> bool {
+        false
+    }
+
+    // true when the explorer and the states below are stopped while this one is open, like a menu,
+    // nothing moves until it is closed
+    fn pauses_simulation(&self) -> bool {
+        false
+    }
+
+    // true when the state writes the title of the window, like a menu,
+    // so the location of the view is not written over it
+    fn shows_title(&self) -> bool {
+        false
+    }
+
+    // what the state asks the game once it has handled an event or a frame,
+    // asked again until it returns none
+    fn transition(&mut self) -> Transition {
+        Transition::None
+    }
+}
+
+// a change of the stack of states asked by one of them
+pub enum Transition {
+    None,
+    // put another state on top of this one
+    Push(Box<dyn GameState>),
+    // remove this state, the ones below get the events again
+    Pop,
+    // drive the explorer, as the commands of the pipe do
+    Command(Command),
+    // drive the explorer and send the status of the command on the channel, to the one who asked it
+    Request(Command, Sender<serde_json::Value>),
+}
+
+// the states over the explorer, the automatic modes like the tour at the bottom
+// and the menus pushed over them, the last one is on top and gets the events first
+#[derive(Default)]
+pub struct StateStack {
+    states: Vec<Box<dyn GameState>>,
+}
+
+impl StateStack {
+    pub fn push(&mut self, state: Box<dyn GameState>) {
+        self.states.push(state);
+    }
+
+    // give the event to the states from the top one down, until one of them captures the input
+    // return true when it has been captured, so the explorer must not get it
+    pub fn input(&mut self, event: &Event<()>, engine: &mut Engine) -> bool {
+        for state in self.states.iter_mut().rev() {
+            state.input(event, engine);
+            if state.captures_input() {
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn shows_title(&self) -> bool {
+        self.states.iter().any(|state| state.shows_title())
+    }
+
+    // true when a state gets the keys and the mouse before the explorer, like a menu
+    pub fn captures_input(&self) -> bool {
+        self.states.iter().any(|state| state.captures_input())
+    }
+
+    pub fn pauses_simulation(&self) -> bool {
+        self.states.iter().any(|state| state.pauses_simulation())
+    }
+
+    // the index of the lowest state updated, the ones below a state pausing the simulation wait
+    fn first_running(&self) -> usize {
+        self.states
+            .iter()
+            .rposition(|state| state.pauses_simulation())
+            .unwrap_or(0)
+    }
+
+    pub fn serves_clients(&self) -> bool {
+        self.states.iter().any(|state| state.serves_clients())
+    }
+
+    pub fn is_driving(&self) -> bool {
+        self.states.iter().any(|state| state.is_driving())
+    }
+
+    pub fn time_step(&self) -> Option<f32> {
+        self.states.iter().find_map(|state| state.time_step())
+    }
+
+    pub fn set_config(&mut self, config: &Config) {
+        for state in self.states.iter_mut() {
+            state.set_config(config);
+        }
+    }
+
+    pub fn execute(&mut self, command: &Command) {
+        for state in self.states.iter_mut() {
+            state.execute(command);
+        }
+    }
+
+    pub fn update(&mut self, engine: &mut Engine, delta_time: f32) {
+        let first = self.first_running();
+        for state in self.states[first..].iter_mut() {
+            state.update(engine, delta_time);
+        }
+    }
+
+    pub fn drive(&mut self, explorer: &mut MandelbrotState, engine: &mut Engine, delta_time: f32) {
+        let first = self.first_running();
+        for state in self.states[first..].iter_mut() {
+            state.drive(explorer, engine, delta_time);
+        }
+    }
+
+    // push and pop the states as they ask, from the top one down,
+    // and return the commands they give to the explorer, with the channel of their status if asked
+    pub fn transitions(&mut self) -> Vec<(Command, Option<Sender<serde_json::Value>>)> {
+        let mut commands = Vec::new();
+        for index in (0..self.states.len()).rev() {
+            loop {
+                match self.states[index].transition() {
+                    Transition::None => break,
+                    Transition::Push(state) => self.states.push(state),
+                    Transition::Pop => {
+                        self.states.remove(index);
+                        break;
+                    }
+                    Transition::Command(command) => commands.push((command, None)),
+                    Transition::Request(command, reply) => commands.push((command, Some(reply))),
+                }
+            }
+        }
+        commands
+    }
+}
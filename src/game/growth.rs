@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+
+use crate::game::config::GrowthConfig;
+
+// the view rendered again and again with more and more iterations, from a few up to the ones
+// it had when the animation started, so the set seems to grow its tendrils
+// the iterations grow exponentially, as the first ones change the shape the most
+pub struct GrowthPlayer {
+    start_iterations: f32,
+    target_iterations: f32,
+    // the iterations fixed before the animation, given back at its end
+    previous_fixed_iterations: Option<u32>,
+    // the share of the animation played, from 0 to 1
+    progress: f32,
+    // the share played by each second, or by each frame when the frames are exported
+    rate: f32,
+    finished: bool,
+    // the directory every frame is saved to, the frames are then played one by one
+    // once they are finished, whatever the time they take
+    export: Option<PathBuf>,
+    frame: u32,
+    // the file the frame being drawn is saved to once it is finished
+    unsaved: Option<PathBuf>,
+}
+
+impl GrowthPlayer {
+    pub fn new(
+        config: &GrowthConfig,
+        target_iterations: u32,
+        previous_fixed_iterations: Option<u32>,
+        export: Option<PathBuf>,
+    ) -> Self {
+        let rate = match export {
+            Some(_) => 1.0 / (config.seconds * config.fps as f32).max(1.0),
+            None => 1.0 / config.seconds,
+        };
+        let target_iterations = target_iterations.max(1);
+        Self {
+            start_iterations: config.start_iterations.clamp(1, target_iterations) as f32,
+            target_iterations: target_iterations as f32,
+            previous_fixed_iterations,
+            progress: 0.0,
+            rate,
+            finished: false,
+            export,
+            frame: 0,
+            unsaved: None,
+        }
+    }
+
+    pub fn is_exporting(&self) -> bool {
+        self.export.is_some()
+    }
+
+    pub fn previous_fixed_iterations(&self) -> Option<u32> {
+        self.previous_fixed_iterations
+    }
+
+    // the iterations of the next frame, none once the frame with the target iterations is done
+    pub fn next_frame(&mut self, delta_time: f32) -> Option<u32> {
+        if self.finished {
+            return None;
+        }
+        let progress = self.progress.min(1.0);
+        self.finished = progress >= 1.0;
+        self.progress += match self.export {
+            Some(_) => self.rate,
+            None => self.rate * delta_time,
+        };
+        self.frame += 1;
+        self.unsaved = self
+            .export
+            .as_ref()
+            .map(|directory| directory.join(format!("growth_{:04}.png", self.frame)));
+        let iterations =
+            self.start_iterations * (self.target_iterations / self.start_iterations).powf(progress);
+        Some(iterations.round() as u32)
+    }
+
+    // the file the last frame given is saved to, once
+    pub fn take_unsaved(&mut self) -> Option<PathBuf> {
+        self.unsaved.take()
+    }
+}
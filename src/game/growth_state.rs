@@ -0,0 +1,144 @@
+use std::path::PathBuf;
+
+use winit::event::{ElementState, Event, VirtualKeyCode, WindowEvent};
+
+use crate::game::command::Command;
+use crate::game::config::{Config, GrowthConfig};
+use crate::game::engine::Engine;
+use crate::game::game_state::{GameState, Transition};
+use crate::game::growth::GrowthPlayer;
+use crate::game::mamndelbrot_state::MandelbrotState;
+
+// play the view with more and more iterations up to its own
+// - F6 to start or stop the animation
+// the frames are saved to a new directory when the growth config asks for it
+pub struct GrowthState {
+    config: GrowthConfig,
+    // the animation playing, none when it is stopped
+    player: Option<GrowthPlayer>,
+    toggle_requested: bool,
+    // the transitions to give to the game, in order
+    transitions: Vec<Transition>,
+}
+
+impl GrowthState {
+    pub fn new(config: GrowthConfig) -> Self {
+        Self {
+            config,
+            player: None,
+            toggle_requested: false,
+            transitions: vec![],
+        }
+    }
+
+    fn start(&mut self, explorer: &MandelbrotState) {
+        let export = if self.config.export {
+            let seconds = instant::SystemTime::now()
+                .duration_since(instant::SystemTime::UNIX_EPOCH)
+                .map_or(0, |time| time.as_secs());
+            let directory = PathBuf::from(format!("growth_{}", seconds));
+            if let Err(error) = std::fs::create_dir_all(&directory) {
+                eprintln!("Impossible to create {}: {}", directory.display(), error);
+                return;
+            }
+            Some(directory)
+        } else {
+            None
+        };
+        let target_iterations = explorer.maximum_iterations();
+        match &export {
+            Some(directory) => eprintln!(
+                "Growing iterations up to {}, saved to {}",
+                target_iterations,
+                directory.display()
+            ),
+            None => eprintln!("Growing iterations up to {}", target_iterations),
+        }
+        self.player = Some(GrowthPlayer::new(
+            &self.config,
+            target_iterations,
+            explorer.fixed_iterations(),
+            export,
+        ));
+    }
+
+    fn stop(&mut self, explorer: &mut MandelbrotState) {
+        if let Some(player) = self.player.take() {
+            explorer.set_fixed_iterations(player.previous_fixed_iterations());
+        }
+    }
+
+    // give the iterations of the next frame of the animation,
+    // an exported frame is saved once all its pixels are finished, before the next one is started
+    fn grow(&mut self, explorer: &mut MandelbrotState, delta_time: f32) {
+        let player = match self.player.as_mut() {
+            Some(player) => player,
+            None => return,
+        };
+        if player.is_exporting() {
+            if explorer.is_refining() {
+                return;
+            }
+            if let Some(path) = player.take_unsaved() {
+                self.transitions
+                    .push(Transition::Command(Command::Screenshot {
+                        path: path.to_string_lossy().into_owned(),
+                    }));
+                return;
+            }
+        }
+        match player.next_frame(delta_time) {
+            Some(iterations) => explorer.set_fixed_iterations(Some(iterations)),
+            None => {
+                self.stop(explorer);
+                eprintln!("Growing iterations: finished");
+            }
+        }
+    }
+}
+
+impl GameState for GrowthState {
+    fn update(&mut self, _engine: &mut Engine, _delta_time: f32) {}
+
+    fn input(&mut self, event: &Event<()>, _engine: &mut Engine) {
+        if let Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { input, .. },
+            ..
+        } = event
+        {
+            if input.state == ElementState::Pressed
+                && input.virtual_keycode == Some(VirtualKeyCode::F6)
+            {
+                self.toggle_requested = true;
+            }
+        }
+    }
+
+    fn drive(&mut self, explorer: &mut MandelbrotState, _engine: &mut Engine, delta_time: f32) {
+        if std::mem::take(&mut self.toggle_requested) {
+            if self.player.is_some() {
+                self.stop(explorer);
+                eprintln!("Growing iterations: stopped");
+            } else {
+                self.start(explorer);
+            }
+        }
+        self.grow(explorer, delta_time);
+    }
+
+    fn is_driving(&self) -> bool {
+        self.player.is_some()
+    }
+
+    fn set_config(&mut self, config: &Config) {
+        self.config = config.growth.clone();
+    }
+
+    fn transition(&mut self) -> Transition {
+        if self.transitions.is_empty() {
+            Transition::None
+        } else {
+            self.transitions.remove(0)
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use crate::game::big_real::BigReal;
+
+// the iterations of z² + c on the cpu, in any precision, without the state of the engine,
+// the orbits of the reference points are computed with them
+
+// how the orbit of a point ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EscapeInfo {
+    // the index of the first z whose square modulus is over mu, none when no z is
+    // before the maximum number of iterations
+    pub escape_iteration: Option<u32>,
+    // the index of the z with the smallest modulus, the period of the atom domain of the point,
+    // which surrounds a minibrot of this period
+    pub atom_period: u32,
+}
+
+// the next z of the orbit of c
+pub fn step<T: BigReal>(z: &(T, T), c: &(T, T)) -> (T, T) {
+    (
+        z.0.times(&z.0).minus(&z.1.times(&z.1)).plus(&c.0),
+        z.0.times(&z.1).times(&T::from_double(2.0)).plus(&c.1),
+    )
+}
+
+// the square of the modulus of z
+pub fn norm<T: BigReal>(z: &(T, T)) -> f64 {
+    z.0.times(&z.0).plus(&z.1.times(&z.1)).to_double()
+}
+
+// the orbit of c, each z is given in turn until one is over mu, which is not given
+pub struct Iterations<'a, T: BigReal> {
+    c: &'a (T, T),
+    // the z given next
+    z: (T, T),
+    // the index of z in the orbit
+    iteration: u32,
+    mu: f64,
+    // the smallest square modulus of the z computed
+    smallest: f64,
+    info: EscapeInfo,
+}
+
+impl<'a, T: BigReal> Iterations<'a, T> {
+    // the orbit of c from z = 0
+    pub fn new(c: &'a (T, T), mu: f64) -> Self {
+        Self::resume(c, (T::from_double(0.0), T::from_double(0.0)), 0, mu)
+    }
+
+    // the orbit of c from the z of the given index, computed before
+    pub fn resume(c: &'a (T, T), z: (T, T), iteration: u32, mu: f64) -> Self {
+        let escape_iteration = (norm(&z) > mu).then_some(iteration);
+        Self {
+            c,
+            z,
+            iteration,
+            mu,
+            smallest: f64::INFINITY,
+            info: EscapeInfo {
+                escape_iteration,
+                atom_period: 0,
+            },
+        }
+    }
+
+    // how the orbit ended, from the z computed since it was started or resumed
+    pub fn escape_info(&self) -> EscapeInfo {
+        self.info
+    }
+
+    // the z which would be given next, to resume the orbit later
+    pub fn into_z(self) -> (T, T) {
+        self.z
+    }
+}
+
+impl<T: BigReal> Iterator for Iterations<'_, T> {
+    type Item = (T, T);
+
+    fn next(&mut self) -> Option<(T, T)> {
+        if self.info.escape_iteration.is_some() {
+            return None;
+        }
+        let next = step(&self.z, self.c);
+        self.iteration += 1;
+        let norm = norm(&next);
+        if norm < self.smallest {
+            self.smallest = norm;
+            self.info.atom_period = self.iteration;
+        }
+        if norm > self.mu {
+            self.info.escape_iteration = Some(self.iteration);
+        }
+        Some(std::mem::replace(&mut self.z, next))
+    }
+}
+
+// the period of the first minibrot whose atom domain meets the disk of the given radius around c,
+// none when the orbit escapes above mu first or no period is found before the maximum
+// the disk is followed with the derivative of z by c: it surrounds 0 once |z| < |dz/dc| * radius
+pub fn find_period<T: BigReal>(
+    c: &(T, T),
+    radius: f64,
+    maximum_iterations: u32,
+    mu: f64,
+) -> Option<u32> {
+    let mut z = (T::from_double(0.0), T::from_double(0.0));
+    let mut dz = (0.0, 0.0);
+    for iteration in 1..=maximum_iterations {
+        let (x, y) = (z.0.to_double(), z.1.to_double());
+        dz = (
+            2.0 * (x * dz.0 - y * dz.1) + 1.0,
+            2.0 * (x * dz.1 + y * dz.0),
+        );
+        z = step(&z, c);
+        let norm = norm(&z);
+        let derivative = dz.0 * dz.0 + dz.1 * dz.1;
+        if norm > mu || !derivative.is_finite() {
+            return None;
+        }
+        if norm < derivative * radius * radius {
+            return Some(iteration);
+        }
+    }
+    None
+}
+
+// the nucleus of the minibrot of the given period near c, where z comes back to 0 after period
+// iterations, found with the method of newton, none when it does not converge within the steps
+// each step is computed in double precision from z and its derivative, then added to c in T,
+// it has converged once the step is far smaller than the radius the minibrot was searched in
+pub fn find_nucleus<T: BigReal>(
+    c: &(T, T),
+    period: u32,
+    radius: f64,
+    steps: u32,
+) -> Option<(T, T)> {
+    let mut c = c.clone();
+    for _ in 0..steps {
+        let mut z = (T::from_double(0.0), T::from_double(0.0));
+        let mut dz = (T::from_double(0.0), T::from_double(0.0));
+        for _ in 0..period {
+            let two = T::from_double(2.0);
+            dz = (
+                z.0.times(&dz.0)
+                    .minus(&z.1.times(&dz.1))
+                    .times(&two)
+                    .plus(&T::from_double(1.0)),
+                z.0.times(&dz.1).plus(&z.1.times(&dz.0)).times(&two),
+            );
+            z = step(&z, &c);
+        }
+        let (x, y) = (z.0.to_double(), z.1.to_double());
+        let (dx, dy) = (dz.0.to_double(), dz.1.to_double());
+        let derivative = dx * dx + dy * dy;
+        if derivative == 0.0 || !derivative.is_finite() {
+            return None;
+        }
+        // z / dz
+        let delta = (
+            (x * dx + y * dy) / derivative,
+            (y * dx - x * dy) / derivative,
+        );
+        c = (
+            c.0.minus(&T::from_double(delta.0)),
+            c.1.minus(&T::from_double(delta.1)),
+        );
+        if delta.0.abs().max(delta.1.abs()) < radius * 1e-9 {
+            return Some(c);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigfloat::BigFloat;
+
+    use super::*;
+
+    const MU: f64 = 4.0;
+
+    // iterate z² + c from z = 0 until the square modulus of z is over mu,
+    // at most the maximum number of iterations
+    fn iterate<T: BigReal>(c: &(T, T), maximum_iterations: u32, mu: f64) -> EscapeInfo {
+        let mut iterations = Iterations::new(c, mu);
+        iterations
+            .by_ref()
+            .take(maximum_iterations as usize)
+            .for_each(drop);
+        iterations.escape_info()
+    }
+
+    fn double(x: f64, y: f64) -> (f64, f64) {
+        (x, y)
+    }
+
+    fn big(x: &str, y: &str) -> (BigFloat, BigFloat) {
+        (BigFloat::parse(x).unwrap(), BigFloat::parse(y).unwrap())
+    }
+
+    #[test]
+    fn points_outside_escape_after_known_iterations() {
+        for (c, escape_iteration) in [
+            (double(1.0, 0.0), 3),
+            (double(2.0, 0.0), 2),
+            (double(0.5, 0.0), 5),
+            (double(-0.75, 0.1), 33),
+        ] {
+            assert_eq!(
+                iterate(&c, 1000, MU).escape_iteration,
+                Some(escape_iteration),
+                "c = {:?}",
+                c
+            );
+        }
+    }
+
+    #[test]
+    fn points_inside_never_escape() {
+        for c in [
+            double(0.0, 0.0),
+            double(-1.0, 0.0),
+            double(-2.0, 0.0),
+            double(0.25, 0.0),
+            double(0.3, 0.5),
+        ] {
+            assert_eq!(iterate(&c, 1000, MU).escape_iteration, None, "c = {:?}", c);
+        }
+    }
+
+    #[test]
+    fn centers_of_minibrots_have_their_period() {
+        for (c, period) in [
+            (double(0.0, 0.0), 1),
+            (double(-1.0, 0.0), 2),
+            (double(-0.1225611668766536, 0.7448617666197442), 3),
+            (double(-1.7548776662466927, 0.0), 3),
+            (double(-1.3107026413368328, 0.0), 4),
+            (double(-1.9407998065294847, 0.0), 4),
+        ] {
+            assert_eq!(iterate(&c, 1000, MU).atom_period, period, "c = {:?}", c);
+        }
+    }
+
+    #[test]
+    fn nucleus_of_the_minibrot_near_a_point_is_found() {
+        // close to the period 3 minibrot on the real axis
+        let c = big("-1.75", "0.001");
+        let period = find_period(&c, 0.01, 1000, MU);
+        assert_eq!(period, Some(3));
+        let (x, y) = find_nucleus(&c, 3, 0.01, 64).unwrap();
+        assert!(
+            (x.to_double() + 1.7548776662466927).abs() < 1e-12,
+            "x = {}",
+            x
+        );
+        assert!(y.to_double().abs() < 1e-12, "y = {}", y);
+    }
+
+    #[test]
+    fn big_float_gives_the_same_result_as_double() {
+        for (x, y) in [
+            ("1.0", "0.0"),
+            ("0.5", "0.0"),
+            ("-0.75", "0.1"),
+            ("-1.0", "0.0"),
+            ("-1.7548776662466927", "0.0"),
+            ("0.3", "0.5"),
+        ] {
+            let c = double(x.parse().unwrap(), y.parse().unwrap());
+            assert_eq!(
+                iterate(&big(x, y), 1000, MU),
+                iterate(&c, 1000, MU),
+                "c = {} + {}i",
+                x,
+                y
+            );
+        }
+    }
+
+    #[test]
+    fn orbit_of_the_center_of_the_period_2_bulb_is_a_cycle() {
+        let c = big("-1.0", "0.0");
+        let mut z = (BigFloat::from_f64(0.0), BigFloat::from_f64(0.0));
+        for expected in [-1.0, 0.0, -1.0, 0.0] {
+            z = step(&z, &c);
+            assert_eq!(z.0.to_double(), expected);
+            assert_eq!(norm(&z), expected * expected);
+        }
+    }
+
+    #[test]
+    fn a_resumed_orbit_goes_on_like_the_whole_orbit() {
+        let c = double(-0.75, 0.1);
+        let whole = Iterations::new(&c, MU).collect::<Vec<_>>();
+        let mut first = Iterations::new(&c, MU);
+        let mut points = first.by_ref().take(10).collect::<Vec<_>>();
+        let mut rest = Iterations::resume(&c, first.into_z(), 10, MU);
+        points.extend(rest.by_ref());
+        assert_eq!(points, whole);
+        let escape_iteration = iterate(&c, 1000, MU).escape_iteration;
+        assert_eq!(rest.escape_info().escape_iteration, escape_iteration);
+    }
+}
@@ -0,0 +1,180 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::game::big_real::{BigReal, Real};
+use crate::game::camera_animator::{CameraAnimator, Easing};
+use crate::game::location::Location;
+
+// a script of keyframes, read from a json or toml file
+// between two keyframes the zoom is interpolated exponentially and the position follows it,
+// like for the camera animations
+#[derive(Debug, Clone, Deserialize)]
+pub struct KeyframeScript {
+    // the number of frames per second, the script is played with a fixed time step
+    #[serde(default = "default_fps")]
+    pub fps: f32,
+    pub keyframes: Vec<Keyframe>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Keyframe {
+    // the time of the keyframe in seconds since the start of the script
+    pub time: f32,
+    #[serde(flatten)]
+    pub location: Location,
+    pub color_palette_scale: Option<f32>,
+}
+
+// the state of the explorer at a given time of the script
+#[derive(Debug, Clone)]
+pub struct KeyframeSample {
+    pub location: Location,
+    pub color_palette_scale: Option<f32>,
+}
+
+fn default_fps() -> f32 {
+    60.0
+}
+
+impl KeyframeScript {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+        let script: KeyframeScript = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => toml::from_str(&text).map_err(|error| error.to_string())?,
+            _ => serde_json::from_str(&text).map_err(|error| error.to_string())?,
+        };
+        script.validate()?;
+        Ok(script)
+    }
+
+    // a dive into a known location, rendered the same way on every run to measure the performances
+    pub fn benchmark(frames: u32) -> Self {
+        let fps = default_fps();
+        let location = |zoom| Location {
+            x: Real::from_text("-1.749922480927599928271333687542289453030433024473703345006508521395924860650654081299355473751219976598678491114359225427863893386542382475600444642781285056640754").unwrap(),
+            y: Real::from_text("-0.000000000000959502198314327569948975707202650233401883670299418141500240641361234506320676962536124684582340235944852850785763764700482870569928474715774446003497").unwrap(),
+            zoom,
+            angle: 0.0,
+        };
+        Self {
+            fps,
+            keyframes: vec![
+                Keyframe {
+                    time: 0.0,
+                    location: location(3.0),
+                    color_palette_scale: None,
+                },
+                Keyframe {
+                    time: frames.saturating_sub(1) as f32 / fps,
+                    location: location(0.000000000001),
+                    color_palette_scale: None,
+                },
+            ],
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.fps > 0.0 && self.fps.is_finite()) {
+            return Err("fps must be strictly positive".to_string());
+        }
+        if self.keyframes.is_empty() {
+            return Err("the script has no keyframe".to_string());
+        }
+        for (index, keyframe) in self.keyframes.iter().enumerate() {
+            if !(keyframe.location.zoom > 0.0 && keyframe.location.zoom.is_finite()) {
+                return Err(format!("keyframe {}: zoom must be strictly positive", index));
+            }
+            if index > 0 && keyframe.time <= self.keyframes[index - 1].time {
+                return Err(format!("keyframe {}: time must be after the previous keyframe", index));
+            }
+        }
+        Ok(())
+    }
+
+    // the time of the last keyframe
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map(|keyframe| keyframe.time).unwrap_or(0.0)
+    }
+
+    // the state of the explorer at the given time
+    pub fn sample(&self, time: f32) -> KeyframeSample {
+        let next = self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.time > time);
+        let (previous, next) = match next {
+            // before the first keyframe or after the last one, the state does not change
+            Some(0) => return self.keyframes[0].sample(),
+            None => return self.keyframes[self.keyframes.len() - 1].sample(),
+            Some(index) => (&self.keyframes[index - 1], &self.keyframes[index]),
+        };
+        let duration = next.time - previous.time;
+        let mut animator =
+            CameraAnimator::new(previous.location.clone(), next.location.clone(), duration)
+                .with_easing(Easing::Linear);
+        let t = (time - previous.time) / duration;
+        let color_palette_scale = match (previous.color_palette_scale, next.color_palette_scale) {
+            (Some(from), Some(to)) => Some(from + (to - from) * t),
+            (from, to) => from.or(to),
+        };
+        KeyframeSample {
+            location: animator.advance(time - previous.time),
+            color_palette_scale,
+        }
+    }
+}
+
+impl Keyframe {
+    fn sample(&self) -> KeyframeSample {
+        KeyframeSample {
+            location: self.location.clone(),
+            color_palette_scale: self.color_palette_scale,
+        }
+    }
+}
+
+// play a script frame by frame, optionally saving every frame as a png file
+pub struct KeyframePlayer {
+    script: KeyframeScript,
+    frame: u32,
+    export_directory: Option<PathBuf>,
+}
+
+impl KeyframePlayer {
+    pub fn new(script: KeyframeScript, export_directory: Option<PathBuf>) -> Self {
+        Self {
+            script,
+            frame: 0,
+            export_directory,
+        }
+    }
+
+    // the fixed time between two frames
+    pub fn delta_time(&self) -> f32 {
+        1.0 / self.script.fps
+    }
+
+    pub fn fps(&self) -> f32 {
+        self.script.fps
+    }
+
+    pub fn is_exporting(&self) -> bool {
+        self.export_directory.is_some()
+    }
+
+    // the state of the next frame and the file to save it to, none once the script is over
+    pub fn next_frame(&mut self) -> Option<(KeyframeSample, Option<PathBuf>)> {
+        let time = self.frame as f32 * self.delta_time();
+        if time > self.script.duration() {
+            return None;
+        }
+        let path = self
+            .export_directory
+            .as_ref()
+            .map(|directory| directory.join(format!("frame_{:05}.png", self.frame)));
+        self.frame += 1;
+        Some((self.script.sample(time), path))
+    }
+}
@@ -0,0 +1,190 @@
+use std::fs;
+use std::path::Path;
+
+use crate::game::big_real::{BigReal, Real, SIGNIFICANT_DIGITS};
+use crate::game::location::Location;
+
+// the location files of Kalles Fraktaler, lines of "name: value" like
+// Re: -1.7499
+// Im: 0.0
+// Zoom: 1E10
+// Iterations: 1000
+// the zoom of Kalles Fraktaler is a magnification, 1 showing a height of 4 around the center,
+// while the zoom of the explorer is the half of the height shown
+// the iterations are ignored when reading a file, the explorer computes them from the zoom
+
+pub fn load(path: &Path) -> Result<Location, String> {
+    load_with_palette_seed(path).map(|(location, _)| location)
+}
+
+// the location and the seed of the generated palette, written by the explorer only
+pub fn load_with_palette_seed(path: &Path) -> Result<(Location, Option<u32>), String> {
+    let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+    let mut x = None;
+    let mut y = None;
+    let mut zoom = None;
+    let mut angle = 0.0;
+    let mut palette_seed = None;
+    for line in text.lines() {
+        let (name, value) = match line.split_once(':') {
+            Some((name, value)) => (name.trim(), value.trim()),
+            None => continue,
+        };
+        match name {
+            "Re" => x = Some(parse_coordinate(name, value)?),
+            "Im" => y = Some(parse_coordinate(name, value)?),
+            "Zoom" => {
+                let magnification = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|magnification| *magnification > 0.0)
+                    .ok_or(format!("invalid Zoom: {}", value))?;
+                let value = (2.0 / magnification) as f32;
+                if value < f32::MIN_POSITIVE {
+                    return Err(format!("the zoom {} is too deep", magnification));
+                }
+                zoom = Some(value);
+            }
+            // in degrees
+            "RotateAngle" => {
+                angle = value
+                    .parse::<f32>()
+                    .map_err(|_| format!("invalid RotateAngle: {}", value))?
+                    .to_radians()
+            }
+            "PaletteSeed" => {
+                palette_seed = Some(
+                    value
+                        .parse::<u32>()
+                        .map_err(|_| format!("invalid PaletteSeed: {}", value))?,
+                )
+            }
+            // the coloring and the rendering settings of Kalles Fraktaler are ignored
+            _ => {}
+        }
+    }
+    let location = Location {
+        x: x.ok_or("missing Re")?,
+        y: y.ok_or("missing Im")?,
+        zoom: zoom.ok_or("missing Zoom")?,
+        angle,
+    };
+    Ok((location, palette_seed))
+}
+
+fn parse_coordinate(name: &str, value: &str) -> Result<Real, String> {
+    let coordinate = Real::from_text(value).ok_or(format!("invalid {}: {}", name, value))?;
+    let digits = significant_digits(value);
+    if digits > SIGNIFICANT_DIGITS {
+        eprintln!(
+            "{} has {} significant digits, only the first {} are kept",
+            name, digits, SIGNIFICANT_DIGITS
+        );
+    }
+    Ok(coordinate)
+}
+
+// the digits of the mantissa of a number, without the zeros around them
+fn significant_digits(value: &str) -> usize {
+    let mantissa = value.split(['e', 'E']).next().unwrap_or_default();
+    let digits = mantissa
+        .chars()
+        .filter(char::is_ascii_digit)
+        .collect::<String>();
+    digits.trim_matches('0').len()
+}
+
+// the seed of the generated palette is written in a line of its own, ignored by Kalles Fraktaler
+pub fn save(
+    path: &Path,
+    location: &Location,
+    iterations: u32,
+    palette_seed: Option<u32>,
+) -> Result<(), String> {
+    let mut text = format!(
+        "Re: {}\r\nIm: {}\r\nZoom: {:E}\r\nIterations: {}\r\nRotateAngle: {}\r\n",
+        location.x.to_text(),
+        location.y.to_text(),
+        2.0 / location.zoom as f64,
+        iterations,
+        location.angle.to_degrees(),
+    );
+    if let Some(seed) = palette_seed {
+        text.push_str(&format!("PaletteSeed: {}\r\n", seed));
+    }
+    fs::write(path, text).map_err(|error| error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temporary_file(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("mandelbrot_{}_{}.kfr", name, std::process::id()))
+    }
+
+    #[test]
+    fn a_saved_location_is_loaded_back() {
+        let path = temporary_file("round_trip");
+        let location = Location {
+            x: Real::from_text("-1.7499370328191234567890123456789").unwrap(),
+            y: Real::from_text("0.0000123456789").unwrap(),
+            zoom: 2e-10,
+            angle: 0.5,
+        };
+        save(&path, &location, 1000, Some(42)).unwrap();
+        let loaded = load_with_palette_seed(&path);
+        fs::remove_file(&path).unwrap();
+        let (loaded, seed) = loaded.unwrap();
+        assert_eq!(loaded.x, location.x);
+        assert_eq!(loaded.y, location.y);
+        assert!((loaded.zoom / location.zoom - 1.0).abs() < 1e-6);
+        assert!((loaded.angle - location.angle).abs() < 1e-6);
+        assert_eq!(seed, Some(42));
+    }
+
+    #[test]
+    fn the_magnification_is_converted_to_the_zoom() {
+        let path = temporary_file("magnification");
+        fs::write(
+            &path,
+            "Re: -0.75\r\nIm: 0.1\r\nZoom: 1E10\r\nIterations: 500\r\n",
+        )
+        .unwrap();
+        let loaded = load(&path);
+        fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+        assert!((loaded.zoom / 2e-10 - 1.0).abs() < 1e-6);
+        // a magnification of 1 shows a height of 4, twice the zoom
+        let location = Location {
+            zoom: 2.0,
+            ..loaded
+        };
+        save(&path, &location, 500, None).unwrap();
+        let text = fs::read_to_string(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(text.unwrap().contains("Zoom: 1E0\r\n"));
+    }
+
+    #[test]
+    fn invalid_files_are_rejected() {
+        let path = temporary_file("invalid");
+        for text in [
+            "Re: 0\nIm: 0\n",
+            "Re: 0\nIm: 0\nZoom: 0\n",
+            "Re: x\nIm: 0\nZoom: 1\n",
+        ] {
+            fs::write(&path, text).unwrap();
+            assert!(load(&path).is_err(), "{:?}", text);
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn the_significant_digits_are_counted() {
+        assert_eq!(significant_digits("-1.75"), 3);
+        assert_eq!(significant_digits("0.000120"), 2);
+        assert_eq!(significant_digits("1.5E-10"), 2);
+        assert_eq!(significant_digits(&format!("0.{}", "3".repeat(50))), 50);
+    }
+}
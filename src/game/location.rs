@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game::big_real::{BigReal, Real};
+
+// a point of view on the mandelbrot set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    // the coordinate of the center of the screen, with the precision of the reference orbit
+    #[serde(with = "real")]
+    pub x: Real,
+    #[serde(with = "real")]
+    pub y: Real,
+    pub zoom: f32,
+    #[serde(default)]
+    pub angle: f32,
+}
+
+impl Location {
+    // read a location saved as json, alone or in a session
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let mut value: serde_json::Value =
+            serde_json::from_str(text).map_err(|error| error.to_string())?;
+        if let Some(location) = value.get_mut("location") {
+            value = location.take();
+        }
+        serde_json::from_value(value).map_err(|error| error.to_string())
+    }
+
+    // a short description of the view, like "zoom 10^-12 at -0.74364388703, 0.13182590420"
+    // the coordinates are rounded to a few digits more than the zoom needs to tell the views apart
+    pub fn summary(&self) -> String {
+        let magnitude = -self.zoom.log10().round() as i32;
+        let digits = (magnitude + 3).clamp(3, 15) as usize;
+        format!(
+            "zoom 10^{} at {:.*}, {:.*}",
+            -magnitude,
+            digits,
+            self.x.to_double(),
+            digits,
+            self.y.to_double()
+        )
+    }
+}
+
+// write the arbitrary precision numbers as strings, so no digit is lost
+pub mod real {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::game::big_real::{BigReal, Real};
+
+    pub fn serialize<S: Serializer>(value: &Real, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_text())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Real, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Real::from_text(&value).ok_or_else(|| D::Error::custom(format!("invalid number: {}", value)))
+    }
+}
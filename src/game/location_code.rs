@@ -0,0 +1,320 @@
+use crate::game::big_real::{BigReal, Real};
+use crate::game::location::Location;
+
+// a short code of a view on the fractal, to share it as a text or in the url of the browser
+// the bytes are a version, then the center, the zoom, the angle, the palette scale and the coloring,
+// written in url safe base64 without padding
+// each coordinate is its sign, its exponent, the number of digits of its mantissa without
+// the trailing zeros, and the digits packed by two in a byte, so no digit is lost,
+// its value is the digits read as an integer times 10 to the exponent
+// the numbers are little endian
+
+// the version of the code, changed when the bytes change
+// the codes of the version 1 have an exponent of one byte and at most 40 digits,
+// the ones of num_bigfloat, they are still read
+const VERSION: u8 = 2;
+
+// the most digits of a coordinate, far more than the ones of the precision of the backends
+const DIGITS: usize = u8::MAX as usize;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+// what is shared of a view
+#[derive(Debug, Clone, PartialEq)]
+pub struct View {
+    pub location: Location,
+    pub color_palette_scale: f32,
+    // the way the pixels are colored, a Coloring
+    pub coloring: u32,
+}
+
+pub fn encode(view: &View) -> String {
+    let mut bytes = vec![VERSION];
+    write_coordinate(&mut bytes, &view.location.x);
+    write_coordinate(&mut bytes, &view.location.y);
+    bytes.extend(view.location.zoom.to_le_bytes());
+    bytes.extend(view.location.angle.to_le_bytes());
+    bytes.extend(view.color_palette_scale.to_le_bytes());
+    bytes.push(view.coloring as u8);
+    base64_encode(&bytes)
+}
+
+// the spaces around the code and a leading # are ignored, so a hash of url can be decoded
+pub fn decode(code: &str) -> Result<View, String> {
+    let code = code.trim();
+    let bytes = base64_decode(code.strip_prefix('#').unwrap_or(code))?;
+    let mut reader = Reader { bytes: &bytes };
+    let version = reader.byte()?;
+    if version != 1 && version != VERSION {
+        return Err("unknown version of location code".to_string());
+    }
+    let x = reader.coordinate(version)?;
+    let y = reader.coordinate(version)?;
+    let zoom = reader.float()?;
+    let angle = reader.float()?;
+    let color_palette_scale = reader.float()?;
+    let coloring = reader.byte()? as u32;
+    if !(zoom > 0.0 && zoom.is_finite()) {
+        return Err(format!("invalid zoom: {}", zoom));
+    }
+    if !(angle.is_finite() && color_palette_scale > 0.0 && color_palette_scale.is_finite()) {
+        return Err("invalid angle or palette scale".to_string());
+    }
+    Ok(View {
+        location: Location { x, y, zoom, angle },
+        color_palette_scale,
+        coloring,
+    })
+}
+
+fn write_coordinate(bytes: &mut Vec<u8>, value: &Real) {
+    let (negative, mut digits, mut exponent) = decimal_parts(&value.to_text()).unwrap_or_default();
+    if digits.len() > DIGITS {
+        exponent += (digits.len() - DIGITS) as i32;
+        digits.truncate(DIGITS);
+    }
+    // a number too small for the exponent is far below the precision of the coordinates
+    if exponent < i16::MIN as i32 {
+        digits.clear();
+    }
+    if digits.is_empty() {
+        exponent = 0;
+    }
+    bytes.push(if negative { -1i8 as u8 } else { 1 });
+    bytes.extend((exponent.min(i16::MAX as i32) as i16).to_le_bytes());
+    bytes.push(digits.len() as u8);
+    bytes.extend(
+        digits
+            .chunks(2)
+            .map(|pair| pair[0] << 4 | pair.get(1).unwrap_or(&0)),
+    );
+}
+
+// the sign, the digits and the exponent of a decimal text like -1.25e-3,
+// without the zeros around the digits, so the value is the digits read as an integer
+// times 10 to the exponent
+fn decimal_parts(text: &str) -> Option<(bool, Vec<u8>, i32)> {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(text) => (true, text),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (mantissa, exponent) = match text.find(['e', 'E']) {
+        Some(index) => (&text[..index], text[index + 1..].parse::<i32>().ok()?),
+        None => (text, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let mut digits = Vec::with_capacity(integer.len() + fraction.len());
+    for character in integer.chars().chain(fraction.chars()) {
+        digits.push(character.to_digit(10)? as u8);
+    }
+    let mut exponent = exponent.checked_sub(fraction.len() as i32)?;
+    while digits.last() == Some(&0) {
+        digits.pop();
+        exponent = exponent.checked_add(1)?;
+    }
+    let leading_zeros = digits.iter().take_while(|digit| **digit == 0).count();
+    digits.drain(..leading_zeros);
+    Some((negative, digits, exponent))
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() < count {
+            return Err("the location code is too short".to_string());
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn float(&mut self) -> Result<f32, String> {
+        let bytes = self.take(4)?;
+        Ok(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    // a coordinate of the given version of the code
+    fn coordinate(&mut self, version: u8) -> Result<Real, String> {
+        let sign = self.byte()? as i8;
+        let exponent = match version {
+            1 => self.byte()? as i8 as i16,
+            _ => {
+                let bytes = self.take(2)?;
+                i16::from_le_bytes([bytes[0], bytes[1]])
+            }
+        };
+        let count = self.byte()? as usize;
+        if version == 1 && count > 40 {
+            return Err("invalid coordinate in the location code".to_string());
+        }
+        let mut digits = String::with_capacity(count + 1);
+        for pair in self.take(count.div_ceil(2))? {
+            for digit in [pair >> 4, pair & 0x0f] {
+                if digit > 9 {
+                    return Err("invalid coordinate in the location code".to_string());
+                }
+                digits.push((b'0' + digit) as char);
+            }
+        }
+        digits.truncate(count);
+        if digits.is_empty() {
+            digits.push('0');
+        }
+        let sign = if sign == -1 { "-" } else { "" };
+        Real::from_text(&format!("{}{}e{}", sign, digits, exponent))
+            .ok_or("invalid coordinate in the location code".to_string())
+    }
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut code = String::with_capacity((bytes.len() * 4).div_ceil(3));
+    for chunk in bytes.chunks(3) {
+        let value = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+        // a chunk of n bytes gives n + 1 characters
+        for index in 0..=chunk.len() {
+            code.push(ALPHABET[(value >> (18 - index * 6)) as usize & 0x3f] as char);
+        }
+    }
+    code
+}
+
+fn base64_decode(code: &str) -> Result<Vec<u8>, String> {
+    let values = code
+        .bytes()
+        .map(|character| {
+            ALPHABET
+                .iter()
+                .position(|letter| *letter == character)
+                .map(|value| value as u32)
+                .ok_or(format!(
+                    "invalid character in the location code: {}",
+                    character as char
+                ))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut bytes = Vec::with_capacity(values.len() * 3 / 4);
+    for chunk in values.chunks(4) {
+        if chunk.len() == 1 {
+            return Err("the location code is truncated".to_string());
+        }
+        let value = chunk.iter().enumerate().fold(0, |value, (index, sextet)| {
+            value | sextet << (18 - index * 6)
+        });
+        bytes.extend(&value.to_be_bytes()[1..chunk.len()]);
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(x: &str, y: &str) -> View {
+        View {
+            location: Location {
+                x: Real::from_text(x).unwrap(),
+                y: Real::from_text(y).unwrap(),
+                zoom: 1.5e-30,
+                angle: 0.25,
+            },
+            color_palette_scale: 200.0,
+            coloring: 2,
+        }
+    }
+
+    // a code with the given coordinates, each a sign, the bytes of an exponent,
+    // then the count of digits and the digits
+    fn code(version: u8, coordinates: &[(u8, &[u8], &[u8])]) -> String {
+        let mut bytes = vec![version];
+        for (sign, exponent, digits) in coordinates {
+            bytes.push(*sign);
+            bytes.extend(*exponent);
+            bytes.extend(*digits);
+        }
+        bytes.extend(1f32.to_le_bytes());
+        bytes.extend(0f32.to_le_bytes());
+        bytes.extend(100f32.to_le_bytes());
+        bytes.push(0);
+        base64_encode(&bytes)
+    }
+
+    #[test]
+    fn a_deep_view_is_decoded_back() {
+        for (x, y) in [
+            (
+                "-1.749922480927599928271333687542289453031",
+                "-0.0000000000009595021983143275699489757072",
+            ),
+            ("0.25", "-1.5e-20"),
+            ("0", "0"),
+            ("-2", "1"),
+        ] {
+            let view = view(x, y);
+            let code = encode(&view);
+            assert_eq!(decode(&code), Ok(view.clone()), "{} {}", x, y);
+            assert_eq!(decode(&format!(" #{}\n", code)), Ok(view));
+        }
+    }
+
+    #[test]
+    fn the_trailing_zeros_are_dropped_and_the_digits_packed() {
+        let mut bytes = vec![];
+        write_coordinate(&mut bytes, &Real::from_text("-12.3400").unwrap());
+        // -1234 times 10^-2, 4 digits packed in 2 bytes
+        assert_eq!(bytes, [0xff, 0xfe, 0xff, 4, 0x12, 0x34]);
+        bytes.clear();
+        // an odd count of digits ends with a half byte
+        write_coordinate(&mut bytes, &Real::from_text("1200").unwrap());
+        assert_eq!(bytes, [1, 2, 0, 2, 0x12]);
+        bytes.clear();
+        write_coordinate(&mut bytes, &Real::from_text("0.0").unwrap());
+        assert_eq!(bytes, [1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn the_codes_of_the_first_version_are_decoded() {
+        // -0.75 and 0.125, with an exponent of one byte
+        let code = code(
+            1,
+            &[(0xff, &[0xfe], &[2, 0x75]), (1, &[0xfd], &[3, 0x12, 0x50])],
+        );
+        let location = decode(&code).unwrap().location;
+        assert_eq!(location.x, Real::from_text("-0.75").unwrap());
+        assert_eq!(location.y, Real::from_text("0.125").unwrap());
+    }
+
+    #[test]
+    fn invalid_codes_are_rejected() {
+        let valid = encode(&view("-0.75", "0.1"));
+        // truncated, in the middle of the bytes or of a character
+        assert!(decode(&valid[..valid.len() - 4]).is_err());
+        assert!(decode(&valid[..valid.len() / 2]).is_err());
+        assert!(decode(&valid[..5]).is_err());
+        assert!(decode("").is_err());
+        // a character out of the alphabet
+        assert!(decode(&valid.replacen('A', "+", 1)).is_err());
+        assert!(decode(&format!("{}=", valid)).is_err());
+        // a nibble above 9 in the digits, or in the half byte after an odd count
+        let zero: (u8, &[u8], &[u8]) = (1, &[0, 0], &[0]);
+        assert!(decode(&code(2, &[(1, &[0, 0], &[2, 0x12]), zero])).is_ok());
+        assert!(decode(&code(2, &[(1, &[0, 0], &[2, 0x1a]), zero])).is_err());
+        assert!(decode(&code(2, &[(1, &[0, 0], &[1, 0x1f]), zero])).is_err());
+        // more digits than the bytes of the code
+        assert!(decode(&code(2, &[(1, &[0, 0], &[9, 0x12]), zero])).is_err());
+        // more digits than the first version had
+        let mut long = vec![41];
+        long.extend([0x11; 21]);
+        assert!(decode(&code(1, &[(1, &[0], &long), (1, &[0], &[0])])).is_err());
+        // an unknown version
+        assert!(decode(&code(3, &[zero, zero])).is_err());
+    }
+}
@@ -1,179 +1,397 @@
-use std::borrow::{Borrow, BorrowMut};
-use std::cell::RefCell;
-use std::ops::{Deref, Div};
-use std::rc::Rc;
+use std::ops::Div;
+use std::path::Path;
 
 use bytemuck::{Pod, Zeroable};
-use wgpu::{BufferBindingType, BufferUsages, ShaderStages};
+use wgpu::ShaderStages;
 use winit::dpi::PhysicalSize;
 use winit::event::{
-    ElementState, Event, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent,
+    ElementState, Event, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta,
+    VirtualKeyCode, WindowEvent,
 };
 
 use to_buffer_representation_derive::ToBufferRepresentation;
 
-use crate::game::engine::Engine;
+use crate::game::camera_animator::CameraAnimator;
+use crate::game::config::{ComparisonConfig, Easing, NavigationConfig, ReferenceConfig};
+use crate::game::cpu_renderer;
+use crate::game::engine::typed_buffer::{StorageBuffer, UniformBuffer};
+use crate::game::big_real::{BigReal, Real};
+use crate::game::blue_noise::{blue_noise, BLUE_NOISE_SIDE};
+use crate::game::engine::julia_preview::JuliaPreview;
+use crate::game::engine::overlay::{ORANGE, WHITE};
+use crate::game::engine::pixel_texture::PixelAccess;
+use crate::game::engine::readback::Readback;
+use crate::game::engine::{scaled_size, BufferHandle, Engine, PixelTextureHandle, TextureHandle};
+use crate::game::exr::write_exr;
 use crate::game::game_state::GameState;
-use crate::game::mandelbrot::MandelbrotData;
+use crate::game::location::Location;
+use crate::game::location_code::View;
+use crate::game::mandelbrot::{Coloring, DebugView, MandelbrotData};
+use crate::game::newton::NewtonPolynomial;
+use crate::game::palette::Palette;
+use crate::game::period_worker::{Minibrot, PeriodWorker};
+use crate::game::session::Session;
+use crate::game::sonification::Sonification;
 use crate::game::to_buffer_representation::ToBufferRepresentation;
 use crate::game::Game;
-use crate::game::{GameBuffer, MandelbrotEngine};
+use crate::game::MandelbrotEngine;
 
 // We need this for Rust to store our data correctly for the shaders
 #[repr(C)]
 // This is so we can store this in a buffer
 #[derive(Copy, Clone, Pod, Zeroable, ToBufferRepresentation)]
+// where the pixels of the frame are computed, the center is a whole number of pixels away
+// from the one of the last frame while panning, so its pixels are moved without being resampled
+// and the slow movements do not get lost in the rounding of the pixels
 pub struct LastRenderedMandelbrot {
     pub center_delta: [f32; 2],
+    // the number of pixels the last frame is moved by
+    pub shift: [i32; 2],
     pub zoom: f32,
-    _padding: u32,
+    // 1 when the last frame is only moved
+    pub reproject: u32,
+    // the most iterations a pixel is advanced by in this frame
+    pub iteration_slice: u32,
+    // the number of the reference orbit, the pixels not finished with another one are started again
+    pub orbit: u32,
+    // the cycle the orbit falls into, the points after its first period are read from it,
+    // no cycle when the period is 0
+    pub cycle_start: u32,
+    pub cycle_period: u32,
+}
+
+// the result of the last computation of a pixel, beside its iteration count
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable, ToBufferRepresentation)]
+pub struct MandelbrotDot {
+    // the derivative, to shade the colors
+    pub derivative: [f32; 2],
+    // the iteration at which |z| was the smallest, the period of the atom domain of the pixel
+    pub atom_period: f32,
+    // the smallest |z|
+    pub atom_distance: f32,
+    // the z before the last one, for the formulas that depend on it like the phoenix one
+    pub previous_z: [f32; 2],
+    // the state of the iterations of a pixel that are not all computed yet, to continue them
+    pub dz: [f32; 2],
+    pub der: [f32; 2],
+    // the number of iterations computed so far
+    pub iteration: f32,
+    // the point of the orbit the delta is relative to
+    pub reference_iteration: i32,
+    // the exponent of the power of two dc and the delta are divided by
+    pub scale: i32,
+    // the orbit the delta is relative to
+    pub orbit: u32,
+}
+
+// the time step of the camera physics, the speeds are damped at this rate whatever the frame rate
+const PHYSICS_STEP: f32 = 1.0 / 120.0;
+// the longest time simulated in one frame, so the camera does not jump after a freeze
+const MAXIMUM_PHYSICS_TIME: f32 = 0.25;
+
+// the change of the speed of the color cycling by each key press, in iterations per second
+const COLOR_CYCLING_STEP: f32 = 2.5;
+// the share of the distance to the fitted palette scale the scale moves by each second
+const AUTO_PALETTE_SCALE_RATE: f32 = 3.0;
+// the smallest palette scale fitted to the iterations, so a flat view does not stretch it to nothing
+const MINIMUM_AUTO_PALETTE_SCALE: f32 = 1.0;
+// the estimated maximum iterations are this many times the largest iterations outside the set
+const AUTO_ITERATION_MARGIN: f32 = 2.0;
+// the share of the pixels that must reach the maximum iterations for it to be raised,
+// below it they are taken as the inside of the set
+const UNESCAPED_SHARE: f32 = 0.001;
+
+// the smallest width and height in pixels of a rectangle selected to zoom on,
+// a smaller one is taken for a click
+const MINIMUM_SELECTION: f32 = 4.0;
+// the seconds of the flight to the rectangle selected
+const SELECTION_FLIGHT_DURATION: f32 = 1.0;
+
+// the number of floats of a dot
+const DOT_FLOATS: usize = std::mem::size_of::<MandelbrotDot>() / 4;
+
+// the frame rate under which the resolution is lowered while the camera moves
+const MINIMUM_FPS: f32 = 30.0;
+// the frame rate over which the resolution is raised back while the camera moves
+const COMFORTABLE_FPS: f32 = 50.0;
+// the change of the share of the resolution the fractal is drawn at, in one frame
+const RENDER_SCALE_STEP: f32 = 0.125;
+// the smallest share of the iterations given by the zoom computed while the camera moves
+const MINIMUM_ITERATION_BUDGET: f32 = 0.1;
+// the largest change of the share of the iterations, in one frame measured,
+// so a single slow frame does not remove most of the details
+const ITERATION_BUDGET_STEP: f32 = 1.25;
+
+// the most points of the orbit drawn by the orbit path, the next ones are hidden
+const MAXIMUM_ORBIT_PATH_POINTS: usize = 10000;
+// the size in pixels of the markers drawn on the points of the fractal, from their center
+const MARKER_RADIUS: f32 = 8.0;
+// the axes are dimmed so they do not hide the details they cross
+const AXIS_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.5];
+// the distance in pixels of the scale bar from the bottom left corner of the window
+const SCALE_BAR_MARGIN: f32 = 24.0;
+const SCALE_BAR_LABEL_HEIGHT: f32 = 12.0;
+
+// the escapes of the pixels of a recent frame, measured by the shader
+struct IterationStatistics {
+    // the smallest and the largest iterations outside the set, none when no pixel escaped
+    range: Option<(f32, f32)>,
+    // the pixels that reached the maximum iterations, the ones that escaped
+    // and the ones known to be inside the set, counted on a sparse grid by the shader
+    unescaped: u32,
+    escaped: u32,
+    interior: u32,
+    // the sum of the whole iterations of the pixels of the grid that escaped
+    iteration_sum: u64,
+}
+
+// how much of the view is inside the set, measured on the pixels of a recent frame
+#[derive(Debug, Clone, Copy)]
+pub struct AreaStatistics {
+    // the share of the pixels inside the set, with the ones that reached the maximum iterations
+    pub inside_share: f32,
+    // the mean iterations of the pixels outside the set, none when no pixel escaped
+    pub mean_iterations: Option<f32>,
+    // the area of the view in the complex plane
+    pub view_area: f64,
+}
+
+impl AreaStatistics {
+    // the estimated area of the part of the set in the view, in the complex plane
+    pub fn inside_area(&self) -> f64 {
+        self.view_area * self.inside_share as f64
+    }
+
+    pub fn summary(&self) -> String {
+        let summary = format!(
+            "{:.1}% inside, area {:.2e}",
+            self.inside_share * 100.0,
+            self.inside_area()
+        );
+        match self.mean_iterations {
+            Some(mean_iterations) => format!("{}, mean {:.0} iterations", summary, mean_iterations),
+            None => summary,
+        }
+    }
+}
+
+// the point the camera rotates around, with the keys or the right mouse button
+#[derive(Debug, Clone)]
+enum RotationPivot {
+    Center,
+    // the point under the cursor, or where the right button has been pressed while dragging
+    Cursor,
+    // a point of the set, the camera orbits around it while zooming
+    Locked(Real, Real),
+}
+
+// the maximum iterations given by the zoom, a number of iterations each time it is divided by 2.1
+pub fn zoom_iterations(zoom: f64, iteration_speed: u32) -> f32 {
+    (1.0 + (1.0 / zoom).log(2.1).max(0.0)) as f32 * iteration_speed as f32
+}
+
+// the keys that move the camera
+pub fn is_navigation_key(keycode: VirtualKeyCode) -> bool {
+    matches!(
+        keycode,
+        VirtualKeyCode::Space
+            | VirtualKeyCode::Return
+            | VirtualKeyCode::NumpadAdd
+            | VirtualKeyCode::NumpadSubtract
+            | VirtualKeyCode::Left
+            | VirtualKeyCode::Right
+            | VirtualKeyCode::Up
+            | VirtualKeyCode::Down
+            | VirtualKeyCode::Z
+            | VirtualKeyCode::Q
+            | VirtualKeyCode::S
+            | VirtualKeyCode::D
+            | VirtualKeyCode::A
+            | VirtualKeyCode::E
+    )
 }
 
 pub struct MandelbrotState {
     mandelbrot: MandelbrotEngine,
     previous_mandelbrot: MandelbrotEngine,
-    mandelbrot_iteration_texture: Rc<RefCell<Vec<f32>>>,
-    previous_mandelbrot_iteration_texture: Rc<RefCell<Vec<f32>>>,
-    mandelbrot_data: Rc<RefCell<Vec<[f32; 2]>>>,
-    previous_mandelbrot_data: Rc<RefCell<Vec<[f32; 2]>>>,
+    // the iterations of the pixels computed by the cpu renderer, written to the texture
+    cpu_iterations: Vec<f32>,
+    mandelbrot_data: StorageBuffer<MandelbrotDot>,
+    previous_mandelbrot_data: StorageBuffer<MandelbrotDot>,
     zoom_speed: f32,
     zoom_acceleration: f32,
     rotate_speed: f32,
     move_speed: (f32, f32),
+    // how the camera slows down, given by the config at startup and changed with G
+    easing: Easing,
+    // the time since the last step of the camera physics, less than a step
+    physics_time: f32,
     iteration_speed: u32,
     size: PhysicalSize<u32>,
     mouse_position: (isize, isize),
     mouse_left_button_pressed: bool,
     mouse_right_button_pressed: bool,
+    // the keys held with the mouse, shift to select a rectangle or to zoom faster with the wheel,
+    // control to zoom slower
+    modifiers: ModifiersState,
+    rotation_pivot: RotationPivot,
+    // where the right button has been pressed, in pixels of the window
+    right_drag_start: [f32; 2],
+    // the corners of the rectangle being selected, in pixels of the window,
+    // the first one where the drag has started
+    selection: Option<[[f32; 2]; 2]>,
+    animator: Option<CameraAnimator>,
+    // searches the minibrot at the center of the view once the camera stops
+    period_worker: PeriodWorker,
+    // the point of the set whose julia set is previewed, the last one under the cursor
+    // on the left half of the screen
+    julia_parameter: [f32; 2],
+    // the real and imaginary axes and the scale bar drawn on top of the fractal
+    shows_axes: bool,
+    // a crosshair drawn on the reference point of the perturbation, where the pixels are the most precise
+    shows_reference: bool,
+    // the orbit of the point under the cursor played as a sound, toggled with F7
+    sonification: Option<Sonification>,
+    navigation: NavigationConfig,
+    // the iterations of the pixels, to read them back
+    iteration_texture: PixelTextureHandle,
+    // the handle of the derivatives of the pixels
+    data_buffer: BufferHandle,
+    // the handle of the orbit of the reference point, replaced by each new orbit
+    orbit_buffer: BufferHandle,
+    // the noise added to the colors
+    blue_noise_texture: TextureHandle,
+    // the polynomial of the newton fractal
+    newton_polynomial: UniformBuffer<NewtonPolynomial>,
+    // the gradient generated from the seed, the palette of the shader when there is no seed
+    palette: UniformBuffer<Palette>,
+    palette_seed: Option<u32>,
+    // the colors of the last frame computed by the cpu renderer
+    cpu_pixels: Vec<u8>,
+    // where the pixels of the frame are computed
+    last_rendered: StorageBuffer<LastRenderedMandelbrot>,
+    // the palette scale follows the iterations of the last frames, toggled with H
+    auto_palette_scale: bool,
+    // the bits of the smallest and the largest iterations outside the set, then the number of pixels
+    // that reached the maximum iterations and the number that escaped, measured by the shader
+    iteration_range: StorageBuffer<u32>,
+    iteration_range_buffer: BufferHandle,
+    // the copy of the range of a frame, read without waiting while the next frames are drawn
+    iteration_range_readback: Option<Readback>,
+    // the maximum iterations estimated from the escapes of the last frames, when the navigation
+    // config asks for it, none until a frame has been measured
+    estimated_iterations: Option<f32>,
+    // the share of the view inside the set is measured, to write it in the title
+    measures_area: bool,
+    area_statistics: Option<AreaStatistics>,
+    // the share of the width and the height of the window the fractal is drawn at
+    render_scale: f32,
+    // the smallest one, while the camera moves and the frames are too slow
+    minimum_render_scale: f32,
+    // the share of the iterations given by the zoom that are computed
+    iteration_budget: f32,
+    // the time in seconds the gpu should take to draw the fractal, none to compute every iteration
+    target_frame_time: Option<f32>,
+    // the most iterations a pixel is advanced by in a frame, 0 to compute them all at once
+    iteration_slice: u32,
+    // the iterations the palette moves by each second, negative to move it the other way
+    color_cycling_speed: f32,
+    // the number of the reference orbit, changed with the reference point or the precision
+    orbit: u32,
+    // the number of frames until every pixel started is finished
+    refining_frames: u32,
+    // the maximum number of iterations used instead of the one given by the zoom
+    fixed_iterations: Option<u32>,
+    // the settings of the right part of the screen when it is split
+    comparison: UniformBuffer<MandelbrotData>,
+    comparison_config: ComparisonConfig,
+    comparing: bool,
 }
 
 impl GameState for MandelbrotState {
     fn update(&mut self, engine: &mut Engine, delta_time: f32) {
-        let epsilon = 0.001;
-        // zoom
-        self.zoom_acceleration *= 0.05_f32.powf(delta_time);
-        if self.zoom_acceleration.abs() < epsilon * 100.0 {
-            self.zoom_acceleration = 0.0;
+        // animation
+        if let Some(animator) = self.animator.as_mut() {
+            let location = animator.advance(delta_time);
+            if animator.is_finished() {
+                // land exactly on the target
+                self.mandelbrot.set_center(location.x, location.y);
+                self.animator = None;
+            } else {
+                self.mandelbrot.move_center_to(&location.x, &location.y);
+            }
+            self.mandelbrot.set_zoom(location.zoom as f64);
+            self.mandelbrot.data.get_mut().angle = location.angle;
         }
-        if self.zoom_speed != 0.0 || self.zoom_acceleration != 0.0 {
-            self.mandelbrot.set_zoom(
-                self.mandelbrot.zoom()
-                    * (1.0 - ((self.zoom_speed + self.zoom_acceleration) * delta_time)),
+        self.simulate(delta_time);
+        // maximum iteration
+        self.govern_iterations(engine);
+        let statistics = self.iteration_statistics(engine);
+        let maximum_iterations = match self.fixed_iterations {
+            Some(iterations) => iterations,
+            None if self.navigation.auto_iterations => {
+                (self.estimate_iterations(statistics.as_ref()) * self.iteration_budget) as u32
+            }
+            None => (self.zoom_iterations() * self.iteration_budget) as u32,
+        };
+        self.mandelbrot.set_maximum_iterations(maximum_iterations);
+        self.fit_palette_scale(statistics.as_ref(), delta_time);
+        if let Some(statistics) = &statistics {
+            self.measure_area(statistics);
+        }
+        self.cycle_colors(delta_time);
+        self.mandelbrot.update(delta_time);
+        if self.mandelbrot.take_orbit_replacement() {
+            engine.replace_buffer(
+                self.orbit_buffer,
+                self.mandelbrot.orbit_point_suite.buffer(),
             );
         }
-        // rotation
-        self.rotate_speed *= 0.05_f32.powf(delta_time);
-        if self.rotate_speed.abs() < epsilon {
-            self.rotate_speed = 0.0;
+        let orbit = self.orbit;
+        if self.mandelbrot.near_orbit_coordinate != self.previous_mandelbrot.near_orbit_coordinate {
+            // the last frame stays where it was, relatively to the new reference point
+            self.previous_mandelbrot
+                .set_reference(&self.mandelbrot.near_orbit_coordinate);
+            self.orbit = self.orbit.wrapping_add(1);
+        } else if self.mandelbrot.data.get().precision_mode
+            != self.previous_mandelbrot.data.get().precision_mode
+        {
+            // the deltas are not computed the same way
+            self.orbit = self.orbit.wrapping_add(1);
         }
-        if self.rotate_speed != 0.0 {
-            self.mandelbrot.data.deref().borrow_mut().angle += self.rotate_speed * delta_time;
+        self.govern_resolution(engine, delta_time);
+        self.reproject();
+        self.count_refining_frames(self.orbit != orbit);
+        self.update_comparison();
+        engine.update_overview(&self.location(), &self.mandelbrot.data.get());
+        self.update_julia_preview(engine);
+        self.update_sonification();
+        if let Some(corners) = self.selection {
+            engine.overlay().rectangle(corners, WHITE);
         }
-        // movement
-        self.move_speed.0 *= 0.05_f32.powf(delta_time);
-        self.move_speed.1 *= 0.05_f32.powf(delta_time);
-        if self.move_speed.0.abs() < epsilon {
-            // define a variable that contain value that is randomly 0.01 or -0.01
-            self.move_speed.0 = 0.0;
+        if engine.shows_orbit_path() {
+            let points = self.mandelbrot.orbit_path(MAXIMUM_ORBIT_PATH_POINTS);
+            engine.update_orbit_path(&points, &self.mandelbrot.data.get());
         }
-        if self.move_speed.1.abs() < epsilon {
-            self.move_speed.1 = 0.0;
+        self.draw_reference(engine);
+        if self.shows_axes {
+            self.draw_axes(engine);
         }
-        let move_speed = (
-            self.move_speed.0 * delta_time,
-            self.move_speed.1 * delta_time,
-        );
-        // if move speed > 0 then move by move speed
-        self.mandelbrot
-            .data
-            .deref()
-            .borrow_mut()
-            .move_by(move_speed);
-        // maximum iteration
-        self.mandelbrot.set_maximum_iterations(
-            ((1.0 + (1.0 / self.mandelbrot.zoom()).log(2.1).max(0.0)) * self.iteration_speed as f32)
-                as u32,
-        );
-        self.mandelbrot.update(delta_time);
-        if self.mandelbrot.near_orbit_coordinate != self.previous_mandelbrot.near_orbit_coordinate {
-            self.previous_mandelbrot.near_orbit_coordinate = self.mandelbrot.near_orbit_coordinate;
-            self.previous_mandelbrot
-                .data
-                .deref()
-                .borrow_mut()
-                .center_delta = self.mandelbrot.data.deref().borrow().center_delta;
-        }
-        engine.update_buffer(GameBuffer::Mandelbrot as usize);
-        engine.update_buffer(GameBuffer::PreviousMandelbrot as usize);
-        engine.update_buffer(GameBuffer::MandelbrotOrbitPointSuite as usize);
-        self.previous_mandelbrot
-            .data
-            .deref()
-            .borrow_mut()
-            .from(&self.mandelbrot.data.deref().borrow());
     }
 
     fn input(&mut self, event: &Event<()>, engine: &mut Engine) {
         if let Event::WindowEvent { ref event, .. } = event {
             match event {
                 WindowEvent::Resized(physical_size) => {
-                    self.mandelbrot
-                        .resize(physical_size.width, physical_size.height);
-                    self.mandelbrot_iteration_texture
-                        .deref()
-                        .borrow_mut()
-                        .resize((physical_size.width * physical_size.height) as usize, -2.0);
-                    self.previous_mandelbrot_iteration_texture
-                        .deref()
-                        .borrow_mut()
-                        .resize((physical_size.width * physical_size.height) as usize, -2.0);
-                    self.mandelbrot_data.deref().borrow_mut().resize(
-                        (physical_size.width * physical_size.height) as usize,
-                        [0.0, 0.0],
-                    );
-                    self.previous_mandelbrot_data.deref().borrow_mut().resize(
-                        (physical_size.width * physical_size.height) as usize,
-                        [0.0, 0.0],
-                    );
-                    engine.update_buffer(GameBuffer::MandelbrotIterationTexture as usize);
-                    engine.update_buffer(GameBuffer::MandelbrotData as usize);
-                    engine.update_buffer(GameBuffer::PreviousMandelbrotData as usize);
-                    engine.update_buffer(GameBuffer::PreviousMandelbrotIterationTexture as usize);
-                    self.size = *physical_size;
+                    self.resize(*physical_size);
                 }
                 WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
                     // new_inner_size is &&mut so we have to dereference it twice
-                    let new_inner_size = **new_inner_size;
-                    self.mandelbrot
-                        .resize(new_inner_size.width, new_inner_size.height);
-                    self.mandelbrot_iteration_texture
-                        .deref()
-                        .borrow_mut()
-                        .resize(
-                            (new_inner_size.width * new_inner_size.height) as usize,
-                            -2.0,
-                        );
-                    self.previous_mandelbrot_iteration_texture
-                        .deref()
-                        .borrow_mut()
-                        .resize(
-                            (new_inner_size.width * new_inner_size.height) as usize,
-                            -2.0,
-                        );
-                    self.mandelbrot_data.deref().borrow_mut().resize(
-                        (new_inner_size.width * new_inner_size.height) as usize,
-                        [0.0, 0.0],
-                    );
-
-                    self.previous_mandelbrot_data.deref().borrow_mut().resize(
-                        (new_inner_size.width * new_inner_size.height) as usize,
-                        [0.0, 0.0],
-                    );
-                    engine.update_buffer(GameBuffer::MandelbrotIterationTexture as usize);
-                    engine.update_buffer(GameBuffer::MandelbrotData as usize);
-                    engine.update_buffer(GameBuffer::PreviousMandelbrotData as usize);
-                    engine.update_buffer(GameBuffer::PreviousMandelbrotIterationTexture as usize);
-                    self.size = new_inner_size;
+                    self.resize(**new_inner_size);
+                }
+                WindowEvent::ModifiersChanged(modifiers) => {
+                    self.modifiers = *modifiers;
                 }
                 // when the mouse scrolls,
                 // update the mandelbrot shader zoom
@@ -181,10 +399,12 @@ impl GameState for MandelbrotState {
                 // depending on the direction of the scroll wheel.
                 WindowEvent::MouseWheel { delta, .. } => match delta {
                     MouseScrollDelta::LineDelta(_, y) => {
+                        self.animator = None;
+                        let acceleration = self.wheel_acceleration();
                         if *y > 0.0 {
-                            self.zoom_acceleration += 2.0;
+                            self.zoom_acceleration += acceleration;
                         } else {
-                            self.zoom_acceleration -= 2.0;
+                            self.zoom_acceleration -= acceleration;
                         }
                         // self.mandelbrot.zoom_in(zoom_factor);
                     }
@@ -196,6 +416,10 @@ impl GameState for MandelbrotState {
                     if input.state == ElementState::Pressed {
                         if let Some(keycode) = input.virtual_keycode {
                             let movement = 1.0;
+                            // the user takes back the control of the camera
+                            if is_navigation_key(keycode) {
+                                self.animator = None;
+                            }
                             match keycode {
                                 // space
                                 VirtualKeyCode::Space => {
@@ -204,32 +428,81 @@ impl GameState for MandelbrotState {
                                     self.rotate_speed = 0.0;
                                 }
                                 // return
-                                VirtualKeyCode::Return => {
-                                    self.mandelbrot.data.deref().borrow_mut().reset();
+                                VirtualKeyCode::Return => self.reset_view(),
+                                VirtualKeyCode::O => engine.toggle_overview(),
+                                VirtualKeyCode::J => engine.toggle_julia_preview(),
+                                VirtualKeyCode::U if self.modifiers.shift() => {
+                                    self.shows_reference = !self.shows_reference;
+                                }
+                                VirtualKeyCode::U => {
+                                    self.mandelbrot.reanchor();
+                                    eprintln!("Moving the reference point to the center");
+                                }
+                                VirtualKeyCode::P => engine.toggle_orbit_path(),
+                                VirtualKeyCode::F7 => self.toggle_sonification(),
+                                VirtualKeyCode::I => self.shows_axes = !self.shows_axes,
+                                VirtualKeyCode::B => self.toggle_comparison(),
+                                // the frame is copied by the game
+                                VirtualKeyCode::C
+                                    if self.modifiers.ctrl() && self.modifiers.shift() => {}
+                                VirtualKeyCode::C => {
+                                    let mut data = self.mandelbrot.data.get_mut();
+                                    data.coloring = Coloring::from(data.coloring).next() as u32;
+                                    eprintln!("Coloring: {:?}", Coloring::from(data.coloring));
+                                }
+                                VirtualKeyCode::W => self.next_rotation_pivot(),
+                                VirtualKeyCode::G => {
+                                    self.easing = self.easing.next();
+                                    eprintln!("Easing: {:?}", self.easing);
+                                }
+                                // n for a new random palette, shift and n for the one of the shader
+                                VirtualKeyCode::N => {
+                                    let seed = (!self.modifiers.shift()).then(rand::random);
+                                    self.set_palette_seed(seed);
+                                    match seed {
+                                        Some(seed) => eprintln!("Palette seed: {}", seed),
+                                        None => eprintln!("Palette of the shader"),
+                                    }
+                                }
+                                VirtualKeyCode::F3 => {
+                                    let mut data = self.mandelbrot.data.get_mut();
+                                    data.debug_view =
+                                        DebugView::from(data.debug_view).next() as u32;
+                                    eprintln!("Debug view: {:?}", DebugView::from(data.debug_view));
+                                }
+                                // home and end to slow down and speed up the color cycling,
+                                // it goes the other way below 0
+                                VirtualKeyCode::Home | VirtualKeyCode::End => {
+                                    self.color_cycling_speed += if keycode == VirtualKeyCode::End {
+                                        COLOR_CYCLING_STEP
+                                    } else {
+                                        -COLOR_CYCLING_STEP
+                                    };
+                                    eprintln!("Color cycling speed: {}", self.color_cycling_speed);
+                                }
+                                VirtualKeyCode::H => {
+                                    self.set_auto_palette_scale(!self.auto_palette_scale);
+                                    eprintln!(
+                                        "Automatic palette scale: {}",
+                                        if self.auto_palette_scale { "on" } else { "off" }
+                                    );
                                 }
                                 // page up
                                 VirtualKeyCode::PageUp => {
-                                    self.mandelbrot
-                                        .data
-                                        .deref()
-                                        .borrow_mut()
-                                        .color_palette_scale *= 1.1;
+                                    self.stop_auto_palette_scale();
+                                    self.mandelbrot.data.get_mut().color_palette_scale *= 1.1;
                                 }
                                 // page down
                                 VirtualKeyCode::PageDown => {
+                                    self.stop_auto_palette_scale();
                                     let value = self
                                         .mandelbrot
                                         .data
-                                        .deref()
-                                        .borrow()
+                                        .get()
                                         .color_palette_scale
                                         .div(1.1)
                                         .max(0.1);
-                                    self.mandelbrot
-                                        .data
-                                        .deref()
-                                        .borrow_mut()
-                                        .color_palette_scale = value;
+                                    self.mandelbrot.data.get_mut().color_palette_scale = value;
                                 }
                                 // add
                                 VirtualKeyCode::NumpadAdd => {
@@ -298,7 +571,19 @@ impl GameState for MandelbrotState {
                 // factorize the mouse MouseInput event
                 WindowEvent::MouseInput { state, button, .. } => {
                     if *state == ElementState::Pressed {
+                        self.animator = None;
                         match button {
+                            // shift and drag to select a rectangle to zoom on, instead of moving
+                            MouseButton::Left if self.modifiers.shift() => {
+                                let position = self.cursor_position();
+                                self.selection = Some([position, position]);
+                            }
+                            // control and click to move the reference point under the cursor
+                            MouseButton::Left if self.modifiers.ctrl() => {
+                                let offset = self.offset_of_pixel(self.cursor_position());
+                                self.mandelbrot.center_orbit_at(offset);
+                                eprintln!("Moving the reference point to the cursor");
+                            }
                             MouseButton::Left => {
                                 self.mouse_position.0 = 0;
                                 self.mouse_position.1 = 0;
@@ -306,6 +591,7 @@ impl GameState for MandelbrotState {
                             }
                             MouseButton::Right => {
                                 self.mouse_right_button_pressed = true;
+                                self.right_drag_start = self.cursor_position();
                             }
                             _ => {}
                         }
@@ -313,6 +599,9 @@ impl GameState for MandelbrotState {
                         match button {
                             MouseButton::Left => {
                                 self.mouse_left_button_pressed = false;
+                                if let Some(selection) = self.selection.take() {
+                                    self.zoom_to_selection(selection);
+                                }
                             }
                             MouseButton::Right => {
                                 self.mouse_right_button_pressed = false;
@@ -323,11 +612,14 @@ impl GameState for MandelbrotState {
                 }
                 // update the mandelbrot shader coordinates when the mouse is moved.
                 WindowEvent::CursorMoved { position, .. } => {
+                    if let Some(selection) = self.selection.as_mut() {
+                        selection[1] = [position.x as f32, position.y as f32];
+                    }
                     if self.mouse_left_button_pressed {
                         if self.mouse_position.0 == 0 && self.mouse_position.1 == 0 {
                             self.mouse_position = (position.x as isize, position.y as isize);
                         }
-                        self.mandelbrot.data.deref().borrow_mut().move_by_pixel(
+                        self.mandelbrot.data.get_mut().move_by_pixel(
                             position.x as isize - self.mouse_position.0,
                             position.y as isize - self.mouse_position.1,
                             self.size.width,
@@ -339,10 +631,12 @@ impl GameState for MandelbrotState {
                     // if the left mouse button is pressed
                     if self.mouse_right_button_pressed {
                         // update the mandelbrot shader coordinates
-                        // set the mandebrot angle to the angle form the center of the window to the mouse position
-                        self.mandelbrot.data.deref().borrow_mut().angle = -(position.x as f32
-                            - self.size.width as f32 / 2.0)
-                            .atan2(position.y as f32 - self.size.height as f32 / 2.0);
+                        // set the mandebrot angle to the angle form the pivot to the mouse position
+                        let pivot = self.pivot_position();
+                        let angle =
+                            -(position.x as f32 - pivot[0]).atan2(position.y as f32 - pivot[1]);
+                        let angle = angle - self.mandelbrot.data.get().angle;
+                        self.rotate(angle);
                     }
                 }
                 _ => {}
@@ -352,99 +646,1192 @@ impl GameState for MandelbrotState {
 }
 
 impl MandelbrotState {
+    // run the camera physics for the time of the frame
+    // the speeds only change at fixed time steps, and the camera moves with them in between,
+    // so a frame drawn between two steps is interpolated between them
+    // and the camera goes the same way at 30 or 144 frames per second
+    fn simulate(&mut self, delta_time: f32) {
+        let mut time = delta_time.clamp(0.0, MAXIMUM_PHYSICS_TIME);
+        while self.physics_time + time >= PHYSICS_STEP {
+            let span = PHYSICS_STEP - self.physics_time;
+            self.advance_camera(span);
+            self.step_physics();
+            time -= span;
+            self.physics_time = 0.0;
+        }
+        self.advance_camera(time);
+        self.physics_time += time;
+    }
+
+    // damp the speeds by one time step
+    fn step_physics(&mut self) {
+        let curve = self.navigation.easing_curve(self.easing);
+        let damping = curve.damping.powf(PHYSICS_STEP);
+        // zoom
+        self.zoom_acceleration *= damping;
+        if self.zoom_acceleration.abs() < curve.zoom_epsilon {
+            self.zoom_acceleration = 0.0;
+        }
+        // rotation
+        self.rotate_speed *= damping;
+        if self.rotate_speed.abs() < curve.rotate_epsilon {
+            self.rotate_speed = 0.0;
+        }
+        // movement
+        self.move_speed.0 *= damping;
+        self.move_speed.1 *= damping;
+        if self.move_speed.0.abs() < curve.move_epsilon {
+            self.move_speed.0 = 0.0;
+        }
+        if self.move_speed.1.abs() < curve.move_epsilon {
+            self.move_speed.1 = 0.0;
+        }
+    }
+
+    // move the camera with the current speeds for a part of a time step
+    fn advance_camera(&mut self, time: f32) {
+        if time <= 0.0 {
+            return;
+        }
+        // the zoom changes by the same factor at each step, so a part of a step is a power of it
+        if self.zoom_speed != 0.0 || self.zoom_acceleration != 0.0 {
+            let step_factor = 1.0 - (self.zoom_speed + self.zoom_acceleration) * PHYSICS_STEP;
+            let factor = step_factor.max(0.0).powf(time / PHYSICS_STEP) as f64;
+            self.mandelbrot.set_zoom(self.mandelbrot.zoom() * factor);
+        }
+        if self.rotate_speed != 0.0 {
+            self.rotate(self.rotate_speed * time);
+        }
+        let move_speed = (self.move_speed.0 * time, self.move_speed.1 * time);
+        self.mandelbrot.data.get_mut().move_by(move_speed);
+    }
+
+    // remember the rendered frame, so the next one can reuse its pixels
+    // the buffers are written when the frame is rendered, so this must be called after it
+    pub fn end_frame(&mut self) {
+        let mut previous = self.previous_mandelbrot.data.get_mut();
+        previous.from(&self.mandelbrot.data.get());
+        // the pixels are where they were computed, which is not exactly the center while panning
+        previous.center_delta = self.last_rendered.get()[0].center_delta;
+    }
+
+    // lower the resolution while the camera moves and the frames are too slow,
+    // and raise it back a step at each frame once they are fast enough or the camera is still
+    fn govern_resolution(&mut self, engine: &mut Engine, delta_time: f32) {
+        let render_scale = if self.is_still() || delta_time < 1.0 / COMFORTABLE_FPS {
+            self.render_scale + RENDER_SCALE_STEP
+        } else if delta_time > 1.0 / MINIMUM_FPS {
+            self.render_scale - RENDER_SCALE_STEP
+        } else {
+            self.render_scale
+        }
+        .clamp(self.minimum_render_scale, 1.0);
+        if render_scale != self.render_scale {
+            self.set_render_scale(engine, render_scale);
+        }
+    }
+
+    // lower the iterations while the camera moves and the gpu takes longer than the target
+    // to draw the fractal, and raise them back as it gets faster, up to the ones given by the zoom
+    // the pixels of the frames in movement are mostly computed again, so their time follows
+    // the iterations, while a still frame only reuses the last one and is not measured
+    // every iteration is computed again once the camera is still
+    fn govern_iterations(&mut self, engine: &mut Engine) {
+        let gpu_time = engine.take_gpu_time();
+        let target_frame_time = match self.target_frame_time {
+            Some(target_frame_time) if !self.mandelbrot.wait_for_orbits => target_frame_time,
+            _ => return,
+        };
+        if self.is_still() {
+            if self.iteration_budget < 1.0 {
+                self.iteration_budget = 1.0;
+                self.invalidate();
+            }
+        } else if let Some(gpu_time) = gpu_time {
+            let ratio = (target_frame_time / gpu_time.as_secs_f32().max(f32::EPSILON))
+                .clamp(1.0 / ITERATION_BUDGET_STEP, ITERATION_BUDGET_STEP);
+            self.iteration_budget =
+                (self.iteration_budget * ratio).clamp(MINIMUM_ITERATION_BUDGET, 1.0);
+        }
+    }
+
+    // the time in milliseconds the gpu should take to draw the fractal while the camera moves,
+    // 0 to always compute every iteration, the frames rendered without a window always do
+    pub fn set_target_frame_time(&mut self, target_frame_time: f32) {
+        self.target_frame_time = if target_frame_time > 0.0 {
+            Some(target_frame_time / 1000.0)
+        } else {
+            None
+        };
+        self.iteration_budget = 1.0;
+    }
+
+    fn set_render_scale(&mut self, engine: &mut Engine, render_scale: f32) {
+        self.render_scale = render_scale;
+        engine.set_render_scale(render_scale);
+        let render_size = scaled_size(self.size, render_scale);
+        self.mandelbrot
+            .resize(render_size.width, render_size.height);
+        // the pixels of the last frame are not the same size anymore
+        self.invalidate();
+    }
+
+    // the smallest share of the width and the height of the window the fractal is drawn at,
+    // the frames rendered without a window are always drawn at the full resolution
+    pub fn set_minimum_resolution(&mut self, engine: &mut Engine, minimum_resolution: f32) {
+        self.minimum_render_scale = if self.mandelbrot.wait_for_orbits {
+            1.0
+        } else {
+            minimum_resolution
+        };
+        if self.render_scale < self.minimum_render_scale {
+            self.set_render_scale(engine, self.minimum_render_scale);
+        }
+    }
+
+    // move the pixels of the last frame by a whole number of pixels when the camera only moves,
+    // the frame is then computed less than half a pixel away from the center
+    fn reproject(&mut self) {
+        let data = *self.mandelbrot.data.get();
+        let previous = *self.previous_mandelbrot.data.get();
+        let cycle = self.mandelbrot.orbit_cycle;
+        let mut last_rendered = LastRenderedMandelbrot {
+            center_delta: data.center_delta,
+            shift: [0, 0],
+            zoom: data.zoom,
+            reproject: 0,
+            iteration_slice: self.frame_iteration_slice(),
+            orbit: self.orbit,
+            cycle_start: cycle.map_or(0, |cycle| cycle.start as u32),
+            cycle_period: cycle.map_or(0, |cycle| cycle.period as u32),
+        };
+        if data.zoom == previous.zoom
+            && data.zoom_exponent == previous.zoom_exponent
+            && data.angle == previous.angle
+            && data.width == previous.width
+            && data.height == previous.height
+            && data.height > 0
+        {
+            let pixel = 2.0 * data.zoom / data.height as f32;
+            let (sin, cos) = data.angle.sin_cos();
+            let movement = [
+                data.center_delta[0] - previous.center_delta[0],
+                data.center_delta[1] - previous.center_delta[1],
+            ];
+            // the movement in pixels, on the axes of the screen
+            let shift = [
+                ((movement[0] * cos + movement[1] * sin) / pixel).round(),
+                ((movement[1] * cos - movement[0] * sin) / pixel).round(),
+            ];
+            // the whole frame is computed again when nothing is left of the last one
+            if shift[0].abs() < data.width as f32 && shift[1].abs() < data.height as f32 {
+                last_rendered = LastRenderedMandelbrot {
+                    center_delta: [
+                        previous.center_delta[0] + (shift[0] * cos - shift[1] * sin) * pixel,
+                        previous.center_delta[1] + (shift[0] * sin + shift[1] * cos) * pixel,
+                    ],
+                    shift: [shift[0] as i32, shift[1] as i32],
+                    zoom: data.zoom,
+                    reproject: 1,
+                    ..last_rendered
+                };
+            }
+        }
+        self.last_rendered.set(vec![last_rendered]);
+    }
+
+    // the most iterations a pixel is advanced by in this frame, the frames rendered without a window
+    // compute them all at once, so they do not depend on the number of frames
+    fn frame_iteration_slice(&self) -> u32 {
+        if self.iteration_slice == 0 || self.mandelbrot.wait_for_orbits {
+            u32::MAX
+        } else {
+            self.iteration_slice
+        }
+    }
+
+    // count the frames the pixels started in this one need to be finished,
+    // every pixel may be started again when the camera has moved or the orbit has changed
+    fn count_refining_frames(&mut self, orbit_changed: bool) {
+        let data = *self.mandelbrot.data.get();
+        let previous = *self.previous_mandelbrot.data.get();
+        let last_rendered = self.last_rendered.get()[0];
+        if data.zoom != previous.zoom
+            || data.zoom_exponent != previous.zoom_exponent
+            || data.angle != previous.angle
+            || data.width != previous.width
+            || data.height != previous.height
+            || last_rendered.center_delta != previous.center_delta
+            || orbit_changed
+        {
+            self.refining_frames =
+                data.maximum_iterations / last_rendered.iteration_slice.max(1) + 1;
+        } else {
+            self.refining_frames = self.refining_frames.saturating_sub(1);
+        }
+    }
+
+    // true while some pixels may not be finished, so the frames must go on
+    pub fn is_refining(&self) -> bool {
+        self.refining_frames > 0
+    }
+
+    // the most iterations a pixel is advanced by in a frame, 0 to compute them all at once
+    pub fn set_iteration_slice(&mut self, iteration_slice: u32) {
+        self.iteration_slice = iteration_slice;
+    }
+
+    // the iterations the palette moves by each second, negative to move it the other way
+    pub fn set_color_cycling_speed(&mut self, color_cycling_speed: f32) {
+        self.color_cycling_speed = color_cycling_speed;
+    }
+
+    // the palette scale follows the iterations of the last frames instead of the keys
+    pub fn set_auto_palette_scale(&mut self, auto_palette_scale: bool) {
+        self.auto_palette_scale = auto_palette_scale;
+        self.iteration_range_readback = None;
+    }
+
+    // page up and page down give the palette scale back to the keys
+    fn stop_auto_palette_scale(&mut self) {
+        if self.auto_palette_scale {
+            self.set_auto_palette_scale(false);
+            eprintln!("Automatic palette scale: off");
+        }
+    }
+
+    fn zoom_iterations(&self) -> f32 {
+        zoom_iterations(self.mandelbrot.zoom(), self.iteration_speed)
+    }
+
+    // the maximum iterations needed by the view, from the escapes of a recent frame
+    // the maximum is raised while enough pixels reach it and the largest iterations outside
+    // the set come close to it, as some of those pixels would escape with more iterations,
+    // and lowered to the largest iterations outside the set with a margin while the camera moves,
+    // the pixels of a still frame are not computed again with a lower maximum
+    // it starts from the one given by the zoom
+    fn estimate_iterations(&mut self, statistics: Option<&IterationStatistics>) -> f32 {
+        let mut estimate = self
+            .estimated_iterations
+            .unwrap_or_else(|| self.zoom_iterations());
+        if let Some(IterationStatistics {
+            range: Some((_, maximum)),
+            unescaped,
+            escaped,
+            ..
+        }) = statistics
+        {
+            let target = (maximum * AUTO_ITERATION_MARGIN).max(self.iteration_speed as f32);
+            let unescaped_share = *unescaped as f32 / (*unescaped + *escaped).max(1) as f32;
+            if target > estimate && unescaped_share > UNESCAPED_SHARE {
+                estimate = target;
+                // the pixels that reached the old maximum are computed again
+                if self.is_still() {
+                    self.invalidate();
+                }
+            } else if target * AUTO_ITERATION_MARGIN < estimate && !self.is_still() {
+                estimate = target;
+            }
+        }
+        self.estimated_iterations = Some(estimate);
+        estimate
+    }
+
+    // move the palette scale toward the range of the iterations of a recent frame,
+    // so the whole palette is spread over the pixels outside the set at any depth
+    fn fit_palette_scale(&mut self, statistics: Option<&IterationStatistics>, delta_time: f32) {
+        if !self.auto_palette_scale {
+            return;
+        }
+        if let Some((minimum, maximum)) = statistics.and_then(|statistics| statistics.range) {
+            let target = (maximum - minimum).max(MINIMUM_AUTO_PALETTE_SCALE);
+            let share = 1.0 - (-AUTO_PALETTE_SCALE_RATE * delta_time).exp();
+            let mut data = self.mandelbrot.data.get_mut();
+            data.color_palette_scale += (target - data.color_palette_scale) * share;
+        }
+    }
+
+    // the escapes of the pixels of a recent frame, none until known or when nothing needs them
+    // the statistics measured by the shader are copied after each frame and read a few frames
+    // later, a copy is started once the previous one has been read
+    fn iteration_statistics(&mut self, engine: &Engine) -> Option<IterationStatistics> {
+        // measured again by each frame, so a copy always holds the statistics of a single frame
+        self.iteration_range.set(vec![u32::MAX, 0, 0, 0, 0, 0, 0]);
+        if !self.auto_palette_scale && !self.navigation.auto_iterations && !self.measures_area {
+            return None;
+        }
+        if engine.is_cpu() {
+            let range = self
+                .cpu_iterations
+                .iter()
+                .filter(|iteration| **iteration >= 0.0)
+                .fold(None, |range, &iteration| match range {
+                    None => Some((iteration, iteration)),
+                    Some((minimum, maximum)) => {
+                        Some((iteration.min(minimum), iteration.max(maximum)))
+                    }
+                });
+            let count = |predicate: fn(f32) -> bool| {
+                self.cpu_iterations
+                    .iter()
+                    .filter(|iteration| predicate(**iteration))
+                    .count() as u32
+            };
+            return Some(IterationStatistics {
+                range,
+                unescaped: count(|iteration| iteration == -1.0),
+                escaped: count(|iteration| iteration >= 0.0),
+                interior: count(|iteration| iteration == -3.0),
+                iteration_sum: self
+                    .cpu_iterations
+                    .iter()
+                    .filter(|iteration| **iteration >= 0.0)
+                    .map(|iteration| *iteration as u64)
+                    .sum(),
+            });
+        }
+        let mut statistics = None;
+        if let Some(readback) = self.iteration_range_readback.as_mut() {
+            match readback.try_read() {
+                None => {}
+                Some(Ok(bytes)) => {
+                    let bits = bytes
+                        .chunks_exact(4)
+                        .map(|chunk| u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                        .collect::<Vec<_>>();
+                    statistics = Some(IterationStatistics {
+                        // the range stays empty when no pixel of the frame is outside the set
+                        range: (bits[0] <= bits[1])
+                            .then(|| (f32::from_bits(bits[0]), f32::from_bits(bits[1]))),
+                        unescaped: bits[2],
+                        escaped: bits[3],
+                        interior: bits[4],
+                        iteration_sum: (bits[6] as u64) << 32 | bits[5] as u64,
+                    });
+                    self.iteration_range_readback = None;
+                }
+                Some(Err(error)) => {
+                    eprintln!("Could not read the range of the iterations: {}", error);
+                    self.iteration_range_readback = None;
+                }
+            }
+        }
+        if self.iteration_range_readback.is_none() {
+            self.iteration_range_readback = Some(engine.read_buffer(self.iteration_range_buffer));
+        }
+        statistics
+    }
+
+    // the share of the view inside the set and the mean iterations outside it, from the escapes
+    // of a recent frame, the pixels not finished yet are left out
+    fn measure_area(&mut self, statistics: &IterationStatistics) {
+        if !self.measures_area {
+            return;
+        }
+        let inside = statistics.unescaped + statistics.interior;
+        let measured = inside + statistics.escaped;
+        if measured == 0 {
+            return;
+        }
+        let data = self.mandelbrot.data.get();
+        // the view spans twice the zoom vertically, and the width follows the window
+        let zoom = data.zoom();
+        let view_area = 4.0 * zoom * zoom * data.width as f64 / data.height.max(1) as f64;
+        self.area_statistics = Some(AreaStatistics {
+            inside_share: inside as f32 / measured as f32,
+            mean_iterations: (statistics.escaped > 0)
+                .then(|| (statistics.iteration_sum as f64 / statistics.escaped as f64) as f32),
+            view_area,
+        });
+    }
+
+    // measure how much of the view is inside the set, to write it in the title of the window
+    pub fn set_measures_area(&mut self, measures_area: bool) {
+        self.measures_area = measures_area;
+        if !measures_area {
+            self.area_statistics = None;
+        }
+    }
+
+    // the share of the view inside the set of a recent frame, none until measured
+    pub fn area_statistics(&self) -> Option<AreaStatistics> {
+        self.area_statistics
+    }
+
+    // move the palette, its phase stays within a period of the palette so it keeps its precision
+    fn cycle_colors(&mut self, delta_time: f32) {
+        if self.color_cycling_speed == 0.0 {
+            return;
+        }
+        let mut data = self.mandelbrot.data.get_mut();
+        data.palette_phase = (data.palette_phase + self.color_cycling_speed * delta_time)
+            .rem_euclid(data.color_palette_scale);
+    }
+
+    // give the settings of the comparison to the right part of the screen when it is split
+    fn update_comparison(&mut self) {
+        let config = &self.comparison_config;
+        let mut data = self.mandelbrot.data.get_mut();
+        data.split = if self.comparing { config.split } else { 1.0 };
+        let mut comparison = *data;
+        let coloring = config
+            .coloring
+            .unwrap_or_else(|| Coloring::from(data.coloring).next());
+        comparison.coloring = coloring as u32;
+        if let Some(color_palette_scale) = config.color_palette_scale {
+            comparison.color_palette_scale = color_palette_scale;
+        }
+        comparison.maximum_iterations =
+            ((data.maximum_iterations as f32 * config.iterations) as u32).max(1);
+        self.comparison.set(comparison);
+    }
+
+    // split the screen to compare the settings of the config with the current ones
+    pub fn toggle_comparison(&mut self) {
+        self.comparing = !self.comparing;
+        eprintln!("Comparison: {}", if self.comparing { "on" } else { "off" });
+        // the pixels of the right part do not have the same iterations
+        self.invalidate();
+    }
+
+    // when the reference point moves to the center of the screen
+    pub fn set_reference_config(&mut self, reference: ReferenceConfig) {
+        self.mandelbrot.reference = reference;
+    }
+
+    pub fn set_comparison(&mut self, comparison_config: ComparisonConfig) {
+        self.comparison_config = comparison_config;
+        if self.comparing {
+            self.invalidate();
+        }
+    }
+
+    // compute the frame on the cpu and give it to the engine, when the adapter can not run the shader
+    // the pixels are computed again only when the camera has moved, like in the shader,
+    // and written to the buffers of the shader so they can be read back the same way
+    pub fn render_cpu(&mut self, engine: &mut Engine) {
+        let mut data = *self.mandelbrot.data.get();
+        data.center_delta = self.last_rendered.get()[0].center_delta;
+        let previous = *self.previous_mandelbrot.data.get();
+        if data.zoom != previous.zoom
+            || data.zoom_exponent != previous.zoom_exponent
+            || data.angle != previous.angle
+            || data.center_delta != previous.center_delta
+        {
+            cpu_renderer::compute(
+                &data,
+                &self.mandelbrot.orbit_point_suite.points(),
+                self.mandelbrot.orbit_cycle,
+                &mut self.cpu_iterations,
+                &mut self.mandelbrot_data.get_mut(),
+            );
+            engine.write_pixel_texture(self.iteration_texture, &self.cpu_iterations, data.width);
+        }
+        cpu_renderer::colorize(
+            &data,
+            &self.palette.get(),
+            &self.cpu_iterations,
+            &self.mandelbrot_data.get(),
+            &mut self.cpu_pixels,
+        );
+        engine.write_cpu_frame(&self.cpu_pixels);
+    }
+
+    // resize the buffers that store a value per pixel
+    pub fn resize(&mut self, size: PhysicalSize<u32>) {
+        let pixel_count = (size.width * size.height) as usize;
+        let render_size = scaled_size(size, self.render_scale);
+        self.mandelbrot
+            .resize(render_size.width, render_size.height);
+        self.cpu_iterations.resize(pixel_count, -2.0);
+        self.mandelbrot_data
+            .resize(pixel_count, MandelbrotDot::zeroed());
+        self.previous_mandelbrot_data
+            .resize(pixel_count, MandelbrotDot::zeroed());
+        self.size = size;
+    }
+
+    // dither the colors with the blue noise, or with a flat noise that changes nothing
+    pub fn set_dither(&self, engine: &Engine, dither: bool) {
+        engine.write_texture(self.blue_noise_texture, &Self::blue_noise_pixels(dither));
+    }
+
+    fn blue_noise_pixels(dither: bool) -> Vec<u8> {
+        let side = BLUE_NOISE_SIDE as usize;
+        let flat = vec![128; side * side];
+        let noise = if dither { blue_noise() } else { &flat };
+        noise
+            .iter()
+            .flat_map(|value| [*value, *value, *value, 255])
+            .collect()
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.size
+    }
+
+    // stop any movement and jump to the given coordinate
+    pub fn goto(&mut self, x: Real, y: Real, zoom: Option<f32>) {
+        self.stop();
+        // a jump ends the flight in progress
+        self.animator = None;
+        self.mandelbrot.set_center(x, y);
+        if let Some(zoom) = zoom {
+            self.mandelbrot.set_zoom(zoom as f64);
+        }
+        self.invalidate();
+    }
+
+    // apply a new navigation config
+    // the startup values are only applied when they have been changed
+    pub fn set_navigation(&mut self, navigation: NavigationConfig) {
+        if navigation.zoom_speed != self.navigation.zoom_speed {
+            self.zoom_speed = navigation.zoom_speed;
+        }
+        if navigation.iteration_speed != self.navigation.iteration_speed {
+            self.iteration_speed = navigation.iteration_speed;
+        }
+        if navigation.easing != self.navigation.easing {
+            self.easing = navigation.easing;
+        }
+        if navigation.auto_iterations != self.navigation.auto_iterations {
+            self.estimated_iterations = None;
+            self.iteration_range_readback = None;
+        }
+        self.navigation = navigation;
+    }
+
+    pub fn zoom_speed(&self) -> f32 {
+        self.zoom_speed
+    }
+
+    pub fn set_zoom_speed(&mut self, zoom_speed: f32) {
+        self.zoom_speed = zoom_speed;
+    }
+
+    // stop any movement and fly to the given location in duration seconds
+    pub fn animate_to(&mut self, target: Location, duration: f32) {
+        self.stop();
+        self.animator = Some(CameraAnimator::new(
+            self.mandelbrot.location(),
+            target,
+            duration,
+        ));
+    }
+
+    // the zoom acceleration of a wheel tick, faster with shift and slower with control
+    fn wheel_acceleration(&self) -> f32 {
+        let navigation = &self.navigation;
+        if self.modifiers.shift() {
+            navigation.wheel_acceleration * navigation.wheel_fast_multiplier
+        } else if self.modifiers.ctrl() {
+            navigation.wheel_acceleration * navigation.wheel_slow_multiplier
+        } else {
+            navigation.wheel_acceleration
+        }
+    }
+
+    // fly so the rectangle selected on the screen fills the window, with the same angle
+    fn zoom_to_selection(&mut self, [start, end]: [[f32; 2]; 2]) {
+        let width = (end[0] - start[0]).abs();
+        let height = (end[1] - start[1]).abs();
+        if width < MINIMUM_SELECTION || height < MINIMUM_SELECTION {
+            return;
+        }
+        let offset = self.offset_of_pixel([(start[0] + end[0]) / 2.0, (start[1] + end[1]) / 2.0]);
+        let (x, y) = self.mandelbrot.point_at(offset);
+        let location = self.location();
+        let target = Location {
+            x,
+            y,
+            zoom: location.zoom
+                * (width / self.size.width.max(1) as f32)
+                    .max(height / self.size.height.max(1) as f32),
+            angle: location.angle,
+        };
+        self.animate_to(target, SELECTION_FLIGHT_DURATION);
+    }
+
+    // the coordinate of a pixel of the window from the center of the screen,
+    // rotated as the shader rotates the pixels, with the exponent of the zoom
+    fn offset_of_pixel(&self, position: [f32; 2]) -> (f32, f32) {
+        let data = self.mandelbrot.data.get();
+        let height = self.size.height.max(1) as f32;
+        let pixel = 2.0 * data.zoom / height;
+        let screen = (
+            (position[0] - self.size.width as f32 / 2.0) * pixel,
+            (height / 2.0 - position[1]) * pixel,
+        );
+        let (sin, cos) = data.angle.sin_cos();
+        (
+            screen.0 * cos - screen.1 * sin,
+            screen.0 * sin + screen.1 * cos,
+        )
+    }
+
+    // the pixel of the window of a coordinate given from the center of the screen
+    fn pixel_of_offset(&self, offset: (f32, f32)) -> [f32; 2] {
+        let data = self.mandelbrot.data.get();
+        let height = self.size.height.max(1) as f32;
+        let pixel = 2.0 * data.zoom / height;
+        let (sin, cos) = data.angle.sin_cos();
+        [
+            (offset.0 * cos + offset.1 * sin) / pixel + self.size.width as f32 / 2.0,
+            height / 2.0 - (offset.1 * cos - offset.0 * sin) / pixel,
+        ]
+    }
+
+    // the coordinate of the pivot of the rotation from the center of the screen
+    fn pivot_offset(&self) -> (f32, f32) {
+        match &self.rotation_pivot {
+            RotationPivot::Center => (0.0, 0.0),
+            RotationPivot::Cursor if self.mouse_right_button_pressed => {
+                self.offset_of_pixel(self.right_drag_start)
+            }
+            RotationPivot::Cursor => self.offset_of_pixel(self.cursor_position()),
+            RotationPivot::Locked(x, y) => self.mandelbrot.offset_of(x, y),
+        }
+    }
+
+    // follow the point under the cursor while it is on the mandelbrot set, the julia set
+    // of the last one stays on the preview while the cursor is over it
+    fn update_julia_preview(&mut self, engine: &mut Engine) {
+        if !engine.shows_julia_preview() {
+            return;
+        }
+        let cursor = self.cursor_position();
+        if !JuliaPreview::covers(self.size, cursor[0]) {
+            let (x, y) = self.mandelbrot.point_at(self.offset_of_pixel(cursor));
+            self.julia_parameter = [x.to_single(), y.to_single()];
+        }
+        engine.update_julia_preview(self.julia_parameter, &self.mandelbrot.data.get());
+        // the point whose julia set is shown, where the cursor left the mandelbrot set
+        let parameter = self.pixel_of_offset(self.mandelbrot.offset_of(
+            &Real::from_double(self.julia_parameter[0] as f64),
+            &Real::from_double(self.julia_parameter[1] as f64),
+        ));
+        if !JuliaPreview::covers(self.size, parameter[0]) {
+            engine.overlay().crosshair(parameter, MARKER_RADIUS, WHITE);
+        }
+    }
+
+    // play the orbit of the point under the cursor, or stop the sound
+    fn toggle_sonification(&mut self) {
+        if self.sonification.take().is_some() {
+            eprintln!("Orbit sound: off");
+            return;
+        }
+        match Sonification::start() {
+            Ok(sonification) => {
+                self.sonification = Some(sonification);
+                eprintln!("Orbit sound: on");
+            }
+            Err(error) => eprintln!("Impossible to play the orbit: {}", error),
+        }
+    }
+
+    // the sound follows the point under the cursor, in double precision which is enough to hear it
+    fn update_sonification(&mut self) {
+        if self.sonification.is_none() {
+            return;
+        }
+        let offset = self.offset_of_pixel(self.cursor_position());
+        let (x, y) = self.mandelbrot.point_at(offset);
+        let point = (x.to_double(), y.to_double());
+        if let Some(sonification) = self.sonification.as_mut() {
+            sonification.set_point(point);
+        }
+    }
+
+    // mark the reference point of the perturbation, with a diamond at the start of its orbit
+    // when the orbit is shown, and with a crosshair when asked for
+    fn draw_reference(&self, engine: &mut Engine) {
+        let shows_orbit_path = engine.shows_orbit_path();
+        if !shows_orbit_path && !self.shows_reference {
+            return;
+        }
+        // the reference point is where the center is moved from
+        let center_delta = self.mandelbrot.data.get().center_delta;
+        let reference = self.pixel_of_offset((-center_delta[0], -center_delta[1]));
+        let overlay = engine.overlay();
+        if shows_orbit_path {
+            overlay.marker(reference, MARKER_RADIUS, ORANGE);
+        }
+        if self.shows_reference {
+            overlay.crosshair(reference, 2.0 * MARKER_RADIUS, ORANGE);
+        }
+    }
+
+    // draw the real and imaginary axes when they cross the screen, and a scale bar of a round length
+    // in the bottom left corner, labeled with its length
+    fn draw_axes(&self, engine: &mut Engine) {
+        let data = self.mandelbrot.data.get();
+        let height = self.size.height.max(1) as f32;
+        let pixel = 2.0 * data.zoom / height;
+        // the axes are drawn from the point the closest to the center,
+        // so the coordinates stay small even when the origin is far away
+        let half_diagonal = (self.size.width as f32).hypot(height) / 2.0 * pixel;
+        let zero = Real::from_double(0.0);
+        let (x, y) = self.mandelbrot.offset_of(&zero, &zero);
+        if y.abs() < half_diagonal {
+            let start = self.pixel_of_offset((-half_diagonal, y));
+            let end = self.pixel_of_offset((half_diagonal, y));
+            engine.overlay().line(start, end, AXIS_COLOR);
+        }
+        if x.abs() < half_diagonal {
+            let start = self.pixel_of_offset((x, -half_diagonal));
+            let end = self.pixel_of_offset((x, half_diagonal));
+            engine.overlay().line(start, end, AXIS_COLOR);
+        }
+        // the length of a pixel without the exponent of the zoom
+        let pixel = 2.0 * data.zoom() / height as f64;
+        // the longest of 1, 2 or 5 times a power of ten under a fifth of the width
+        let target = (self.size.width as f64 / 5.0 * pixel).max(f64::MIN_POSITIVE);
+        let power = 10f64.powf(target.log10().floor());
+        let length = [5.0, 2.0, 1.0]
+            .into_iter()
+            .map(|factor| factor * power)
+            .find(|length| *length <= target)
+            .unwrap_or(power);
+        let bar_pixels = (length / pixel) as f32;
+        let (left, bottom) = (SCALE_BAR_MARGIN, height - SCALE_BAR_MARGIN);
+        let overlay = engine.overlay();
+        overlay.line([left, bottom], [left + bar_pixels, bottom], WHITE);
+        overlay.line([left, bottom - 4.0], [left, bottom + 4.0], WHITE);
+        let right = left + bar_pixels;
+        overlay.line([right, bottom - 4.0], [right, bottom + 4.0], WHITE);
+        let label = format!("{:.0e}", length);
+        let label_top = bottom - 8.0 - SCALE_BAR_LABEL_HEIGHT;
+        overlay.text([left, label_top], SCALE_BAR_LABEL_HEIGHT, &label, WHITE);
+    }
+
+    fn cursor_position(&self) -> [f32; 2] {
+        [self.mouse_position.0 as f32, self.mouse_position.1 as f32]
+    }
+
+    // the pixel of the window of the pivot of the rotation
+    fn pivot_position(&self) -> [f32; 2] {
+        self.pixel_of_offset(self.pivot_offset())
+    }
+
+    // turn the camera by an angle around the pivot
+    fn rotate(&mut self, angle: f32) {
+        let pivot = self.pivot_offset();
+        self.mandelbrot.data.get_mut().rotate_around(angle, pivot);
+    }
+
+    // rotate around the center of the screen, the cursor, or the point under the cursor now
+    fn next_rotation_pivot(&mut self) {
+        self.rotation_pivot = match self.rotation_pivot {
+            RotationPivot::Center => RotationPivot::Cursor,
+            RotationPivot::Cursor => {
+                let offset = self.offset_of_pixel(self.cursor_position());
+                let (x, y) = self.mandelbrot.point_at(offset);
+                RotationPivot::Locked(x, y)
+            }
+            RotationPivot::Locked(..) => RotationPivot::Center,
+        };
+        match &self.rotation_pivot {
+            RotationPivot::Locked(x, y) => {
+                eprintln!("Rotation pivot: locked at {}, {}", x.to_text(), y.to_text())
+            }
+            pivot => eprintln!("Rotation pivot: {:?}", pivot),
+        }
+    }
+
+    // fly to a location with the given animator
+    pub fn animate(&mut self, animator: CameraAnimator) {
+        self.stop();
+        self.animator = Some(animator);
+    }
+
+    // true when the camera does not move and the user is not dragging it
+    pub fn is_still(&self) -> bool {
+        self.zoom_speed == 0.0
+            && self.zoom_acceleration == 0.0
+            && self.rotate_speed == 0.0
+            && self.move_speed == (0.0, 0.0)
+            && self.animator.is_none()
+            && !self.mouse_left_button_pressed
+            && !self.mouse_right_button_pressed
+    }
+
+    // stop the flight in progress where the camera is
+    pub fn stop_animation(&mut self) {
+        self.animator = None;
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.animator.is_some()
+    }
+
+    pub fn location(&self) -> Location {
+        self.mandelbrot.location()
+    }
+
+    // a warning while the zoom is close to the deepest one the precision of the coordinates allows
+    pub fn depth_warning(&self) -> Option<&'static str> {
+        self.mandelbrot.depth_warning()
+    }
+
+    // how much of the orbit of the reference point is computed, none once it is complete,
+    // the frames are drawn with the last complete orbit meanwhile
+    pub fn orbit_progress(&self) -> Option<f32> {
+        self.mandelbrot.orbit_progress()
+    }
+
+    // the number of points of the orbit of the reference point computed and stored,
+    // only the first period of a cyclic orbit is stored
+    pub fn orbit_length(&self) -> usize {
+        self.mandelbrot.last_orbit_iteration as usize
+    }
+
+    // the minibrot at the center of the view, none while it is searched or when there is none
+    // it is searched in the background once the camera stops, only for the mandelbrot set
+    pub fn minibrot(&mut self) -> Option<Minibrot> {
+        let location = self.location();
+        if self.is_still() {
+            let data = *self.mandelbrot.data.get();
+            self.period_worker
+                .search(location.clone(), data.maximum_iterations, data.mu as f64);
+        }
+        self.period_worker.minibrot(&location)
+    }
+
+    // the state of the navigation, saved when the explorer exits
+    pub fn session(&self) -> Session {
+        let location = self.location();
+        let data = self.mandelbrot.data.get();
+        Session {
+            location,
+            zoom_speed: self.zoom_speed,
+            iteration_speed: self.iteration_speed,
+            color_palette_scale: data.color_palette_scale,
+            coloring: data.coloring,
+        }
+    }
+
+    // go back to a saved state of the navigation
+    pub fn restore(&mut self, session: &Session) {
+        self.set_view(&View {
+            location: session.location.clone(),
+            color_palette_scale: session.color_palette_scale,
+            coloring: session.coloring,
+        });
+        self.zoom_speed = session.zoom_speed;
+        self.iteration_speed = session.iteration_speed.clamp(10, 10000);
+    }
+
+    // what is shared of the current view
+    pub fn view(&self) -> View {
+        let location = self.location();
+        let data = self.mandelbrot.data.get();
+        View {
+            location,
+            color_palette_scale: data.color_palette_scale,
+            coloring: data.coloring,
+        }
+    }
+
+    // stop any movement and show a shared view
+    pub fn set_view(&mut self, view: &View) {
+        let location = view.location.clone();
+        self.goto(location.x, location.y, Some(location.zoom));
+        let mut data = self.mandelbrot.data.get_mut();
+        data.angle = location.angle;
+        data.color_palette_scale = view.color_palette_scale;
+        data.coloring = Coloring::from(view.coloring) as u32;
+    }
+
+    pub fn maximum_iterations(&self) -> u32 {
+        self.mandelbrot.maximum_iterations()
+    }
+
+    pub fn fixed_iterations(&self) -> Option<u32> {
+        self.fixed_iterations
+    }
+
+    // compute the given number of iterations whatever the zoom, none to go back to the zoom
+    pub fn set_fixed_iterations(&mut self, fixed_iterations: Option<u32>) {
+        self.fixed_iterations = fixed_iterations;
+        self.invalidate();
+    }
+
+    // true when the buffers of a frame of this size fit in the limits of the device
+    pub fn supports_size(engine: &Engine, size: PhysicalSize<u32>) -> bool {
+        let limits = engine.limits();
+        let pixel_count = size.width as u64 * size.height as u64;
+        size.width <= limits.max_texture_dimension_2d
+            && size.height <= limits.max_texture_dimension_2d
+            && pixel_count * std::mem::size_of::<MandelbrotDot>() as u64
+                <= limits.max_storage_buffer_binding_size as u64
+    }
+
+    // the smooth iteration count of each pixel of the last frame, from the bottom line,
+    // -1 inside the set
+    pub fn iterations(&self, engine: &Engine) -> Result<Vec<f32>, String> {
+        let size = {
+            let data = self.mandelbrot.data.get();
+            PhysicalSize::new(data.width, data.height)
+        };
+        engine.read_pixel_texture_blocking(self.iteration_texture, size)
+    }
+
+    // the sum of the iterations of every pixel of the last frame,
+    // the pixels inside the set count for the maximum number of iterations
+    pub fn rendered_iterations(&self, engine: &Engine) -> Result<f64, String> {
+        let maximum_iterations = self.maximum_iterations() as f64;
+        Ok(self
+            .iterations(engine)?
+            .into_iter()
+            .map(|iterations| match iterations {
+                // the maximum number of iterations has been reached
+                _ if iterations == -1.0 => maximum_iterations,
+                _ if iterations > 0.0 => iterations as f64,
+                _ => 0.0,
+            })
+            .sum())
+    }
+
+    // write the raw result of the last frame to an OpenEXR file, to color it in other tools:
+    // the smooth iteration count, its integer part and the derivative, -1 inside the set
+    pub fn export_exr(&self, engine: &Engine, path: &Path) -> Result<(), String> {
+        let iterations = self.iterations(engine)?;
+        let dots = f32_values(&engine.read_buffer_blocking(self.data_buffer)?);
+        // the size the last frame was drawn at
+        let (width, height) = {
+            let data = self.mandelbrot.data.get();
+            (data.width as usize, data.height as usize)
+        };
+        if iterations.len() < width * height || dots.len() < width * height * DOT_FLOATS {
+            return Err("the frame has not been rendered yet".to_string());
+        }
+        let mut smooth = Vec::with_capacity(width * height);
+        let mut derivative_x = Vec::with_capacity(width * height);
+        let mut derivative_y = Vec::with_capacity(width * height);
+        // the buffers start from the bottom line, the file from the top one
+        for line in (0..height).rev() {
+            for index in line * width..(line + 1) * width {
+                smooth.push(iterations[index]);
+                derivative_x.push(dots[index * DOT_FLOATS]);
+                derivative_y.push(dots[index * DOT_FLOATS + 1]);
+            }
+        }
+        let integer = smooth.iter().map(|value| value.floor().max(-1.0)).collect();
+        write_exr(
+            path,
+            width as u32,
+            height as u32,
+            &[
+                ("smooth", smooth),
+                ("iterations", integer),
+                ("derivative.x", derivative_x),
+                ("derivative.y", derivative_y),
+            ],
+        )
+    }
+
+    // stop any movement and move the camera to the given location
+    // the current orbit is kept as long as it is close enough, so the previous frame can be reused
+    pub fn set_location(&mut self, location: Location) {
+        self.stop();
+        self.animator = None;
+        self.mandelbrot.move_center_to(&location.x, &location.y);
+        self.mandelbrot.set_zoom(location.zoom as f64);
+        self.mandelbrot.data.get_mut().angle = location.angle;
+    }
+
+    // the polynomial of the newton fractal, from its coefficient of highest degree
+    pub fn set_newton_polynomial(&mut self, coefficients: &[f32]) {
+        self.newton_polynomial.set(NewtonPolynomial::new(coefficients));
+        self.invalidate();
+    }
+
+    // color the iterations with the palette generated from the seed,
+    // or with the palette of the shader without seed
+    pub fn set_palette_seed(&mut self, seed: Option<u32>) {
+        self.palette_seed = seed;
+        let palette = seed.map(Palette::generate).unwrap_or_default();
+        self.palette.set(palette);
+    }
+
+    pub fn palette_seed(&self) -> Option<u32> {
+        self.palette_seed
+    }
+
+    // wait for the orbit of each new reference point instead of computing it in the background,
+    // so the frames exported are the same whatever the speed of the computer
+    pub fn set_wait_for_orbits(&mut self, wait_for_orbits: bool) {
+        self.mandelbrot.wait_for_orbits = wait_for_orbits;
+    }
+
+    pub fn set_color_palette_scale(&mut self, color_palette_scale: f32) {
+        self.mandelbrot.data.get_mut().color_palette_scale = color_palette_scale;
+    }
+
+    // stop any movement and set the zoom
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.stop();
+        self.mandelbrot.set_zoom(zoom as f64);
+        self.invalidate();
+    }
+
+    // go back to the zoom and the rotation of the whole set
+    pub fn reset_view(&mut self) {
+        self.mandelbrot.data.get_mut().reset();
+    }
+
+    // stop the zoom, the rotation and the movement
+    pub fn stop(&mut self) {
+        self.zoom_speed = 0.0;
+        self.zoom_acceleration = 0.0;
+        self.rotate_speed = 0.0;
+        self.move_speed = (0.0, 0.0);
+    }
+
+    // force the shader to compute again every pixel on the next frame
+    // instead of reusing the previous frame, which is only valid for a continuous movement
+    pub fn invalidate(&mut self) {
+        self.previous_mandelbrot.data.get_mut().zoom = 0.0;
+    }
+
+    // a json description of the current location
+    pub fn status(&self) -> serde_json::Value {
+        let (x, y) = self.mandelbrot.center();
+        let data = self.mandelbrot.data.get();
+        serde_json::json!({
+            "x": x.to_text(),
+            "y": y.to_text(),
+            "zoom": data.zoom(),
+            "angle": data.angle,
+            "maximum_iterations": data.maximum_iterations,
+            "color_palette_scale": data.color_palette_scale,
+            "inside_share": self.area_statistics.map(|area| area.inside_share),
+            "mean_iterations": self.area_statistics.and_then(|area| area.mean_iterations),
+            "inside_area": self.area_statistics.map(|area| area.inside_area()),
+        })
+    }
+
     // new
-    pub fn new(size: PhysicalSize<u32>, engine: &mut Engine) -> Self {
+    pub fn new(size: PhysicalSize<u32>, engine: &mut Engine, navigation: NavigationConfig) -> Self {
         let mandelbrot = MandelbrotEngine::new(100, size.width, size.height);
         let previous_mandelbrot = MandelbrotEngine::new(100, size.width, size.height);
-        let mandelbrot_iteration_texture = Rc::new(RefCell::new(vec![
-            -2.0;
-            (size.width * size.height)
-                as usize
-        ]));
-        // create a buffer to store the previous mandelbrot texture
-        let previous_mandelbrot_iteration_texture = Rc::new(RefCell::new(vec![
-            -2.0;
-            (size.width * size.height)
-                as usize
-        ]));
-        // create a buffer to store the z complex (a tuple of two float values) of the mandelbrot
-        let mandelbrot_data = Rc::new(RefCell::new(vec![
-            [0.0, 0.0];
-            (size.width * size.height) as usize
-        ]));
-        let previous_mandelbrot_data = Rc::new(RefCell::new(vec![
-            [0.0, 0.0];
-            (size.width * size.height)
-                as usize
-        ]));
-        engine.add_buffer(
-            BufferUsages::UNIFORM | BufferUsages::COPY_DST,
-            BufferBindingType::Uniform,
-            ShaderStages::FRAGMENT,
-            mandelbrot.data.clone(),
-        );
-        engine.add_buffer(
-            BufferUsages::UNIFORM | BufferUsages::COPY_DST,
-            BufferBindingType::Uniform,
-            ShaderStages::FRAGMENT,
-            previous_mandelbrot.data.clone(),
-        );
-        engine.add_buffer(
-            BufferUsages::STORAGE | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
-            BufferBindingType::Storage { read_only: false },
-            ShaderStages::FRAGMENT,
-            mandelbrot_iteration_texture.clone(),
-        );
-        engine.add_buffer(
-            BufferUsages::STORAGE | BufferUsages::COPY_DST,
-            BufferBindingType::Storage { read_only: false },
+        let pixel_count = (size.width * size.height) as usize;
+        // create a buffer to store the derivative and the atom domain of each pixel
+        let mandelbrot_data = StorageBuffer::new(vec![MandelbrotDot::zeroed(); pixel_count]);
+        let previous_mandelbrot_data = StorageBuffer::new(vec![MandelbrotDot::zeroed(); pixel_count]);
+        // the bindings of each group of the shader follow the order in which the buffers are added
+        engine.add_uniform_buffer(&mandelbrot.data, ShaderStages::FRAGMENT);
+        engine.add_uniform_buffer(&previous_mandelbrot.data, ShaderStages::FRAGMENT);
+        // the iterations of the pixels, in textures resized with the frames
+        let iteration_texture =
+            engine.add_pixel_texture(PixelAccess::Write, -2.0, ShaderStages::FRAGMENT);
+        let previous_iteration_texture =
+            engine.add_pixel_texture(PixelAccess::Read, -2.0, ShaderStages::FRAGMENT);
+        let data_buffer = engine.add_storage_buffer(&mandelbrot_data, ShaderStages::FRAGMENT);
+        let previous_data_buffer =
+            engine.add_storage_buffer(&previous_mandelbrot_data, ShaderStages::FRAGMENT);
+        let orbit_buffer = engine.add_storage_buffer(
+            mandelbrot.orbit_point_suite.buffer(),
             ShaderStages::FRAGMENT,
-            previous_mandelbrot_iteration_texture.clone(),
         );
-        engine.add_buffer(
-            BufferUsages::STORAGE | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
-            BufferBindingType::Storage { read_only: false },
+        let last_rendered = StorageBuffer::new(vec![LastRenderedMandelbrot {
+            center_delta: [0.0, 0.0],
+            shift: [0, 0],
+            zoom: 1.0,
+            reproject: 0,
+            iteration_slice: u32::MAX,
+            orbit: 0,
+            cycle_start: 0,
+            cycle_period: 0,
+        }]);
+        engine.add_storage_buffer(&last_rendered, ShaderStages::FRAGMENT);
+        let iteration_range = StorageBuffer::new(vec![u32::MAX, 0, 0, 0, 0, 0, 0]);
+        let iteration_range_buffer =
+            engine.add_storage_buffer(&iteration_range, ShaderStages::FRAGMENT);
+        let blue_noise_texture = engine.add_texture(
+            BLUE_NOISE_SIDE,
+            BLUE_NOISE_SIDE,
+            &Self::blue_noise_pixels(false),
             ShaderStages::FRAGMENT,
-            mandelbrot_data.clone(),
         );
-        engine.add_buffer(
-            BufferUsages::STORAGE | BufferUsages::COPY_DST,
-            BufferBindingType::Storage { read_only: false },
-            ShaderStages::FRAGMENT,
-            previous_mandelbrot_data.clone(),
-        );
-        engine.add_buffer(
-            BufferUsages::STORAGE | BufferUsages::COPY_DST,
-            BufferBindingType::Storage { read_only: false },
-            ShaderStages::FRAGMENT,
-            mandelbrot.orbit_point_suite.clone(),
-        );
-        engine.add_buffer(
-            BufferUsages::STORAGE | BufferUsages::COPY_DST,
-            BufferBindingType::Storage { read_only: false },
-            ShaderStages::FRAGMENT,
-            Rc::new(RefCell::new(LastRenderedMandelbrot {
-                zoom: 1.0,
-                center_delta: [0.0, 0.0],
-                _padding: 0,
-            })),
-        );
-        Self {
+        // z³ - 1 until the config gives another polynomial
+        let newton_polynomial = UniformBuffer::new(NewtonPolynomial::new(&[1.0, 0.0, 0.0, -1.0]));
+        engine.add_uniform_buffer(&newton_polynomial, ShaderStages::FRAGMENT);
+        let comparison = UniformBuffer::new(*mandelbrot.data.get());
+        engine.add_uniform_buffer(&comparison, ShaderStages::FRAGMENT);
+        let palette = UniformBuffer::new(Palette::default());
+        engine.add_uniform_buffer(&palette, ShaderStages::FRAGMENT);
+        // the pixels of the frame are kept to be reused by the next one
+        engine.add_pixel_texture_copy(iteration_texture, previous_iteration_texture);
+        engine.add_buffer_copy(data_buffer, previous_data_buffer);
+        let mut state = Self {
             mandelbrot,
             previous_mandelbrot,
-            mandelbrot_iteration_texture,
-            previous_mandelbrot_iteration_texture,
+            cpu_iterations: vec![-2.0; pixel_count],
             mandelbrot_data,
             previous_mandelbrot_data,
-            zoom_speed: 0.5,
+            zoom_speed: navigation.zoom_speed,
             rotate_speed: 0.0,
             zoom_acceleration: 0.0,
             move_speed: (0.0, 0.0),
-            iteration_speed: 100,
+            easing: navigation.easing,
+            physics_time: 0.0,
+            iteration_speed: navigation.iteration_speed,
             size,
             mouse_position: (0, 0),
             mouse_left_button_pressed: false,
             mouse_right_button_pressed: false,
-        }
+            modifiers: ModifiersState::empty(),
+            rotation_pivot: RotationPivot::Center,
+            right_drag_start: [0.0, 0.0],
+            selection: None,
+            animator: None,
+            period_worker: PeriodWorker::new(),
+            julia_parameter: [0.0, 0.0],
+            shows_axes: false,
+            shows_reference: false,
+            sonification: None,
+            navigation,
+            iteration_texture,
+            data_buffer,
+            orbit_buffer,
+            blue_noise_texture,
+            newton_polynomial,
+            palette,
+            palette_seed: None,
+            last_rendered,
+            auto_palette_scale: false,
+            iteration_range,
+            iteration_range_buffer,
+            iteration_range_readback: None,
+            estimated_iterations: None,
+            measures_area: false,
+            area_statistics: None,
+            render_scale: 1.0,
+            minimum_render_scale: 1.0,
+            iteration_budget: 1.0,
+            target_frame_time: None,
+            iteration_slice: 0,
+            color_cycling_speed: 0.0,
+            orbit: 0,
+            refining_frames: 0,
+            fixed_iterations: None,
+            comparison,
+            comparison_config: ComparisonConfig::default(),
+            comparing: false,
+            cpu_pixels: vec![],
+        };
+        // nothing has been rendered yet, every pixel must be computed on the first frame
+        state.invalidate();
+        state
     }
 }
+
+// the 32 bits floats of a buffer read from the gpu
+fn f32_values(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|bytes| f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect()
+}
@@ -1,15 +1,19 @@
-use std::cell::RefCell;
 use std::convert::Into;
 use std::default::Default;
-use std::ops::Deref;
-use std::rc::Rc;
 use std::vec::Vec;
 
 use bytemuck::{Pod, Zeroable};
-use num_bigfloat::BigFloat;
+use serde::Deserialize;
 
 use to_buffer_representation_derive::ToBufferRepresentation;
 
+use crate::game::big_real::{BigReal, Real, RELATIVE_PRECISION};
+use crate::game::config::{ReferenceConfig, ReferencePolicy};
+use crate::game::engine::typed_buffer::UniformBuffer;
+use crate::game::iteration::{norm, step, Iterations};
+use crate::game::location::Location;
+use crate::game::orbit_worker::{CycleFinder, OrbitCycle, OrbitWorker};
+use crate::game::packed_orbit::PackedOrbit;
 use crate::game::to_buffer_representation::ToBufferRepresentation;
 
 // use array
@@ -35,6 +39,152 @@ pub struct MandelbrotData {
     // a value used to calculate the maximum value to consider that the mathematics suite is divergent
     pub mu: f32,
     pub color_palette_scale: f32,
+    // the offset of the palette in iterations, moved by the color cycling
+    pub palette_phase: f32,
+    // the way the pixels are colored, a Coloring
+    pub coloring: u32,
+    // the way the iterations are computed, a Precision chosen from the zoom
+    pub precision_mode: u32,
+    // the raw data of the pixels shown instead of the colors, a DebugView
+    pub debug_view: u32,
+    // the share of the width from which the pixels use the settings of the comparison,
+    // 1 when the screen is not split
+    pub split: f32,
+    // the power of two the zoom and the center delta are multiplied by, so the zoom goes
+    // deeper than the smallest f32 in the extended exponent mode, 0 in the other modes
+    pub zoom_exponent: i32,
+}
+
+// the ways to color the pixels
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Coloring {
+    // by the number of iterations before escaping
+    Iterations,
+    // by the period of the atom domain, the iteration at which |z| was the smallest,
+    // every domain surrounds a minibrot of this period
+    AtomDomains,
+}
+
+impl Coloring {
+    pub fn next(self) -> Self {
+        match self {
+            Coloring::Iterations => Coloring::AtomDomains,
+            Coloring::AtomDomains => Coloring::Iterations,
+        }
+    }
+}
+
+impl From<u32> for Coloring {
+    fn from(value: u32) -> Self {
+        match value {
+            1 => Coloring::AtomDomains,
+            _ => Coloring::Iterations,
+        }
+    }
+}
+
+// the raw data of the pixels the mandelbrot shader can show instead of the colors,
+// to see what it computes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DebugView {
+    // the colors of the coloring
+    Off,
+    // the iterations in grayscale, from black to white at the maximum
+    Iterations,
+    // the magnitude of the derivative, on a logarithmic scale
+    Derivative,
+    // the iterations done since the pixel was last rebased on the start of the orbit
+    ReferenceDifference,
+    // in red the pixels rebased on the start of the orbit, which would glitch without it
+    GlitchMask,
+    // where the pixel comes from: blue when reused, green when moved from the last frame,
+    // red when started in this frame
+    ReprojectionSource,
+}
+
+impl DebugView {
+    pub fn next(self) -> Self {
+        match self {
+            DebugView::Off => DebugView::Iterations,
+            DebugView::Iterations => DebugView::Derivative,
+            DebugView::Derivative => DebugView::ReferenceDifference,
+            DebugView::ReferenceDifference => DebugView::GlitchMask,
+            DebugView::GlitchMask => DebugView::ReprojectionSource,
+            DebugView::ReprojectionSource => DebugView::Off,
+        }
+    }
+}
+
+impl From<u32> for DebugView {
+    fn from(value: u32) -> Self {
+        match value {
+            1 => DebugView::Iterations,
+            2 => DebugView::Derivative,
+            3 => DebugView::ReferenceDifference,
+            4 => DebugView::GlitchMask,
+            5 => DebugView::ReprojectionSource,
+            _ => DebugView::Off,
+        }
+    }
+}
+
+// the way the shader computes the iterations, the cheapest one precise enough for the zoom
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Precision {
+    // z² + c directly in simple precision, only the reference point of the orbit is needed
+    Direct,
+    // the difference to the orbit of the reference point, computed in arbitrary precision
+    Perturbation,
+    // the perturbation with the differences stored divided by a power of two,
+    // so they do not become too small for a f32 at the deepest zooms
+    ExtendedExponent,
+}
+
+// under this zoom a f32 has not enough digits to tell two pixels apart
+const DIRECT_MINIMUM_ZOOM: f64 = 1e-3;
+// under this zoom the differences to the orbit come close to the smallest f32
+const PERTURBATION_MINIMUM_ZOOM: f64 = 1e-30;
+// the deepest zoom, the zoom is a f32 mantissa with an exponent so it is only limited
+// by the digits of the coordinates
+pub const MINIMUM_ZOOM: f64 = 1e-250;
+// the exponent of the zoom is a multiple of it, so the mantissa stays far from the limits of a f32
+const ZOOM_EXPONENT_STEP: i32 = 32;
+// the digits of the coordinates kept between two pixels, so the pixels do not become blocks
+const PIXEL_DIGITS: f64 = 100.0;
+// the zoom is said to be close to the deepest one within this factor of it
+const DEPTH_WARNING_FACTOR: f64 = 100.0;
+
+impl Precision {
+    pub fn for_zoom(zoom: f64) -> Self {
+        if zoom >= DIRECT_MINIMUM_ZOOM {
+            Precision::Direct
+        } else if zoom >= PERTURBATION_MINIMUM_ZOOM {
+            Precision::Perturbation
+        } else {
+            Precision::ExtendedExponent
+        }
+    }
+}
+
+// the exponent of the zoom, a multiple of ZOOM_EXPONENT_STEP in the extended exponent mode
+fn zoom_exponent(zoom: f64) -> i32 {
+    match Precision::for_zoom(zoom) {
+        Precision::ExtendedExponent => {
+            (zoom.log2() / ZOOM_EXPONENT_STEP as f64).floor() as i32 * ZOOM_EXPONENT_STEP
+        }
+        Precision::Direct | Precision::Perturbation => 0,
+    }
+}
+
+impl From<u32> for Precision {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => Precision::Direct,
+            2 => Precision::ExtendedExponent,
+            _ => Precision::Perturbation,
+        }
+    }
 }
 
 impl MandelbrotData {
@@ -55,11 +205,26 @@ impl MandelbrotData {
         self.height = other.height;
         self.mu = other.mu;
         self.color_palette_scale = other.color_palette_scale;
+        self.palette_phase = other.palette_phase;
         self.angle = other.angle;
+        self.coloring = other.coloring;
+        self.precision_mode = other.precision_mode;
+        self.zoom_exponent = other.zoom_exponent;
     }
 
-    pub fn zoom(&self) -> f32 {
-        self.zoom
+    // the zoom with its exponent
+    pub fn zoom(&self) -> f64 {
+        self.zoom as f64 * 2f64.powi(self.zoom_exponent)
+    }
+
+    // change the exponent of the zoom, the zoom and the center delta are scaled to keep their value
+    pub fn set_zoom_exponent(&mut self, zoom_exponent: i32) {
+        let factor = 2f64.powi(self.zoom_exponent - zoom_exponent);
+        self.zoom = (self.zoom as f64 * factor) as f32;
+        self.center_delta = self
+            .center_delta
+            .map(|delta| (delta as f64 * factor) as f32);
+        self.zoom_exponent = zoom_exponent;
     }
 
     pub fn center_at(&mut self, mouse_x: f32, mouse_y: f32, window_width: u32, window_height: u32) {
@@ -94,6 +259,16 @@ impl MandelbrotData {
         }
     }
 
+    // rotate the view by an angle around a point given from the center of the screen,
+    // the center moves so the point stays at the same place on the screen
+    pub fn rotate_around(&mut self, angle: f32, pivot: (f32, f32)) {
+        let (sin, cos) = angle.sin_cos();
+        let rotated = (pivot.0 * cos - pivot.1 * sin, pivot.0 * sin + pivot.1 * cos);
+        self.center_delta[0] += pivot.0 - rotated.0;
+        self.center_delta[1] += pivot.1 - rotated.1;
+        self.angle += angle;
+    }
+
     pub fn move_by_pixel(
         &mut self,
         mouse_x: isize,
@@ -156,17 +331,45 @@ impl MandelbrotData {
 
     // function reset the mandelbrot set to its default values
     pub fn reset(&mut self) {
+        self.set_zoom_exponent(0);
         self.zoom = 3.0;
         self.angle = 0.0;
     }
 }
 
+// the distance to the reference point, in zooms, above which the deltas of the pixels lose
+// too much precision, the orbit of a new reference point is then waited for whatever the policy
+const MAXIMUM_DRIFT: f32 = 1000.0;
+
+// the most points of the orbit stored, so the most iterations of a pixel outside of a cycle
+const MAXIMUM_ORBIT_LENGTH: usize = 1000000;
+
+// the offsets from the center of the screen of the candidate reference points
+// whose orbits are computed at once, in zooms, the center is preferred among the ones as long
+const CANDIDATE_OFFSETS: [(f64, f64); 5] =
+    [(0.0, 0.0), (0.5, 0.0), (-0.5, 0.0), (0.0, 0.5), (0.0, -0.5)];
+
 pub struct MandelbrotEngine {
-    pub near_orbit_coordinate: (BigFloat, BigFloat),
-    pub last_orbit_z: (BigFloat, BigFloat),
+    pub near_orbit_coordinate: (Real, Real),
+    pub last_orbit_z: (Real, Real),
     pub last_orbit_iteration: u32,
-    pub orbit_point_suite: Rc<RefCell<Vec<[f32; 2]>>>,
-    pub data: Rc<RefCell<MandelbrotData>>,
+    // only grown to the length the orbit needs, and shrunk to its first period once it is cyclic
+    pub orbit_point_suite: PackedOrbit,
+    // the cycle the orbit falls into, its next points are read from its first period
+    pub orbit_cycle: Option<OrbitCycle>,
+    cycle_finder: CycleFinder,
+    // true once the orbit has escaped, it has no more points
+    orbit_escaped: bool,
+    // true once the points of the orbit are in a new buffer, which must be bound in place of the old one
+    orbit_replaced: bool,
+    pub data: UniformBuffer<MandelbrotData>,
+    // the orbit of the next reference point, computed in the background
+    orbit_worker: OrbitWorker,
+    // wait for the orbit of each new reference point, so the frames do not depend on the time
+    // it takes, to render them without a window
+    pub wait_for_orbits: bool,
+    // when the reference point moves to the center of the screen
+    pub reference: ReferenceConfig,
 }
 
 // x: -0.81448036, y: 0.18333414,
@@ -192,17 +395,22 @@ pub struct MandelbrotEngine {
 // x: -5.572506229492064091994520833394481793049e-1, y: 6.355989165839159099969652617613951003226e-1, zoom: 0.0000000000000000000000000000000000015172783
 impl Default for MandelbrotEngine {
     fn default() -> Self {
-        let mut orbit_point_suite = Vec::new();
-        orbit_point_suite.resize_with(1000000, || [0.0, 0.0]);
         Self {
             near_orbit_coordinate: (
-                BigFloat::parse("-1.749922480927599928271333687542289453030433024473703345006508521395924860650654081299355473751219976598678491114359225427863893386542382475600444642781285056640754").unwrap(),
-                BigFloat::parse("-0.000000000000959502198314327569948975707202650233401883670299418141500240641361234506320676962536124684582340235944852850785763764700482870569928474715774446003497").unwrap(),
+                Real::from_text("-1.749922480927599928271333687542289453030433024473703345006508521395924860650654081299355473751219976598678491114359225427863893386542382475600444642781285056640754").unwrap(),
+                Real::from_text("-0.000000000000959502198314327569948975707202650233401883670299418141500240641361234506320676962536124684582340235944852850785763764700482870569928474715774446003497").unwrap(),
             ),
-            last_orbit_z: (0.0.into(), 0.0.into()),
-            orbit_point_suite: Rc::new(RefCell::new(orbit_point_suite)),
+            last_orbit_z: (Real::from_double(0.0), Real::from_double(0.0)),
+            orbit_point_suite: PackedOrbit::new(&[[0.0, 0.0]; 2]),
+            orbit_cycle: None,
+            cycle_finder: CycleFinder::default(),
+            orbit_escaped: false,
+            orbit_replaced: false,
             last_orbit_iteration: 0,
-            data: Rc::new(RefCell::new(MandelbrotData {
+            orbit_worker: OrbitWorker::new(),
+            wait_for_orbits: false,
+            reference: ReferenceConfig::default(),
+            data: UniformBuffer::new(MandelbrotData {
                 generation: 0,
                 time_elapsed: 0.0,
                 zoom: 3.0,
@@ -213,120 +421,431 @@ impl Default for MandelbrotEngine {
                 height: 0,
                 mu: 10000.0,
                 color_palette_scale: 100.0,
+                palette_phase: 0.0,
                 angle: 0.0,
-            })),
+                coloring: Coloring::Iterations as u32,
+                precision_mode: Precision::Direct as u32,
+                debug_view: DebugView::Off as u32,
+                split: 1.0,
+                zoom_exponent: 0,
+            }),
         }
     }
 }
 
 impl MandelbrotEngine {
     pub fn resize(&mut self, width: u32, height: u32) {
-        self.data.deref().borrow_mut().resize(width, height);
+        self.data.get_mut().resize(width, height);
     }
 
     pub fn maximum_iterations(&self) -> u32 {
-        self.data.borrow().maximum_iterations
+        self.data.get().maximum_iterations
     }
 
     pub fn set_maximum_iterations(&mut self, maximum_iterations: u32) -> &mut Self {
-        self.data.deref().borrow_mut().maximum_iterations = maximum_iterations;
+        self.data.get_mut().maximum_iterations = maximum_iterations;
         self.calculate_orbit_point_suite(false);
         self
     }
 
-    pub fn zoom(&self) -> f32 {
-        self.data.borrow().zoom
+    pub fn zoom(&self) -> f64 {
+        self.data.get().zoom()
     }
 
-    pub fn set_zoom(&mut self, zoom: f32) -> &mut Self {
-        self.data.deref().borrow_mut().zoom = zoom;
+    // the zoom can not go deeper than the deepest zoom of the precision
+    pub fn set_zoom(&mut self, zoom: f64) -> &mut Self {
+        let deepest_zoom = self.deepest_zoom();
+        if zoom < deepest_zoom && self.zoom() > deepest_zoom {
+            eprintln!("The deepest zoom of the precision is reached");
+        }
+        let zoom = zoom.max(deepest_zoom);
+        // the exponent is changed first, the zoom may not fit in a f32 with the previous one
+        let zoom_exponent = zoom_exponent(zoom);
+        if zoom_exponent != self.data.get().zoom_exponent {
+            self.set_zoom_exponent(zoom_exponent);
+        }
+        self.data.get_mut().zoom = (zoom * 2f64.powi(-zoom_exponent)) as f32;
+        self.update_precision();
         self
     }
 
+    // the deepest zoom at which the pixels are still computed with enough digits,
+    // the coordinates lose their last digits long before the zoom reaches MINIMUM_ZOOM,
+    // and sooner far from 0 since the digits are relative to the coordinates
+    pub fn deepest_zoom(&self) -> f64 {
+        let (x, y) = self.center();
+        let magnitude = x.to_double().abs().max(y.to_double().abs()).max(1.0);
+        let half_height = self.data.get().height.max(1) as f64 / 2.0;
+        let zoom = magnitude * RELATIVE_PRECISION * PIXEL_DIGITS * half_height;
+        zoom.max(MINIMUM_ZOOM)
+    }
+
+    // a warning to show while the zoom is close to the deepest one, none above it
+    pub fn depth_warning(&self) -> Option<&'static str> {
+        let deepest_zoom = self.deepest_zoom();
+        if self.zoom() <= deepest_zoom {
+            Some("deepest zoom of the precision reached")
+        } else if self.zoom() <= deepest_zoom * DEPTH_WARNING_FACTOR {
+            Some("close to the deepest zoom of the precision")
+        } else {
+            None
+        }
+    }
+
+    pub fn precision(&self) -> Precision {
+        Precision::from(self.data.get().precision_mode)
+    }
+
+    // switch to the precision of the zoom,
+    // the whole orbit is computed when leaving the direct mode, which only needs its reference point
+    // the exponent of the zoom follows it in the extended exponent mode
+    fn update_precision(&mut self) {
+        let zoom = self.zoom();
+        let precision = Precision::for_zoom(zoom);
+        let zoom_exponent = zoom_exponent(zoom);
+        if zoom_exponent != self.data.get().zoom_exponent {
+            self.set_zoom_exponent(zoom_exponent);
+        }
+        let previous = self.precision();
+        if precision != previous {
+            log::info!("Precision: {:?}", precision);
+            self.data.get_mut().precision_mode = precision as u32;
+            if previous == Precision::Direct {
+                self.calculate_orbit_point_suite(false);
+            }
+        }
+    }
+
+    // change the exponent of the zoom, the orbit is moved to the center when the center delta
+    // does not fit in a f32 with the new exponent, it is far too far from the center anyway
+    fn set_zoom_exponent(&mut self, zoom_exponent: i32) {
+        let center = self.center();
+        self.data.get_mut().set_zoom_exponent(zoom_exponent);
+        let center_delta = self.data.get().center_delta;
+        if center_delta.iter().any(|delta| !delta.is_finite()) {
+            self.orbit_worker.cancel();
+            self.near_orbit_coordinate = center;
+            self.set_orbit_center();
+        }
+    }
+
+    // the number of points of the orbit needed by the shader
+    fn orbit_length(&self) -> usize {
+        let maximum_iterations = self.data.get().maximum_iterations as usize;
+        match self.precision() {
+            // the reference point is the second point of the orbit
+            Precision::Direct => 2,
+            Precision::Perturbation | Precision::ExtendedExponent => maximum_iterations,
+        }
+        .min(MAXIMUM_ORBIT_LENGTH)
+    }
+
+    // grow the buffer of the orbit to hold the given number of points, at most the longest orbit,
+    // by powers of 2 so it is not created again each time the iterations are raised
+    fn reserve_orbit(&mut self, length: usize) {
+        if length > self.orbit_point_suite.len() {
+            let capacity = length.next_power_of_two().min(MAXIMUM_ORBIT_LENGTH);
+            self.orbit_point_suite.resize(capacity);
+        }
+    }
+
+    // write points of the orbit from the given index
+    fn store_orbit_points(&mut self, start: usize, points: &[[f32; 2]]) {
+        self.reserve_orbit(start + points.len());
+        self.orbit_point_suite.write(start, points);
+    }
+
+    // keep only the first period of the cycle the orbit has fallen into
+    fn set_orbit_cycle(&mut self, cycle: OrbitCycle) {
+        self.orbit_cycle = Some(cycle);
+        self.last_orbit_iteration = cycle.end() as u32;
+        self.orbit_point_suite.resize(cycle.end());
+    }
+
+    // the point of the orbit of the given index, read from the first period once it is cyclic
+    fn orbit_point(&self, index: usize) -> [f32; 2] {
+        let index = self.orbit_cycle.map_or(index, |cycle| cycle.fold(index));
+        self.orbit_point_suite.point(index)
+    }
+
     pub fn update(&mut self, delta_time: f32) {
-        self.data.deref().borrow_mut().generation += 1;
-        self.data.deref().borrow_mut().time_elapsed += delta_time;
-        // if the center is too far away from the orbit, reset the orbit
-        let delta = self.data.deref().borrow().center_delta;
-        // calculate the delta length
-        let delta_length = delta[0].abs() + delta[1].abs();
-        if delta_length >= self.zoom() * 2.0 {
-            self.near_orbit_coordinate.0 += BigFloat::from_f32(delta[0]);
-            self.near_orbit_coordinate.1 += BigFloat::from_f32(delta[1]);
-            self.data.deref().borrow_mut().center_delta = [0.0, 0.0];
-            self.last_orbit_iteration = 0;
-            self.last_orbit_z = (0.0.into(), 0.0.into());
-            self.calculate_orbit_point_suite(false);
+        self.data.get_mut().generation += 1;
+        self.data.get_mut().time_elapsed += delta_time;
+        // the zoom may have been changed directly in the data, by a reset
+        self.update_precision();
+        // if the center is too far away from the orbit, move the orbit to the center
+        // the new orbit is computed in the background and the current one is used until then
+        // the center delta and the zoom have the same exponent, which is left out
+        let data = *self.data.get();
+        let delta_length = data.center_delta[0].abs() + data.center_delta[1].abs();
+        if delta_length >= data.zoom * MAXIMUM_DRIFT {
+            self.orbit_worker.cancel();
+            self.near_orbit_coordinate = self.center();
+            self.set_orbit_center();
+        } else if self.reference.policy == ReferencePolicy::Automatic
+            && delta_length >= data.zoom * self.reference.threshold
+            && !self.is_next_orbit_near()
+        {
+            self.reanchor();
+        }
+        let orbit = if self.wait_for_orbits {
+            self.orbit_worker.wait()
         } else {
-            self.calculate_orbit_point_suite(true);
+            self.orbit_worker.poll()
+        };
+        if let Some(orbit) = orbit {
+            self.set_reference(&orbit.c);
+            self.last_orbit_iteration = orbit.points.len() as u32;
+            self.orbit_escaped = orbit.cycle.is_none() && orbit.points.len() < orbit.length;
+            // the points are moved to a new buffer instead of being copied to the current one
+            self.orbit_point_suite = PackedOrbit::new(&orbit.points);
+            self.orbit_replaced = true;
+            self.last_orbit_z = orbit.last_z;
+            self.cycle_finder = orbit.cycle_finder;
+            self.orbit_cycle = None;
+            if let Some(cycle) = orbit.cycle {
+                self.set_orbit_cycle(cycle);
+            }
+        }
+        self.calculate_orbit_point_suite(true);
+    }
+
+    // the first points of the orbit of the reference point, relative to the center of the screen
+    // and divided by the power of two of the exponent of the zoom, until it escapes
+    pub fn orbit_path(&self, count: usize) -> Vec<[f32; 2]> {
+        let data = self.data.get();
+        let c = (
+            self.near_orbit_coordinate.0.to_double(),
+            self.near_orbit_coordinate.1.to_double(),
+        );
+        let exponent = 2f64.powi(data.zoom_exponent);
+        let center = (
+            c.0 + data.center_delta[0] as f64 * exponent,
+            c.1 + data.center_delta[1] as f64 * exponent,
+        );
+        let count = count.min(data.maximum_iterations as usize);
+        match self.precision() {
+            // the shader only needs the reference point, the orbit is computed here,
+            // in double precision which is enough at this zoom
+            Precision::Direct => {
+                let mut points = Vec::with_capacity(count);
+                let mut z = (0.0, 0.0);
+                while points.len() < count {
+                    points.push([
+                        ((z.0 - center.0) / exponent) as f32,
+                        ((z.1 - center.1) / exponent) as f32,
+                    ]);
+                    if norm(&z) > data.mu as f64 {
+                        break;
+                    }
+                    z = step(&z, &c);
+                }
+                points
+            }
+            Precision::Perturbation | Precision::ExtendedExponent => {
+                // a cyclic orbit never escapes, its next points are read from its first period
+                let computed = match self.orbit_cycle {
+                    Some(_) => count,
+                    None => count.min(self.last_orbit_iteration as usize),
+                };
+                (0..computed)
+                    .map(|i| {
+                        let z = self.orbit_point(i);
+                        [
+                            ((z[0] as f64 - center.0) / exponent) as f32,
+                            ((z[1] as f64 - center.1) / exponent) as f32,
+                        ]
+                    })
+                    .collect()
+            }
         }
     }
 
+    // use another reference point, the center does not move, only its delta to the reference
+    pub fn set_reference(&mut self, reference: &(Real, Real)) {
+        let (x, y) = self.center();
+        let exponent = 2f64.powi(-self.data.get().zoom_exponent);
+        self.data.get_mut().center_delta = [
+            (x.minus(&reference.0).to_double() * exponent) as f32,
+            (y.minus(&reference.1).to_double() * exponent) as f32,
+        ];
+        self.near_orbit_coordinate.clone_from(reference);
+    }
+
+    // true when the orbit computed in the background is close enough to the center to be used
+    fn is_next_orbit_near(&self) -> bool {
+        let distance = self.reference.threshold + self.reference.hysteresis;
+        self.orbit_worker.reference().is_some_and(|c| {
+            let (x, y) = self.center();
+            x.minus(&c.0).to_double().abs() + y.minus(&c.1).to_double().abs()
+                < self.zoom() * distance as f64
+        })
+    }
+
+    // compute the orbits of the center of the screen and of a few points around it in the background,
+    // the one lasting the longest becomes the reference once they are complete
+    pub fn reanchor(&mut self) {
+        let (x, y) = self.center();
+        let zoom = self.zoom();
+        let candidates = CANDIDATE_OFFSETS
+            .iter()
+            .map(|(dx, dy)| {
+                (
+                    x.plus(&Real::from_double(dx * zoom)),
+                    y.plus(&Real::from_double(dy * zoom)),
+                )
+            })
+            .collect();
+        let length = self.orbit_length();
+        let mu = self.data.get().mu as f64;
+        self.orbit_worker.start(candidates, length, mu);
+    }
+
+    // compute the next points of the orbit, at most 50 when partial,
+    // until it escapes or falls into a cycle
     fn calculate_orbit_point_suite(&mut self, partial: bool) {
-        let two = BigFloat::parse("2.0").unwrap();
-        let mu = self.data.borrow().mu.into();
-        let c = self.near_orbit_coordinate;
-        let mut z: (BigFloat, BigFloat) = self.last_orbit_z;
-        let mut derivative: (BigFloat, BigFloat) = (0.0.into(), 0.0.into());
-        let mut i = self.last_orbit_iteration as usize;
-        let mut count = 0;
-        while i < self.data.borrow().maximum_iterations as usize && (!partial || count < 50) {
-            self.orbit_point_suite.deref().borrow_mut()[i as usize] = [z.0.to_f32(), z.1.to_f32()];
-            // derivative = derivative * 2 * z;
-            derivative = (
-                derivative.0 * two,
-                derivative.1 * two,
-            );
-            derivative = (
-                derivative.0 + z.0 - derivative.1 * z.1,
-                derivative.0 + z.1 + derivative.1 * z.0,
-            );
-            // z = z * z + c;
-            z = (z.0 * z.0 - z.1 * z.1 + c.0, z.0 * z.1 * two + c.1);
-            self.last_orbit_z = z;
-            // calculate z.norm
-            let z_norm = (z.0 * z.0 + z.1 * z.1);
-            if z_norm > mu {
+        profiling::scope!("MandelbrotEngine::calculate_orbit_point_suite");
+        if self.orbit_cycle.is_some() || self.orbit_escaped {
+            return;
+        }
+        let mu = self.data.get().mu as f64;
+        let start = self.last_orbit_iteration as usize;
+        let orbit_length = self.orbit_length();
+        self.reserve_orbit(orbit_length);
+        let count = orbit_length.saturating_sub(start);
+        let count = if partial { count.min(50) } else { count };
+        let zero = (Real::from_double(0.0), Real::from_double(0.0));
+        let z = std::mem::replace(&mut self.last_orbit_z, zero);
+        let mut iterations = Iterations::resume(&self.near_orbit_coordinate, z, start as u32, mu);
+        // only the new points are written to the gpu
+        let mut points = Vec::with_capacity(count);
+        let mut cycle = None;
+        for z in iterations.by_ref().take(count) {
+            let point = [z.0.to_single(), z.1.to_single()];
+            points.push(point);
+            cycle = self.cycle_finder.push(start + points.len() - 1, point);
+            if cycle.is_some() {
                 break;
             }
-            i += 1;
-            count += 1;
         }
-        self.last_orbit_iteration = i as u32;
+        self.orbit_escaped = iterations.escape_info().escape_iteration.is_some();
+        self.last_orbit_z = iterations.into_z();
+        self.store_orbit_points(start, &points);
+        self.last_orbit_iteration = (start + points.len()) as u32;
+        if let Some(cycle) = cycle {
+            self.set_orbit_cycle(cycle);
+        }
     }
 
-    pub fn center_orbit_at(
-        &mut self,
-        mouse_x: isize,
-        mouse_y: isize,
-        window_width: u32,
-        window_height: u32,
-    ) {
-        let normalized_mouse_vector = (
-            (BigFloat::from_f64(mouse_x as f64)
-                - (BigFloat::from_f64(window_width as f64) / BigFloat::parse("2.0").unwrap()))
-                / (BigFloat::from_f64(window_width as f64) / BigFloat::parse("2.0").unwrap()),
-            (BigFloat::from_f64(mouse_y as f64)
-                - (BigFloat::from_f64(window_height as f64) / BigFloat::parse("2.0").unwrap()))
-                / (BigFloat::from_f64(window_height as f64) / BigFloat::parse("2.0").unwrap())
-                * BigFloat::parse("-1.0").unwrap(),
-        );
-        let delta = (
-            normalized_mouse_vector.0
-                * (BigFloat::from_f64(self.data.borrow().width as f64)
-                    / BigFloat::from_f64(self.data.borrow().height as f64))
-                * BigFloat::from_f64(self.data.borrow().zoom as f64),
-            normalized_mouse_vector.1 * BigFloat::from_f64(self.data.borrow().zoom as f64),
-        );
-        self.near_orbit_coordinate.0 +=
-            delta.0 + BigFloat::from_f64(self.data.borrow().center_delta[0] as f64);
-        self.near_orbit_coordinate.1 +=
-            delta.1 + BigFloat::from_f64(self.data.borrow().center_delta[1] as f64);
-        self.data.deref().borrow_mut().center_delta[0] = -delta.0.to_f32();
-        self.data.deref().borrow_mut().center_delta[1] = -delta.1.to_f32();
+    // compute the orbit again from its first point
+    fn restart_orbit(&mut self) {
         self.last_orbit_iteration = 0;
-        self.last_orbit_z = (0.0.into(), 0.0.into());
-        self.calculate_orbit_point_suite(true);
+        self.last_orbit_z = (Real::from_double(0.0), Real::from_double(0.0));
+        self.orbit_cycle = None;
+        self.cycle_finder = CycleFinder::default();
+        self.orbit_escaped = false;
+    }
+
+    // true once after the points of the orbit have been moved to a new buffer
+    pub fn take_orbit_replacement(&mut self) -> bool {
+        std::mem::take(&mut self.orbit_replaced)
+    }
+
+    // how much of the orbit is computed, from 0 to 1, none once it is complete:
+    // the orbit of the next reference point while it is computed in the background,
+    // otherwise the orbit of the current one, computed a little at each frame
+    // when the iterations are raised or the reference point is moved at once
+    pub fn orbit_progress(&self) -> Option<f32> {
+        if let Some(progress) = self.orbit_worker.progress() {
+            return Some(progress);
+        }
+        let length = self.orbit_length();
+        let computed = self.last_orbit_iteration as usize;
+        (self.orbit_cycle.is_none() && !self.orbit_escaped && computed < length)
+            .then(|| computed as f32 / length as f32)
+    }
+
+    // compute the orbit of the point at the given offset from the center of the screen
+    // in the background, it becomes the reference once complete
+    // the offset has the exponent of the zoom, like the center delta
+    pub fn center_orbit_at(&mut self, offset: (f32, f32)) {
+        let (x, y) = self.center();
+        let exponent = 2f64.powi(self.data.get().zoom_exponent);
+        let point = (
+            x.plus(&Real::from_double(offset.0 as f64 * exponent)),
+            y.plus(&Real::from_double(offset.1 as f64 * exponent)),
+        );
+        let length = self.orbit_length();
+        let mu = self.data.get().mu as f64;
+        self.orbit_worker.start(vec![point], length, mu);
+    }
+
+    // the coordinate at the given offset from the center of the screen,
+    // the offset has the exponent of the zoom, like the center delta
+    pub fn point_at(&self, offset: (f32, f32)) -> (Real, Real) {
+        let (x, y) = self.center();
+        let exponent = 2f64.powi(self.data.get().zoom_exponent);
+        (
+            x.plus(&Real::from_double(offset.0 as f64 * exponent)),
+            y.plus(&Real::from_double(offset.1 as f64 * exponent)),
+        )
+    }
+
+    // the offset of a coordinate from the center of the screen, with the exponent of the zoom
+    pub fn offset_of(&self, x: &Real, y: &Real) -> (f32, f32) {
+        let (center_x, center_y) = self.center();
+        let exponent = 2f64.powi(-self.data.get().zoom_exponent);
+        (
+            (x.minus(&center_x).to_double() * exponent) as f32,
+            (y.minus(&center_y).to_double() * exponent) as f32,
+        )
+    }
+
+    // the coordinate of the center of the screen, in the precision of the orbit
+    pub fn center(&self) -> (Real, Real) {
+        let data = self.data.get();
+        let exponent = 2f64.powi(data.zoom_exponent);
+        let (x, y) = &self.near_orbit_coordinate;
+        (
+            x.plus(&Real::from_double(data.center_delta[0] as f64 * exponent)),
+            y.plus(&Real::from_double(data.center_delta[1] as f64 * exponent)),
+        )
+    }
+
+    // move the orbit to the given coordinate and center the screen on it
+    pub fn set_center(&mut self, x: Real, y: Real) {
+        self.orbit_worker.cancel();
+        self.near_orbit_coordinate = (x, y);
+        self.set_orbit_center();
+    }
+
+    // restart the orbit from the reference point, at the center of the screen
+    fn set_orbit_center(&mut self) {
+        self.data.get_mut().center_delta = [0.0, 0.0];
+        self.restart_orbit();
+        self.calculate_orbit_point_suite(false);
+    }
+
+    // move the center of the screen to the given coordinate, keeping the current orbit
+    // the orbit is moved by update when the center goes too far away from it
+    pub fn move_center_to(&mut self, x: &Real, y: &Real) {
+        let (orbit_x, orbit_y) = &self.near_orbit_coordinate;
+        let exponent = 2f64.powi(-self.data.get().zoom_exponent);
+        self.data.get_mut().center_delta = [
+            (x.minus(orbit_x).to_double() * exponent) as f32,
+            (y.minus(orbit_y).to_double() * exponent) as f32,
+        ];
+    }
+
+    // the zoom of a location is a f32, it stops at the smallest one
+    pub fn location(&self) -> Location {
+        let (x, y) = self.center();
+        Location {
+            x,
+            y,
+            zoom: (self.zoom() as f32).max(f32::MIN_POSITIVE),
+            angle: self.data.get().angle,
+        }
     }
 
     // implement new for MandelbrotShader, without zoom, x, y, mu
@@ -334,7 +853,46 @@ impl MandelbrotEngine {
         let mut value = Self {
             ..Default::default()
         };
+        value.update_precision();
         value.calculate_orbit_point_suite(false);
         value
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_zoom_and_the_center_keep_their_value_when_the_exponent_changes() {
+        let mut data = *MandelbrotEngine::default().data.get();
+        data.zoom = 1e-3;
+        data.center_delta = [2e-3, -1e-3];
+        data.set_zoom_exponent(-64);
+        assert_eq!(data.zoom_exponent, -64);
+        assert!((data.zoom() / 1e-3 - 1.0).abs() < 1e-6);
+        assert!((data.center_delta[0] as f64 * 2f64.powi(-64) / 2e-3 - 1.0).abs() < 1e-6);
+        data.set_zoom_exponent(0);
+        assert_eq!(data.zoom, 1e-3);
+        assert_eq!(data.center_delta, [2e-3, -1e-3]);
+    }
+
+    #[test]
+    fn the_extended_exponent_mode_stores_the_exponent_of_the_zoom() {
+        let mut mandelbrot = MandelbrotEngine::default();
+        mandelbrot.data.get_mut().center_delta = [1.0, 0.5];
+        let center = mandelbrot.center();
+        let zoom = mandelbrot.deepest_zoom() * 2.0;
+        mandelbrot.set_zoom(zoom);
+        let data = *mandelbrot.data.get();
+        assert_eq!(mandelbrot.precision(), Precision::ExtendedExponent);
+        assert_eq!(data.zoom_exponent % ZOOM_EXPONENT_STEP, 0);
+        assert!(data.zoom_exponent < 0);
+        assert!(data.zoom >= 1.0 && data.zoom < 2f32.powi(ZOOM_EXPONENT_STEP));
+        assert!((mandelbrot.zoom() / zoom - 1.0).abs() < 1e-6);
+        assert_eq!(mandelbrot.center(), center);
+        // the zoom can not go deeper than the digits of the coordinates allow
+        mandelbrot.set_zoom(zoom / 1e10);
+        assert!((mandelbrot.zoom() / mandelbrot.deepest_zoom() - 1.0).abs() < 1e-6);
+    }
+}
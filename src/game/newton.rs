@@ -0,0 +1,110 @@
+use bytemuck::{Pod, Zeroable};
+use num::complex::Complex64;
+
+use to_buffer_representation_derive::ToBufferRepresentation;
+
+use crate::game::to_buffer_representation::ToBufferRepresentation;
+
+// the highest degree of the polynomials of the newton fractal
+pub const MAXIMUM_DEGREE: usize = 7;
+
+// the polynomial whose roots are searched by the newton fractal, and its roots
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable, ToBufferRepresentation)]
+pub struct NewtonPolynomial {
+    // for each term, from the constant one, the coefficient in x and y and a root in z and w
+    pub terms: [[f32; 4]; MAXIMUM_DEGREE + 1],
+    pub degree: u32,
+    #[padding]
+    _padding: [u32; 3],
+}
+
+impl NewtonPolynomial {
+    // the polynomial of the given real coefficients, from the highest degree,
+    // the degree must be between 1 and MAXIMUM_DEGREE
+    pub fn new(coefficients: &[f32]) -> Self {
+        // the leading zeros do not change the degree
+        let highest = coefficients
+            .iter()
+            .position(|coefficient| *coefficient != 0.0)
+            .unwrap_or(coefficients.len());
+        let coefficients = coefficients[highest..]
+            .iter()
+            .rev()
+            .map(|coefficient| Complex64::new(*coefficient as f64, 0.0))
+            .collect::<Vec<_>>();
+        let roots = roots(&coefficients);
+        let mut terms = [[0.0; 4]; MAXIMUM_DEGREE + 1];
+        for (index, coefficient) in coefficients.iter().enumerate() {
+            terms[index][0] = coefficient.re as f32;
+            terms[index][1] = coefficient.im as f32;
+        }
+        for (index, root) in roots.iter().enumerate() {
+            terms[index][2] = root.re as f32;
+            terms[index][3] = root.im as f32;
+        }
+        Self {
+            terms,
+            degree: roots.len() as u32,
+            _padding: [0; 3],
+        }
+    }
+}
+
+// check the coefficients of a polynomial, from the highest degree
+pub fn validate(coefficients: &[f32]) -> Result<(), String> {
+    let degree = coefficients
+        .iter()
+        .skip_while(|coefficient| **coefficient == 0.0)
+        .count() as isize
+        - 1;
+    if !(1..=MAXIMUM_DEGREE as isize).contains(&degree) {
+        return Err(format!(
+            "the degree of the polynomial must be between 1 and {}",
+            MAXIMUM_DEGREE
+        ));
+    }
+    if coefficients.iter().any(|coefficient| !coefficient.is_finite()) {
+        return Err("the coefficients of the polynomial must be finite".to_string());
+    }
+    Ok(())
+}
+
+// the roots of a polynomial of coefficients from the constant term, with the Durand-Kerner method:
+// every root is moved at the same time toward the zero of the polynomial divided by the others
+fn roots(coefficients: &[Complex64]) -> Vec<Complex64> {
+    let degree = coefficients.len() - 1;
+    let leading = coefficients[degree];
+    let evaluate = |z: Complex64| {
+        coefficients
+            .iter()
+            .rev()
+            .fold(Complex64::new(0.0, 0.0), |value, coefficient| value * z + coefficient)
+            / leading
+    };
+    // the starting points must not be real nor symmetric
+    let seed = Complex64::new(0.4, 0.9);
+    let mut roots = (0..degree)
+        .map(|index| seed.powu(index as u32))
+        .collect::<Vec<_>>();
+    for _ in 0..500 {
+        let mut change: f64 = 0.0;
+        for index in 0..degree {
+            let root = roots[index];
+            let denominator = roots
+                .iter()
+                .enumerate()
+                .filter(|(other, _)| *other != index)
+                .fold(Complex64::new(1.0, 0.0), |product, (_, other)| {
+                    product * (root - other)
+                });
+            let step = evaluate(root) / denominator;
+            roots[index] = root - step;
+            change = change.max(step.norm());
+        }
+        if change < 1e-12 {
+            break;
+        }
+    }
+    roots
+}
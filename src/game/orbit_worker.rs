@@ -0,0 +1,428 @@
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+
+use crate::game::big_real::{BigReal, Real};
+use crate::game::iteration::Iterations;
+
+// the number of points of the orbit sent at once
+const CHUNK: usize = 1000;
+
+// the orbit of a reference point, in simple precision for the shader
+pub struct Orbit {
+    // shared with the task until the orbit is complete
+    pub c: Arc<(Real, Real)>,
+    // the number of points requested, fewer are computed when the orbit escapes or is cyclic
+    pub length: usize,
+    pub points: Vec<[f32; 2]>,
+    // the value after the last point, to compute the orbit further
+    pub last_z: (Real, Real),
+    // the search of the cycle of the orbit, to search it further
+    pub cycle_finder: CycleFinder,
+    // the cycle the orbit ends in, the points after its first period are not computed
+    pub cycle: Option<OrbitCycle>,
+}
+
+impl Orbit {
+    // the number of iterations the orbit follows, all the ones requested when it falls into a cycle
+    fn reach(&self) -> usize {
+        match self.cycle {
+            Some(_) => self.length,
+            None => self.points.len(),
+        }
+    }
+}
+
+// the cycle the orbit of a point inside the set falls into, once its points repeat exactly
+// in simple precision, only the points until the end of its first period are stored
+// and the next ones are read from them, so the orbit of a minibrot takes a few kilobytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrbitCycle {
+    // the index of the first point of the cycle
+    pub start: usize,
+    pub period: usize,
+}
+
+impl OrbitCycle {
+    // the index of the stored point equal to the point of the given index
+    pub fn fold(&self, index: usize) -> usize {
+        if index < self.start {
+            index
+        } else {
+            self.start + (index - self.start) % self.period
+        }
+    }
+
+    // the number of points stored
+    pub fn end(&self) -> usize {
+        self.start + self.period
+    }
+}
+
+// search the cycle of an orbit from its points given one by one, with the method of brent:
+// the points are compared to a saved one, which is moved forward after 1, 2, 4... points,
+// and a period is kept once the points following it have repeated for a whole period
+#[derive(Debug, Clone, Default)]
+pub struct CycleFinder {
+    // the index of the saved point
+    start: usize,
+    // the points from the saved one
+    points: Vec<[f32; 2]>,
+    // the number of points after which the saved point is moved
+    limit: usize,
+    // the period the points seem to have, until it is confirmed
+    period: Option<usize>,
+}
+
+impl CycleFinder {
+    // give the point of the given index, the points already given are ignored,
+    // the cycle is returned once it is confirmed
+    pub fn push(&mut self, index: usize, point: [f32; 2]) -> Option<OrbitCycle> {
+        if index < self.start + self.points.len() {
+            return None;
+        }
+        if let Some(period) = self.period {
+            if self.points[index - self.start - period] != point {
+                self.period = None;
+            } else if index + 1 >= self.start + 2 * period {
+                return Some(OrbitCycle {
+                    start: self.start,
+                    period,
+                });
+            }
+        } else if self.points.first() == Some(&point) {
+            self.period = Some(index - self.start);
+        } else if self.points.len() >= self.limit {
+            self.start = index;
+            self.points.clear();
+            self.limit = (self.limit * 2).max(1);
+        }
+        self.points.push(point);
+        None
+    }
+}
+
+// a part of an orbit computed by a job
+struct Chunk {
+    // the index of the candidate reference point of the orbit
+    candidate: usize,
+    points: Vec<[f32; 2]>,
+    // the end of the orbit, with the last chunk of its task
+    end: Option<OrbitEnd>,
+}
+
+// what is left once the orbit is complete or has escaped
+struct OrbitEnd {
+    last_z: (Real, Real),
+    cycle_finder: CycleFinder,
+    cycle: Option<OrbitCycle>,
+}
+
+// compute the orbits of new reference points in arbitrary precision on the rayon pool,
+// while the frames are still drawn with the current orbit
+// each point of an orbit depends on the previous one, so an orbit can not be split between threads,
+// instead the orbits of several candidate points are computed at once, each by its own task,
+// and the one lasting the longest is kept, as the pixels are rebased less often on it
+// the tasks send their points by chunks on the channel of their job and stop as soon as
+// the channel is dropped, once a candidate is kept or when another orbit is requested
+// because the user has moved too far again
+pub struct OrbitWorker {
+    // the chunks of the current job, none when there is no job
+    receiver: Option<Receiver<Chunk>>,
+    // the orbits of the candidates of the current job, the first one is preferred
+    // among the ones as long, empty when there is no job
+    orbits: Vec<Orbit>,
+    // true for each candidate whose orbit has ended
+    ended: Vec<bool>,
+}
+
+impl OrbitWorker {
+    pub fn new() -> Self {
+        Self {
+            receiver: None,
+            orbits: vec![],
+            ended: vec![],
+        }
+    }
+
+    // the first candidate reference point of the current job
+    pub fn reference(&self) -> Option<&(Real, Real)> {
+        self.orbits.first().map(|orbit| &*orbit.c)
+    }
+
+    // the share of the longest orbit being computed already received, from 0 to 1,
+    // none when there is no job
+    pub fn progress(&self) -> Option<f32> {
+        self.orbits
+            .iter()
+            .map(|orbit| orbit.points.len() as f32 / orbit.length.max(1) as f32)
+            .reduce(f32::max)
+    }
+
+    // stop the current job, the chunks its tasks have already sent are dropped
+    pub fn cancel(&mut self) {
+        self.receiver = None;
+        self.orbits.clear();
+        self.ended.clear();
+    }
+
+    // compute the first points of the orbits of the candidate points at once, until they escape
+    // above mu or fall into a cycle, the current job is cancelled
+    pub fn start(&mut self, mut candidates: Vec<(Real, Real)>, length: usize, mu: f64) {
+        // there is no thread in the browser, only the first candidate is computed
+        #[cfg(target_arch = "wasm32")]
+        candidates.truncate(1);
+        self.cancel();
+        let (sender, receiver) = channel();
+        self.receiver = Some(receiver);
+        for (candidate, c) in candidates.drain(..).enumerate() {
+            let c = Arc::new(c);
+            self.orbits.push(Orbit {
+                c: Arc::clone(&c),
+                length,
+                points: Vec::with_capacity(length),
+                last_z: (Real::from_double(0.0), Real::from_double(0.0)),
+                cycle_finder: CycleFinder::default(),
+                cycle: None,
+            });
+            self.ended.push(false);
+            let sender = sender.clone();
+            let task = move || compute_orbit(candidate, c, length, mu, sender);
+            #[cfg(target_arch = "wasm32")]
+            task();
+            #[cfg(not(target_arch = "wasm32"))]
+            rayon::spawn(task);
+        }
+    }
+
+    // gather the chunks sent so far, the orbit kept is returned once it is known
+    pub fn poll(&mut self) -> Option<Orbit> {
+        loop {
+            let chunk = match self.receiver.as_ref()?.try_recv() {
+                Ok(chunk) => chunk,
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => return self.fail(),
+            };
+            if let Some(orbit) = self.gather(chunk) {
+                return Some(orbit);
+            }
+        }
+    }
+
+    // wait for the orbit kept by the current job, none when there is no job
+    pub fn wait(&mut self) -> Option<Orbit> {
+        loop {
+            let chunk = match self.receiver.as_ref()?.recv() {
+                Ok(chunk) => chunk,
+                Err(_) => return self.fail(),
+            };
+            if let Some(orbit) = self.gather(chunk) {
+                return Some(orbit);
+            }
+        }
+    }
+
+    // the tasks have stopped before the end of the orbits, one of them has panicked
+    fn fail(&mut self) -> Option<Orbit> {
+        eprintln!("Impossible to compute the orbit of the reference point");
+        self.cancel();
+        None
+    }
+
+    // add a chunk of the current job to its orbit, the orbit kept is returned once it is known
+    fn gather(&mut self, chunk: Chunk) -> Option<Orbit> {
+        let orbit = self.orbits.get_mut(chunk.candidate)?;
+        orbit.points.extend(chunk.points);
+        if let Some(end) = chunk.end {
+            orbit.last_z = end.last_z;
+            orbit.cycle_finder = end.cycle_finder;
+            orbit.cycle = end.cycle;
+            self.ended[chunk.candidate] = true;
+        }
+        let best = self.best()?;
+        let orbit = self.orbits.swap_remove(best);
+        // the other tasks stop at their next chunk
+        self.cancel();
+        Some(orbit)
+    }
+
+    // the index of the candidate kept, the one whose orbit lasts the longest, the first one
+    // among the ones as long, none while a candidate which could be kept has not ended
+    fn best(&self) -> Option<usize> {
+        let mut best: Option<usize> = None;
+        for (index, orbit) in self.orbits.iter().enumerate() {
+            // the next candidates can not last longer than all the iterations
+            if best.is_some_and(|best| self.orbits[best].reach() >= orbit.length) {
+                break;
+            }
+            if !self.ended[index] {
+                return None;
+            }
+            if best.is_none_or(|best| orbit.reach() > self.orbits[best].reach()) {
+                best = Some(index);
+            }
+        }
+        best
+    }
+}
+
+// compute the orbit of a candidate point and send it by chunks, until the channel is dropped
+fn compute_orbit(
+    candidate: usize,
+    c: Arc<(Real, Real)>,
+    length: usize,
+    mu: f64,
+    sender: Sender<Chunk>,
+) {
+    let mut iterations = Iterations::new(&c, mu);
+    let mut index = 0;
+    let mut cycle_finder = CycleFinder::default();
+    let mut cycle = None;
+    loop {
+        let count = CHUNK.min(length - index);
+        let mut points = Vec::with_capacity(count);
+        for z in iterations.by_ref().take(count) {
+            let point = [z.0.to_single(), z.1.to_single()];
+            points.push(point);
+            cycle = cycle_finder.push(index, point);
+            index += 1;
+            if cycle.is_some() {
+                break;
+            }
+        }
+        let escaped = iterations.escape_info().escape_iteration.is_some();
+        if escaped || cycle.is_some() || index >= length {
+            let end = OrbitEnd {
+                last_z: iterations.into_z(),
+                cycle_finder,
+                cycle,
+            };
+            // the point is released first, so the complete orbit owns it alone
+            drop(c);
+            let _ = sender.send(Chunk {
+                candidate,
+                points,
+                end: Some(end),
+            });
+            return;
+        }
+        let chunk = Chunk {
+            candidate,
+            points,
+            end: None,
+        };
+        if sender.send(chunk).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(x: f64, y: f64) -> (Real, Real) {
+        (Real::from_double(x), Real::from_double(y))
+    }
+
+    // the cycle found in the first points of the orbit of c, until it escapes
+    fn find_cycle(c: (f64, f64), length: usize) -> Option<OrbitCycle> {
+        let mut cycle_finder = CycleFinder::default();
+        Iterations::new(&c, 4.0)
+            .take(length)
+            .enumerate()
+            .find_map(|(index, z)| cycle_finder.push(index, [z.0 as f32, z.1 as f32]))
+    }
+
+    #[test]
+    fn the_orbit_of_0_stays_at_its_fixed_point() {
+        let cycle = find_cycle((0.0, 0.0), 100);
+        assert_eq!(
+            cycle,
+            Some(OrbitCycle {
+                start: 0,
+                period: 1
+            })
+        );
+    }
+
+    #[test]
+    fn the_orbit_of_minus_1_has_the_period_2() {
+        let cycle = find_cycle((-1.0, 0.0), 100).unwrap();
+        assert_eq!(
+            cycle,
+            OrbitCycle {
+                start: 1,
+                period: 2
+            }
+        );
+        // 0, -1, 0, -1...
+        assert_eq!(cycle.fold(10), 2);
+        assert_eq!(cycle.fold(11), 1);
+    }
+
+    #[test]
+    fn an_escaping_orbit_has_no_cycle() {
+        assert_eq!(find_cycle((1.0, 0.0), 100), None);
+        assert_eq!(find_cycle((-0.75, 0.1), 1000), None);
+    }
+
+    #[test]
+    fn a_period_is_dropped_once_a_point_does_not_repeat() {
+        let mut cycle_finder = CycleFinder::default();
+        // the points seem to have the period 2, until the fifth one differs from the third one
+        let points = [
+            [0.0, 0.0],
+            [1.0, 0.0],
+            [0.0, 0.0],
+            [1.0, 0.0],
+            [2.0, 0.0],
+            [3.0, 0.0],
+        ];
+        for (index, point) in points.into_iter().enumerate() {
+            assert_eq!(cycle_finder.push(index, point), None, "index {}", index);
+        }
+        assert_eq!(cycle_finder.period, None);
+    }
+
+    #[test]
+    fn the_orbit_waited_for_stops_where_it_escapes() {
+        let mut worker = OrbitWorker::new();
+        worker.start(vec![point(1.0, 0.0)], 100, 4.0);
+        let orbit = worker.wait().unwrap();
+        assert_eq!(orbit.points, vec![[0.0, 0.0], [1.0, 0.0], [2.0, 0.0]]);
+        assert_eq!(orbit.last_z.0.to_double(), 5.0);
+        assert!(orbit.cycle.is_none());
+        assert!(worker.progress().is_none());
+    }
+
+    #[test]
+    fn a_task_stopped_before_the_end_of_the_orbit_is_not_waited_for() {
+        let mut worker = OrbitWorker::new();
+        worker.start(vec![point(0.0, 0.0)], 100, 4.0);
+        // the channel of a task which has panicked, its sender is dropped without the last chunk
+        let (_, receiver) = channel();
+        worker.receiver = Some(receiver);
+        assert!(worker.wait().is_none());
+        assert!(worker.progress().is_none());
+        assert!(worker.poll().is_none());
+    }
+
+    #[test]
+    fn the_candidate_whose_orbit_lasts_the_longest_is_kept() {
+        let mut worker = OrbitWorker::new();
+        // 1 escapes after 3 points, -1 and 0 fall into cycles, -1 comes first
+        worker.start(
+            vec![point(1.0, 0.0), point(-1.0, 0.0), point(0.0, 0.0)],
+            100,
+            4.0,
+        );
+        let orbit = worker.wait().unwrap();
+        assert_eq!(orbit.c.0.to_double(), -1.0);
+        assert!(orbit.cycle.is_some());
+        assert!(worker.reference().is_none());
+        // among escaping orbits the longest one is kept
+        worker.start(vec![point(1.0, 0.0), point(-2.5, 0.0)], 100, 4.0);
+        let orbit = worker.wait().unwrap();
+        assert_eq!(orbit.c.0.to_double(), 1.0);
+    }
+}
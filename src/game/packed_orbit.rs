@@ -0,0 +1,208 @@
+use half::f16;
+
+use crate::game::engine::typed_buffer::StorageBuffer;
+
+// the points of the orbit in a block, the first one is stored as it is and the next ones
+// are computed from it, so reading a point costs at most this number of squares less one
+pub const BLOCK_LENGTH: usize = 8;
+
+// the differences are multiplied by 2 to this power before being rounded to a f16,
+// they are far smaller than the points, of the order of the precision of a f32
+const DIFFERENCE_EXPONENT: i32 = 20;
+
+// the first two words are the second point of the orbit, its reference point c
+const HEADER_LENGTH: usize = 2;
+
+// the points of the reference orbit in about 4.5 bytes each instead of 8, read by the shader
+// the orbit is split in blocks, the first point of a block is stored as two f32,
+// each next one as its difference with the square of the previous one plus c,
+// which is a point of the orbit too up to the rounding of the f32, in a word of two f16
+// the shader computes the square like here, so both read the same points
+pub struct PackedOrbit {
+    words: StorageBuffer<u32>,
+    // the number of points, the ones not written yet are 0
+    length: usize,
+}
+
+impl PackedOrbit {
+    pub fn new(points: &[[f32; 2]]) -> Self {
+        let mut orbit = Self {
+            words: StorageBuffer::new(vec![]),
+            length: 0,
+        };
+        orbit.resize(points.len());
+        orbit.write(0, points);
+        orbit
+    }
+
+    pub fn buffer(&self) -> &StorageBuffer<u32> {
+        &self.words
+    }
+
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    // the points added are 0, they must be written in order after the ones already written
+    pub fn resize(&mut self, length: usize) {
+        let blocks = (length + BLOCK_LENGTH - 1) / BLOCK_LENGTH;
+        self.words
+            .resize(HEADER_LENGTH + blocks * (BLOCK_LENGTH + 1), 0);
+        self.length = length;
+    }
+
+    // write points from the given index, the ones before it must have been written
+    pub fn write(&mut self, start: usize, points: &[[f32; 2]]) {
+        if points.is_empty() {
+            return;
+        }
+        let end = start + points.len();
+        if start <= 1 && end > 1 {
+            let c = points[1 - start];
+            self.words
+                .get_range_mut(0..HEADER_LENGTH)
+                .copy_from_slice(&c.map(f32::to_bits));
+        }
+        let c = self.c();
+        let mut previous = match start {
+            0 => [0.0, 0.0],
+            _ => self.point(start - 1),
+        };
+        let first_word = word_index(start);
+        let mut words = self.words.get_range_mut(first_word..word_end(end - 1));
+        for (index, point) in (start..end).zip(points) {
+            let word = word_index(index) - first_word;
+            previous = if index % BLOCK_LENGTH == 0 {
+                words[word..word + 2].copy_from_slice(&point.map(f32::to_bits));
+                *point
+            } else {
+                let square = square_plus(previous, c);
+                let difference = [0, 1].map(|axis| {
+                    let difference = (point[axis] - square[axis]) * 2f32.powi(DIFFERENCE_EXPONENT);
+                    f16::from_f32(difference.clamp(f16::MIN.to_f32(), f16::MAX.to_f32()))
+                });
+                words[word] =
+                    difference[0].to_bits() as u32 | (difference[1].to_bits() as u32) << 16;
+                next_point(previous, c, words[word])
+            };
+        }
+    }
+
+    // the point of the given index, computed from the first point of its block
+    pub fn point(&self, index: usize) -> [f32; 2] {
+        let words = self.words.get();
+        let c = self.c();
+        let first = index - index % BLOCK_LENGTH;
+        let word = word_index(first);
+        let mut point = [f32::from_bits(words[word]), f32::from_bits(words[word + 1])];
+        for index in first + 1..=index {
+            point = next_point(point, c, words[word_index(index)]);
+        }
+        point
+    }
+
+    // all the points, for the cpu renderer
+    pub fn points(&self) -> Vec<[f32; 2]> {
+        let words = self.words.get();
+        let c = self.c();
+        let mut points = Vec::with_capacity(self.length);
+        let mut point = [0.0, 0.0];
+        for index in 0..self.length {
+            let word = word_index(index);
+            point = if index % BLOCK_LENGTH == 0 {
+                [f32::from_bits(words[word]), f32::from_bits(words[word + 1])]
+            } else {
+                next_point(point, c, words[word])
+            };
+            points.push(point);
+        }
+        points
+    }
+
+    fn c(&self) -> [f32; 2] {
+        let words = self.words.get();
+        [f32::from_bits(words[0]), f32::from_bits(words[1])]
+    }
+}
+
+// the word of a point, the first of the two of the first point of a block
+fn word_index(index: usize) -> usize {
+    let offset = index % BLOCK_LENGTH;
+    HEADER_LENGTH + index / BLOCK_LENGTH * (BLOCK_LENGTH + 1) + offset + (offset != 0) as usize
+}
+
+// the end of the words of a point
+fn word_end(index: usize) -> usize {
+    word_index(index) + if index % BLOCK_LENGTH == 0 { 2 } else { 1 }
+}
+
+// the same operations in the same order as in the shader
+fn square_plus(z: [f32; 2], c: [f32; 2]) -> [f32; 2] {
+    [z[0] * z[0] - z[1] * z[1] + c[0], 2.0 * z[0] * z[1] + c[1]]
+}
+
+fn next_point(previous: [f32; 2], c: [f32; 2], word: u32) -> [f32; 2] {
+    let square = square_plus(previous, c);
+    let scale = 2f32.powi(-DIFFERENCE_EXPONENT);
+    [
+        square[0] + f16::from_bits(word as u16).to_f32() * scale,
+        square[1] + f16::from_bits((word >> 16) as u16).to_f32() * scale,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::iteration::Iterations;
+
+    // the points of the orbit of c until it escapes, as f32
+    fn orbit(c: (f64, f64), length: usize) -> Vec<[f32; 2]> {
+        Iterations::new(&c, 4.0)
+            .take(length)
+            .map(|z| [z.0 as f32, z.1 as f32])
+            .collect()
+    }
+
+    #[test]
+    fn the_points_are_read_with_the_precision_of_a_f32() {
+        for c in [(-0.75, 0.1), (-1.7499, 0.0), (0.3, 0.5), (-0.1, 0.65)] {
+            let points = orbit(c, 1000);
+            let packed = PackedOrbit::new(&points);
+            assert_eq!(packed.len(), points.len());
+            let read = packed.points();
+            for (index, (point, read)) in points.iter().zip(&read).enumerate() {
+                for axis in 0..2 {
+                    assert!(
+                        (point[axis] - read[axis]).abs() <= point[axis].abs().max(1.0) * 1e-6,
+                        "c = {:?}, point {}: {:?} for {:?}",
+                        c,
+                        index,
+                        read,
+                        point
+                    );
+                }
+                assert_eq!(packed.point(index), *read);
+            }
+        }
+    }
+
+    #[test]
+    fn the_points_written_in_parts_are_the_points_written_at_once() {
+        let points = orbit((-0.5, 0.1), 100);
+        let mut packed = PackedOrbit::new(&[]);
+        packed.resize(points.len());
+        for (start, end) in [(0, 1), (1, 9), (9, 50), (50, 100)] {
+            packed.write(start, &points[start..end]);
+        }
+        assert_eq!(
+            *packed.buffer().get(),
+            *PackedOrbit::new(&points).buffer().get()
+        );
+    }
+
+    #[test]
+    fn a_point_takes_less_than_5_bytes() {
+        let packed = PackedOrbit::new(&orbit((-0.5, 0.0), 1000));
+        assert!(packed.buffer().get().len() * 4 < 1000 * 5);
+    }
+}
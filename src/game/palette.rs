@@ -0,0 +1,128 @@
+use bytemuck::{Pod, Zeroable};
+
+use to_buffer_representation_derive::ToBufferRepresentation;
+
+use crate::game::to_buffer_representation::ToBufferRepresentation;
+
+// the most stops of a generated palette
+pub const MAXIMUM_STOPS: usize = 8;
+
+// the hues of the stops of a palette around the first one, in turns of the color wheel,
+// the schemes whose hues go well together
+const HARMONIES: [&[f32]; 4] = [
+    // analogous
+    &[0.0, 1.0 / 12.0, -1.0 / 12.0],
+    // complementary
+    &[0.0, 0.5],
+    // triadic
+    &[0.0, 1.0 / 3.0, 2.0 / 3.0],
+    // split complementary
+    &[0.0, 5.0 / 12.0, 7.0 / 12.0],
+];
+
+// the gradient the iterations are colored with, instead of the palette written in the shader
+// a palette is generated from a seed, so the same seed always gives back the same colors
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Pod, Zeroable, ToBufferRepresentation)]
+pub struct Palette {
+    // the color of each stop in x, y and z, and its position along the palette from 0 to 1 in w
+    pub stops: [[f32; 4]; MAXIMUM_STOPS],
+    // the number of stops, 0 for the palette written in the shader
+    pub count: u32,
+    #[padding]
+    _padding: [u32; 3],
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            stops: [[0.0; 4]; MAXIMUM_STOPS],
+            count: 0,
+            _padding: [0; 3],
+        }
+    }
+}
+
+impl Palette {
+    // a random gradient of 4 to 6 stops, whose hues follow one of the harmonies,
+    // alternating dark and light stops so the bands of iterations stay apart
+    pub fn generate(seed: u32) -> Self {
+        let mut random = SplitMix64(seed as u64);
+        let harmony = HARMONIES[(random.next() % HARMONIES.len() as u64) as usize];
+        let count = 4 + (random.next() % 3) as usize;
+        let base_hue = random.unit();
+        let dark_first = random.next().is_multiple_of(2);
+        let mut palette = Self {
+            count: count as u32,
+            ..Self::default()
+        };
+        for (index, stop) in palette.stops.iter_mut().take(count).enumerate() {
+            let hue = base_hue + harmony[index % harmony.len()] + (random.unit() - 0.5) * 0.05;
+            let saturation = 0.45 + 0.45 * random.unit();
+            let lightness = if (index % 2 == 0) == dark_first {
+                0.15 + 0.2 * random.unit()
+            } else {
+                0.55 + 0.3 * random.unit()
+            };
+            // the first and the last stops are at the ends, the others are moved a little
+            let spacing = 1.0 / (count - 1) as f32;
+            let position = if index == 0 || index == count - 1 {
+                index as f32 * spacing
+            } else {
+                (index as f32 + (random.unit() - 0.5) * 0.6) * spacing
+            };
+            let [red, green, blue] = hsl_to_rgb(hue.rem_euclid(1.0), saturation, lightness);
+            *stop = [red, green, blue, position];
+        }
+        palette
+    }
+
+    // the color at a position along the palette from 0 to 1, as the shader computes it,
+    // none for the palette written in the shader
+    pub fn color(&self, t: f32) -> Option<[f32; 3]> {
+        let count = self.count as usize;
+        if count == 0 {
+            return None;
+        }
+        let mut color = [self.stops[0][0], self.stops[0][1], self.stops[0][2]];
+        for index in 1..count {
+            let (previous, stop) = (self.stops[index - 1], self.stops[index]);
+            if t >= previous[3] {
+                let share = ((t - previous[3]) / (stop[3] - previous[3])).clamp(0.0, 1.0);
+                for (channel, value) in color.iter_mut().enumerate() {
+                    *value = previous[channel] + (stop[channel] - previous[channel]) * share;
+                }
+            }
+        }
+        Some(color)
+    }
+}
+
+// a small generator whose numbers never change with the versions of the dependencies,
+// so a seed saved in a file gives the same palette later
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // a number between 0 and 1
+    fn unit(&mut self) -> f32 {
+        (self.next() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+// the hue, the saturation and the lightness between 0 and 1
+fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> [f32; 3] {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let channel = |offset: f32| {
+        let k = (offset + hue * 12.0) % 12.0;
+        lightness - chroma / 2.0 * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+    };
+    [channel(0.0), channel(8.0), channel(4.0)]
+}
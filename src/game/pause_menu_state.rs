@@ -0,0 +1,201 @@
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use winit::event::{ElementState, Event, VirtualKeyCode, WindowEvent};
+use winit::window::Window;
+
+use crate::game::bookmarks;
+use crate::game::command::Command;
+use crate::game::engine::Engine;
+use crate::game::gallery_state::GalleryState;
+use crate::game::game_state::{GameState, Transition};
+use crate::game::TITLE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Item {
+    Resume,
+    ResetView,
+    SaveBookmark,
+    Bookmarks,
+    Settings,
+    Quit,
+}
+
+impl Item {
+    const ALL: [Item; 6] = [
+        Item::Resume,
+        Item::ResetView,
+        Item::SaveBookmark,
+        Item::Bookmarks,
+        Item::Settings,
+        Item::Quit,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Item::Resume => "Resume",
+            Item::ResetView => "Reset view",
+            Item::SaveBookmark => "Save bookmark",
+            Item::Bookmarks => "Bookmarks",
+            Item::Settings => "Settings",
+            Item::Quit => "Quit",
+        }
+    }
+}
+
+// the menu opened by escape over the explorer, instead of quitting at once
+// - Up and Down to choose an item, Enter to select it, or 1 to 6 to select one directly
+// - Escape to resume
+// the menu is written in the title of the window, the explorer gets no key nor mouse
+// and does not move while it is open
+pub struct PauseMenuState {
+    window: Option<Rc<Window>>,
+    // the config file, opened to edit the settings
+    config: PathBuf,
+    selected: usize,
+    // the transitions asked by the selected items, given to the game in order
+    transitions: Vec<Transition>,
+}
+
+impl PauseMenuState {
+    pub fn new(window: Option<Rc<Window>>, config: PathBuf) -> Self {
+        let menu = Self {
+            window,
+            config,
+            selected: 0,
+            transitions: vec![],
+        };
+        let labels = Item::ALL
+            .iter()
+            .enumerate()
+            .map(|(index, item)| format!("{} {}", index + 1, item.label()))
+            .collect::<Vec<_>>();
+        eprintln!("Paused: {}", labels.join(", "));
+        menu.show();
+        menu
+    }
+
+    fn show(&self) {
+        if let Some(window) = &self.window {
+            let labels = Item::ALL
+                .iter()
+                .enumerate()
+                .map(|(index, item)| {
+                    if index == self.selected {
+                        format!("[{}]", item.label())
+                    } else {
+                        item.label().to_string()
+                    }
+                })
+                .collect::<Vec<_>>();
+            window.set_title(&format!("Paused - {}", labels.join("  ")));
+        }
+    }
+
+    fn close(&mut self) {
+        if let Some(window) = &self.window {
+            window.set_title(TITLE);
+        }
+        self.transitions.push(Transition::Pop);
+    }
+
+    fn select(&mut self, item: Item) {
+        match item {
+            Item::Resume => {}
+            Item::ResetView => self.transitions.push(Transition::Command(Command::Reset)),
+            Item::SaveBookmark => {
+                let path = bookmarks::next_path();
+                eprintln!("Saving the bookmark to {}", path.display());
+                self.transitions.push(Transition::Command(Command::Save {
+                    path: path.to_string_lossy().into_owned(),
+                }));
+            }
+            // the gallery replaces the menu
+            Item::Bookmarks => match GalleryState::new(self.window.clone()) {
+                Some(gallery) => {
+                    self.transitions.push(Transition::Push(Box::new(gallery)));
+                    self.transitions.push(Transition::Pop);
+                    return;
+                }
+                None => {
+                    eprintln!("No bookmark saved yet");
+                    return;
+                }
+            },
+            // the config file is reloaded as soon as it is saved, the menu stays open meanwhile
+            Item::Settings => {
+                #[cfg(not(target_arch = "wasm32"))]
+                match crate::game::config::open_in_editor(&self.config) {
+                    Ok(()) => eprintln!(
+                        "Settings: {} opened, the changes are applied as soon as it is saved",
+                        self.config.display()
+                    ),
+                    Err(error) => {
+                        eprintln!("Impossible to open {}: {}", self.config.display(), error)
+                    }
+                }
+                #[cfg(target_arch = "wasm32")]
+                eprintln!("There is no config file in the browser");
+                return;
+            }
+            Item::Quit => {
+                self.transitions.push(Transition::Command(Command::Quit));
+                return;
+            }
+        }
+        self.close();
+    }
+}
+
+impl GameState for PauseMenuState {
+    fn update(&mut self, _engine: &mut Engine, _delta_time: f32) {}
+
+    fn input(&mut self, event: &Event<()>, _engine: &mut Engine) {
+        let keycode = match event {
+            Event::WindowEvent {
+                event: WindowEvent::KeyboardInput { input, .. },
+                ..
+            } if input.state == ElementState::Pressed => input.virtual_keycode,
+            _ => None,
+        };
+        match keycode {
+            Some(VirtualKeyCode::Escape) => self.close(),
+            Some(VirtualKeyCode::Up) => {
+                self.selected = (self.selected + Item::ALL.len() - 1) % Item::ALL.len();
+                self.show();
+            }
+            Some(VirtualKeyCode::Down) => {
+                self.selected = (self.selected + 1) % Item::ALL.len();
+                self.show();
+            }
+            Some(VirtualKeyCode::Return) => self.select(Item::ALL[self.selected]),
+            Some(VirtualKeyCode::Key1) => self.select(Item::Resume),
+            Some(VirtualKeyCode::Key2) => self.select(Item::ResetView),
+            Some(VirtualKeyCode::Key3) => self.select(Item::SaveBookmark),
+            Some(VirtualKeyCode::Key4) => self.select(Item::Bookmarks),
+            Some(VirtualKeyCode::Key5) => self.select(Item::Settings),
+            Some(VirtualKeyCode::Key6) => self.select(Item::Quit),
+            _ => {}
+        }
+    }
+
+    fn captures_input(&self) -> bool {
+        true
+    }
+
+    fn pauses_simulation(&self) -> bool {
+        true
+    }
+
+    fn shows_title(&self) -> bool {
+        true
+    }
+
+    fn transition(&mut self) -> Transition {
+        if self.transitions.is_empty() {
+            Transition::None
+        } else {
+            self.transitions.remove(0)
+        }
+    }
+}
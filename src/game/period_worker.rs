@@ -0,0 +1,90 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use crate::game::big_real::{BigReal, Real};
+use crate::game::iteration::{find_nucleus, find_period};
+use crate::game::location::Location;
+
+// the most steps of the method of newton to reach the nucleus
+const NEWTON_STEPS: u32 = 64;
+
+// the minibrot found at the center of a view
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Minibrot {
+    pub period: u32,
+    // the distance from the center of the view to the nucleus, none when newton has not converged
+    pub distance: Option<f64>,
+}
+
+impl Minibrot {
+    // a short description of the minibrot, like "period 134, nucleus at 2.5e-12"
+    pub fn summary(&self) -> String {
+        match self.distance {
+            Some(distance) => format!("period {}, nucleus at {:.1e}", self.period, distance),
+            None => format!("period {}", self.period),
+        }
+    }
+}
+
+// search the minibrot at the center of a view in arbitrary precision on the rayon pool,
+// while the frames are still drawn
+// the period is the first one whose atom domain meets the view, then newton finds its nucleus
+pub struct PeriodWorker {
+    sender: Sender<(Location, Option<Minibrot>)>,
+    receiver: Receiver<(Location, Option<Minibrot>)>,
+    // the view searched last, the results of the other ones are dropped
+    searched: Option<Location>,
+    // what has been found in the view searched last, none while it is searched
+    found: Option<Minibrot>,
+}
+
+impl PeriodWorker {
+    pub fn new() -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            sender,
+            receiver,
+            searched: None,
+            found: None,
+        }
+    }
+
+    // search the minibrot of a view, unless it is the one searched last
+    pub fn search(&mut self, location: Location, maximum_iterations: u32, mu: f64) {
+        if self.searched.as_ref() == Some(&location) {
+            return;
+        }
+        self.searched = Some(location.clone());
+        self.found = None;
+        let sender = self.sender.clone();
+        let task = move || {
+            let c = (Real::clone(&location.x), Real::clone(&location.y));
+            let radius = location.zoom as f64;
+            let minibrot = find_period(&c, radius, maximum_iterations, mu).map(|period| {
+                let distance = find_nucleus(&c, period, radius, NEWTON_STEPS).map(|nucleus| {
+                    let x = nucleus.0.minus(&c.0).to_double();
+                    let y = nucleus.1.minus(&c.1).to_double();
+                    x.hypot(y)
+                });
+                Minibrot { period, distance }
+            });
+            sender.send((location, minibrot)).ok();
+        };
+        // there is no thread in the browser
+        #[cfg(target_arch = "wasm32")]
+        task();
+        #[cfg(not(target_arch = "wasm32"))]
+        rayon::spawn(task);
+    }
+
+    // the minibrot found at the center of the given view, none while it is searched,
+    // when no minibrot has been found or when another view is searched
+    pub fn minibrot(&mut self, location: &Location) -> Option<Minibrot> {
+        for (searched, minibrot) in self.receiver.try_iter() {
+            if self.searched.as_ref() == Some(&searched) {
+                self.found = minibrot;
+            }
+        }
+        self.found
+            .filter(|_| self.searched.as_ref() == Some(location))
+    }
+}
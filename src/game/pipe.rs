@@ -0,0 +1,47 @@
+use std::io::{BufRead, Write};
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+
+use crate::game::command::Command;
+
+// read commands from stdin on a separate thread, so the event loop is never blocked,
+// and write the status of each command as a json line to stdout
+pub struct Pipe {
+    receiver: Receiver<Result<Command, String>>,
+}
+
+impl Pipe {
+    pub fn new() -> Self {
+        let (sender, receiver) = channel();
+        thread::spawn(move || {
+            let stdin = std::io::stdin();
+            for line in stdin.lock().lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                if sender.send(Command::parse(&line)).is_err() {
+                    return;
+                }
+            }
+            // when stdin is closed there is nothing left to drive the explorer
+            sender.send(Ok(Command::Quit)).ok();
+        });
+        Self { receiver }
+    }
+
+    // the next command received, if any
+    pub fn try_recv(&self) -> Option<Result<Command, String>> {
+        self.receiver.try_recv().ok()
+    }
+
+    pub fn respond(&self, status: serde_json::Value) {
+        let stdout = std::io::stdout();
+        let mut stdout = stdout.lock();
+        writeln!(stdout, "{}", status).ok();
+        stdout.flush().ok();
+    }
+}
@@ -0,0 +1,62 @@
+use std::fs;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, TryLockError};
+
+use crate::game::session::Session;
+
+// the navigation written to a recovery file when the explorer panics, like on an error of the driver,
+// and offered at the next launch, so a long dive is not lost
+// the hook of the panic can not reach the game, which gives it the session at each frame
+pub struct Recovery {
+    session: Arc<Mutex<Option<Session>>>,
+}
+
+impl Recovery {
+    // write the last session given to the file when a panic happens, after the message of the panic
+    pub fn install(path: PathBuf) -> Self {
+        let session = Arc::new(Mutex::new(None::<Session>));
+        let shared = session.clone();
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            previous_hook(info);
+            // the panic may happen while the session is given
+            let session = match shared.try_lock() {
+                Ok(session) => session.clone(),
+                Err(TryLockError::Poisoned(error)) => error.into_inner().clone(),
+                Err(TryLockError::WouldBlock) => None,
+            };
+            if let Some(session) = session {
+                match session.save(&path) {
+                    Ok(()) => eprintln!("The view has been saved to {}", path.display()),
+                    Err(error) => eprintln!("Impossible to save the view: {}", error),
+                }
+            }
+        }));
+        Self { session }
+    }
+
+    // the session to write if the explorer panics
+    pub fn update(&self, session: Session) {
+        if let Ok(mut shared) = self.session.lock() {
+            *shared = Some(session);
+        }
+    }
+
+    // the session written when the last launch panicked, the file is removed so it is offered once
+    pub fn take(path: &Path) -> Option<Session> {
+        match Session::load(path) {
+            Ok(Some(session)) => {
+                if let Err(error) = fs::remove_file(path) {
+                    eprintln!("Impossible to remove {}: {}", path.display(), error);
+                }
+                Some(session)
+            }
+            Ok(None) => None,
+            Err(error) => {
+                eprintln!("Impossible to read {}: {}", path.display(), error);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use std::io::ErrorKind;
+use std::net::{IpAddr, TcpListener, TcpStream};
+use std::path::{Component, Path};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use tungstenite::{Message, WebSocket};
+use winit::event::Event;
+
+use crate::game::command::Command;
+use crate::game::engine::Engine;
+use crate::game::game_state::{GameState, Transition};
+use crate::game::mamndelbrot_state::MandelbrotState;
+
+// the time between two state updates streamed to the clients, in seconds
+const UPDATE_INTERVAL: f32 = 0.1;
+
+// a command received from a client, with the channel to send its status back to this client
+type Request = (Sender<serde_json::Value>, Result<Command, String>);
+
+// drive the explorer remotely: the commands of the pipe mode are received as websocket messages,
+// the status of each command is sent back to the client which sent it,
+// and the changes of the location are streamed to every client
+// each client is served on its own thread, so the event loop is never blocked
+// the clients may be on another machine, so the files they write are kept in the working directory
+pub struct RemoteState {
+    receiver: Receiver<Request>,
+    // the channels to the threads of the connected clients
+    clients: Arc<Mutex<Vec<Sender<serde_json::Value>>>>,
+    // set when a client connects, so it receives the current state
+    client_connected: Arc<AtomicBool>,
+    last_status: Option<serde_json::Value>,
+    since_last_update: f32,
+    // the commands received, given to the game in order
+    transitions: Vec<Transition>,
+}
+
+impl RemoteState {
+    pub fn listen(address: IpAddr, port: u16) -> Result<Self, String> {
+        let listener = TcpListener::bind((address, port)).map_err(|error| error.to_string())?;
+        let (sender, receiver) = channel();
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let client_connected = Arc::new(AtomicBool::new(false));
+        let accept_clients = clients.clone();
+        let accept_client_connected = client_connected.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+                let (client_sender, client_receiver) = channel();
+                accept_clients.lock().unwrap().push(client_sender.clone());
+                accept_client_connected.store(true, Ordering::Relaxed);
+                let sender = sender.clone();
+                thread::spawn(move || serve(stream, sender, client_sender, client_receiver));
+            }
+        });
+        eprintln!(
+            "Listening for websocket connections on {}:{}",
+            address, port
+        );
+        Ok(Self {
+            receiver,
+            clients,
+            client_connected,
+            last_status: None,
+            since_last_update: 0.0,
+            transitions: vec![],
+        })
+    }
+
+    // send a message to every client, forgetting the ones that are disconnected
+    fn broadcast(&self, message: serde_json::Value) {
+        self.clients
+            .lock()
+            .unwrap()
+            .retain(|client| client.send(message.clone()).is_ok());
+    }
+
+    // stream the state to the clients when it has changed since the last update,
+    // or when a new client is connected
+    fn send_update(&mut self, status: serde_json::Value) {
+        if self.since_last_update < UPDATE_INTERVAL {
+            return;
+        }
+        self.since_last_update = 0.0;
+        let client_connected = self.client_connected.swap(false, Ordering::Relaxed);
+        if !client_connected && self.last_status.as_ref() == Some(&status) {
+            return;
+        }
+        let mut update = status.clone();
+        update["status"] = "update".into();
+        self.broadcast(update);
+        self.last_status = Some(status);
+    }
+}
+
+impl GameState for RemoteState {
+    // give the commands received since the last frame to the game,
+    // the invalid ones are answered at once
+    fn update(&mut self, _engine: &mut Engine, delta_time: f32) {
+        self.since_last_update += delta_time;
+        while let Ok((client, command)) = self.receiver.try_recv() {
+            match command {
+                Ok(command) => {
+                    // the screenshot is taken on the next render, wait for it before going on
+                    let is_screenshot = matches!(command, Command::Screenshot { .. });
+                    self.transitions.push(Transition::Request(command, client));
+                    if is_screenshot {
+                        break;
+                    }
+                }
+                // the client may be disconnected already
+                Err(error) => {
+                    let _ = client.send(serde_json::json!({
+                        "status": "error",
+                        "error": error,
+                    }));
+                }
+            }
+        }
+    }
+
+    fn input(&mut self, _event: &Event<()>, _engine: &mut Engine) {}
+
+    fn drive(&mut self, explorer: &mut MandelbrotState, _engine: &mut Engine, _delta_time: f32) {
+        self.send_update(explorer.status());
+    }
+
+    fn serves_clients(&self) -> bool {
+        true
+    }
+
+    fn transition(&mut self) -> Transition {
+        if self.transitions.is_empty() {
+            Transition::None
+        } else {
+            self.transitions.remove(0)
+        }
+    }
+}
+
+// only the relative paths that do not go up are accepted for the files written,
+// so a client cannot overwrite a file outside the working directory
+fn check_path(command: Command) -> Result<Command, String> {
+    if let Command::Screenshot { path } | Command::Exr { path } | Command::Save { path } = &command
+    {
+        let inside = Path::new(path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+        if !inside {
+            return Err(format!("{} is outside the working directory", path));
+        }
+    }
+    Ok(command)
+}
+
+// read the commands of a client and write it the messages for it until it is disconnected
+// the reads time out, so the messages are written while the client is silent
+fn serve(
+    stream: TcpStream,
+    sender: Sender<Request>,
+    client: Sender<serde_json::Value>,
+    messages: Receiver<serde_json::Value>,
+) {
+    let mut socket: WebSocket<TcpStream> = match tungstenite::accept(stream) {
+        Ok(socket) => socket,
+        Err(error) => {
+            eprintln!("Websocket handshake failed: {}", error);
+            return;
+        }
+    };
+    // the handshake is done with blocking reads
+    if socket
+        .get_ref()
+        .set_read_timeout(Some(Duration::from_millis(20)))
+        .is_err()
+    {
+        return;
+    }
+    loop {
+        match socket.read_message() {
+            Ok(Message::Text(text)) => {
+                if text.trim().is_empty() {
+                    continue;
+                }
+                let command = Command::parse(&text).and_then(check_path);
+                if sender.send((client.clone(), command)).is_err() {
+                    return;
+                }
+            }
+            Ok(Message::Close(_)) => return,
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(error))
+                if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(_) => return,
+        }
+        while let Ok(message) = messages.try_recv() {
+            if socket
+                .write_message(Message::Text(message.to_string()))
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screenshot(path: &str) -> Command {
+        Command::Screenshot {
+            path: path.to_string(),
+        }
+    }
+
+    #[test]
+    fn the_files_are_written_in_the_working_directory() {
+        assert!(check_path(screenshot("out.png")).is_ok());
+        assert!(check_path(screenshot("frames/out.png")).is_ok());
+        for path in [
+            "/tmp/out.png",
+            "../out.png",
+            "frames/../../out.png",
+            "./out.png",
+        ] {
+            assert!(check_path(screenshot(path)).is_err(), "{}", path);
+        }
+        let exr = Command::Exr {
+            path: "/etc/out.exr".to_string(),
+        };
+        assert!(check_path(exr).is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use winit::dpi::PhysicalSize;
+
+use crate::game::config::NavigationConfig;
+use crate::game::engine::Engine;
+use crate::game::game_state::GameState;
+use crate::game::location::Location;
+use crate::game::mamndelbrot_state::MandelbrotState;
+
+// what to render in a frame
+#[derive(Debug, Clone)]
+pub struct RenderParams {
+    pub location: Location,
+    // the size of the texture view rendered into
+    pub width: u32,
+    pub height: u32,
+    pub color_palette_scale: f32,
+    // the time since the previous frame, in seconds
+    pub delta_time: f32,
+}
+
+// draw the mandelbrot set with the device of another wgpu application,
+// so it can be embedded as a component, without a window or an event loop
+// the previous frame is reused when the location moves continuously, like in the explorer
+pub struct MandelbrotRenderer {
+    engine: Engine,
+    state: MandelbrotState,
+}
+
+impl MandelbrotRenderer {
+    pub fn new(
+        device: Arc<wgpu::Device>,
+        queue: Arc<wgpu::Queue>,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let size = PhysicalSize::new(1, 1);
+        let mut engine = Engine::from_device(device, queue, format, size);
+        // the camera is only moved by the render parameters
+        let navigation = NavigationConfig {
+            zoom_speed: 0.0,
+            ..NavigationConfig::default()
+        };
+        let state = MandelbrotState::new(size, &mut engine, navigation);
+        state.set_dither(&engine, true);
+        engine.create_pipeline();
+        Self { engine, state }
+    }
+
+    // draw a frame in a texture view of the format given at creation
+    pub fn render(&mut self, view: &wgpu::TextureView, params: RenderParams) {
+        let size = PhysicalSize::new(params.width.max(1), params.height.max(1));
+        if size != self.state.size() {
+            self.engine.resize(size);
+            self.state.resize(size);
+            self.state.invalidate();
+        }
+        self.state.set_location(params.location);
+        self.state
+            .set_color_palette_scale(params.color_palette_scale);
+        self.state.update(&mut self.engine, params.delta_time);
+        self.engine.render_to_view(view, size);
+        self.state.end_frame();
+    }
+}
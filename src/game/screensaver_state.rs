@@ -0,0 +1,108 @@
+use rand::Rng;
+use winit::event::{Event, WindowEvent};
+
+use crate::game::big_real::BigReal;
+use crate::game::bookmarks;
+use crate::game::command::Command;
+use crate::game::engine::Engine;
+use crate::game::game_state::{GameState, Transition};
+use crate::game::location::Location;
+
+// fly through the set while nobody uses the explorer, like a screensaver
+// the camera slowly zooms into a random bookmark, then explores from there,
+// or explores from the current view when there is no bookmark
+// any key, click or move of the mouse goes back to the view the user left
+pub struct ScreensaverState {
+    // the view of the user, restored when the screensaver stops
+    view: Location,
+    // the seconds left until the bookmark is reached and the exploration starts,
+    // none once exploring
+    flight: Option<f32>,
+    stopped: bool,
+    // the transitions to give to the game, in order
+    transitions: Vec<Transition>,
+}
+
+impl ScreensaverState {
+    pub fn new(view: Location, flight_duration: f32) -> Self {
+        let bookmarks = bookmarks::load_all();
+        let mut transitions = vec![];
+        let flight = if bookmarks.is_empty() {
+            transitions.push(Transition::Command(Command::Explore { enabled: true }));
+            None
+        } else {
+            let target = &bookmarks[rand::thread_rng().gen_range(0..bookmarks.len())];
+            transitions.push(Transition::Command(Command::Goto {
+                x: target.x.to_text(),
+                y: target.y.to_text(),
+                zoom: Some(target.zoom),
+                duration: Some(flight_duration),
+            }));
+            Some(flight_duration)
+        };
+        eprintln!("Screensaver started");
+        Self {
+            view,
+            flight,
+            stopped: false,
+            transitions,
+        }
+    }
+
+    fn stop(&mut self) {
+        if self.stopped {
+            return;
+        }
+        self.stopped = true;
+        eprintln!("Screensaver stopped");
+        self.transitions = vec![
+            Transition::Command(Command::Explore { enabled: false }),
+            Transition::Command(Command::Goto {
+                x: self.view.x.to_text(),
+                y: self.view.y.to_text(),
+                zoom: Some(self.view.zoom),
+                duration: None,
+            }),
+            Transition::Pop,
+        ];
+    }
+}
+
+impl GameState for ScreensaverState {
+    fn update(&mut self, _engine: &mut Engine, delta_time: f32) {
+        if let Some(time) = self.flight.as_mut() {
+            *time -= delta_time;
+            if *time <= 0.0 && !self.stopped {
+                self.flight = None;
+                self.transitions
+                    .push(Transition::Command(Command::Explore { enabled: true }));
+            }
+        }
+    }
+
+    fn input(&mut self, event: &Event<()>, _engine: &mut Engine) {
+        if let Event::WindowEvent {
+            event:
+                WindowEvent::KeyboardInput { .. }
+                | WindowEvent::MouseInput { .. }
+                | WindowEvent::MouseWheel { .. }
+                | WindowEvent::CursorMoved { .. },
+            ..
+        } = event
+        {
+            self.stop();
+        }
+    }
+
+    fn captures_input(&self) -> bool {
+        true
+    }
+
+    fn transition(&mut self) -> Transition {
+        if self.transitions.is_empty() {
+            Transition::None
+        } else {
+            self.transitions.remove(0)
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use winit::event::Event;
+
+use crate::game::command::Command;
+use crate::game::engine::Engine;
+use crate::game::game_state::{GameState, Transition};
+use crate::game::keyframes::KeyframePlayer;
+use crate::game::mamndelbrot_state::MandelbrotState;
+
+// play a script of keyframes, or the benchmark, with a fixed time step
+// each frame the explorer is moved to the state of the script at that time, and saved when exported
+// the state is removed once the script is over, the explorer quits if it was exported
+pub struct ScriptState {
+    player: KeyframePlayer,
+    // the transitions to give to the game, in order
+    transitions: Vec<Transition>,
+}
+
+impl ScriptState {
+    pub fn new(player: KeyframePlayer) -> Self {
+        Self {
+            player,
+            transitions: vec![],
+        }
+    }
+
+    fn finish(&mut self, engine: &mut Engine) {
+        eprintln!("Script finished");
+        let mut quit = self.player.is_exporting();
+        match engine.finish_video() {
+            Some(Ok(path)) => {
+                eprintln!("Video saved to {}", path.display());
+                quit = true;
+            }
+            Some(Err(error)) => {
+                eprintln!("Impossible to finish the video: {}", error);
+                quit = true;
+            }
+            None => {}
+        }
+        if quit {
+            self.transitions.push(Transition::Command(Command::Quit));
+        }
+        self.transitions.push(Transition::Pop);
+    }
+}
+
+impl GameState for ScriptState {
+    fn update(&mut self, _engine: &mut Engine, _delta_time: f32) {}
+
+    fn input(&mut self, _event: &Event<()>, _engine: &mut Engine) {}
+
+    fn drive(&mut self, explorer: &mut MandelbrotState, engine: &mut Engine, _delta_time: f32) {
+        let (sample, path) = match self.player.next_frame() {
+            Some(frame) => frame,
+            None => {
+                self.finish(engine);
+                return;
+            }
+        };
+        explorer.set_location(sample.location);
+        if let Some(color_palette_scale) = sample.color_palette_scale {
+            explorer.set_color_palette_scale(color_palette_scale);
+        }
+        if let Some(path) = path {
+            self.transitions
+                .push(Transition::Command(Command::Screenshot {
+                    path: path.to_string_lossy().into_owned(),
+                }));
+        }
+        engine.request_video_frame();
+    }
+
+    fn is_driving(&self) -> bool {
+        true
+    }
+
+    fn time_step(&self) -> Option<f32> {
+        Some(self.player.delta_time())
+    }
+
+    fn transition(&mut self) -> Transition {
+        if self.transitions.is_empty() {
+            Transition::None
+        } else {
+            self.transitions.remove(0)
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::location::Location;
+
+// the state of the navigation saved when the explorer exits, and restored at the next launch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub location: Location,
+    pub zoom_speed: f32,
+    // the number of iterations added each time the zoom is divided by 2.1
+    pub iteration_speed: u32,
+    pub color_palette_scale: f32,
+    // the way the pixels are colored, a Coloring
+    pub coloring: u32,
+}
+
+impl Session {
+    // read the session saved in the file, none when there is no file
+    pub fn load(path: &Path) -> Result<Option<Self>, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map(Some)
+                .map_err(|error| error.to_string()),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(self).map_err(|error| error.to_string())?;
+        fs::write(path, text).map_err(|error| error.to_string())
+    }
+}
@@ -0,0 +1,159 @@
+use std::f32::consts::PI;
+use std::sync::{Arc, Mutex};
+
+use crate::game::iteration::{norm, step};
+
+// the orbit of a point played as a sound, each point of the orbit in turn for a short note,
+// again and again: the frequency follows the modulus of z and the balance between the left
+// and the right speakers follows its argument
+// the orbit stops where it escapes, so the points far outside the set are silent
+// the sound is only played when built with the sound feature
+
+// the most points of the orbit played before it starts again
+const ORBIT_LENGTH: u32 = 200;
+// the modulus of z past which the orbit has escaped
+const ESCAPE_RADIUS: f64 = 2.0;
+// the frequency of z at 0, each unit of its modulus adds two octaves
+const BASE_FREQUENCY: f32 = 110.0;
+const OCTAVES_PER_UNIT: f32 = 2.0;
+// the length of the note of each point of the orbit, in seconds
+#[cfg(all(feature = "sound", not(target_arch = "wasm32")))]
+const NOTE_SECONDS: f32 = 0.08;
+#[cfg(all(feature = "sound", not(target_arch = "wasm32")))]
+const VOLUME: f32 = 0.2;
+
+// the notes of the orbit, the frequency in hertz and the balance from -1 on the left
+// to 1 on the right, shared with the thread of the sound
+type Notes = Arc<Mutex<Vec<(f32, f32)>>>;
+
+#[cfg(all(feature = "sound", not(target_arch = "wasm32")))]
+type Stream = cpal::Stream;
+#[cfg(not(all(feature = "sound", not(target_arch = "wasm32"))))]
+type Stream = ();
+
+pub struct Sonification {
+    notes: Notes,
+    // the sound stops when the stream is dropped
+    _stream: Stream,
+    // the point whose orbit is played
+    point: Option<(f64, f64)>,
+}
+
+impl Sonification {
+    // start the sound on the default output, silent until a point is given
+    pub fn start() -> Result<Self, String> {
+        let notes = Notes::default();
+        Ok(Self {
+            _stream: play(notes.clone())?,
+            notes,
+            point: None,
+        })
+    }
+
+    // play the orbit of z² + c from z at 0
+    pub fn set_point(&mut self, c: (f64, f64)) {
+        if self.point == Some(c) {
+            return;
+        }
+        self.point = Some(c);
+        let notes = orbit_notes(c);
+        *self.notes.lock().unwrap() = notes;
+    }
+}
+
+fn orbit_notes(c: (f64, f64)) -> Vec<(f32, f32)> {
+    let mut z = (0.0, 0.0);
+    let mut notes = vec![];
+    for _ in 0..ORBIT_LENGTH {
+        z = step(&z, &c);
+        let modulus = norm(&z).sqrt();
+        if modulus > ESCAPE_RADIUS {
+            break;
+        }
+        let frequency = BASE_FREQUENCY * (modulus as f32 * OCTAVES_PER_UNIT).exp2();
+        let balance = z.1.atan2(z.0) as f32 / PI;
+        notes.push((frequency, balance));
+    }
+    notes
+}
+
+#[cfg(not(all(feature = "sound", not(target_arch = "wasm32"))))]
+fn play(_notes: Notes) -> Result<Stream, String> {
+    Err("built without the sound feature, run with --features sound".to_string())
+}
+
+#[cfg(all(feature = "sound", not(target_arch = "wasm32")))]
+fn play(notes: Notes) -> Result<Stream, String> {
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+    let device = cpal::default_host()
+        .default_output_device()
+        .ok_or("there is no sound output")?;
+    let config = device
+        .default_output_config()
+        .map_err(|error| error.to_string())?;
+    let stream = match config.sample_format() {
+        cpal::SampleFormat::F32 => build_stream::<f32>(&device, &config.into(), notes),
+        cpal::SampleFormat::I16 => build_stream::<i16>(&device, &config.into(), notes),
+        cpal::SampleFormat::U16 => build_stream::<u16>(&device, &config.into(), notes),
+        format => return Err(format!("the samples of the sound output are {}", format)),
+    }?;
+    stream.play().map_err(|error| error.to_string())?;
+    Ok(stream)
+}
+
+#[cfg(all(feature = "sound", not(target_arch = "wasm32")))]
+fn build_stream<T: cpal::SizedSample + cpal::FromSample<f32>>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    notes: Notes,
+) -> Result<Stream, String> {
+    use cpal::traits::DeviceTrait;
+
+    let sample_rate = config.sample_rate.0 as f32;
+    let channels = config.channels as usize;
+    let note_samples = (NOTE_SECONDS * sample_rate) as u32;
+    // the wave goes on from one note to the next, so they follow each other without a click
+    let mut phase = 0.0f32;
+    let mut note = 0;
+    let mut note_sample = 0;
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _| {
+                // the notes being changed are not waited for, this part is silent instead
+                let guard = notes.try_lock();
+                let notes = guard.as_deref().map_or(&[][..], |notes| &notes[..]);
+                for frame in data.chunks_mut(channels) {
+                    let (frequency, balance) = match notes.len() {
+                        0 => (0.0, 0.0),
+                        length => notes[note % length],
+                    };
+                    phase = (phase + frequency / sample_rate).fract();
+                    let value = (phase * 2.0 * PI).sin() * VOLUME;
+                    let angle = (balance + 1.0) * PI / 4.0;
+                    let (left, right) = if notes.is_empty() {
+                        (0.0, 0.0)
+                    } else {
+                        (value * angle.cos(), value * angle.sin())
+                    };
+                    for (channel, sample) in frame.iter_mut().enumerate() {
+                        *sample = T::from_sample(match channel {
+                            0 if channels == 1 => left + right,
+                            0 => left,
+                            1 => right,
+                            _ => 0.0,
+                        });
+                    }
+                    note_sample += 1;
+                    if note_sample >= note_samples {
+                        note_sample = 0;
+                        note += 1;
+                    }
+                }
+            },
+            |error| eprintln!("Impossible to play the orbit: {}", error),
+            None,
+        )
+        .map_err(|error| error.to_string())
+}
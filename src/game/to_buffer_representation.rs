@@ -2,6 +2,11 @@ use bytemuck::{Pod, Zeroable};
 
 pub trait ToBufferRepresentation {
     fn to_bits(&self) -> &[u8];
+
+    // the number of bytes of the data, to size the buffer without reading them
+    fn len_bytes(&self) -> usize {
+        self.to_bits().len()
+    }
 }
 
 // implement the trait for Vec<T> that are Pod, Zeroable, Copy and Clone
@@ -12,4 +17,50 @@ where
     fn to_bits(&self) -> &[u8] {
         bytemuck::cast_slice(self.as_slice())
     }
+
+    fn len_bytes(&self) -> usize {
+        std::mem::size_of_val(self.as_slice())
+    }
+}
+
+// the slices that do not grow, like the tables read by the shaders
+impl<T: Pod> ToBufferRepresentation for Box<[T]> {
+    fn to_bits(&self) -> &[u8] {
+        bytemuck::cast_slice(self)
+    }
+
+    fn len_bytes(&self) -> usize {
+        std::mem::size_of_val(&**self)
+    }
 }
+
+// the arrays of any Pod, a blanket impl for every Pod would overlap with the ones of the
+// containers and of the derive
+impl<T: Pod, const N: usize> ToBufferRepresentation for [T; N] {
+    fn to_bits(&self) -> &[u8] {
+        bytemuck::cast_slice(self)
+    }
+
+    fn len_bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
+}
+
+// the single values, which do not need a struct of their own
+macro_rules! impl_to_buffer_representation {
+    ($($ty:ty),*) => {
+        $(
+            impl ToBufferRepresentation for $ty {
+                fn to_bits(&self) -> &[u8] {
+                    bytemuck::bytes_of(self)
+                }
+
+                fn len_bytes(&self) -> usize {
+                    std::mem::size_of::<Self>()
+                }
+            }
+        )*
+    };
+}
+
+impl_to_buffer_representation!(f32, i32, u32);
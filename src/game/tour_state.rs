@@ -0,0 +1,253 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use winit::event::{ElementState, Event, VirtualKeyCode, WindowEvent};
+
+use crate::game::camera_animator::{CameraAnimator, Easing};
+use crate::game::config::{Config, TourConfig};
+use crate::game::engine::Engine;
+use crate::game::game_state::GameState;
+use crate::game::location::Location;
+use crate::game::mamndelbrot_state::{is_navigation_key, MandelbrotState};
+
+// a location of a tour, and the time to fly to it from the previous one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Waypoint {
+    #[serde(flatten)]
+    pub location: Location,
+    pub duration: f32,
+    // the size of the image of the waypoint rendered by the gallery, the one of --size when missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    // the maximum number of iterations of the image, the one given by the zoom when missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iterations: Option<u32>,
+}
+
+// record a sequence of locations and play them back as a continuous flight
+// - R to record the current location as a waypoint
+// - T to play or stop the tour
+// - Backspace to remove the last waypoint
+// - M to turn and zoom slowly at constant rates, for the unattended recordings
+pub struct TourState {
+    path: PathBuf,
+    waypoints: Vec<Waypoint>,
+    // the duration of the legs recorded and the rates of the cinematic mode
+    config: TourConfig,
+    // true while the camera turns and zooms at constant rates
+    cinematic: bool,
+    // true once after the cinematic mode has been stopped, so the camera stops with it
+    cinematic_stopped: bool,
+    // the index of the next waypoint to fly to while the tour is played
+    next_waypoint: Option<usize>,
+    record_requested: bool,
+    // the time since the tour has been started
+    elapsed: f32,
+}
+
+// the waypoints of a tour file, none when it does not exist yet
+pub fn load_waypoints(path: &Path) -> Vec<Waypoint> {
+    if !path.exists() {
+        return vec![];
+    }
+    fs::read_to_string(path)
+        .map_err(|error| error.to_string())
+        .and_then(|text| serde_json::from_str(&text).map_err(|error| error.to_string()))
+        .unwrap_or_else(|error| {
+            eprintln!("Impossible to read the tour {}: {}", path.display(), error);
+            vec![]
+        })
+}
+
+impl TourState {
+    // create a tour saved in the given file, with the waypoints already in it
+    pub fn new(path: PathBuf, config: TourConfig) -> Self {
+        Self {
+            waypoints: load_waypoints(&path),
+            path,
+            config,
+            cinematic: false,
+            cinematic_stopped: false,
+            next_waypoint: None,
+            record_requested: false,
+            elapsed: 0.0,
+        }
+    }
+
+    // true once after the record key has been pressed
+    fn take_record_request(&mut self) -> bool {
+        std::mem::take(&mut self.record_requested)
+    }
+
+    // add a waypoint at the end of the tour and save it
+    fn record(&mut self, location: Location) {
+        self.waypoints.push(Waypoint {
+            location,
+            duration: self.config.leg_duration,
+            width: None,
+            height: None,
+            iterations: None,
+        });
+        eprintln!("Waypoint {} recorded", self.waypoints.len());
+        self.save();
+    }
+
+    // remove the waypoint recorded last, so a wrong one does not cost the whole tour
+    fn remove_last(&mut self) {
+        self.stop();
+        if self.waypoints.pop().is_none() {
+            eprintln!("No waypoint to remove");
+            return;
+        }
+        eprintln!("Waypoint {} removed", self.waypoints.len() + 1);
+        self.save();
+    }
+
+    fn play(&mut self) {
+        if self.waypoints.is_empty() {
+            eprintln!("No waypoint to play, press R to record one");
+            return;
+        }
+        self.stop_cinematic();
+        self.next_waypoint = Some(0);
+        self.elapsed = 0.0;
+    }
+
+    fn stop(&mut self) {
+        self.next_waypoint = None;
+        self.stop_cinematic();
+    }
+
+    fn toggle_cinematic(&mut self) {
+        if self.cinematic {
+            self.stop_cinematic();
+        } else {
+            self.next_waypoint = None;
+            self.cinematic = true;
+            eprintln!("Cinematic mode started");
+        }
+    }
+
+    fn stop_cinematic(&mut self) {
+        if self.cinematic {
+            self.cinematic = false;
+            self.cinematic_stopped = true;
+            eprintln!("Cinematic mode stopped");
+        }
+    }
+
+    // true once after the cinematic mode has been stopped
+    fn take_cinematic_stop(&mut self) -> bool {
+        std::mem::take(&mut self.cinematic_stopped)
+    }
+
+    fn is_playing(&self) -> bool {
+        self.next_waypoint.is_some()
+    }
+
+    // the animation to the next waypoint, once the camera has reached the previous one
+    // or the endless motion of the cinematic mode, once the camera is free
+    fn next_leg(&mut self, current: Location, camera_is_animating: bool) -> Option<CameraAnimator> {
+        if self.cinematic && !camera_is_animating {
+            return Some(CameraAnimator::endless(
+                current,
+                self.config.cinematic_zoom_speed,
+                self.config.cinematic_rotation_speed,
+            ));
+        }
+        let index = self.next_waypoint?;
+        if camera_is_animating {
+            return None;
+        }
+        if index >= self.waypoints.len() {
+            eprintln!("Tour finished in {:.1} seconds", self.elapsed);
+            self.stop();
+            return None;
+        }
+        self.next_waypoint = Some(index + 1);
+        // only slow down at the start and at the end of the tour, so the flight is continuous
+        let last = self.waypoints.len() - 1;
+        let easing = match index {
+            _ if last == 0 => Easing::InOut,
+            0 => Easing::In,
+            _ if index == last => Easing::Out,
+            _ => Easing::Linear,
+        };
+        let waypoint = &self.waypoints[index];
+        Some(
+            CameraAnimator::new(current, waypoint.location.clone(), waypoint.duration)
+                .with_easing(easing),
+        )
+    }
+
+    fn save(&self) {
+        let result = serde_json::to_string_pretty(&self.waypoints)
+            .map_err(|error| error.to_string())
+            .and_then(|text| fs::write(&self.path, text).map_err(|error| error.to_string()));
+        if let Err(error) = result {
+            eprintln!("Impossible to save the tour {}: {}", self.path.display(), error);
+        }
+    }
+}
+
+impl GameState for TourState {
+    fn update(&mut self, _engine: &mut Engine, delta_time: f32) {
+        if self.is_playing() {
+            self.elapsed += delta_time;
+        }
+    }
+
+    fn drive(&mut self, explorer: &mut MandelbrotState, _engine: &mut Engine, _delta_time: f32) {
+        if self.take_record_request() {
+            self.record(explorer.location());
+        }
+        if self.take_cinematic_stop() {
+            explorer.stop_animation();
+        }
+        if let Some(animator) = self.next_leg(explorer.location(), explorer.is_animating()) {
+            explorer.animate(animator);
+        }
+    }
+
+    fn is_driving(&self) -> bool {
+        self.is_playing() || self.cinematic
+    }
+
+    // the new rates are used the next time the cinematic mode is started
+    fn set_config(&mut self, config: &Config) {
+        self.config = config.tour.clone();
+    }
+
+    fn input(&mut self, event: &Event<()>, _engine: &mut Engine) {
+        if let Event::WindowEvent { event, .. } = event {
+            match event {
+                WindowEvent::KeyboardInput { input, .. } if input.state == ElementState::Pressed => {
+                    match input.virtual_keycode {
+                        Some(VirtualKeyCode::R) => self.record_requested = true,
+                        Some(VirtualKeyCode::T) => {
+                            if self.is_playing() {
+                                self.stop();
+                            } else {
+                                self.play();
+                            }
+                        }
+                        Some(VirtualKeyCode::Back) => self.remove_last(),
+                        Some(VirtualKeyCode::M) => self.toggle_cinematic(),
+                        // the navigation keys take back the control of the camera
+                        Some(keycode) if is_navigation_key(keycode) => self.stop(),
+                        _ => {}
+                    }
+                }
+                WindowEvent::MouseWheel { .. } => self.stop(),
+                WindowEvent::MouseInput {
+                    state: ElementState::Pressed,
+                    ..
+                } => self.stop(),
+                _ => {}
+            }
+        }
+    }
+}
@@ -1,17 +1,48 @@
 use std::rc::Rc;
 
-use winit::dpi::PhysicalSize;
-use winit::event::{ElementState, Event, KeyboardInput, MouseButton, VirtualKeyCode, WindowEvent};
+use winit::dpi::{LogicalSize, PhysicalSize};
+use winit::event::{
+    ElementState, Event, KeyboardInput, ModifiersState, MouseButton, VirtualKeyCode, WindowEvent,
+};
 use winit::window::{Fullscreen, Window};
 
+use crate::game::config::MiniViewerConfig;
 use crate::game::engine::Engine;
-use crate::game::Game;
+use crate::game::{Game, Location, TITLE};
 use crate::game::game_state::GameState;
+use crate::game::mamndelbrot_state::AreaStatistics;
+use crate::game::period_worker::Minibrot;
+
+// the seconds between two updates of the location in the title of the window
+const TITLE_INTERVAL: f32 = 1.0;
+// written in the title while the view lost when the last launch panicked can be restored
+const RECOVERY_OFFER: &str = "Y to go back to where the last launch stopped";
 
 #[derive(Debug)]
 pub struct WindowState {
     window: Rc<Window>,
     is_fullscreen: bool,
+    // the size of the window before it became a mini viewer, if it is one
+    size_before_mini: Option<PhysicalSize<u32>>,
+    // true while the window has the focus
+    focused: bool,
+    modifiers: ModifiersState,
+    // the view written in the title at the next update, none when the title is left as it is
+    location: Option<Location>,
+    // the minibrot at the center of the view, written after the location
+    minibrot: Option<Minibrot>,
+    // the share of the view inside the set, written after the minibrot
+    area_statistics: Option<AreaStatistics>,
+    // written after the location while the zoom is close to the deepest one of the precision
+    depth_warning: Option<&'static str>,
+    // written after the location while the orbit of the reference point is computed
+    orbit_progress: Option<f32>,
+    // the seconds since the title has been written
+    title_time: f32,
+    // the error of the gpu written in the title instead of the view until the frames go on
+    gpu_error: Option<String>,
+    // true while the view lost when the last launch panicked can be restored
+    recovery_offer: bool,
 }
 
 impl WindowState {
@@ -19,22 +50,181 @@ impl WindowState {
         Self {
             window,
             is_fullscreen: false,
+            size_before_mini: None,
+            focused: true,
+            modifiers: ModifiersState::empty(),
+            location: None,
+            minibrot: None,
+            area_statistics: None,
+            depth_warning: None,
+            orbit_progress: None,
+            title_time: TITLE_INTERVAL,
+            gpu_error: None,
+            recovery_offer: false,
+        }
+    }
+
+    // give the view to write in the title, none to leave the title to a menu or when it is disabled
+    pub fn set_location(&mut self, location: Option<Location>) {
+        self.location = location;
+    }
+
+    // give the minibrot at the center of the view, none when it is not known
+    pub fn set_minibrot(&mut self, minibrot: Option<Minibrot>) {
+        self.minibrot = minibrot;
+    }
+
+    // give the share of the view inside the set, none when it is not written
+    pub fn set_area_statistics(&mut self, area_statistics: Option<AreaStatistics>) {
+        self.area_statistics = area_statistics;
+    }
+
+    pub fn set_depth_warning(&mut self, depth_warning: Option<&'static str>) {
+        self.depth_warning = depth_warning;
+    }
+
+    // give how much of the orbit of the reference point is computed, none once it is complete
+    pub fn set_orbit_progress(&mut self, orbit_progress: Option<f32>) {
+        self.orbit_progress = orbit_progress;
+    }
+
+    // write an error of the gpu in the title at once, with the key to draw the frames again,
+    // none to give the title back to the view
+    pub fn set_gpu_error(&mut self, gpu_error: Option<String>) {
+        match &gpu_error {
+            Some(error) => {
+                // the messages of wgpu span several lines, the first one tells what failed
+                let first_line = error.lines().find(|line| !line.trim().is_empty());
+                self.window.set_title(&format!(
+                    "{} - GPU ERROR: {} - Enter to draw again",
+                    TITLE,
+                    first_line.unwrap_or_default().trim()
+                ));
+            }
+            None => {
+                self.reset_title();
+                self.title_time = TITLE_INTERVAL;
+            }
+        }
+        self.gpu_error = gpu_error;
+    }
+
+    // write in the title that the view lost when the last launch panicked can be restored with Y
+    pub fn set_recovery_offer(&mut self, recovery_offer: bool) {
+        self.recovery_offer = recovery_offer;
+        if recovery_offer {
+            self.window
+                .set_title(&format!("{} - {}", TITLE, RECOVERY_OFFER));
+        } else {
+            self.reset_title();
+            self.title_time = TITLE_INTERVAL;
+        }
+    }
+
+    // write the title of the game alone again
+    pub fn reset_title(&mut self) {
+        self.window.set_title(TITLE);
+    }
+
+    pub fn is_mini(&self) -> bool {
+        self.size_before_mini.is_some()
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    // true for a press of the left button with alt in the mini viewer, which moves the window
+    // since it has no frame to be dragged by, the view does not move with it
+    pub fn moves_window(&self, event: &Event<()>) -> bool {
+        self.is_mini()
+            && self.modifiers.alt()
+            && matches!(
+                event,
+                Event::WindowEvent {
+                    event: WindowEvent::MouseInput {
+                        state: ElementState::Pressed,
+                        button: MouseButton::Left,
+                        ..
+                    },
+                    window_id,
+                } if *window_id == self.window.id()
+            )
+    }
+
+    // turn the window into a small frameless window always on top of the others, or back
+    // the clicks never go through it, the window could not get the focus back to leave it
+    pub fn set_mini(&mut self, mini: bool, config: &MiniViewerConfig) {
+        if mini == self.is_mini() {
+            return;
+        }
+        if mini {
+            self.size_before_mini = Some(self.window.inner_size());
+            self.is_fullscreen = false;
+            self.window.set_fullscreen(None);
+            self.window.set_decorations(false);
+            self.window.set_always_on_top(true);
+            self.window
+                .set_inner_size(LogicalSize::new(config.width, config.height));
+        } else {
+            self.window.set_always_on_top(false);
+            self.window.set_decorations(true);
+            if let Some(size) = self.size_before_mini.take() {
+                self.window.set_inner_size(size);
+            }
         }
     }
 }
 
 impl GameState for WindowState {
-    fn update(&mut self, engine: &mut Engine, delta_time: f32) {
+    fn update(&mut self, _engine: &mut Engine, delta_time: f32) {
         // engine.resize(self.size);
+        // the title is written once in a while, the systems are slow to draw it
+        self.title_time += delta_time;
+        if self.title_time < TITLE_INTERVAL || self.gpu_error.is_some() {
+            return;
+        }
+        if let Some(location) = self.location.take() {
+            self.title_time = 0.0;
+            let mut title = format!("{} - {}", TITLE, location.summary());
+            if let Some(minibrot) = &self.minibrot {
+                title = format!("{} - {}", title, minibrot.summary());
+            }
+            if let Some(area_statistics) = &self.area_statistics {
+                title = format!("{} - {}", title, area_statistics.summary());
+            }
+            if let Some(depth_warning) = self.depth_warning {
+                title = format!("{} - {}", title, depth_warning.to_uppercase());
+            }
+            if let Some(progress) = self.orbit_progress {
+                let percent = (progress * 100.0).floor();
+                title = format!("{} - computing the reference {}%", title, percent);
+            }
+            if self.recovery_offer {
+                title = format!("{} - {}", title, RECOVERY_OFFER);
+            }
+            self.window.set_title(&title);
+        }
     }
 
     fn input(&mut self, event: &Event<()>, engine: &mut Engine) {
+        if self.moves_window(event) {
+            if let Err(error) = self.window.drag_window() {
+                eprintln!("Impossible to move the window: {}", error);
+            }
+        }
         match event {
             Event::WindowEvent {
                 ref event,
                 window_id,
             } if *window_id == self.window.id() => match event {
-                // toogle fullscreen on f11
+                WindowEvent::Focused(focused) => {
+                    self.focused = *focused;
+                }
+                WindowEvent::ModifiersChanged(modifiers) => {
+                    self.modifiers = *modifiers;
+                }
+                // toogle fullscreen on f11, except for the mini viewer
                 WindowEvent::KeyboardInput {
                     input:
                     KeyboardInput {
@@ -43,7 +233,7 @@ impl GameState for WindowState {
                         ..
                     },
                     ..
-                } => {
+                } if !self.is_mini() => {
                     self.is_fullscreen = !self.is_fullscreen;
                     if self.is_fullscreen {
                         self.window.set_fullscreen(Some(Fullscreen::Borderless(None)));
@@ -51,6 +241,18 @@ impl GameState for WindowState {
                         self.window.set_fullscreen(None);
                     }
                 }
+                // switch between the present modes, to measure the speed without the vertical sync
+                WindowEvent::KeyboardInput {
+                    input:
+                    KeyboardInput {
+                        virtual_keycode: Some(VirtualKeyCode::V),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                    ..
+                } => {
+                    eprintln!("Present mode: {:?}", engine.next_present_mode());
+                }
                 _ => {}
             },
             _ => {}
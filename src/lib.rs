@@ -0,0 +1,7 @@
+// the explorer, and the renderer to embed the mandelbrot set in other wgpu applications
+mod game;
+mod options;
+mod runner;
+
+pub use game::{BigReal, Location, MandelbrotRenderer, Real, RenderParams};
+pub use runner::run;
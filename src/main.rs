@@ -1,8 +1,12 @@
-extern crate core;
-
-mod game;
-mod runner;
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    pollster::block_on(mandelbrot::run());
+}
 
+// in the browser the event loop can not be blocked, the future is run by the javascript event loop
+#[cfg(target_arch = "wasm32")]
 fn main() {
-    pollster::block_on(runner::run());
+    std::panic::set_hook(Box::new(console_error_panic_hook::hook));
+    console_log::init_with_level(log::Level::Warn).expect("Impossible to initialize the logger");
+    wasm_bindgen_futures::spawn_local(mandelbrot::run());
 }
@@ -0,0 +1,222 @@
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::PathBuf;
+
+// the options given on the command line
+#[derive(Debug, Clone)]
+pub struct Options {
+    // read commands from stdin and write their status as json to stdout
+    pub pipe: bool,
+    // the path of the config file, reloaded when it changes
+    pub config: PathBuf,
+    // start as a small window on top of the others
+    pub mini: bool,
+    // the file where the waypoints of the tour are saved
+    pub tour: PathBuf,
+    // a keyframe script to play
+    pub script: Option<PathBuf>,
+    // the directory where every frame of the script is saved, the program exits at the end of the script
+    pub export: Option<PathBuf>,
+    // the video file the frames of the script are encoded into by ffmpeg,
+    // the program exits at the end of the script
+    pub video: Option<PathBuf>,
+    // the bitrate of the video, given to ffmpeg as it is
+    pub bitrate: String,
+    // the frames per second of the script, instead of the ones it gives
+    pub fps: Option<f32>,
+    // render without a window
+    pub headless: bool,
+    // the size of the frames rendered headless
+    pub size: (u32, u32),
+    // the number of frames rendered headless when there is no script nor pipe,
+    // or by the benchmark
+    pub frames: Option<u32>,
+    // the file the last frame rendered headless is saved to
+    pub output: PathBuf,
+    // the source of the shader, reloaded when it changes
+    pub shader: PathBuf,
+    // the index or a part of the name of the gpu to use
+    pub gpu: Option<String>,
+    // use the software adapter instead of the gpu
+    pub force_fallback: bool,
+    // compute the frames on the cpu instead of the shader
+    pub cpu: bool,
+    // the csv file the timings of the frames are written to
+    pub profile: Option<PathBuf>,
+    // the csv file the statistics of every frame are written to
+    pub stats_out: Option<PathBuf>,
+    // render a fixed zoom without a window and print the frame times
+    pub benchmark: bool,
+    // a Kalles Fraktaler file with the location to start at
+    pub location: Option<PathBuf>,
+    // the port to accept websocket connections on, to drive the explorer remotely
+    pub listen: Option<u16>,
+    // the address the websocket connections are accepted on, only from this machine by default
+    pub listen_address: IpAddr,
+    // the formula of the custom fractal, explored at startup
+    pub formula: Option<String>,
+    // the file where the navigation is saved when the explorer exits
+    pub session: PathBuf,
+    // start from the default location instead of the one of the last session
+    pub fresh: bool,
+    // the directory where every waypoint of the tour is rendered without a window
+    pub gallery: Option<PathBuf>,
+    // the demo file the inputs of the user are recorded to
+    pub record: Option<PathBuf>,
+    // a demo file whose inputs are played again, the program exits at the end of the demo
+    // when there is no window
+    pub replay: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            pipe: false,
+            config: PathBuf::from("mandelbrot.toml"),
+            mini: false,
+            tour: PathBuf::from("tour.json"),
+            script: None,
+            export: None,
+            video: None,
+            bitrate: "8M".to_string(),
+            fps: None,
+            headless: false,
+            size: (1280, 720),
+            frames: None,
+            output: PathBuf::from("mandelbrot.png"),
+            shader: PathBuf::from("src/shaders/mandelbrot.wgsl"),
+            gpu: None,
+            force_fallback: false,
+            cpu: false,
+            profile: None,
+            stats_out: None,
+            benchmark: false,
+            location: None,
+            listen: None,
+            listen_address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            formula: None,
+            session: PathBuf::from("session.json"),
+            fresh: false,
+            gallery: None,
+            record: None,
+            replay: None,
+        }
+    }
+}
+
+impl Options {
+    // parse the options from the arguments of the program
+    pub fn from_args() -> Self {
+        let mut options = Self::default();
+        let mut arguments = std::env::args().skip(1);
+        while let Some(argument) = arguments.next() {
+            match argument.as_str() {
+                "--pipe" => options.pipe = true,
+                "--mini" => options.mini = true,
+                "--tour" => match arguments.next() {
+                    Some(path) => options.tour = PathBuf::from(path),
+                    None => eprintln!("Missing path after --tour"),
+                },
+                "--config" => match arguments.next() {
+                    Some(path) => options.config = PathBuf::from(path),
+                    None => eprintln!("Missing path after --config"),
+                },
+                "--script" => match arguments.next() {
+                    Some(path) => options.script = Some(PathBuf::from(path)),
+                    None => eprintln!("Missing path after --script"),
+                },
+                "--export" => match arguments.next() {
+                    Some(path) => options.export = Some(PathBuf::from(path)),
+                    None => eprintln!("Missing directory after --export"),
+                },
+                "--video" => match arguments.next() {
+                    Some(path) => options.video = Some(PathBuf::from(path)),
+                    None => eprintln!("Missing path after --video"),
+                },
+                "--bitrate" => match arguments.next() {
+                    Some(bitrate) => options.bitrate = bitrate,
+                    None => eprintln!("Missing bitrate after --bitrate"),
+                },
+                "--fps" => match arguments.next().and_then(|value| value.parse().ok()) {
+                    Some(fps) if fps > 0.0 && f32::is_finite(fps) => options.fps = Some(fps),
+                    _ => eprintln!("Expected a number of frames per second after --fps"),
+                },
+                "--headless" => options.headless = true,
+                "--benchmark" => options.benchmark = true,
+                "--size" => match arguments.next().as_deref().and_then(parse_size) {
+                    Some(size) => options.size = size,
+                    None => eprintln!("Expected a size like 1280x720 after --size"),
+                },
+                "--frames" => match arguments.next().and_then(|value| value.parse().ok()) {
+                    Some(frames) if frames > 0 => options.frames = Some(frames),
+                    _ => eprintln!("Expected a number of frames after --frames"),
+                },
+                "--shader" => match arguments.next() {
+                    Some(path) => options.shader = PathBuf::from(path),
+                    None => eprintln!("Missing path after --shader"),
+                },
+                "--gpu" => match arguments.next() {
+                    Some(gpu) => options.gpu = Some(gpu),
+                    None => eprintln!("Missing index or name after --gpu"),
+                },
+                "--force-fallback" => options.force_fallback = true,
+                "--cpu" => options.cpu = true,
+                "--profile" => match arguments.next() {
+                    Some(path) => options.profile = Some(PathBuf::from(path)),
+                    None => eprintln!("Missing path after --profile"),
+                },
+                "--stats-out" => match arguments.next() {
+                    Some(path) => options.stats_out = Some(PathBuf::from(path)),
+                    None => eprintln!("Missing path after --stats-out"),
+                },
+                "--location" => match arguments.next() {
+                    Some(path) => options.location = Some(PathBuf::from(path)),
+                    None => eprintln!("Missing path after --location"),
+                },
+                "--listen" => match arguments.next().and_then(|value| value.parse().ok()) {
+                    Some(port) => options.listen = Some(port),
+                    None => eprintln!("Expected a port after --listen"),
+                },
+                "--listen-address" => match arguments.next().and_then(|value| value.parse().ok()) {
+                    Some(address) => options.listen_address = address,
+                    None => eprintln!("Expected an ip address after --listen-address"),
+                },
+                "--formula" => match arguments.next() {
+                    Some(formula) => options.formula = Some(formula),
+                    None => eprintln!("Missing formula after --formula"),
+                },
+                "--session" => match arguments.next() {
+                    Some(path) => options.session = PathBuf::from(path),
+                    None => eprintln!("Missing path after --session"),
+                },
+                "--fresh" => options.fresh = true,
+                "--gallery" => match arguments.next() {
+                    Some(path) => options.gallery = Some(PathBuf::from(path)),
+                    None => eprintln!("Missing directory after --gallery"),
+                },
+                "--record" => match arguments.next() {
+                    Some(path) => options.record = Some(PathBuf::from(path)),
+                    None => eprintln!("Missing path after --record"),
+                },
+                "--replay" => match arguments.next() {
+                    Some(path) => options.replay = Some(PathBuf::from(path)),
+                    None => eprintln!("Missing path after --replay"),
+                },
+                "--output" => match arguments.next() {
+                    Some(path) => options.output = PathBuf::from(path),
+                    None => eprintln!("Missing path after --output"),
+                },
+                _ => eprintln!("Unknown argument: {}", argument),
+            }
+        }
+        options
+    }
+}
+
+// parse a size like 1280x720
+fn parse_size(value: &str) -> Option<(u32, u32)> {
+    let (width, height) = value.split_once('x')?;
+    match (width.parse().ok()?, height.parse().ok()?) {
+        (0, _) | (_, 0) => None,
+        size => Some(size),
+    }
+}
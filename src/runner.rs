@@ -7,16 +7,30 @@ use winit::event_loop::{ControlFlow, EventLoop};
 use winit::window::{Fullscreen, Icon, WindowBuilder};
 
 // import game module
-use crate::game::Game;
+use crate::game::{Game, TITLE};
+use crate::options::Options;
 
 pub async fn run() {
-    // print control
-    print_controls();
+    let options = Options::from_args();
+    // the scopes are sent to tracy once it is connected, the explorer runs without it meanwhile
+    #[cfg(feature = "tracy")]
+    profiling::tracy_client::Client::start();
+    #[cfg(not(target_arch = "wasm32"))]
+    if options.headless || options.benchmark || options.gallery.is_some() {
+        env_logger::init();
+        run_headless(&options).await;
+        return;
+    }
+    // print control, unless stdout is used to write the status of the commands
+    if !options.pipe {
+        print_controls();
+    }
     // create event loop
+    #[cfg(not(target_arch = "wasm32"))]
     env_logger::init();
     let event_loop = EventLoop::new();
     let window = WindowBuilder::new().build(&event_loop).unwrap();
-    window.set_title("Realtime Mandelbrot Explorer");
+    window.set_title(TITLE);
     window.set_inner_size(winit::dpi::LogicalSize::new(800.0, 800.0));
     // decode a png file into a vector of u8
     let icon = image::load_from_memory(include_bytes!("../assets/logo.png"))
@@ -24,25 +38,106 @@ pub async fn run() {
         .to_rgba8();
     // add an icon to the window
     window.set_window_icon(Some(Icon::from_rgba(icon.into_raw(), 256, 256).unwrap()));
+    // in the browser the window is a canvas that must be added to the page
+    #[cfg(target_arch = "wasm32")]
+    {
+        use winit::platform::web::WindowExtWebSys;
+        web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.body())
+            .and_then(|body| {
+                body.append_child(&web_sys::Element::from(window.canvas()))
+                    .ok()
+            })
+            .expect("Impossible to add the canvas to the page");
+    }
     let window = Rc::new(window);
     // create a reference counted pointer to the window
-    let mut game = Game::new(window.clone()).await;
+    let mut game = match Game::new(window.clone(), &options).await {
+        Some(game) => game,
+        None => {
+            eprintln!("There is no adapter to draw in the window");
+            eprintln!("Run with --headless to compute the image on the cpu");
+            return;
+        }
+    };
     event_loop.run(move |event, _, control_flow| game.input(event, control_flow));
 }
 
+// render without creating a window, then exit
+// the benchmark and the gallery are rendered the same way so the window does not limit the frame rate
+#[cfg(not(target_arch = "wasm32"))]
+async fn run_headless(options: &Options) {
+    let size = winit::dpi::PhysicalSize::new(options.size.0, options.size.1);
+    let mut game = match Game::new_headless(size, options).await {
+        Some(game) => game,
+        // the frames computed on the cpu are drawn by the adapter,
+        // without one only a single image can be computed and saved
+        None if options.gallery.is_none() && !options.benchmark => {
+            eprintln!("The image is computed on the cpu");
+            if let Err(error) = Game::render_without_adapter(size, options) {
+                eprintln!("Impossible to render {}: {}", options.output.display(), error);
+            }
+            return;
+        }
+        None => {
+            eprintln!("The gallery and the benchmark need an adapter");
+            return;
+        }
+    };
+    if let Some(directory) = &options.gallery {
+        game.run_gallery(&options.tour, directory, size);
+    } else if options.benchmark {
+        game.run_benchmark(options.frames.unwrap_or(300));
+    } else {
+        game.run_headless(options.frames.unwrap_or(1), options.output.clone());
+    }
+}
+
 // a function that print the keys to control the game
 fn print_controls() {
     println!("Controls:");
-    println!("  - Mouse wheel to zoom at center of screen");
+    println!("  - Mouse wheel to zoom at center of screen, faster with shift and slower with ctrl");
     println!("  - Left mouse pressed to move");
+    println!("  - Shift and left mouse pressed to select a rectangle to zoom on");
     println!("  - Right mouse pressed to rotate");
     println!("  - Arrow keys or ZQSD to move");
     println!("  - A and E to rotate left and right");
+    println!("  - W to rotate around the center, the cursor or the point locked under it");
     println!("  - Numpad + and - to change the zoom speed");
     println!("  - Numpad / and * to change the iteration count");
     println!("  - Space pause the animation");
     println!("  - Entrer to reset the zoom and rotation");
     println!("  - Page up/down to increase/decrease the color palette scale");
+    println!("  - H to fit the color palette scale to the iterations of the view");
+    println!("  - N to generate a random palette, shift and N for the palette of the shader");
+    println!("  - Home and End to slow down and speed up the color cycling");
+    println!("  - 1 and 2 to change the gamma, 3 and 4 the brightness, 5 and 6 the contrast");
+    println!("  - O to show or hide the overview of the whole set");
+    println!("  - J to show or hide the julia set of the point under the cursor");
+    println!("  - U to move the reference point to the center of the screen");
+    println!("  - Shift and U to show or hide the reference point");
+    println!("  - Ctrl and left click to move the reference point under the cursor");
+    println!("  - B to split the screen and compare the settings of the config");
+    println!("  - P to show or hide the orbit of the reference point");
+    println!("  - I to show or hide the axes and the scale bar");
+    println!("  - C to color by the iterations or by the atom domains");
+    println!("  - F3 to cycle the debug views of the shader");
+    println!("  - G to cycle the easing of the camera: custom, snappy, smooth or cinematic");
+    println!("  - F to switch between the mandelbrot, newton, phoenix, nova and custom fractals");
+    println!("  - V to switch between the present modes");
+    println!("  - R to record the current location as a waypoint of the tour");
+    println!("  - T to play or stop the tour");
+    println!("  - Backspace to remove the last waypoint of the tour");
+    println!("  - M to turn and zoom slowly at constant rates, for the recordings");
+    println!("  - X to start or stop exploring the detailed regions");
+    println!("  - K to copy the location code of the view, L to go to the one in the clipboard");
+    println!("  - Ctrl, shift and C to copy the frame to the clipboard");
+    println!("  - F9 to save the last seconds as a gif");
+    println!("  - F6 to play or stop the view growing with more and more iterations");
+    println!("  - F7 to hear the orbit of the point under the cursor, with the sound feature");
+    println!("  - Y to go back to the view of the last launch when it crashed");
+    println!("  - F10 to toggle the mini viewer, alt and drag to move it");
     println!("  - F11 to toggle fullscreen");
-    println!("  - Escape to quit");
+    println!("  - Escape to open the pause menu, to reset the view, save or browse the bookmarks, edit the settings, or quit");
 }
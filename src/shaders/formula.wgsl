@@ -0,0 +1,156 @@
+// The fractals of a formula iterated in simple precision, without perturbation
+// the formula is inserted in place of the FORMULA line, it defines
+// - fn start(c: vec2<f32>) -> vec2<f32>, the first z of the point c
+// - fn iterate(z: vec2<f32>, previous_z: vec2<f32>, c: vec2<f32>) -> vec2<f32>, the next z
+// a point stops when z escapes or when z does not move anymore
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) coordinate: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) coord: vec2<f32>,
+};
+
+// the structs of the buffers, written from the rust ones so they can not differ
+// STRUCTS
+
+@group(1) @binding(0)
+var<uniform> mandelbrot: Mandelbrot;
+@group(2) @binding(0)
+var mandelbrotTexture: texture_storage_2d<rgba32float, write>;
+@group(2) @binding(2)
+var<storage, read_write> mandelbrotData: array<MandelbrotDot>;
+// the orbit of the reference point of the mandelbrot set, packed after its second point,
+// the reference point, whose two f32 are the first two words
+@group(2) @binding(4)
+var<storage, read_write> mandelbrotOrbitPointSuite: array<u32>;
+// the smallest and the largest iterations of the points that stopped, as the bits of the floats,
+// then on a sparse grid the number of points that did not stop, the number that did,
+// one left at 0 for the points known to be inside the mandelbrot set,
+// and the low and high words of the sum of the iterations of the ones that stopped
+@group(2) @binding(6)
+var<storage, read_write> iterationRange: array<atomic<u32>, 7>;
+@group(0) @binding(0)
+var blueNoise: texture_2d<f32>;
+// the generated gradient of the palette, the cosine one is used when it has no stop
+@group(1) @binding(4)
+var<uniform> palette: Palette;
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(model.position, 1.0);
+    out.coord = model.coordinate.xy;
+    return out;
+}
+
+// count the points of one in 16 that stopped or not, and sum the iterations of the ones that stopped
+fn count_iteration(pixel: vec2<u32>, stopped: bool, iterations: u32) {
+    if (pixel.x % 4u == 0u && pixel.y % 4u == 0u) {
+        if (stopped) {
+            atomicAdd(&iterationRange[3], 1u);
+            let low = atomicAdd(&iterationRange[5], iterations);
+            if (low > 0xffffffffu - iterations) {
+                atomicAdd(&iterationRange[6], 1u);
+            }
+        } else {
+            atomicAdd(&iterationRange[2], 1u);
+        }
+    }
+}
+
+// widen the range of the iterations of the frame, the atomics are only written when it changes
+fn measure_iteration(iteration: f32) {
+    let bits = bitcast<u32>(iteration);
+    if (bits < atomicLoad(&iterationRange[0])) {
+        atomicMin(&iterationRange[0], bits);
+    }
+    if (bits > atomicLoad(&iterationRange[1])) {
+        atomicMax(&iterationRange[1], bits);
+    }
+}
+
+// the color of the generated palette at t from 0 to 1, between the two stops around it
+fn palette_color(t: f32) -> vec3<f32> {
+    var color = palette.stops[0].rgb;
+    for (var index = 1u; index < palette.count; index += 1u) {
+        let previous = palette.stops[index - 1u];
+        let stop = palette.stops[index];
+        if (t >= previous.w) {
+            color = mix(previous.rgb, stop.rgb, clamp((t - previous.w) / (stop.w - previous.w), 0.0, 1.0));
+        }
+    }
+    return color;
+}
+
+fn cmul(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(a.x * b.x - a.y * b.y, a.x * b.y + a.y * b.x);
+}
+
+fn cdiv(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+    var denominator: f32 = b.x * b.x + b.y * b.y;
+    return vec2<f32>((a.x * b.x + a.y * b.y) / denominator, (a.y * b.x - a.x * b.y) / denominator);
+}
+
+// FORMULA
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let pixel = vec2<u32>(
+        u32((in.coord.x + 1.0) / 2.0 * f32(mandelbrot.width)),
+        u32((in.coord.y + 1.0) / 2.0 * f32(mandelbrot.height))
+    );
+    let index = pixel.y * mandelbrot.width + pixel.x;
+    var coord = in.coord * mandelbrot.zoom;
+    coord.x *= f32(mandelbrot.width) / f32(mandelbrot.height);
+    coord = vec2<f32>(
+        coord.x * cos(mandelbrot.angle) - coord.y * sin(mandelbrot.angle),
+        coord.x * sin(mandelbrot.angle) + coord.y * cos(mandelbrot.angle)
+    );
+    let reference = bitcast<vec2<f32>>(vec2<u32>(mandelbrotOrbitPointSuite[0], mandelbrotOrbitPointSuite[1]));
+    let c = reference + mandelbrot.center_delta + coord;
+    var z = start(c);
+    var previous_z = vec2<f32>(0.0, 0.0);
+    var i = 0u;
+    var stopped = false;
+    loop {
+        if (i >= mandelbrot.maximum_iterations) {
+            break;
+        }
+        let next_z = iterate(z, previous_z, c);
+        previous_z = z;
+        z = next_z;
+        i += 1u;
+        let step = z - previous_z;
+        if (dot(z, z) > mandelbrot.mu || dot(step, step) < 1e-10) {
+            stopped = true;
+            break;
+        }
+    }
+    var color = vec3<f32>(0.0, 0.0, 0.0);
+    count_iteration(pixel, stopped, i);
+    if (stopped) {
+        let iterations = f32(i);
+        measure_iteration(iterations);
+        let t = abs(1.0 - ((iterations + mandelbrot.palette_phase) % mandelbrot.color_palette_scale) * 2.0 / mandelbrot.color_palette_scale);
+        color = 0.5 + 0.5 * cos(6.28318 * (t + vec3<f32>(0.0, 0.33, 0.67)));
+        if (palette.count > 0u) {
+            color = palette_color(t);
+        }
+        textureStore(mandelbrotTexture, vec2<i32>(pixel), vec4<f32>(iterations, 0.0, 0.0, 0.0));
+    } else {
+        textureStore(mandelbrotTexture, vec2<i32>(pixel), vec4<f32>(-1.0, 0.0, 0.0, 0.0));
+    }
+    mandelbrotData[index] = MandelbrotDot(
+        vec2<f32>(0.0, 0.0), 0.0, 0.0, previous_z,
+        vec2<f32>(0.0, 0.0), vec2<f32>(0.0, 0.0), 0.0, 0, 0, 0u
+    );
+    // add less than one step of an 8 bits channel, to break up the banding of the smooth gradients
+    let noise_size = textureDimensions(blueNoise);
+    let noise = textureLoad(blueNoise, vec2<i32>(in.clip_position.xy) % noise_size, 0).r;
+    return vec4<f32>(color + (noise - 0.5) / 255.0, 1.0);
+}
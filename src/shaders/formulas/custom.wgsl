@@ -0,0 +1,56 @@
+// a formula of the user, EXPRESSION is replaced by the compiled formula
+fn cre(a: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(a.x, 0.0);
+}
+
+fn cim(a: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(a.y, 0.0);
+}
+
+fn cabs(a: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(length(a), 0.0);
+}
+
+fn cconj(a: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(a.x, -a.y);
+}
+
+fn cexp(a: vec2<f32>) -> vec2<f32> {
+    return exp(a.x) * vec2<f32>(cos(a.y), sin(a.y));
+}
+
+fn clog(a: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(log(length(a)), atan2(a.y, a.x));
+}
+
+fn csin(a: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(sin(a.x) * cosh(a.y), cos(a.x) * sinh(a.y));
+}
+
+fn ccos(a: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(cos(a.x) * cosh(a.y), -sin(a.x) * sinh(a.y));
+}
+
+// a power by a positive integer
+fn cpowi(a: vec2<f32>, n: i32) -> vec2<f32> {
+    var result = a;
+    for (var i = 1; i < n; i += 1) {
+        result = cmul(result, a);
+    }
+    return result;
+}
+
+fn cpow(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+    if (dot(a, a) == 0.0) {
+        return vec2<f32>(0.0, 0.0);
+    }
+    return cexp(cmul(b, clog(a)));
+}
+
+fn start(c: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(0.0, 0.0);
+}
+
+fn iterate(z: vec2<f32>, previous_z: vec2<f32>, c: vec2<f32>) -> vec2<f32> {
+    return EXPRESSION;
+}
@@ -0,0 +1,10 @@
+// the nova fractal, a newton step toward a root of z³ - 1 shifted by c, starting from the root 1
+fn start(c: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(1.0, 0.0);
+}
+
+fn iterate(z: vec2<f32>, previous_z: vec2<f32>, c: vec2<f32>) -> vec2<f32> {
+    let z2 = cmul(z, z);
+    let z3 = cmul(z2, z);
+    return z - cdiv(z3 - vec2<f32>(1.0, 0.0), 3.0 * z2) + c;
+}
@@ -0,0 +1,8 @@
+// the phoenix fractal, z² + c + p z_{n-1}, with p = -0.5
+fn start(c: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(0.0, 0.0);
+}
+
+fn iterate(z: vec2<f32>, previous_z: vec2<f32>, c: vec2<f32>) -> vec2<f32> {
+    return cmul(z, z) + c - 0.5 * previous_z;
+}
@@ -0,0 +1,74 @@
+// Draw the julia set of the point of the mandelbrot set under the cursor,
+// on the right half of the screen
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) coordinate: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) coord: vec2<f32>,
+};
+
+// the structs of the buffers, written from the rust ones so they can not differ
+// STRUCTS
+
+// the size, the iterations and the colors of the preview
+@group(0) @binding(0)
+var<uniform> preview: Mandelbrot;
+// the point of the mandelbrot set the julia set is drawn for
+@group(0) @binding(1)
+var<uniform> parameter: JuliaParameter;
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(model.position, 1.0);
+    out.coord = model.coordinate.xy;
+    return out;
+}
+
+fn colorize(iterations: f32) -> vec4<f32> {
+    if(iterations < 0.0) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+    var t = abs(1.0 - ((iterations + preview.palette_phase) % preview.color_palette_scale) * 2.0 / preview.color_palette_scale);
+    return vec4<f32>(
+        0.5 + 0.5 * cos(t * 6.28 + 0.9),
+        0.5 + 0.5 * sin(t * 5.88 - 3.14),
+        0.5 + 0.5 * cos(t * 3.14 - 3.64),
+        1.0
+    );
+}
+
+// the same iteration as the mandelbrot set, but from the pixel with the parameter fixed
+fn compute_iteration(start: vec2<f32>) -> f32 {
+    var z = start;
+    var i = 0u;
+    while (i < preview.maximum_iterations) {
+        z = vec2<f32>(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y) + parameter.c;
+        if (dot(z, z) >= preview.mu) {
+            // add the rest to i to get a smooth color gradient
+            let log_zn = log(dot(z, z)) / 2.0;
+            let nu = log(log_zn / log(2.0)) / log(2.0);
+            return f32(i) + 1.0 - nu;
+        }
+        i += 1u;
+    }
+    return -1.0;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // the size of a pixel in the coord space
+    let pixel = 2.0 / f32(preview.width);
+    // a thin line between the two planes
+    if(in.coord.x < -1.0 + pixel * 2.0) {
+        return vec4<f32>(0.5, 0.5, 0.5, 1.0);
+    }
+    let ratio = f32(preview.width) / f32(preview.height);
+    let z = vec2<f32>(in.coord.x * ratio, in.coord.y) * parameter.zoom;
+    return colorize(compute_iteration(z));
+}
@@ -5,7 +5,6 @@
 // TODO Calculate a distance from the border when outside of the mandelbrot set
 // TODO Calculate the distance from the border when inside the mandelbrot set https://www.shadertoy.com/view/lsX3W4
 // TODO https://en.wikibooks.org/wiki/Fractals/Iterations_in_the_complex_plane/demm#Interior_distance_estimation
-// TODO Render with max iterations 1000 and then render another 1000 in the remaning area
 // TODO Use arbitraty precision number to calculate orbit
 // TODO https://www.shadertoy.com/view/wdBfDK Smart AA
 // TODO https://www.shadertoy.com/view/4sdXWX
@@ -33,55 +32,61 @@ struct VertexOutput {
     @location(0) coord: vec2<f32>,
 };
 
-// Define the uniform buffer from Mandelbrot Shader struct
-struct Mandelbrot {
-    // the number of frame rendered so far
-    generation: u32,
-    // the time elapsed since the start of the program
-    time_elapsed: f32,
-    // the zoom factor
-    zoom: f32,
-    angle: f32,
-    // the delta between the the dc dot and the  mandelbrot coordinate at the screen center
-    center_delta: vec2<f32>,
-    epsilon: f32,
-    maximum_iterations: u32,
-    // the width in pixel of the screen
-    width: u32,
-    // the height in pixel of the screen
-    height: u32,
-    // the maximum value to consider the point is in the mandelbrot set
-    mu: f32,
-    // the color palette scale factor
-    color_palette_scale: f32,
-};
+// the structs of the buffers, written from the rust ones so they can not differ
+// STRUCTS
 
-struct LastRenderedMandelbrot {
-    center_delta: vec2<f32>,
-    zoom: f32,
-}
+// the value of a pixel whose iterations are not all computed yet, drawn like the inside of the set
+let in_progress: f32 = -4.0;
 
+// the layout of the packed orbit, the same as in packed_orbit.rs
+let orbit_block_length: u32 = 8u;
+let orbit_difference_exponent: f32 = 20.0;
 
-@group(0) @binding(0)
+
+// the textures are in the group 0, the uniforms in the group 1 and the data of the pixels in the group 2
+@group(1) @binding(0)
 var<uniform> mandelbrot: Mandelbrot;
-@group(0) @binding(1)
+@group(1) @binding(1)
 var<uniform> previous_mandelbrot: Mandelbrot;
 
 // add the storage buffer
-@group(0) @binding(2)
-var<storage, read_write> mandelbrotTexture: array<f32>;
-@group(0) @binding(3)
-var<storage, read_write> previousMandelbrotTexture: array<f32>;
-@group(0) @binding(4)
-var<storage, read_write> mandelbrotData: array<vec2<f32>>;
-@group(0) @binding(5)
-var<storage, read_write> previousMandelbrotData: array<vec2<f32>>;
+// the iterations of the pixels in the red channel, written for this frame and read from the last one
+@group(2) @binding(0)
+var mandelbrotTexture: texture_storage_2d<rgba32float, write>;
+@group(2) @binding(1)
+var previousMandelbrotTexture: texture_2d<f32>;
+@group(2) @binding(2)
+var<storage, read_write> mandelbrotData: array<MandelbrotDot>;
+@group(2) @binding(3)
+var<storage, read_write> previousMandelbrotData: array<MandelbrotDot>;
 
-// add the storage buffer
-@group(0) @binding(6)
-var<storage, read_write> mandelbrotOrbitPointSuite: array<vec2<f32>>;
-@group(0) @binding(7)
+// the orbit of the reference point, packed by blocks of orbit_block_length points after its second point c,
+// the first point of a block is stored as two f32, the next ones as their differences with the square
+// of the previous one plus c, as two f16 multiplied by 2 to the power of orbit_difference_exponent
+@group(2) @binding(4)
+var<storage, read_write> mandelbrotOrbitPointSuite: array<u32>;
+@group(2) @binding(5)
 var<storage, read_write> lastRenderedMandelbrot: LastRenderedMandelbrot;
+// the smallest and the largest iterations of the pixels outside the set in the frame,
+// as the bits of the floats, which are in the same order as the positive floats,
+// then the number of pixels of a sparse grid that reached the maximum iterations,
+// the number of them that escaped, to estimate the iterations needed,
+// the number of them known to be inside the set, and the low and high words of the sum
+// of the iterations of the ones that escaped, for the statistics of the area of the view
+@group(2) @binding(6)
+var<storage, read_write> iterationRange: array<atomic<u32>, 7>;
+// a tile of blue noise to dither the colors, flat when the dithering is disabled
+@group(0) @binding(0)
+var blueNoise: texture_2d<f32>;
+// the settings of the right part of the screen when it is split, to compare them with the left one
+@group(1) @binding(3)
+var<uniform> comparison: Mandelbrot;
+// the generated gradient of the palette, the one of colorize is used when it has no stop
+@group(1) @binding(4)
+var<uniform> palette: Palette;
+
+// the settings of the pixel, the ones of the comparison in the right part of a split screen
+var<private> settings: Mandelbrot;
 
 @vertex
 fn vs_main(
@@ -98,6 +103,16 @@ fn vpow2(v: vec2<f32>) -> vec2<f32> {
      return vec2(v.x * v.x - v.y * v.y, 2. * v.x * v.y);
 }
 
+// multiply by 2 to the power of the exponent, in two halves so the power of two does not leave
+// the range of a f32 when the result does not
+fn times_power_of_two(v: vec2<f32>, exponent: i32) -> vec2<f32> {
+    if (exponent == 0) {
+        return v;
+    }
+    let half = exponent / 2;
+    return v * exp2(f32(half)) * exp2(f32(exponent - half));
+}
+
 // cmul is a complex multiplication
 fn cmul(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
     return vec2<f32>(a.x * b.x - a.y * b.y, a.x * b.y + a.y * b.x);
@@ -109,11 +124,63 @@ fn cdiv(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
     return vec2<f32>((a.x * b.x + a.y * b.y) / denominator, (a.y * b.x - a.x * b.y) / denominator);
 }
 
+// true when a column of pixels is in the right part of a split screen
+fn is_compared(x: f32) -> bool {
+    return x >= mandelbrot.split * f32(mandelbrot.width);
+}
+
+// add to the sum of the iterations, carried to its high word when the low one wraps around
+fn add_iterations(iterations: u32) {
+    let low = atomicAdd(&iterationRange[5], iterations);
+    if (low > 0xffffffffu - iterations) {
+        atomicAdd(&iterationRange[6], 1u);
+    }
+}
+
+// widen the range of the iterations of the frame, the atomics are only written when it changes,
+// and count the pixels of one in 16 that escaped or not, the right part of a split screen
+// has its own maximum and is left out
+fn measure_iteration(pixel: vec2<u32>, iteration: f32) {
+    if (pixel.x % 4u == 0u && pixel.y % 4u == 0u && !is_compared(f32(pixel.x))) {
+        if (iteration == -1.0) {
+            atomicAdd(&iterationRange[2], 1u);
+        } else if (iteration == -3.0) {
+            atomicAdd(&iterationRange[4], 1u);
+        } else if (iteration >= 0.0) {
+            atomicAdd(&iterationRange[3], 1u);
+            add_iterations(u32(iteration));
+        }
+    }
+    if (iteration < 0.0) {
+        return;
+    }
+    let bits = bitcast<u32>(iteration);
+    if (bits < atomicLoad(&iterationRange[0])) {
+        atomicMin(&iterationRange[0], bits);
+    }
+    if (bits > atomicLoad(&iterationRange[1])) {
+        atomicMax(&iterationRange[1], bits);
+    }
+}
+
+// the color of the generated palette at t from 0 to 1, between the two stops around it
+fn palette_color(t: f32) -> vec3<f32> {
+    var color = palette.stops[0].rgb;
+    for (var index = 1u; index < palette.count; index += 1u) {
+        let previous = palette.stops[index - 1u];
+        let stop = palette.stops[index];
+        if (t >= previous.w) {
+            color = mix(previous.rgb, stop.rgb, clamp((t - previous.w) / (stop.w - previous.w), 0.0, 1.0));
+        }
+    }
+    return color;
+}
+
 // create a function that colorize a pixel based on the number of iterations has seen below
 fn colorize(coordinate: vec2<f32>, dc: vec2<f32>, iterations: f32, derivative: vec2<f32>) -> vec4<f32> {
     var color = vec4<f32>(0.0,0.0,0.0,1.0);
     if(iterations >= 0.0) {
-        var t = abs(1.0 - ((iterations + mandelbrot.time_elapsed * 5.0) % mandelbrot.color_palette_scale) * 2.0 / mandelbrot.color_palette_scale);
+        var t = abs(1.0 - ((iterations + settings.palette_phase) % settings.color_palette_scale) * 2.0 / settings.color_palette_scale);
         var dx = coordinate.x / 5.0;
         var dy = coordinate.y / 5.0;
         color = vec4<f32>(
@@ -122,6 +189,9 @@ fn colorize(coordinate: vec2<f32>, dc: vec2<f32>, iterations: f32, derivative: v
             0.5 + 0.5 * cos(t * 3.14 - 3.14 + cos(dx * 3.14) - 0.5),
             1.0
         );
+        if(palette.count > 0u) {
+            color = vec4<f32>(palette_color(t), 1.0);
+        }
         // multiply the color by the phong shading using the derivative
         // the light is rotated around the z axis to give a nice effect
         var light = normalize(vec3<f32>(cos(mandelbrot.time_elapsed * 0.5), sin(mandelbrot.time_elapsed * 0.5), 1.5));
@@ -134,27 +204,186 @@ fn colorize(coordinate: vec2<f32>, dc: vec2<f32>, iterations: f32, derivative: v
     return color;
 }
 
-fn compute_iteration(dc: vec2<f32>, index: u32, max_iteration: u32) -> f32 {
+// color a pixel by the period of its atom domain, inside the set too,
+// brighter where |z| came closer to 0, around the nucleus of the minibrot of this period
+fn colorize_atom_domain(iterations: f32, pixel: MandelbrotDot) -> vec4<f32> {
+    if(iterations < -1.5) {
+        return vec4<f32>(0.0,0.0,0.0,1.0);
+    }
+    // the golden ratio spreads the hues of the successive periods
+    let hue = fract(pixel.atom_period * 0.618034);
+    var color = 0.5 + 0.5 * cos(6.28318 * (hue + vec3<f32>(0.0, 0.33, 0.67)));
+    let shade = 1.0 / (1.0 + 4.0 * pixel.atom_distance);
+    if(iterations < 0.0) {
+        color *= 0.5;
+    }
+    return vec4<f32>(color * shade, 1.0);
+}
+
+// the raw data of a pixel instead of its color, for the debug view of the uniform
+// 1: the iterations in grayscale, 2: the magnitude of the derivative,
+// 3: the iterations since the pixel was last rebased on the start of the orbit,
+// 4: in red the pixels rebased, which would glitch without it,
+// 5: blue when the pixel of the last frame is reused, green when it is moved, red when started again
+fn debug_color(iterations: f32, pixel: MandelbrotDot, source: u32) -> vec4<f32> {
+    let maximum = f32(settings.maximum_iterations);
+    let gray = max(iterations, 0.0) / maximum;
+    // the iterations since the last rebase, the reference iteration follows them until then
+    let rebased = max(iterations - f32(pixel.reference_iteration), 0.0);
+    if (settings.debug_view == 1u) {
+        return vec4<f32>(vec3<f32>(gray), 1.0);
+    }
+    if (settings.debug_view == 2u) {
+        return vec4<f32>(vec3<f32>(clamp(log2(1.0 + length(pixel.derivative)) / 16.0, 0.0, 1.0)), 1.0);
+    }
+    if (settings.debug_view == 3u) {
+        return vec4<f32>(vec3<f32>(rebased / maximum), 1.0);
+    }
+    if (settings.debug_view == 4u) {
+        if (rebased > 1.0) {
+            return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+        }
+        return vec4<f32>(vec3<f32>(gray * 0.5), 1.0);
+    }
+    if (source == 1u) {
+        return vec4<f32>(0.0, 1.0, 0.0, 1.0);
+    }
+    if (source == 2u) {
+        return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+    }
+    return vec4<f32>(0.0, 0.0, 1.0, 1.0);
+}
+
+// the second point of the orbit, the reference point c
+fn orbit_c() -> vec2<f32> {
+    return bitcast<vec2<f32>>(vec2<u32>(mandelbrotOrbitPointSuite[0], mandelbrotOrbitPointSuite[1]));
+}
+
+// the word of a point of the packed orbit, the first of the two of the first point of a block
+fn orbit_word(i: u32) -> u32 {
+    let offset = i % orbit_block_length;
+    return 2u + i / orbit_block_length * (orbit_block_length + 1u) + offset + u32(offset != 0u);
+}
+
+// the point of the packed orbit following the given one, i is the index of the next point
+// the operations are the same as in packed_orbit.rs, in the same order
+fn next_orbit_point(z: vec2<f32>, i: u32) -> vec2<f32> {
+    let difference = unpack2x16float(mandelbrotOrbitPointSuite[orbit_word(i)]);
+    return vpow2(z) + orbit_c() + difference * exp2(-orbit_difference_exponent);
+}
+
+// the point of the packed orbit of the given index, computed from the first point of its block
+fn orbit_point(i: u32) -> vec2<f32> {
+    let first = i - i % orbit_block_length;
+    let word = orbit_word(first);
+    var z = bitcast<vec2<f32>>(vec2<u32>(mandelbrotOrbitPointSuite[word], mandelbrotOrbitPointSuite[word + 1u]));
+    for (var j = first + 1u; j <= i; j++) {
+        z = next_orbit_point(z, j);
+    }
+    return z;
+}
+
+// the index of the stored point of the orbit of the given iteration,
+// when the orbit is cyclic, only its first period is stored and the next points are read from it
+fn orbit_index(i: i32) -> u32 {
+    let start = lastRenderedMandelbrot.cycle_start;
+    let period = lastRenderedMandelbrot.cycle_period;
+    if (period != 0u && u32(i) >= start) {
+        return start + (u32(i) - start) % period;
+    }
+    return u32(i);
+}
+
+// the point of the orbit the pixel is compared to,
+// 0 in the direct mode so the delta is the value of z itself
+fn reference_point(i: i32) -> vec2<f32> {
+    if (mandelbrot.precision_mode == 0u) {
+        return vec2<f32>(0.0, 0.0);
+    }
+    return orbit_point(orbit_index(i));
+}
+
+// the point of the orbit after the given one of index i, the pixels go through the orbit in order
+// so it is computed from the given one, unless it starts a block or the cycle again
+fn next_reference_point(i: i32, z: vec2<f32>) -> vec2<f32> {
+    if (mandelbrot.precision_mode == 0u) {
+        return vec2<f32>(0.0, 0.0);
+    }
+    let next = orbit_index(i + 1);
+    if (next == orbit_index(i) + 1u && next % orbit_block_length != 0u) {
+        return next_orbit_point(z, next);
+    }
+    return orbit_point(next);
+}
+
+// start the iterations of a pixel from z = 0, they are computed by compute_iteration
+fn start_iteration(index: u32, scale: i32) -> f32 {
+    mandelbrotData[index].dz = vec2<f32>(0.0, 0.0);
+    mandelbrotData[index].der = vec2<f32>(1.0, 0.0);
+    mandelbrotData[index].iteration = 0.0;
+    mandelbrotData[index].reference_iteration = 0;
+    mandelbrotData[index].scale = scale;
+    mandelbrotData[index].orbit = lastRenderedMandelbrot.orbit;
+    mandelbrotData[index].atom_period = 0.0;
+    // the square of the smallest |z| until the iterations are finished
+    mandelbrotData[index].atom_distance = mandelbrot.mu;
+    return in_progress;
+}
+
+// continue the iterations of a pixel for at most iteration_slice iterations,
+// in_progress is returned and the state is kept for the next frame when they are not finished
+// dc and the delta are divided by 2 to the power of scale while the delta is tiny,
+// so they stay far from the smallest f32 at the deepest zooms, scale is 0 otherwise
+// center_delta and coord have the exponent of the zoom
+fn compute_iteration(center_delta: vec2<f32>, coord: vec2<f32>, index: u32, max_iteration: u32) -> f32 {
     var max_iteration: f32 = f32(max_iteration);
+    let state = mandelbrotData[index];
+    var scale = state.scale;
+    var dc = times_power_of_two(center_delta + coord, mandelbrot.zoom_exponent - scale);
+    // in the direct mode dc is moved from the reference point, the second point of the orbit, to 0
+    if (mandelbrot.precision_mode == 0u) {
+        dc += orbit_c();
+    }
     // draw a mandelbrot set
-    var z = mandelbrotOrbitPointSuite[0];
-    var dz = vec2<f32>(0.0, 0.0);
-    var der = vec2<f32>(1.0, 0.0);
-    var distance = 0.0;
-    var i = 0.0;
-    var ref_i = 0;
+    var z: vec2<f32>;
+    var dz = state.dz;
+    var der = state.der;
+    var i = state.iteration;
+    var ref_i = state.reference_iteration;
     var max = mandelbrot.mu;
     // create an epsilon var that is smaller when the zoom is bigger
     var epsilon = mandelbrot.epsilon;
+    var atom_period = state.atom_period;
+    var atom_distance = state.atom_distance;
+    var slice = lastRenderedMandelbrot.iteration_slice;
+    // the point of the orbit of ref_i, followed with it
+    var reference = reference_point(ref_i);
     // calculate the iteration
     while (i < max_iteration) {
-        z = mandelbrotOrbitPointSuite[ref_i];
-        dz = 2.0 * cmul(dz, z) + cmul(dz, dz) + dc;
+        if (slice == 0u) {
+            // the rest is computed in the next frames
+            mandelbrotData[index].dz = dz;
+            mandelbrotData[index].der = der;
+            mandelbrotData[index].iteration = i;
+            mandelbrotData[index].reference_iteration = ref_i;
+            mandelbrotData[index].scale = scale;
+            mandelbrotData[index].atom_period = atom_period;
+            mandelbrotData[index].atom_distance = atom_distance;
+            return in_progress;
+        }
+        slice -= 1u;
+        dz = 2.0 * cmul(dz, reference) + times_power_of_two(cmul(dz, dz), scale) + dc;
+        reference = next_reference_point(ref_i, reference);
         ref_i += 1;
+        let delta = times_power_of_two(dz, scale);
         // if squared module of dz
-        z = mandelbrotOrbitPointSuite[ref_i] + dz;
-        mandelbrotData[index] = cdiv(der,z);
+        z = reference + delta;
+        mandelbrotData[index].derivative = cdiv(der,z);
         let dot_z = dot(z, z);
+        if (dot_z < atom_distance) {
+            atom_distance = dot_z;
+            atom_period = i + 1.0;
+        }
          // if is bigger than a max value, then we are out of the mandelbrot set
         if (dot_z >= max) {
             break;
@@ -164,10 +393,18 @@ fn compute_iteration(dc: vec2<f32>, index: u32, max_iteration: u32) -> f32 {
             break;
         }
         der = cmul(der * 2.0, z);
-        let dot_dz = dot(dz, dz);
+        let dot_dz = dot(delta, delta);
         if (dot_z < dot_dz || f32(ref_i) == max_iteration) {
             dz = z;
             ref_i = 0;
+            reference = vec2<f32>(0.0, 0.0);
+            dc = times_power_of_two(dc, scale);
+            scale = 0;
+        } else if (scale != 0 && dot_dz > 1e-30) {
+            // the delta is big enough to be stored as it is
+            dz = delta;
+            dc = times_power_of_two(dc, scale);
+            scale = 0;
         } else {
 //   /* bivariate linear approximation */
 //    T := lookup table [ exponent(|z|^2) ]
@@ -177,12 +414,16 @@ fn compute_iteration(dc: vec2<f32>, index: u32, max_iteration: u32) -> f32 {
         }
         i += 1.0;
     }
+    mandelbrotData[index].atom_period = atom_period;
+    mandelbrotData[index].atom_distance = sqrt(atom_distance);
+    mandelbrotData[index].reference_iteration = ref_i;
     if(i >= max_iteration ) {
         i = -1.0;
     } else {
         if( i > 0.0) {
             // add the rest to i to get a smooth color gradient
-            let log_zn = log(dz.x * dz.x + dz.y * dz.y) / 2.0;
+            let escaped = times_power_of_two(dz, scale);
+            let log_zn = log(escaped.x * escaped.x + escaped.y * escaped.y) / 2.0;
             var nu = log(log_zn / log(2.0)) / log(2.0);
             i += (1.0 - nu) ;
         }
@@ -200,6 +441,10 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     );
     let screen_ratio = f32(mandelbrot.width) / f32(mandelbrot.height);
     var index = pixel.y * mandelbrot.width + pixel.x;
+    settings = mandelbrot;
+    if (is_compared(f32(pixel.x))) {
+        settings = comparison;
+    }
     var coord = in.coord;
     // scale the coord with zoom
     coord = coord * mandelbrot.zoom;
@@ -209,15 +454,51 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
         coord.x * cos(mandelbrot.angle) - coord.y * sin(mandelbrot.angle),
         coord.x * sin(mandelbrot.angle) + coord.y * cos(mandelbrot.angle)
     );
+    let center_delta = lastRenderedMandelbrot.center_delta;
     var dc = vec2<f32>(
-        mandelbrot.center_delta.x + coord.x,
-        mandelbrot.center_delta.y + coord.y
+        center_delta.x + coord.x,
+        center_delta.y + coord.y
     );
-    var movement = mandelbrot.center_delta - previous_mandelbrot.center_delta;
+    // the exponent of the power of two of the zoom, the deltas are divided by it
+    // in the extended exponent mode
+    var scale = 0;
+    if (mandelbrot.precision_mode == 2u) {
+        scale = i32(floor(log2(mandelbrot.zoom))) + mandelbrot.zoom_exponent;
+    }
+    // the difference of the exponents of the zooms of the two frames
+    let exponent_change = previous_mandelbrot.zoom_exponent - mandelbrot.zoom_exponent;
+    var movement = center_delta - times_power_of_two(previous_mandelbrot.center_delta, exponent_change);
     movement.x = movement.x / (f32(mandelbrot.width) / f32(mandelbrot.height)) / mandelbrot.zoom;
     movement.y = movement.y / mandelbrot.zoom;
-    if(
+    let shift = lastRenderedMandelbrot.shift;
+    // 0 when the pixel of the last frame is reused, 1 when it is moved, 2 when it is started again
+    var source = 0u;
+    // the iterations of the pixel in the last frame, kept when it is not moved
+    var iteration = textureLoad(previousMandelbrotTexture, vec2<i32>(pixel), 0).r;
+    if (lastRenderedMandelbrot.reproject == 1u) {
+        // only the pixels that were not in the last frame are computed
+        if (shift.x != 0 || shift.y != 0) {
+            let previous_pixel = vec2<i32>(pixel) + shift;
+            if (
+                previous_pixel.x >= 0
+                && previous_pixel.y >= 0
+                && previous_pixel.x < i32(mandelbrot.width)
+                && previous_pixel.y < i32(mandelbrot.height)
+                // the pixels of the other part of a split screen do not have the same iterations
+                && is_compared(f32(previous_pixel.x)) == is_compared(f32(pixel.x))
+            ) {
+                let previous_index = u32(previous_pixel.y) * mandelbrot.width + u32(previous_pixel.x);
+                iteration = textureLoad(previousMandelbrotTexture, previous_pixel, 0).r;
+                mandelbrotData[index] = previousMandelbrotData[previous_index];
+                source = 1u;
+            } else {
+                iteration = start_iteration(index, scale);
+                source = 2u;
+            }
+        }
+    } else if(
         mandelbrot.zoom != previous_mandelbrot.zoom
+        || exponent_change != 0
         || mandelbrot.angle != previous_mandelbrot.angle
         || movement.x != 0.0
         || movement.y != 0.0
@@ -228,7 +509,10 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
         let norm_mandelbrot = sqrt(f32(mandelbrot.width) * f32(mandelbrot.width) + f32(mandelbrot.height) * f32(mandelbrot.height));
         // make the norm follow a square curve
         let norm_square = 1u + u32(norm * norm * norm_mandelbrot / 100.0);
-        let zoom_factor = mandelbrot.zoom / previous_mandelbrot.zoom;
+        let zoom_factor = times_power_of_two(
+            vec2<f32>(mandelbrot.zoom / previous_mandelbrot.zoom),
+            -exponent_change
+        ).x;
         // calculat angle delta from previous_mandelbrot.angle and mandelbrot.angle
         // angle_delta vari between 0 and 2 pi
         let angle_delta = mandelbrot.angle - previous_mandelbrot.angle;
@@ -261,7 +545,7 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
         if(
            mandelbrot.angle == previous_mandelbrot.angle
            && (
-               mandelbrot.zoom == previous_mandelbrot.zoom
+               (mandelbrot.zoom == previous_mandelbrot.zoom && exponent_change == 0)
                || (
                  !(pixel.x % norm_square == u32(random * f32(norm_square)))
                  && !(pixel.y % norm_square == u32(random * f32(norm_square)))
@@ -273,16 +557,42 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
                 && previous_pixel.y < f32(mandelbrot.height)
                 && previous_pixel.x >= 0.0
                 && previous_pixel.y >= 0.0
+                && is_compared(previous_pixel.x) == is_compared(f32(pixel.x))
             ) {
-//                mandelbrotTexture[index] = previousMandelbrotTexture[previous_index];
-                mandelbrotTexture[index] = previousMandelbrotTexture[previous_index];
+                iteration = textureLoad(previousMandelbrotTexture, vec2<i32>(previous_pixel), 0).r;
                 mandelbrotData[index] = previousMandelbrotData[previous_index];
+                source = 1u;
             } else {
-                mandelbrotTexture[index] = compute_iteration(dc, index, mandelbrot.maximum_iterations);
+                iteration = start_iteration(index, scale);
+                source = 2u;
             }
         } else {
-            mandelbrotTexture[index] = compute_iteration(dc, index, mandelbrot.maximum_iterations);
+            iteration = start_iteration(index, scale);
+            source = 2u;
         }
     }
-    return colorize(in.coord, dc, mandelbrotTexture[index], mandelbrotData[index]);
+    // the pixels started in this frame, and the ones not finished in the last frames, are advanced
+    if (iteration == in_progress) {
+        if (mandelbrotData[index].orbit != lastRenderedMandelbrot.orbit) {
+            // the delta is relative to another orbit
+            iteration = start_iteration(index, scale);
+            source = 2u;
+        }
+        iteration = compute_iteration(center_delta, coord, index, settings.maximum_iterations);
+    }
+    textureStore(mandelbrotTexture, vec2<i32>(pixel), vec4<f32>(iteration, 0.0, 0.0, 0.0));
+    measure_iteration(pixel, iteration);
+    if (settings.debug_view != 0u) {
+        return debug_color(iteration, mandelbrotData[index], source);
+    }
+    var color: vec4<f32>;
+    if(settings.coloring == 1u) {
+        color = colorize_atom_domain(iteration, mandelbrotData[index]);
+    } else {
+        color = colorize(in.coord, dc, iteration, mandelbrotData[index].derivative);
+    }
+    // add less than one step of an 8 bits channel, to break up the banding of the smooth gradients
+    let noise_size = textureDimensions(blueNoise);
+    let noise = textureLoad(blueNoise, vec2<i32>(in.clip_position.xy) % noise_size, 0).r;
+    return vec4<f32>(color.rgb + (noise - 0.5) / 255.0, color.a);
 }
@@ -0,0 +1,120 @@
+// Newton fractal
+// each point is moved by the newton method toward a root of the polynomial,
+// it is colored by the root it converges to and darkened by the number of steps it takes
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) coordinate: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) coord: vec2<f32>,
+};
+
+// the structs of the buffers, written from the rust ones so they can not differ
+// STRUCTS
+
+@group(1) @binding(0)
+var<uniform> mandelbrot: Mandelbrot;
+@group(2) @binding(0)
+var mandelbrotTexture: texture_storage_2d<rgba32float, write>;
+@group(2) @binding(2)
+var<storage, read_write> mandelbrotData: array<MandelbrotDot>;
+// the orbit of the reference point of the mandelbrot set, packed after its second point,
+// the reference point, whose two f32 are the first two words
+@group(2) @binding(4)
+var<storage, read_write> mandelbrotOrbitPointSuite: array<u32>;
+@group(0) @binding(0)
+var blueNoise: texture_2d<f32>;
+@group(1) @binding(2)
+var<uniform> polynomial: Polynomial;
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(model.position, 1.0);
+    out.coord = model.coordinate.xy;
+    return out;
+}
+
+fn cmul(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(a.x * b.x - a.y * b.y, a.x * b.y + a.y * b.x);
+}
+
+fn cdiv(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+    var denominator: f32 = b.x * b.x + b.y * b.y;
+    return vec2<f32>((a.x * b.x + a.y * b.y) / denominator, (a.y * b.x - a.x * b.y) / denominator);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let pixel = vec2<u32>(
+        u32((in.coord.x + 1.0) / 2.0 * f32(mandelbrot.width)),
+        u32((in.coord.y + 1.0) / 2.0 * f32(mandelbrot.height))
+    );
+    let index = pixel.y * mandelbrot.width + pixel.x;
+    var coord = in.coord * mandelbrot.zoom;
+    coord.x *= f32(mandelbrot.width) / f32(mandelbrot.height);
+    coord = vec2<f32>(
+        coord.x * cos(mandelbrot.angle) - coord.y * sin(mandelbrot.angle),
+        coord.x * sin(mandelbrot.angle) + coord.y * cos(mandelbrot.angle)
+    );
+    let reference = bitcast<vec2<f32>>(vec2<u32>(mandelbrotOrbitPointSuite[0], mandelbrotOrbitPointSuite[1]));
+    var z = reference + mandelbrot.center_delta + coord;
+    let degree = i32(polynomial.degree);
+    var i = 0u;
+    var converged = false;
+    var last_step = 1.0;
+    loop {
+        if (i >= mandelbrot.maximum_iterations) {
+            break;
+        }
+        // evaluate the polynomial and its derivative with the horner method
+        var value = polynomial.terms[degree].xy;
+        var derivative = vec2<f32>(0.0, 0.0);
+        for (var term = degree - 1; term >= 0; term -= 1) {
+            derivative = cmul(derivative, z) + value;
+            value = cmul(value, z) + polynomial.terms[term].xy;
+        }
+        let step = cdiv(value, derivative);
+        z -= step;
+        i += 1u;
+        last_step = dot(step, step);
+        if (last_step < 1e-10) {
+            converged = true;
+            break;
+        }
+    }
+    var color = vec3<f32>(0.0, 0.0, 0.0);
+    if (converged) {
+        // the closest root gives the hue
+        var closest = 0;
+        var closest_distance = 1e30;
+        for (var root = 0; root < degree; root += 1) {
+            let offset = z - polynomial.terms[root].zw;
+            let root_distance = dot(offset, offset);
+            if (root_distance < closest_distance) {
+                closest_distance = root_distance;
+                closest = root;
+            }
+        }
+        let hue = f32(closest) / f32(degree);
+        color = 0.5 + 0.5 * cos(6.28318 * (hue + vec3<f32>(0.0, 0.33, 0.67)));
+        // the steps shrink quadratically near a root, which gives a smooth count of the steps
+        let smooth_i = f32(i) - log2(max(log(last_step) / log(1e-10), 1e-6));
+        color *= exp(-smooth_i * 20.0 / mandelbrot.color_palette_scale);
+        textureStore(mandelbrotTexture, vec2<i32>(pixel), vec4<f32>(smooth_i, 0.0, 0.0, 0.0));
+    } else {
+        textureStore(mandelbrotTexture, vec2<i32>(pixel), vec4<f32>(-1.0, 0.0, 0.0, 0.0));
+    }
+    mandelbrotData[index] = MandelbrotDot(
+        vec2<f32>(0.0, 0.0), 0.0, 0.0, vec2<f32>(0.0, 0.0),
+        vec2<f32>(0.0, 0.0), vec2<f32>(0.0, 0.0), 0.0, 0, 0, 0u
+    );
+    // add less than one step of an 8 bits channel, to break up the banding of the smooth gradients
+    let noise_size = textureDimensions(blueNoise);
+    let noise = textureLoad(blueNoise, vec2<i32>(in.clip_position.xy) % noise_size, 0).r;
+    return vec4<f32>(color + (noise - 0.5) / 255.0, 1.0);
+}
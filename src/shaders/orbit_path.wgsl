@@ -0,0 +1,27 @@
+// Draw the orbit of the reference point as a line on top of the fractal,
+// from white at its first point to orange at its last one
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    // the share of the orbit before the point, from 0 to 1
+    @location(1) progress: f32,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) progress: f32,
+};
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(model.position, 0.0, 1.0);
+    out.progress = model.progress;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(mix(vec3<f32>(1.0, 1.0, 1.0), vec3<f32>(1.0, 0.5, 0.0), in.progress), 1.0);
+}
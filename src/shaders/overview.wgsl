@@ -0,0 +1,92 @@
+// Draw the whole mandelbrot set in a corner of the screen,
+// with a rectangle indicating the part of the set currently explored
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) coordinate: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) coord: vec2<f32>,
+};
+
+// the structs of the buffers, written from the rust ones so they can not differ
+// STRUCTS
+
+// the center delta is the center of the overview
+@group(0) @binding(0)
+var<uniform> overview: Mandelbrot;
+// the part of the set currently explored
+@group(0) @binding(1)
+var<uniform> viewport: Viewport;
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(model.position, 1.0);
+    out.coord = model.coordinate.xy;
+    return out;
+}
+
+fn colorize(iterations: f32) -> vec4<f32> {
+    if(iterations < 0.0) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+    var t = abs(1.0 - ((iterations + overview.palette_phase) % overview.color_palette_scale) * 2.0 / overview.color_palette_scale);
+    return vec4<f32>(
+        0.5 + 0.5 * cos(t * 6.28 + 0.9),
+        0.5 + 0.5 * sin(t * 5.88 - 3.14),
+        0.5 + 0.5 * cos(t * 3.14 - 3.64),
+        1.0
+    );
+}
+
+fn compute_iteration(c: vec2<f32>) -> f32 {
+    var z = vec2<f32>(0.0, 0.0);
+    var i = 0u;
+    while (i < overview.maximum_iterations) {
+        z = vec2<f32>(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y) + c;
+        if (dot(z, z) >= overview.mu) {
+            // add the rest to i to get a smooth color gradient
+            let log_zn = log(dot(z, z)) / 2.0;
+            let nu = log(log_zn / log(2.0)) / log(2.0);
+            return f32(i) + 1.0 - nu;
+        }
+        i += 1u;
+    }
+    return -1.0;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // the size of a pixel in the coord space
+    let pixel = 2.0 / f32(overview.height);
+    // a thin frame around the overview
+    if(abs(in.coord.x) > 1.0 - pixel * 2.0 || abs(in.coord.y) > 1.0 - pixel * 2.0) {
+        return vec4<f32>(0.5, 0.5, 0.5, 1.0);
+    }
+    let ratio = f32(overview.width) / f32(overview.height);
+    let c = vec2<f32>(in.coord.x * ratio, in.coord.y) * overview.zoom + overview.center_delta;
+    // the position relatively to the viewport, without its rotation
+    var p = c - viewport.center;
+    p = vec2<f32>(
+        p.x * cos(-viewport.angle) - p.y * sin(-viewport.angle),
+        p.x * sin(-viewport.angle) + p.y * cos(-viewport.angle)
+    );
+    // the rectangle stays visible when the viewport is smaller than a few pixels
+    let width = pixel * overview.zoom;
+    let half_size = vec2<f32>(
+        max(viewport.zoom * viewport.ratio, width * 3.0),
+        max(viewport.zoom, width * 3.0)
+    );
+    if(
+        abs(p.x) <= half_size.x + width
+        && abs(p.y) <= half_size.y + width
+        && (abs(p.x) >= half_size.x - width || abs(p.y) >= half_size.y - width)
+    ) {
+        return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+    }
+    return colorize(compute_iteration(c));
+}
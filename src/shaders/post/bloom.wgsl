@@ -0,0 +1,72 @@
+// Add a halo around the bright parts of the image
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) coordinate: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var t_input: texture_2d<f32>;
+@group(0) @binding(1)
+var s_input: sampler;
+// the blurred bright parts, only read by the last pass
+@group(0) @binding(2)
+var t_bloom: texture_2d<f32>;
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(model.position, 1.0);
+    // the texture coordinates go down from the top left corner
+    out.uv = vec2<f32>(model.coordinate.x * 0.5 + 0.5, 0.5 - model.coordinate.y * 0.5);
+    return out;
+}
+
+// the brightness over which a color glows
+let threshold: f32 = 0.7;
+// the share of the halo added to the image
+let strength: f32 = 0.4;
+
+@fragment
+fn fs_threshold(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_input, s_input, in.uv).rgb;
+    let brightness = max(color.r, max(color.g, color.b));
+    let glow = max(brightness - threshold, 0.0) / max(1.0 - threshold, 0.0001);
+    return vec4<f32>(color * glow, 1.0);
+}
+
+// a gaussian blur of 9 pixels in the given direction
+fn blur(uv: vec2<f32>, direction: vec2<f32>) -> vec4<f32> {
+    let texel_step = direction / vec2<f32>(textureDimensions(t_input));
+    var weights = array<f32, 5>(0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+    var color = textureSample(t_input, s_input, uv).rgb * weights[0];
+    for (var i = 1; i < 5; i = i + 1) {
+        let offset = texel_step * f32(i);
+        color = color + textureSample(t_input, s_input, uv + offset).rgb * weights[i];
+        color = color + textureSample(t_input, s_input, uv - offset).rgb * weights[i];
+    }
+    return vec4<f32>(color, 1.0);
+}
+
+@fragment
+fn fs_blur_horizontal(in: VertexOutput) -> @location(0) vec4<f32> {
+    return blur(in.uv, vec2<f32>(1.0, 0.0));
+}
+
+@fragment
+fn fs_blur_vertical(in: VertexOutput) -> @location(0) vec4<f32> {
+    return blur(in.uv, vec2<f32>(0.0, 1.0));
+}
+
+@fragment
+fn fs_composite(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_input, s_input, in.uv);
+    let bloom = textureSample(t_bloom, s_input, in.uv).rgb;
+    return vec4<f32>(min(color.rgb + bloom * strength, vec3<f32>(1.0)), color.a);
+}
@@ -0,0 +1,45 @@
+// Correct the gamma, the brightness and the contrast of the colors, for the screen they are shown on
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) coordinate: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+struct Levels {
+    gamma: f32,
+    brightness: f32,
+    contrast: f32,
+    padding: u32,
+};
+
+@group(0) @binding(0)
+var t_input: texture_2d<f32>;
+@group(0) @binding(1)
+var s_input: sampler;
+@group(0) @binding(2)
+var<uniform> levels: Levels;
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(model.position, 1.0);
+    // the texture coordinates go down from the top left corner
+    out.uv = vec2<f32>(model.coordinate.x * 0.5 + 0.5, 0.5 - model.coordinate.y * 0.5);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_input, s_input, in.uv);
+    // a gamma above 1 lightens the dark colors
+    let corrected = pow(max(color.rgb, vec3<f32>(0.0)), vec3<f32>(1.0 / levels.gamma));
+    // the contrast spreads the colors around the middle gray, then the brightness moves them all
+    let adjusted = (corrected - vec3<f32>(0.5)) * levels.contrast + vec3<f32>(0.5 + levels.brightness);
+    return vec4<f32>(max(adjusted, vec3<f32>(0.0)), color.a);
+}
@@ -0,0 +1,39 @@
+// Encode the linear colors in srgb, for the surfaces that show the values as they are written
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) coordinate: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var t_input: texture_2d<f32>;
+@group(0) @binding(1)
+var s_input: sampler;
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(model.position, 1.0);
+    // the texture coordinates go down from the top left corner
+    out.uv = vec2<f32>(model.coordinate.x * 0.5 + 0.5, 0.5 - model.coordinate.y * 0.5);
+    return out;
+}
+
+fn encode(value: f32) -> f32 {
+    if (value <= 0.0031308) {
+        return value * 12.92;
+    }
+    return 1.055 * pow(value, 1.0 / 2.4) - 0.055;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_input, s_input, in.uv);
+    return vec4<f32>(encode(color.r), encode(color.g), encode(color.b), color.a);
+}
@@ -0,0 +1,39 @@
+// Sharpen the edges by removing the average of the neighbours of each pixel
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) coordinate: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var t_input: texture_2d<f32>;
+@group(0) @binding(1)
+var s_input: sampler;
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(model.position, 1.0);
+    // the texture coordinates go down from the top left corner
+    out.uv = vec2<f32>(model.coordinate.x * 0.5 + 0.5, 0.5 - model.coordinate.y * 0.5);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(t_input));
+    let color = textureSample(t_input, s_input, in.uv);
+    let neighbours = textureSample(t_input, s_input, in.uv + vec2<f32>(texel.x, 0.0))
+        + textureSample(t_input, s_input, in.uv - vec2<f32>(texel.x, 0.0))
+        + textureSample(t_input, s_input, in.uv + vec2<f32>(0.0, texel.y))
+        + textureSample(t_input, s_input, in.uv - vec2<f32>(0.0, texel.y));
+    let strength = 0.5;
+    let sharpened = color.rgb + strength * (4.0 * color.rgb - neighbours.rgb);
+    return vec4<f32>(clamp(sharpened, vec3<f32>(0.0), vec3<f32>(1.0)), color.a);
+}
@@ -2,11 +2,16 @@ extern crate proc_macro;
 
 use proc_macro::TokenStream;
 
+use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
-use syn::DeriveInput;
+use syn::{Data, DeriveInput, Expr, Fields, Ident, Lit, Type};
 
 // create a derive macro for ToBufferRepresentation
-#[proc_macro_derive(ToBufferRepresentation)]
+// the struct is checked at compile time to have the layout of the same struct in WGSL,
+// the fields marked #[padding] are only bytes between the others
+// the struct also gets a wgsl_struct function that writes the same struct in WGSL,
+// for the shaders to include it
+#[proc_macro_derive(ToBufferRepresentation, attributes(padding))]
 pub fn derive_to_buffer_representation(input: TokenStream) -> TokenStream {
     let ast = syn::parse(input).unwrap();
     impl_to_buffer_representation(&ast)
@@ -15,12 +20,225 @@ pub fn derive_to_buffer_representation(input: TokenStream) -> TokenStream {
 fn impl_to_buffer_representation(ast: &DeriveInput) -> TokenStream {
     // get the name of the struct that is being derived without the added reference
     let name = &ast.ident;
+    let fields = match fields(ast) {
+        Ok(fields) => fields,
+        Err(error) => return error.to_compile_error().into(),
+    };
+    let layout_check = layout_check(name, &fields);
+    let wgsl_struct = wgsl_struct(name, &fields);
     let gen = quote! {
         impl ToBufferRepresentation for #name {
             fn to_bits(&self) -> &[u8] {
                 bytemuck::bytes_of(self)
             }
         }
+
+        #layout_check
+
+        #wgsl_struct
     };
     gen.into()
 }
+
+// the type of a field in WGSL
+enum WgslType {
+    // f32, i32 or u32
+    Scalar(String),
+    // an array of 2, 3 or 4 scalars
+    Vector(String, usize),
+    // an array of anything else, with its length
+    Array(Box<WgslType>, Expr),
+    // bytes only there to fill the space before the next field
+    Padding(Type),
+}
+
+impl WgslType {
+    fn parse(ty: &Type) -> Option<WgslType> {
+        match ty {
+            Type::Path(path) => {
+                let ident = path.path.get_ident()?.to_string();
+                ["f32", "i32", "u32"]
+                    .contains(&ident.as_str())
+                    .then_some(WgslType::Scalar(ident))
+            }
+            Type::Array(array) => {
+                let element = WgslType::parse(&array.elem)?;
+                match (element, array_length(&array.len)) {
+                    (WgslType::Scalar(scalar), Some(length @ 2..=4)) => {
+                        Some(WgslType::Vector(scalar, length))
+                    }
+                    (element, _) => Some(WgslType::Array(Box::new(element), array.len.clone())),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    // the alignment of the type, in bytes
+    fn align(&self) -> usize {
+        match self {
+            WgslType::Scalar(_) => 4,
+            WgslType::Vector(_, 2) => 8,
+            WgslType::Vector(_, _) => 16,
+            WgslType::Array(element, _) => element.align(),
+            WgslType::Padding(_) => 4,
+        }
+    }
+
+    // the size of the type, in bytes, as a constant expression
+    fn size(&self) -> TokenStream2 {
+        match self {
+            WgslType::Scalar(_) => quote!(4usize),
+            WgslType::Vector(_, length) => quote!((4usize * #length)),
+            // the elements of an array are spaced by their size rounded up to their alignment
+            WgslType::Array(element, length) => {
+                let size = element.size();
+                let align = element.align();
+                quote!(((#size).div_ceil(#align) * #align * (#length)))
+            }
+            WgslType::Padding(ty) => quote!(core::mem::size_of::<#ty>()),
+        }
+    }
+
+    // the name of the type in WGSL, as an expression giving a String,
+    // the length of the arrays is only known when the code runs
+    fn name(&self) -> TokenStream2 {
+        match self {
+            WgslType::Scalar(scalar) => quote!(#scalar.to_string()),
+            WgslType::Vector(scalar, length) => {
+                let name = format!("vec{}<{}>", length, scalar);
+                quote!(#name.to_string())
+            }
+            WgslType::Array(element, length) => {
+                let element = element.name();
+                quote!(format!("array<{}, {}>", #element, #length))
+            }
+            WgslType::Padding(_) => quote!(String::new()),
+        }
+    }
+}
+
+fn array_length(length: &Expr) -> Option<usize> {
+    match length {
+        Expr::Lit(literal) => match &literal.lit {
+            Lit::Int(int) => int.base10_parse().ok(),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// the named fields of the struct with their type in WGSL
+fn fields(ast: &DeriveInput) -> syn::Result<Vec<(&Ident, WgslType)>> {
+    let name = &ast.ident;
+    let fields = match &ast.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new_spanned(ast, "the fields must be named")),
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                ast,
+                "only structs can be sent to the shaders",
+            ))
+        }
+    };
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let ident = field.ident.as_ref().unwrap();
+            if field.attrs.iter().any(|attr| attr.path.is_ident("padding")) {
+                // the padding is added to the field before it in WGSL
+                if index == 0 {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        "the first field can not be padding",
+                    ));
+                }
+                return Ok((ident, WgslType::Padding(field.ty.clone())));
+            }
+            let wgsl_type = WgslType::parse(&field.ty).ok_or_else(|| {
+                syn::Error::new_spanned(
+                    &field.ty,
+                    format!(
+                        "`{}::{}` has no type of WGSL, use f32, i32, u32 or arrays of them, \
+                         or mark it #[padding]",
+                        name, ident
+                    ),
+                )
+            })?;
+            Ok((ident, wgsl_type))
+        })
+        .collect()
+}
+
+// a constant that does not compile when a field is not at the offset WGSL gives it,
+// or when the size of the struct is not the one of WGSL
+fn layout_check(name: &Ident, fields: &[(&Ident, WgslType)]) -> TokenStream2 {
+    let mut checks = Vec::new();
+    let mut struct_align = 4;
+    for (ident, wgsl_type) in fields {
+        let align = wgsl_type.align();
+        let size = wgsl_type.size();
+        struct_align = struct_align.max(align);
+        let message = format!(
+            "`{}::{}` is not aligned to {} bytes as in WGSL, add a #[padding] field before it",
+            name, ident, align
+        );
+        checks.push(quote! {
+            offset = offset.div_ceil(#align) * #align;
+            if core::mem::offset_of!(#name, #ident) != offset {
+                panic!(#message);
+            }
+            offset += #size;
+        });
+    }
+    let message = format!(
+        "the size of `{}` is not a multiple of {} bytes as in WGSL, add a #[padding] field at its end",
+        name, struct_align
+    );
+    quote! {
+        const _: () = {
+            let mut offset = 0usize;
+            #(#checks)*
+            if core::mem::size_of::<#name>() != offset.div_ceil(#struct_align) * #struct_align {
+                panic!(#message);
+            }
+        };
+    }
+}
+
+// the struct written in WGSL with the given name, the padding is added to the size
+// of the field before it
+fn wgsl_struct(name: &Ident, fields: &[(&Ident, WgslType)]) -> TokenStream2 {
+    let mut lines = Vec::new();
+    for (index, (ident, wgsl_type)) in fields.iter().enumerate() {
+        if let WgslType::Padding(_) = wgsl_type {
+            continue;
+        }
+        let field = ident.to_string();
+        let type_name = wgsl_type.name();
+        let size = wgsl_type.size();
+        let padding = fields[index + 1..]
+            .iter()
+            .take_while(|(_, wgsl_type)| matches!(wgsl_type, WgslType::Padding(_)))
+            .map(|(_, wgsl_type)| wgsl_type.size())
+            .collect::<Vec<_>>();
+        lines.push(if padding.is_empty() {
+            quote!(format!("    {}: {},\n", #field, #type_name))
+        } else {
+            quote!(format!("    @size({}) {}: {},\n", #size #(+ #padding)*, #field, #type_name))
+        });
+    }
+    quote! {
+        impl #name {
+            pub fn wgsl_struct(name: &str) -> String {
+                let mut source = format!("struct {} {{\n", name);
+                #(source.push_str(&#lines);)*
+                source.push_str("};\n");
+                source
+            }
+        }
+    }
+}